@@ -49,7 +49,7 @@ rocket.simulate_flight_3dof(
 duration_1 = perf_counter() - start_time
 
 # Run a second time with data logging to check the final array size
-time_steps_1, _ = rocket.simulate_flight_3dof(
+flight_1 = rocket.simulate_flight_3dof(
     initial_state=initial_state,
     integration_method=OdeMethod.RK45,
     timestep_config=ats,
@@ -68,7 +68,7 @@ rocket.simulate_flight_3dof(
 )
 duration_2 = perf_counter() - start_time
 
-time_steps_2, _ = rocket.simulate_flight_3dof(
+flight_2 = rocket.simulate_flight_3dof(
     initial_state=initial_state,
     integration_method=OdeMethod.RK45,
     timestep_config=ats,
@@ -87,13 +87,13 @@ rocket.simulate_flight_3dof(
 )
 duration_3 = perf_counter() - start_time
 
-time_steps_3, _ = rocket.simulate_flight_3dof(
+flight_3 = rocket.simulate_flight_3dof(
     initial_state=initial_state,
     integration_method=OdeMethod.RK45,
     timestep_config=ats,
 )
 
 # Print out the profiling metrics to analyze the adaptive step behavior
-print(f"Run 1 (Tolerances: 1.0e-0) | Time: {duration_1:.3e} s | Points: {len(time_steps_1)}")
-print(f"Run 2 (Tolerances: 1.0e-2) | Time: {duration_2:.3e} s | Points: {len(time_steps_2)}")
-print(f"Run 3 (Tolerances: 1.0e-4) | Time: {duration_3:.3e} s | Points: {len(time_steps_3)}")
+print(f"Run 1 (Tolerances: 1.0e-0) | Time: {duration_1:.3e} s | Points: {len(flight_1)}")
+print(f"Run 2 (Tolerances: 1.0e-2) | Time: {duration_2:.3e} s | Points: {len(flight_2)}")
+print(f"Run 3 (Tolerances: 1.0e-4) | Time: {duration_3:.3e} s | Points: {len(flight_3)}")
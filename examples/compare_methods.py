@@ -37,14 +37,15 @@ fig = make_subplots(
 
 # Plots the position and velocity for each integration method
 for method in methods_to_compare:
-    time_steps, state_matrix = rocket.simulate_flight_3dof(
+    flight = rocket.simulate_flight_3dof(
         initial_state=initial_state,
         integration_method=method,
     )
 
     # Extract the vertical position (y) and vertical velocity (vy) for this method
-    y_3dof = state_matrix[:, 1]
-    vy_3dof = state_matrix[:, 4]
+    time_steps = flight.time
+    y_3dof = flight.altitude
+    vy_3dof = flight.vertical_velocity
 
     method_label = str(method)
 
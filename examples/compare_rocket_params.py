@@ -45,7 +45,7 @@ for mass in mass_values:
         stab_margin_dimensional=0.5,
         cl_a=11.0,
     )
-    time_steps, state_matrix = rocket.simulate_flight_3dof(
+    flight = rocket.simulate_flight_3dof(
         initial_state=initial_state,
         integration_method=OdeMethod.RK45,
     )
@@ -53,7 +53,7 @@ for mass in mass_values:
     # Plot the flights for the mass sweep onto the first row
     fig.add_trace(
         go.Scatter(
-            x=time_steps, y=state_matrix[:, 1], mode="lines", name=f"{mass} kg", legend="legend1"
+            x=flight.time, y=flight.altitude, mode="lines", name=f"{mass} kg", legend="legend1"
         ),
         row=1,
         col=1,
@@ -70,7 +70,7 @@ for cd in cd_values:
         stab_margin_dimensional=0.5,
         cl_a=11.0,
     )
-    time_steps, state_matrix = rocket.simulate_flight_3dof(
+    flight = rocket.simulate_flight_3dof(
         initial_state=initial_state,
         integration_method=OdeMethod.RK45,
     )
@@ -78,7 +78,7 @@ for cd in cd_values:
     # Plot the flights for the drag sweep onto the second row
     fig.add_trace(
         go.Scatter(
-            x=time_steps, y=state_matrix[:, 1], mode="lines", name=f"Cd = {cd}", legend="legend2"
+            x=flight.time, y=flight.altitude, mode="lines", name=f"Cd = {cd}", legend="legend2"
         ),
         row=2,
         col=1,
@@ -95,7 +95,7 @@ for margin in margin_values:
         stab_margin_dimensional=margin,
         cl_a=11.0,
     )
-    time_steps, state_matrix = rocket.simulate_flight_3dof(
+    flight = rocket.simulate_flight_3dof(
         initial_state=initial_state,
         integration_method=OdeMethod.RK45,
     )
@@ -103,8 +103,8 @@ for margin in margin_values:
     # Plot the flights for the stability sweep onto the third row
     fig.add_trace(
         go.Scatter(
-            x=time_steps,
-            y=state_matrix[:, 2],
+            x=flight.time,
+            y=flight.column("angle"),
             mode="lines",
             name=f"Margin = {margin}m",
             legend="legend3",
@@ -124,7 +124,7 @@ for moi in moi_values:
         stab_margin_dimensional=0.5,
         cl_a=11.0,
     )
-    time_steps, state_matrix = rocket.simulate_flight_3dof(
+    flight = rocket.simulate_flight_3dof(
         initial_state=initial_state,
         integration_method=OdeMethod.RK45,
     )
@@ -132,7 +132,11 @@ for moi in moi_values:
     # Plot the flights for the MOI sweep onto the fourth row
     fig.add_trace(
         go.Scatter(
-            x=time_steps, y=state_matrix[:, 5], mode="lines", name=f"MOI = {moi}", legend="legend4"
+            x=flight.time,
+            y=flight.column("angular_rate"),
+            mode="lines",
+            name=f"MOI = {moi}",
+            legend="legend4",
         ),
         row=4,
         col=1,
@@ -149,7 +153,7 @@ for cla in cla_values:
         stab_margin_dimensional=0.5,
         cl_a=cla,
     )
-    time_steps, state_matrix = rocket.simulate_flight_3dof(
+    flight = rocket.simulate_flight_3dof(
         initial_state=initial_state,
         integration_method=OdeMethod.RK45,
     )
@@ -157,7 +161,11 @@ for cla in cla_values:
     # Plot the flights for the cl_a sweep onto the fifth row
     fig.add_trace(
         go.Scatter(
-            x=time_steps, y=state_matrix[:, 2], mode="lines", name=f"cl_a = {cla}", legend="legend5"
+            x=flight.time,
+            y=flight.column("angle"),
+            mode="lines",
+            name=f"cl_a = {cla}",
+            legend="legend5",
         ),
         row=5,
         col=1,
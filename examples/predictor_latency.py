@@ -0,0 +1,38 @@
+"""This script benchmarks the per-call latency of ApogeePredictor against calling
+Rocket.predict_apogee_1dof with a fresh simulation each time, as a flight computer would."""
+
+from time import perf_counter
+from hprm import ApogeePredictor, InitialState1DOF, Rocket, OdeMethod
+
+
+rocket = Rocket(
+    mass=17.0,
+    cd=0.39,
+    area_drag=0.0182,
+    area_lift=0.0182,
+    moment_of_inertia=11.5,
+    stab_margin_dimensional=0.5,
+    cl_a=0.2,
+)
+
+# Pretend we're a flight computer updating the prediction at 100 Hz while coasting up from
+# 150 m/s, so every call starts from a slightly different state
+num_calls = 2000
+states = [(i * 0.01, 1.5 * i, 150.0 - 0.1 * i) for i in range(num_calls)]
+
+start_time = perf_counter()
+for time, altitude, velocity in states:
+    rocket.predict_apogee_1dof(InitialState1DOF(altitude, velocity), OdeMethod.RK45)
+fresh_duration = perf_counter() - start_time
+
+# The predictor is created once before the flight, and then only updated
+predictor = ApogeePredictor.new_1dof(rocket, OdeMethod.RK45)
+
+start_time = perf_counter()
+for time, altitude, velocity in states:
+    predictor.update(time, altitude, velocity)
+predictor_duration = perf_counter() - start_time
+
+print(f"predict_apogee_1dof:     {fresh_duration / num_calls * 1e6:8.1f} us per call")
+print(f"ApogeePredictor.update:  {predictor_duration / num_calls * 1e6:8.1f} us per call")
+print(f"Last predicted apogee:   {predictor.apogee:8.1f} m")
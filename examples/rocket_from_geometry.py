@@ -0,0 +1,58 @@
+"""This script shows how to let HPRM estimate the aerodynamic properties of a rocket from its
+geometry (Barrowman method + drag buildup) instead of entering cd, cl_a and the stability margin
+by hand."""
+
+from hprm import (
+    BodyTube,
+    FinSet,
+    InitialState1DOF,
+    LaunchLug,
+    NoseCone,
+    NoseShape,
+    OdeMethod,
+    Rocket,
+    RocketGeometry,
+    Transition,
+)
+
+# Roughly a 6 inch, 2.5 m long high power rocket with a small boattail. All lengths are in meters,
+# and the fin position is measured from the tip of the nose cone to the fin root leading edge.
+geometry = RocketGeometry(
+    nose_cone=NoseCone(NoseShape.Ogive, length=0.75, diameter=0.155),
+    body=[
+        BodyTube(length=1.6, diameter=0.155),
+        Transition(length=0.1, fore_diameter=0.155, aft_diameter=0.13),
+    ],
+    fins=FinSet(
+        count=4,
+        root_chord=0.3,
+        tip_chord=0.1,
+        span=0.15,
+        sweep_length=0.2,
+        thickness=0.005,
+        position=2.05,
+    ),
+    launch_lugs=[LaunchLug(length=0.05, outer_diameter=0.02, inner_diameter=0.015)],
+)
+
+# The center of gravity is also measured from the nose tip
+estimate = geometry.estimate(cg_location=1.5)
+
+print(f"CN_alpha:         {estimate.cn_alpha:.2f} /rad")
+print(f"CP location:      {estimate.cp_location:.3f} m")
+print(f"Stability margin: {estimate.stability_margin:.3f} m ({estimate.stability_calibers:.2f} cal)")
+print(f"Cmq:              {estimate.cmq:.1f} /rad")
+print(
+    f"Cd:               {estimate.cd:.3f} (friction {estimate.cd_friction:.3f}, "
+    f"pressure {estimate.cd_pressure:.3f}, base {estimate.cd_base:.3f}, "
+    f"interference {estimate.cd_interference:.3f})"
+)
+
+# Or just build the rocket from the geometry directly
+rocket = Rocket.from_geometry(geometry, mass=17.0, moment_of_inertia=11.5, cg_location=1.5)
+
+apogee = rocket.predict_apogee_1dof(
+    InitialState1DOF(initial_height=0.0, initial_velocity=150.0),
+    integration_method=OdeMethod.RK45,
+)
+print(f"1DOF Predicted Apogee: {apogee:.2f} m")
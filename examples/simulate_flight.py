@@ -4,7 +4,7 @@ and displays every single plot on a single page using a unified subplot layout."
 import math
 import plotly.graph_objects as go
 from plotly.subplots import make_subplots
-from hprm import Rocket, OdeMethod, InitialState1DOF, InitialState3DOF
+from hprm import Rocket, OdeMethod, InitialState1DOF, InitialState3DOF, DerivedChannel
 
 
 # Create your rocket, this is roughly what a heavier 6-inch diameter rocket might look like, but you
@@ -24,16 +24,17 @@ initial_state_1dof = InitialState1DOF(
     initial_velocity=150.0,
 )
 
-# Runs a 1dof simulation and returns the time steps and state matrix as NumPy arrays.
-# The state matrix has 2 columns: [altitude, velocity]
-time_1dof, state_1dof = rocket.simulate_flight_1dof(
+# Runs a 1dof simulation and returns the logged flight. Its state matrix has 3 columns:
+# [altitude, vertical_velocity, vertical_acceleration]
+flight_1dof = rocket.simulate_flight_1dof(
     initial_state=initial_state_1dof,
     integration_method=OdeMethod.RK45,
 )
 
-# You can access the altitude and velocity data from the 1DOF state matrix by slicing
-alt_1dof = state_1dof[:, 0]
-vel_1dof = state_1dof[:, 1]
+# You can access the altitude and velocity data of the 1DOF flight by name
+time_1dof = flight_1dof.time
+alt_1dof = flight_1dof.altitude
+vel_1dof = flight_1dof.vertical_velocity
 
 initial_state_3dof = InitialState3DOF(
     x=0.0,
@@ -46,19 +47,33 @@ initial_state_3dof = InitialState3DOF(
 
 # Runs a 3DOF simulation. We launch at 88 degrees (tilted 2 degrees) so we can actually see the
 # horizontal and rotational elements react.
-# The state matrix has 6 columns: [x, y, theta, vx, vy, omega]
-time_3dof, state_3dof = rocket.simulate_flight_3dof(
+# Its state matrix has 9 columns, listed in flight_3dof.columns: the state [x, y, theta, vx, vy,
+# omega] and the accelerations. Derived quantities such as the Mach number and dynamic pressure
+# are only logged when requested.
+flight_3dof = rocket.simulate_flight_3dof(
     initial_state=initial_state_3dof,
     integration_method=OdeMethod.RK45,
+    derived_channels=[DerivedChannel.Mach, DerivedChannel.DynamicPressure],
 )
 
-# You can access the full 3DOF state space by slicing each column of the matrix
-x_3dof = state_3dof[:, 0]
-y_3dof = state_3dof[:, 1]
-theta_3dof = state_3dof[:, 2]
-vx_3dof = state_3dof[:, 3]
-vy_3dof = state_3dof[:, 4]
-omega_3dof = state_3dof[:, 5]
+# You can access the full 3DOF state space by the name of each column
+time_3dof = flight_3dof.time
+x_3dof = flight_3dof.downrange
+y_3dof = flight_3dof.altitude
+theta_3dof = flight_3dof.column("angle")
+vx_3dof = flight_3dof.column("horizontal_velocity")
+vy_3dof = flight_3dof.vertical_velocity
+omega_3dof = flight_3dof.column("angular_rate")
+
+# The derived channels are accessed by name as well
+print(f"Max Mach: {flight_3dof.column('mach').max():.3f}")
+
+# The summary reports the milestones of the flight, located between the logged rows, including the
+# landing after the logged ascent
+summary = flight_3dof.summary
+print(f"Apogee: {summary.apogee.value:.1f} m at {summary.apogee.time:.2f} s")
+print(f"Max-Q: {summary.max_dynamic_pressure.value:.0f} Pa")
+print(f"Landing: {summary.landing.downrange:.1f} m downrange at {summary.landing.value:.1f} m/s")
 
 # Plots the flights
 fig = make_subplots(
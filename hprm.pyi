@@ -1,7 +1,7 @@
 from __future__ import annotations
 
 from enum import Enum
-from typing import Optional
+from typing import Callable, Optional
 import numpy as np
 
 class OdeMethod(Enum):
@@ -178,157 +178,2940 @@ class InitialState3DOF:
         """
         ...
 
-class RocketProperties:
+class SkinFriction:
     """
-    Internal physical property group for the rocket.
-    Accessible from Python for reading or updating fields dynamically.
+    Reynolds number dependent skin friction drag. When a rocket has a skin friction model, its cd
+    only represents the pressure and base drag, and the friction drag is computed from the local
+    Reynolds number (laminar, turbulent or roughness-limited) on top of it.
+
+    The Reynolds number uses the International Standard Atmosphere density and Sutherland's
+    law viscosity at the rocket's altitude, so it falls as the air thins on the way to apogee. The
+    drag forces themselves still use the sea level air density.
     """
 
-    mass: float
+    wetted_area: float
     """
-    Mass of the rocket in kilograms (kg).
+    Wetted area of the rocket, including any form factors, in square meters (m²).
     """
-
-    cd: float
+    length: float
     """
-    Drag coefficient (dimensionless).
+    Length used for the Reynolds number, usually the length of the rocket, in meters (m).
+    """
+    roughness: float
+    """
+    Surface roughness height in meters (m).
+    """
+    transition_reynolds: float
+    """
+    Reynolds number at which the boundary layer becomes turbulent, 0 for fully turbulent flow.
     """
 
-    area_drag: float
+    def __init__(
+        self,
+        wetted_area: float,
+        length: float,
+        roughness: float = 0.0,
+        transition_reynolds: float = 5e5,
+    ) -> None: ...
+    def drag_coefficient(
+        self, velocity: float, reference_area: float, altitude: float = 0.0
+    ) -> float:
+        """
+        Friction drag coefficient at the given airspeed and altitude.
+
+        :param velocity: Airspeed in meters per second (m/s).
+        :param reference_area: Reference area of the coefficient in square meters (m²).
+        :param altitude: Altitude of the standard atmosphere in meters (m).
+        """
+        ...
+
+class AeroTable:
     """
-    Reference area for drag in square meters (m²).
+    Axial force coefficient, normal force coefficient and center of pressure tables as a function
+    of Mach number and angle of attack, e.g. exported from RASAero II or CFD. The tables are
+    interpolated bilinearly and clamped at their edges. Only positive angles of attack are
+    tabulated since the rocket is assumed to be symmetric.
+
+    When a rocket has an aero table, it replaces cd, cl_a and stab_margin_dimensional. The 3-DOF
+    model applies the forces in body axes and uses the tabulated CP together with the rocket's
+    cg_location, while the 1-DOF model uses the axial force coefficient at zero angle of attack.
     """
 
-    area_lift: float
+    mach: list[float]
+    """
+    Mach number breakpoints.
     """
-    Reference area for lift in square meters (m²).
+    alpha: list[float]
+    """
+    Angle of attack breakpoints in radians.
     """
 
-    moment_of_inertia: float
+    def __init__(
+        self,
+        mach: np.ndarray,
+        alpha: np.ndarray,
+        ca: np.ndarray,
+        cn: np.ndarray,
+        cp: np.ndarray,
+    ) -> None:
+        """
+        :param mach: Strictly increasing Mach number breakpoints.
+        :param alpha: Strictly increasing angle of attack breakpoints in radians.
+        :param ca: Axial force coefficients with shape (len(mach), len(alpha)).
+        :param cn: Normal force coefficients with shape (len(mach), len(alpha)).
+        :param cp: Distances from the nose tip to the center of pressure in meters, with shape
+            (len(mach), len(alpha)).
+        """
+        ...
+    @staticmethod
+    def from_csv(
+        path: str,
+        mach_column: str = "Mach",
+        alpha_column: str = "Alpha",
+        ca_column: str = "CA",
+        cn_column: str = "CN",
+        cp_column: str = "CP",
+        alpha_in_degrees: bool = True,
+        cp_scale: float = 1.0,
+    ) -> AeroTable:
+        """
+        Loads a table from a long format CSV file with one row per (Mach, alpha) pair, like the
+        aerodynamic exports of RASAero II. The rows must cover the full Mach x alpha grid.
+
+        :param path: Path to the CSV file.
+        :param alpha_in_degrees: Whether the angle of attack column is in degrees.
+        :param cp_scale: Factor converting the CP column to meters, e.g. 0.0254 for inches.
+        """
+        ...
+    def lookup(self, mach: float, alpha: float) -> tuple[float, float, float]:
+        """
+        Interpolated (CA, CN, CP) at the given Mach number and angle of attack in radians.
+        """
+        ...
+
+class Curve:
     """
-    Moment of inertia about the z-axis in kilogram square meters (kg·m²).
+    A 1D function, given either as a piecewise linear table or as a polynomial. Used for the
+    deployment and Mach number dependence of add-ons like airbrakes.
     """
 
-    stab_margin_dimensional: float
+    @staticmethod
+    def table(x: list[float], y: list[float]) -> Curve:
+        """
+        Piecewise linear table through the points (x, y), holding the end values outside of it.
+
+        :param x: Strictly increasing x values.
+        :param y: Function values at x.
+        """
+        ...
+    @staticmethod
+    def polynomial(coefficients: list[float]) -> Curve:
+        """
+        Polynomial c0 + c1*x + c2*x² + ..., with the coefficients in increasing order of power.
+        """
+        ...
+    @staticmethod
+    def constant(value: float) -> Curve:
+        """
+        Constant function.
+        """
+        ...
+    def evaluate(self, x: float) -> float:
+        """
+        Evaluates the curve at x.
+        """
+        ...
+
+class Actuator:
     """
-    Static stability margin in meters (m).
+    Actuator dynamics for add-ons like airbrakes. The actuator's states are integrated together
+    with the flight dynamics, and its position (a fraction from 0 to 1) lags behind the commanded
+    position. The position is saturated to [0, 1].
     """
 
-    cl_a: float
+    initial_position: float
     """
-    Lift coefficient slope per radian (1/rad).
+    Position of the actuator at the start of the simulation, from 0 to 1.
     """
 
-class Rocket:
+    @staticmethod
+    def first_order(time_constant: float, initial_position: float = 0.0) -> Actuator:
+        """
+        First-order lag, dx/dt = (command - x) / time_constant.
+
+        :param time_constant: Time constant in seconds (s).
+        """
+        ...
+    @staticmethod
+    def second_order(
+        natural_frequency: float,
+        damping_ratio: float,
+        rate_limit: float = float("inf"),
+        initial_position: float = 0.0,
+    ) -> Actuator:
+        """
+        Second-order servo with rate saturation.
+
+        :param natural_frequency: Natural frequency in radians per second (rad/s).
+        :param damping_ratio: Damping ratio (dimensionless).
+        :param rate_limit: Maximum rate of the position in fractions per second (1/s).
+        """
+        ...
+    @staticmethod
+    def dc_motor(
+        resistance: float,
+        inductance: float,
+        torque_constant: float,
+        inertia: float,
+        viscous_damping: float,
+        travel: float,
+        position_gain: float,
+        max_voltage: float,
+        initial_position: float = 0.0,
+    ) -> Actuator:
+        """
+        Proportionally controlled DC motor. The motor parameters are referred to the output
+        shaft of the gearbox.
+
+        :param resistance: Armature resistance in ohms (Ω).
+        :param inductance: Armature inductance in henries (H).
+        :param torque_constant: Torque constant in N·m/A, equal to the back-EMF constant in V·s/rad.
+        :param inertia: Rotor and mechanism inertia in kg·m².
+        :param viscous_damping: Viscous damping in N·m·s/rad.
+        :param travel: Shaft angle for full deployment in radians (rad).
+        :param position_gain: Motor voltage per radian of angle error (V/rad).
+        :param max_voltage: Supply voltage limit in volts (V).
+        """
+        ...
+
+class Airbrake:
     """
-    The main class for simulating rocket flight. Contains methods for 1-DOF and 3-DOF simulations,
-    as well as apogee predictions.
+    Airbrake add-on which increases the drag of the rocket as it deploys. It works with both the
+    1-DOF and 3-DOF models. With deployment d and Mach number M, the rocket's drag becomes
+
+        D = q * (cd + cd_increment(d) * mach_factor(M)) * (area_drag + area_increment(d))
+
+    Without an actuator the airbrakes are instantly at deployment. With an actuator, deployment is
+    the commanded position and the actual deployment follows the actuator dynamics.
     """
 
-    rocket_properties: RocketProperties
+    deployment: float
     """
-    The physical properties of the rocket.
+    Deployment fraction from 0 (retracted) to 1 (fully deployed). Values outside of this range
+    are clamped.
+    """
+    cd_increment: Curve
+    """
+    Drag coefficient increment as a function of the deployment fraction.
+    """
+    area_increment: Optional[Curve]
+    """
+    Extra drag reference area as a function of the deployment fraction in square meters (m²).
+    """
+    mach_factor: Optional[Curve]
+    """
+    Optional factor on the drag coefficient increment as a function of Mach number.
+    """
+    actuator: Optional[Actuator]
+    """
+    Optional actuator dynamics between the commanded and actual deployment.
     """
 
     def __init__(
         self,
-        mass: float,
-        cd: float,
-        area_drag: float,
-        area_lift: float,
-        moment_of_inertia: float,
-        stab_margin_dimensional: float,
-        cl_a: float,
-    ) -> None:
+        cd_increment: Curve,
+        area_increment: Optional[Curve] = None,
+        mach_factor: Optional[Curve] = None,
+        deployment: float = 0.0,
+        actuator: Optional[Actuator] = None,
+    ) -> None: ...
+    def increments(self, mach: float) -> tuple[float, float]:
         """
-        Creates a new Rocket instance and initializes its underlying RocketProperties group.
+        Drag coefficient and reference area increments at deployment and the given Mach number.
+        """
+        ...
 
-        :param mass: Mass of the rocket in kilograms.
-        :param cd: Drag coefficient.
-        :param area_drag: Reference area for drag in square meters.
-        :param area_lift: Reference area for lift in square meters.
-        :param moment_of_inertia: Moment of inertia about the z-axis in kg·m².
-        :param stab_margin_dimensional: Static stability margin in meters.
-        :param cl_a: Lift coefficient slope per radian.
+class Controller:
+    """
+    Closed-loop airbrake controller which runs inside the simulation. At every update it sees the
+    simulated state and the apogee predicted with the current command held, and outputs a new
+    airbrake deployment command (0 to 1). The command is held constant between updates, and the
+    timesteps of the simulation end at the update times so that every update is run.
+
+    The apogee is predicted with the same model (1-DOF or 3-DOF) as the simulated flight, assuming
+    the airbrakes instantly move to the command.
+    """
+
+    rate_hz: float
+    """
+    Update rate of the controller in hertz (Hz).
+    """
+
+    @staticmethod
+    def pid(
+        target_apogee: float,
+        kp: float,
+        ki: float = 0.0,
+        kd: float = 0.0,
+        rate_hz: float = 100.0,
+    ) -> Controller:
+        """
+        PID controller on the apogee error (predicted - target, in meters). The integral is only
+        accumulated while the command is not saturated, to avoid windup.
+
+        :param target_apogee: Target apogee in meters (m).
+        :param kp: Proportional gain in deployment per meter (1/m).
+        :param ki: Integral gain in deployment per meter second (1/(m·s)).
+        :param kd: Derivative gain in deployment seconds per meter (s/m).
+        :param rate_hz: Update rate in hertz (Hz).
         """
         ...
+    @staticmethod
+    def bang_bang(target_apogee: float, deadband: float = 0.0, rate_hz: float = 100.0) -> Controller:
+        """
+        Bang-bang controller which fully deploys when the predicted apogee is more than deadband
+        above the target, retracts when it is more than deadband below, and holds the command in
+        between.
 
-    def simulate_flight_1dof(
-        self,
-        initial_state: InitialState1DOF,
-        integration_method: OdeMethod,
-        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
-        max_iterations: int = 100000,
-        print_output: bool = False,
-    ) -> tuple[np.ndarray, np.ndarray]:
+        :param target_apogee: Target apogee in meters (m).
+        :param deadband: Deadband around the target in meters (m).
+        :param rate_hz: Update rate in hertz (Hz).
         """
-        Simulate the rocket's flight using a 1-DOF model (vertical motion only).
+        ...
+    @staticmethod
+    def python(
+        callback: Callable[[float, list[float], float], float], rate_hz: float = 100.0
+    ) -> Controller:
+        """
+        Controller which calls callback(time, state, predicted_apogee), where state is the current
+        row of the state log, and uses its return value as the command.
 
-        :param initial_state: The initial height and velocity of the rocket.
-        :param integration_method: Numerical integration method to use.
-        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
-        :param max_iterations: Maximum integration iterations allowed.
-        :param print_output: Whether to print simulation progress to stdout.
-        :return: A tuple containing (time_array, state_matrix) as NumPy arrays.
+        :param callback: Control law returning the deployment command (0 to 1).
+        :param rate_hz: Update rate in hertz (Hz).
         """
         ...
 
-    def simulate_flight_3dof(
-        self,
-        initial_state: InitialState3DOF,
+class PredictionStatus(Enum):
+    """
+    Outcome of a simulation run, e.g. of an apogee prediction.
+    """
+
+    Converged = 0
+    """
+    The exit condition (e.g. apogee) was reached.
+    """
+
+    BudgetExhausted = 1
+    """
+    The iteration budget or the deadline ran out before the exit condition was reached.
+    """
+
+    Diverged = 2
+    """
+    The state stopped being finite, e.g. because of a bad initial state or timestep.
+    """
+
+class ApogeePrediction:
+    """
+    Apogee prediction together with how it ended.
+    """
+
+    apogee: float
+    """
+    Predicted apogee in meters (m). If the prediction didn't converge, this is the best estimate so
+    far: the current altitude plus the height the rocket would still coast with constant drag.
+    """
+    status: PredictionStatus
+    iterations: int
+    """
+    Number of timesteps taken.
+    """
+    elapsed: float
+    """
+    Wall-clock time the prediction took in seconds (s).
+    """
+
+class ApogeePredictor:
+    """
+    Long-lived apogee predictor for flight computers, which are called many times a second with
+    the latest estimate of the rocket's state.
+
+    The rocket and solver configuration are set up once, the simulation log is reused between
+    calls, and an adaptive solver starts from the step size it adapted to in the previous call, so
+    update doesn't allocate. examples/predictor_latency.py measures the latency of updates against
+    fresh predictions on your hardware.
+    """
+
+    step_size: float
+    """
+    Step size to start the next prediction from in seconds (s).
+    """
+    last_time: float
+    """
+    Time of the last update in seconds (s), or NaN before the first update.
+    """
+    apogee: float
+    """
+    Apogee predicted by the last update in meters (m), or NaN before the first update. If the
+    update didn't converge, this is the best estimate it found.
+    """
+    status: PredictionStatus
+    """
+    How the last update ended.
+    """
+    deadline: Optional[float]
+    """
+    Wall-clock time each update may take in seconds (s), or None for no limit.
+    """
+
+    @staticmethod
+    def new_1dof(
+        rocket: Rocket,
         integration_method: OdeMethod,
         timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
         max_iterations: int = 100000,
-        print_output: bool = False,
-    ) -> tuple[np.ndarray, np.ndarray]:
+        deadline: Optional[float] = None,
+    ) -> ApogeePredictor:
         """
-        Simulate the rocket's flight using a 3-DOF model (2D translation and rotation).
+        Predictor which uses the 1-DOF model.
 
-        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param rocket: The rocket, which is copied.
         :param integration_method: Numerical integration method to use.
         :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
-        :param max_iterations: Maximum integration iterations allowed.
-        :param print_output: Whether to print simulation progress to stdout.
-        :return: A tuple containing (time_array, state_matrix) as NumPy arrays.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :param deadline: Wall-clock time each update may take in seconds (s), or None for no limit.
         """
         ...
-
-    def predict_apogee_1dof(
-        self,
-        initial_state: InitialState1DOF,
+    @staticmethod
+    def new_3dof(
+        rocket: Rocket,
         integration_method: OdeMethod,
         timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
         max_iterations: int = 100000,
-        print_output: bool = False,
-    ) -> float:
+        deadline: Optional[float] = None,
+    ) -> ApogeePredictor:
         """
-        Predict the apogee (maximum altitude) using a 1-DOF model.
+        Predictor which uses the 3-DOF model.
 
-        :param initial_state: The initial height and velocity of the rocket.
+        :param rocket: The rocket, which is copied.
         :param integration_method: Numerical integration method to use.
         :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
-        :param max_iterations: Maximum integration iterations allowed.
-        :param print_output: Whether to print simulation progress to stdout.
-        :return: Maximum altitude reached in meters.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :param deadline: Wall-clock time each update may take in seconds (s), or None for no limit.
         """
         ...
-
-    def predict_apogee_3dof(
+    def update(
         self,
-        initial_state: InitialState3DOF,
-        integration_method: OdeMethod,
-        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
-        max_iterations: int = 100000,
-        print_output: bool = False,
+        time: float,
+        altitude: float,
+        vertical_velocity: float,
+        horizontal_velocity: float = 0.0,
+        angle: float = 1.5707963267948966,
+        angular_rate: float = 0.0,
+        airbrake_deployment: Optional[float] = None,
     ) -> float:
         """
-        Predict the apogee (maximum altitude) using a 3-DOF model.
+        Predict the apogee from the given state. The 1-DOF model only uses the altitude and
+        vertical velocity.
 
-        :param initial_state: The initial 6-DOF condition of the rocket.
-        :param integration_method: Numerical integration method to use.
-        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
-        :param max_iterations: Maximum integration iterations allowed.
-        :param print_output: Whether to print simulation progress to stdout.
+        :param time: Time of the state in seconds (s). If it is earlier than the last update (e.g.
+            a new flight), the warm start is reset.
+        :param altitude: Altitude in meters (m).
+        :param vertical_velocity: Vertical velocity in meters per second (m/s).
+        :param horizontal_velocity: Horizontal velocity in meters per second (m/s).
+        :param angle: Pitch angle from the horizon in radians (rad).
+        :param angular_rate: Pitch rate in radians per second (rad/s).
+        :param airbrake_deployment: Holds the airbrakes at this deployment for this and all later
+            predictions. The rocket must have an airbrake.
         :return: Maximum altitude reached in meters.
         """
         ...
+    def reset(self) -> None:
+        """
+        Reset the warm start and the last prediction.
+        """
+        ...
+
+class EventKind(Enum):
+    """
+    Kind of event in a flight.
+    """
+
+    Apogee = 0
+    """
+    The vertical velocity reached zero.
+    """
+
+    Burnout = 1
+    """
+    The motor burned out, at the rocket's burn_time. Flights which start at burnout have a
+    burn_time of 0, so this is their initial state.
+    """
+
+    MaxSpeed = 2
+    """
+    The speed relative to the ground peaked.
+    """
+
+    MaxMach = 3
+    """
+    The Mach number peaked.
+    """
+
+    MaxAcceleration = 4
+    """
+    The magnitude of the acceleration peaked.
+    """
+
+    MaxDynamicPressure = 5
+    """
+    The dynamic pressure peaked (max-Q).
+    """
+
+    AirbrakeDeployment = 6
+    """
+    The airbrakes were first commanded to deploy.
+    """
+
+    Landing = 7
+    """
+    The rocket hit the ground.
+    """
+
+class FlightEvent:
+    """
+    An event in a flight. Events are detected between the timesteps of the simulation, e.g. by
+    backtracking to the apogee, rather than taken from the nearest logged row.
+    """
+
+    kind: EventKind
+    time: float
+    """
+    Time of the event in seconds (s).
+    """
+    altitude: float
+    """
+    Altitude at the event in meters (m).
+    """
+    downrange: float
+    """
+    Horizontal position at the event in meters (m), always zero for the 1-DOF model.
+    """
+    value: float
+    """
+    Value of the quantity the event is about, e.g. the speed for MaxSpeed or the altitude for
+    Apogee.
+    """
+
+class FlightSummary:
+    """
+    Summary of a flight, with the event of each milestone, or None if the flight didn't get there.
+    The models have no thrust or launch rail, so there is no rail exit, and burnout is at the
+    rocket's burn_time: the initial state for flights which start at burnout.
+    """
+
+    burnout: Optional[FlightEvent]
+    """
+    Motor burnout at the rocket's burn_time, or the initial state if it is 0, with the speed as its
+    value.
+    """
+    max_speed: Optional[FlightEvent]
+    """
+    Largest speed relative to the ground in meters per second (m/s).
+    """
+    max_mach: Optional[FlightEvent]
+    """
+    Largest Mach number.
+    """
+    max_acceleration: Optional[FlightEvent]
+    """
+    Largest magnitude of the acceleration in meters per second squared (m/s^2).
+    """
+    max_dynamic_pressure: Optional[FlightEvent]
+    """
+    Largest dynamic pressure in pascals (Pa).
+    """
+    airbrake_deployment: Optional[FlightEvent]
+    """
+    First time the airbrakes were commanded to deploy, with the commanded deployment as its value.
+    """
+    apogee: Optional[FlightEvent]
+    """
+    The apogee, with the altitude as its value.
+    """
+    landing: Optional[FlightEvent]
+    """
+    Impact with the ground after a ballistic descent, with the speed as its value.
+    """
+
+class DerivedChannel(Enum):
+    """
+    Quantity derived from the state of a flight, which can be logged alongside the states.
+    """
+
+    Mach = 0
+    """
+    Airspeed divided by the speed of sound. Logged as "mach".
+    """
+
+    DynamicPressure = 1
+    """
+    Dynamic pressure 1/2 * rho * V^2 of the airspeed in pascals (Pa). Logged as
+    "dynamic_pressure".
+    """
+
+    AngleOfAttack = 2
+    """
+    Angle between the rocket's axis and the airspeed in radians (rad), positive with the rocket
+    pointing counter-clockwise of it. Always zero for the 1-DOF model. Logged as
+    "angle_of_attack".
+    """
+
+    DragForce = 3
+    """
+    Aerodynamic force opposing the airspeed in newtons (N). Logged as "drag_force".
+    """
+
+    LiftForce = 4
+    """
+    Aerodynamic force perpendicular to the airspeed, towards the side the nose points to, in
+    newtons (N). Always zero for the 1-DOF model. Logged as "lift_force".
+    """
+
+    Density = 5
+    """
+    Air density in kilograms per cubic meter (kg/m^3). Logged as "density".
+    """
+
+    StabilityCalibers = 6
+    """
+    Static stability margin in body diameters (calibers), using the reference length or else the
+    diameter of the drag reference area. Logged as "stability_calibers".
+    """
+
+class SimulationResult:
+    """
+    Logged flight of a simulation, with a column per logged quantity of the model and the
+    settings the flight was simulated with. The 1-DOF model logs altitude, vertical_velocity and
+    vertical_acceleration; the 3-DOF model logs downrange, altitude, angle, horizontal_velocity,
+    vertical_velocity, angular_rate, horizontal_acceleration, vertical_acceleration and
+    angular_acceleration.
+    """
+
+    time: np.ndarray
+    """
+    Logged times in seconds (s).
+    """
+    states: np.ndarray
+    """
+    Logged states, with a row per time and a column per entry of columns.
+    """
+    columns: list[str]
+    """
+    Name of each column of states.
+    """
+    units: list[str]
+    """
+    Unit of each column of states.
+    """
+    altitude: np.ndarray
+    """
+    Altitude in meters (m) at each logged time.
+    """
+    vertical_velocity: np.ndarray
+    """
+    Vertical velocity in meters per second (m/s) at each logged time.
+    """
+    vertical_acceleration: np.ndarray
+    """
+    Vertical acceleration in meters per second squared (m/s^2) at each logged time.
+    """
+    downrange: np.ndarray
+    """
+    Horizontal position in meters (m) at each logged time, which is always zero for the 1-DOF
+    model.
+    """
+    speed: np.ndarray
+    """
+    Speed in meters per second (m/s) at each logged time.
+    """
+    derived: np.ndarray
+    """
+    Logged derived channels, with a row per time and a column per entry of derived_channels. Has
+    no columns if no channels were requested.
+    """
+    derived_channels: list[DerivedChannel]
+    """
+    The derived channels which were logged, in the column order of derived.
+    """
+    derived_columns: list[str]
+    """
+    Name of each column of derived.
+    """
+    derived_units: list[str]
+    """
+    Unit of each column of derived.
+    """
+    control: Optional[np.ndarray]
+    """
+    Control log of a controlled flight, with a row per controller update and a column per entry
+    of control_columns, or None for an uncontrolled flight.
+    """
+    control_columns: list[str]
+    """
+    Name of each column of control.
+    """
+    rocket: Rocket
+    """
+    The rocket the flight was simulated with.
+    """
+    integration_method: OdeMethod
+    """
+    The integration method the flight was simulated with.
+    """
+    timestep_config: FixedTimeStep | AdaptiveTimeStep
+    """
+    The time step configuration the flight was simulated with.
+    """
+    status: PredictionStatus
+    """
+    How the simulation ended: Converged if it reached apogee.
+    """
+    summary: FlightSummary
+    """
+    Summary of the flight. A flight which reached apogee is continued on to the ground for the
+    summary, so it includes the descent and landing which aren't logged.
+    """
+    events: list[FlightEvent]
+    """
+    Events of the flight in time order, from the summary.
+    """
+
+    def column(self, name: str) -> np.ndarray:
+        """
+        Get the values of a column of states or derived at each logged time.
+
+        :param name: Name of the column, one of columns or derived_columns.
+        :return: The values of the column.
+        :raises ValueError: If the column wasn't logged.
+        """
+        ...
+    def __len__(self) -> int: ...
+
+class SolveStatus(Enum):
+    """
+    Outcome of a targeting search.
+    """
+
+    Converged = 0
+    """
+    The output is within the tolerance of the target.
+    """
+
+    MaxEvaluations = 1
+    """
+    The evaluation budget ran out before the tolerance was met. The best value found so far is
+    reported.
+    """
+
+    TargetUnreachable = 2
+    """
+    The target lies outside the range of outputs at the bounds of the search. The bound which
+    comes closest is reported.
+    """
+
+class SolveReport:
+    """
+    Result of a targeting search, e.g. the airbrake deployment which hits a target apogee.
+    """
+
+    value: float
+    """
+    The value of the searched variable.
+    """
+    achieved: float
+    """
+    The output (e.g. apogee) achieved with value.
+    """
+    target: float
+    """
+    The output which was searched for.
+    """
+    evaluations: int
+    """
+    Number of times the output was evaluated (i.e. simulations run).
+    """
+    status: SolveStatus
+
+class LaunchObjective(Enum):
+    """
+    What a launch angle search optimizes.
+    """
+
+    MaxApogee = 0
+    """
+    Maximize the apogee.
+    """
+
+    MinDrift = 1
+    """
+    Minimize the horizontal distance between the initial and landing positions.
+    """
+
+    LandingTarget = 2
+    """
+    Land as close as possible to a target downrange position.
+    """
+
+class LaunchOptimum:
+    """
+    Result of a launch angle search.
+    """
+
+    angle: float
+    """
+    The optimal launch angle in radians (rad), with the same convention as the 3-DOF state's
+    angle.
+    """
+    initial_state: InitialState3DOF
+    """
+    The initial state launched at angle, e.g. to simulate the optimal flight.
+    """
+    apogee: float
+    """
+    Apogee of the optimal flight in meters (m).
+    """
+    landing_downrange: float
+    """
+    Downrange position at which the optimal flight lands in meters (m).
+    """
+    evaluations: int
+    """
+    Number of flights simulated.
+    """
+    status: SolveStatus
+    """
+    Converged if the optimal angle was bracketed to within the tolerance, otherwise
+    MaxEvaluations.
+    """
+
+class Measurement:
+    """
+    A sensor reading, together with the model which predicts it from a state estimator's state.
+    """
+
+    noise_std: float
+    """
+    Standard deviation of the sensor noise, in the units of the reading.
+    """
+
+    @staticmethod
+    def barometric_altitude(altitude: float, noise_std: float) -> Measurement:
+        """
+        Altitude from a barometer in meters (m).
+        """
+        ...
+    @staticmethod
+    def axial_acceleration(acceleration: float, noise_std: float) -> Measurement:
+        """
+        Specific force along the rocket's axis from an accelerometer in meters per second squared
+        (m/s^2), which is 0 in free fall. With the 1-DOF model, the rocket's axis is vertical.
+        """
+        ...
+    @staticmethod
+    def gps(altitude: float, noise_std: float, downrange: Optional[float] = None) -> Measurement:
+        """
+        Position from a GPS receiver in meters (m). The downrange (horizontal) position can only be
+        used with the 3-DOF model.
+        """
+        ...
+
+class ExtendedKalmanFilter:
+    """
+    Extended Kalman filter which uses the 1-DOF or 3-DOF model as its process model, for fusing
+    sensor data on a flight computer. The state can be augmented with a bias on the drag
+    coefficient, which is estimated alongside the flight state. The Jacobians of the process and
+    measurement models are exact derivatives, computed with forward-mode dual numbers. The airbrakes
+    are treated as held at their current deployment.
+    """
+
+    state: np.ndarray
+    """
+    Current state estimate.
+    """
+    covariance: np.ndarray
+    """
+    Covariance of the current state estimate.
+    """
+
+    @staticmethod
+    def new_1dof(
+        rocket: Rocket,
+        initial_state: InitialState1DOF,
+        initial_covariance: list[float],
+        process_noise: list[float],
+        estimate_cd_bias: bool = False,
+        integration_method: OdeMethod = OdeMethod.RK3,
+        max_step: float = 0.01,
+    ) -> ExtendedKalmanFilter:
+        """
+        Filter which uses the 1-DOF model, with the state (altitude, vertical velocity), followed by
+        the cd bias if estimate_cd_bias is set.
+
+        :param rocket: The rocket, which is copied.
+        :param initial_state: The initial state estimate.
+        :param initial_covariance: Variance of each state of the initial estimate.
+        :param process_noise: Variance added to each state per second of prediction.
+        :param estimate_cd_bias: Whether to estimate a bias on cd. Not possible with an aero table.
+        :param integration_method: Fixed timestep integration method (Euler or RK3).
+        :param max_step: Longest integration step in seconds (s) a prediction is split into.
+        :raises ValueError: If the covariances have the wrong length or the configuration is
+            invalid.
+        """
+        ...
+    @staticmethod
+    def new_3dof(
+        rocket: Rocket,
+        initial_state: InitialState3DOF,
+        initial_covariance: list[float],
+        process_noise: list[float],
+        estimate_cd_bias: bool = False,
+        integration_method: OdeMethod = OdeMethod.RK3,
+        max_step: float = 0.01,
+    ) -> ExtendedKalmanFilter:
+        """
+        Filter which uses the 3-DOF model, with the state (x, altitude, angle, vx, vy, angular
+        rate), followed by the cd bias if estimate_cd_bias is set.
+
+        :param rocket: The rocket, which is copied.
+        :param initial_state: The initial state estimate.
+        :param initial_covariance: Variance of each state of the initial estimate.
+        :param process_noise: Variance added to each state per second of prediction.
+        :param estimate_cd_bias: Whether to estimate a bias on cd. Not possible with an aero table.
+        :param integration_method: Fixed timestep integration method (Euler or RK3).
+        :param max_step: Longest integration step in seconds (s) a prediction is split into.
+        :raises ValueError: If the covariances have the wrong length or the configuration is
+            invalid.
+        """
+        ...
+    def predict(self, dt: float) -> None:
+        """
+        Propagate the estimate dt seconds forward with the process model.
+        """
+        ...
+    def update(self, measurement: Measurement) -> None:
+        """
+        Correct the estimate with a measurement.
+
+        :raises ValueError: If the measurement can't be predicted by the model.
+        """
+        ...
+
+class ApogeeDistribution:
+    """
+    Distribution of the apogee predicted from the samples (sigma points or particles) of a state
+    estimator.
+    """
+
+    mean: float
+    """
+    Mean apogee in meters (m).
+    """
+    std: float
+    """
+    Standard deviation of the apogee in meters (m).
+    """
+    samples: np.ndarray
+    """
+    Apogee predicted from each sample in meters (m).
+    """
+    weights: np.ndarray
+    """
+    Weight of each sample in the mean. Sigma point weights can be negative.
+    """
+
+class UnscentedKalmanFilter:
+    """
+    Unscented Kalman filter which uses the 1-DOF or 3-DOF model as its process model. Instead of
+    linearising the models, a set of sigma points is propagated through them with the scaled
+    unscented transform, which captures the nonlinearity of the drag. The airbrakes are treated as
+    held at their current deployment.
+    """
+
+    state: np.ndarray
+    """
+    Current state estimate.
+    """
+    covariance: np.ndarray
+    """
+    Covariance of the current state estimate.
+    """
+
+    @staticmethod
+    def new_1dof(
+        rocket: Rocket,
+        initial_state: InitialState1DOF,
+        initial_covariance: list[float],
+        process_noise: list[float],
+        estimate_cd_bias: bool = False,
+        integration_method: OdeMethod = OdeMethod.RK3,
+        max_step: float = 0.01,
+        alpha: float = 1e-3,
+        beta: float = 2.0,
+        kappa: float = 0.0,
+    ) -> UnscentedKalmanFilter:
+        """
+        Filter which uses the 1-DOF model, with the state (altitude, vertical velocity), followed by
+        the cd bias if estimate_cd_bias is set.
+
+        :param rocket: The rocket, which is copied.
+        :param initial_state: The initial state estimate.
+        :param initial_covariance: Variance of each state of the initial estimate.
+        :param process_noise: Variance added to each state per second of prediction.
+        :param estimate_cd_bias: Whether to estimate a bias on cd. Not possible with an aero table.
+        :param integration_method: Fixed timestep integration method (Euler or RK3).
+        :param max_step: Longest integration step in seconds (s) a prediction is split into.
+        :param alpha: Spread of the sigma points about the mean.
+        :param beta: Prior knowledge of the distribution; 2 is optimal for Gaussians.
+        :param kappa: Secondary scaling parameter of the sigma points.
+        :raises ValueError: If the covariances have the wrong length or the configuration is
+            invalid.
+        """
+        ...
+    @staticmethod
+    def new_3dof(
+        rocket: Rocket,
+        initial_state: InitialState3DOF,
+        initial_covariance: list[float],
+        process_noise: list[float],
+        estimate_cd_bias: bool = False,
+        integration_method: OdeMethod = OdeMethod.RK3,
+        max_step: float = 0.01,
+        alpha: float = 1e-3,
+        beta: float = 2.0,
+        kappa: float = 0.0,
+    ) -> UnscentedKalmanFilter:
+        """
+        Filter which uses the 3-DOF model, with the state (x, altitude, angle, vx, vy, angular
+        rate), followed by the cd bias if estimate_cd_bias is set.
+
+        :param rocket: The rocket, which is copied.
+        :param initial_state: The initial state estimate.
+        :param initial_covariance: Variance of each state of the initial estimate.
+        :param process_noise: Variance added to each state per second of prediction.
+        :param estimate_cd_bias: Whether to estimate a bias on cd. Not possible with an aero table.
+        :param integration_method: Fixed timestep integration method (Euler or RK3).
+        :param max_step: Longest integration step in seconds (s) a prediction is split into.
+        :param alpha: Spread of the sigma points about the mean.
+        :param beta: Prior knowledge of the distribution; 2 is optimal for Gaussians.
+        :param kappa: Secondary scaling parameter of the sigma points.
+        :raises ValueError: If the covariances have the wrong length or the configuration is
+            invalid.
+        """
+        ...
+    def predict(self, dt: float) -> None:
+        """
+        Propagate the estimate dt seconds forward with the process model.
+
+        :raises ValueError: If the covariance is no longer positive definite.
+        """
+        ...
+    def update(self, measurement: Measurement) -> None:
+        """
+        Correct the estimate with a measurement.
+
+        :raises ValueError: If the measurement can't be predicted by the model or the covariance
+            is no longer positive definite.
+        """
+        ...
+    def apogee_distribution(self) -> ApogeeDistribution:
+        """
+        Distribution of the apogee, predicted from each sigma point of the current estimate.
+
+        :raises ValueError: If the covariance is no longer positive definite.
+        """
+        ...
+
+class ParticleFilter:
+    """
+    Bootstrap particle filter which uses the 1-DOF or 3-DOF model as its process model. The
+    estimate is represented by weighted samples (particles), so it copes with strongly non-Gaussian
+    uncertainty at the cost of propagating every particle. The particles are resampled
+    systematically when the effective sample size falls below half the number of particles.
+    """
+
+    state: np.ndarray
+    """
+    Weighted mean of the particles.
+    """
+    covariance: np.ndarray
+    """
+    Weighted covariance of the particles.
+    """
+    weights: np.ndarray
+    """
+    Weight of each particle, which sum to 1.
+    """
+    effective_sample_size: float
+    """
+    Effective number of particles, 1 / sum(w^2).
+    """
+
+    @staticmethod
+    def new_1dof(
+        rocket: Rocket,
+        initial_state: InitialState1DOF,
+        initial_covariance: list[float],
+        process_noise: list[float],
+        num_particles: int = 1000,
+        seed: int = 0,
+        estimate_cd_bias: bool = False,
+        integration_method: OdeMethod = OdeMethod.RK3,
+        max_step: float = 0.01,
+    ) -> ParticleFilter:
+        """
+        Filter which uses the 1-DOF model, with the state (altitude, vertical velocity), followed by
+        the cd bias if estimate_cd_bias is set.
+
+        :param rocket: The rocket, which is copied.
+        :param initial_state: The initial state estimate.
+        :param initial_covariance: Variance of each state of the initial estimate.
+        :param process_noise: Variance added to each state per second of prediction.
+        :param num_particles: Number of particles, drawn from a normal distribution about the
+            initial state.
+        :param seed: Seed of the random number generator, so runs are reproducible.
+        :param estimate_cd_bias: Whether to estimate a bias on cd. Not possible with an aero table.
+        :param integration_method: Fixed timestep integration method (Euler or RK3).
+        :param max_step: Longest integration step in seconds (s) a prediction is split into.
+        :raises ValueError: If the covariances have the wrong length or the configuration is
+            invalid.
+        """
+        ...
+    @staticmethod
+    def new_3dof(
+        rocket: Rocket,
+        initial_state: InitialState3DOF,
+        initial_covariance: list[float],
+        process_noise: list[float],
+        num_particles: int = 1000,
+        seed: int = 0,
+        estimate_cd_bias: bool = False,
+        integration_method: OdeMethod = OdeMethod.RK3,
+        max_step: float = 0.01,
+    ) -> ParticleFilter:
+        """
+        Filter which uses the 3-DOF model, with the state (x, altitude, angle, vx, vy, angular
+        rate), followed by the cd bias if estimate_cd_bias is set.
+
+        :param rocket: The rocket, which is copied.
+        :param initial_state: The initial state estimate.
+        :param initial_covariance: Variance of each state of the initial estimate.
+        :param process_noise: Variance added to each state per second of prediction.
+        :param num_particles: Number of particles, drawn from a normal distribution about the
+            initial state.
+        :param seed: Seed of the random number generator, so runs are reproducible.
+        :param estimate_cd_bias: Whether to estimate a bias on cd. Not possible with an aero table.
+        :param integration_method: Fixed timestep integration method (Euler or RK3).
+        :param max_step: Longest integration step in seconds (s) a prediction is split into.
+        :raises ValueError: If the covariances have the wrong length or the configuration is
+            invalid.
+        """
+        ...
+    def predict(self, dt: float) -> None:
+        """
+        Propagate each particle dt seconds forward with the process model, and add process noise.
+        """
+        ...
+    def update(self, measurement: Measurement) -> None:
+        """
+        Reweight the particles by the likelihood of a measurement, and resample them if too few
+        carry most of the weight.
+
+        :raises ValueError: If the measurement can't be predicted by the model, or no particle is
+            consistent with it.
+        """
+        ...
+    def apogee_distribution(self) -> ApogeeDistribution:
+        """
+        Distribution of the apogee, predicted from each particle. This runs a simulation per
+        particle, so it is much slower than the other methods.
+        """
+        ...
+
+class FitParameter(Enum):
+    """
+    Rocket property which can be fitted to flight data.
+    """
+
+    Cd = 0
+    """
+    Drag coefficient cd. Not available with an aero table, which replaces it.
+    """
+
+    CdMachKnots = 1
+    """
+    Axial force coefficient of the aero table at zero angle of attack, with one value per Mach
+    breakpoint of the table. The rest of each Mach row is shifted along with it.
+    """
+
+    ClA = 2
+    """
+    Lift coefficient slope cl_a, which only affects the 3-DOF model.
+    """
+
+    StabilityMargin = 3
+    """
+    Static stability margin stab_margin_dimensional, which only affects the 3-DOF model.
+    """
+
+class FitResult:
+    """
+    Result of fitting rocket properties to flight data.
+    """
+
+    parameters: list[FitParameter]
+    """
+    The fitted parameter behind each entry of values. CdMachKnots is repeated once per Mach
+    breakpoint.
+    """
+    values: np.ndarray
+    """
+    Fitted value of each parameter.
+    """
+    residuals: np.ndarray
+    """
+    Residuals of the fit, (model - data) / std, with the altitudes followed by the accelerations.
+    """
+    covariance: np.ndarray
+    """
+    Covariance of the fitted values, scaled by the residual variance of the fit. NaN if the
+    parameters can't be told apart from the data.
+    """
+    standard_errors: np.ndarray
+    """
+    Standard error of each fitted value, from the diagonal of the covariance.
+    """
+    cost: float
+    """
+    Sum of the squared residuals.
+    """
+    iterations: int
+    """
+    Number of Levenberg-Marquardt iterations taken.
+    """
+    converged: bool
+    """
+    Whether the fit converged before running out of iterations.
+    """
+    rocket: Rocket
+    """
+    Copy of the rocket with the fitted properties.
+    """
+
+class Distribution:
+    """
+    Probability distribution of an uncertain parameter, e.g. the prior of a fitted parameter.
+    """
+
+    mean: float
+    """
+    Mean of the distribution.
+    """
+    std: float
+    """
+    Standard deviation of the distribution.
+    """
+
+    @staticmethod
+    def uniform(low: float, high: float) -> Distribution:
+        """
+        Uniform distribution between low and high.
+
+        :raises ValueError: If low is not below high.
+        """
+        ...
+    @staticmethod
+    def normal(mean: float, std: float) -> Distribution:
+        """
+        Normal distribution with the given mean and standard deviation.
+
+        :raises ValueError: If std is not positive.
+        """
+        ...
+
+class PosteriorSamples:
+    """
+    Samples from the posterior distribution of fitted rocket properties.
+    """
+
+    parameters: list[FitParameter]
+    """
+    The parameter behind each column of samples. CdMachKnots is repeated once per Mach breakpoint.
+    """
+    samples: np.ndarray
+    """
+    Samples of the parameters, with a row per sample.
+    """
+    log_posterior: np.ndarray
+    """
+    Log posterior density of each sample, up to a constant.
+    """
+    mean: np.ndarray
+    """
+    Posterior mean of each parameter.
+    """
+    std: np.ndarray
+    """
+    Posterior standard deviation of each parameter.
+    """
+    acceptance_rate: float
+    """
+    Fraction of the proposals after burn-in which were accepted.
+    """
+    apogee: ApogeeDistribution
+    """
+    Posterior predictive distribution of the apogee of the recorded flight.
+    """
+
+class DispersedParameter(Enum):
+    """
+    Input of the simulation which can be dispersed in a Monte Carlo analysis. Each run replaces
+    the rocket's or initial state's value with one drawn from the parameter's distribution.
+    """
+
+    Mass = 0
+    """
+    Mass in kilograms (kg).
+    """
+
+    Cd = 1
+    """
+    Drag coefficient cd.
+    """
+
+    AreaDrag = 2
+    """
+    Reference area for drag in square meters.
+    """
+
+    AreaLift = 3
+    """
+    Reference area for lift in square meters.
+    """
+
+    MomentOfInertia = 4
+    """
+    Moment of inertia about the z-axis in kg·m².
+    """
+
+    StabilityMargin = 5
+    """
+    Static stability margin stab_margin_dimensional in meters.
+    """
+
+    ClA = 6
+    """
+    Lift coefficient slope cl_a per radian.
+    """
+
+    Cmq = 7
+    """
+    Pitch damping moment coefficient derivative cmq per radian.
+    """
+
+    ReferenceLength = 8
+    """
+    Reference length for the pitch damping coefficient in meters.
+    """
+
+    MassFlowRate = 9
+    """
+    Propellant mass flow rate for thrust-jet damping in kg/s.
+    """
+
+    NozzleDistance = 10
+    """
+    Distance from the center of gravity to the nozzle exit in meters.
+    """
+
+    CgLocation = 11
+    """
+    Distance from the nose tip to the center of gravity in meters.
+    """
+
+    WindSpeed = 12
+    """
+    Horizontal wind speed in m/s, blowing towards +x (3-DOF only).
+    """
+
+    ImpulseDeviation = 13
+    """
+    Deviation of the motor's total impulse from its nominal value in N·s. The simulations
+    start at burnout, so it changes the burnout speed along the rocket's axis by the deviation
+    over the mass.
+    """
+
+    InitialAltitude = 14
+    """
+    Altitude of the 1-DOF initial state, or y of the 3-DOF initial state.
+    """
+
+    InitialVerticalVelocity = 15
+    """
+    Velocity of the 1-DOF initial state, or vy of the 3-DOF initial state.
+    """
+
+    InitialDownrange = 16
+    """
+    x of the 3-DOF initial state.
+    """
+
+    InitialHorizontalVelocity = 17
+    """
+    vx of the 3-DOF initial state.
+    """
+
+    InitialAngle = 18
+    """
+    Angle of the 3-DOF initial state.
+    """
+
+    InitialAngularRate = 19
+    """
+    Angular rate of the 3-DOF initial state.
+    """
+
+class DispersionOutput(Enum):
+    """
+    Output of each run of a Monte Carlo analysis.
+    """
+
+    Apogee = 0
+    """
+    Apogee in meters (m).
+    """
+
+    TimeToApogee = 1
+    """
+    Time from the start of the run to apogee in seconds (s).
+    """
+
+    LandingDownrange = 2
+    """
+    Horizontal distance from the origin at which the rocket lands in meters (m), which is always
+    zero for the 1-DOF model.
+    """
+
+class DispersionStatistics:
+    """
+    Summary statistics of an output over the runs which completed it. Every statistic is NaN if
+    no run completed the output.
+    """
+
+    mean: float
+    std: float
+    """
+    Sample standard deviation, which is 0 if only one run completed the output.
+    """
+    min: float
+    max: float
+    p5: float
+    """
+    5th percentile.
+    """
+    p50: float
+    """
+    Median.
+    """
+    p95: float
+    """
+    95th percentile.
+    """
+
+class MonteCarloResult:
+    """
+    Outputs of the runs of a Monte Carlo analysis. Outputs a run didn't reach, e.g. because it
+    diverged or ran out of iterations, are NaN and left out of the statistics.
+    """
+
+    parameters: list[DispersedParameter]
+    """
+    The parameter behind each column of samples.
+    """
+    samples: np.ndarray
+    """
+    Dispersed values of the parameters, with a row per run.
+    """
+    apogee: np.ndarray
+    """
+    Apogee of each run in meters (m).
+    """
+    time_to_apogee: np.ndarray
+    """
+    Time from the start of each run to its apogee in seconds (s).
+    """
+    landing_downrange: np.ndarray
+    """
+    Horizontal distance from the origin at which each run lands in meters (m).
+    """
+    failed_runs: int
+    """
+    Number of runs which didn't complete every output.
+    """
+
+    def statistics(self, output: DispersionOutput) -> DispersionStatistics:
+        """
+        Summary statistics of an output.
+        """
+        ...
+    def percentile(self, output: DispersionOutput, q: float) -> float:
+        """
+        Percentile q (0 to 100) of an output, interpolated linearly between the runs.
+
+        :raises ValueError: If q is outside 0 to 100.
+        """
+        ...
+
+class SensitivityMethod(Enum):
+    """
+    Method of a local sensitivity analysis.
+    """
+
+    FiniteDifference = 0
+    """
+    Central finite differences of whole simulations. Adaptive time steps and the apogee detection
+    make the differences noisy for small steps.
+    """
+
+    Forward = 1
+    """
+    Forward-mode sensitivities: every step of the ODE solver is repeated with dual numbers, which
+    carries the derivatives of the state through it exactly, with the same step sizes.
+    """
+
+class Sensitivity:
+    """
+    Derivatives of the apogee, and optionally of the trajectory, with respect to parameters.
+    """
+
+    parameters: list[DispersedParameter]
+    """
+    The parameter behind each entry of gradient.
+    """
+    method: SensitivityMethod
+    """
+    The method the derivatives were computed with.
+    """
+    apogee: float
+    """
+    Apogee of the nominal flight in meters (m).
+    """
+    gradient: np.ndarray
+    """
+    Derivative of the apogee with respect to each parameter.
+    """
+    time: Optional[np.ndarray]
+    """
+    Times of the nominal trajectory in seconds (s), if its sensitivities were requested.
+    """
+    trajectory: Optional[np.ndarray]
+    """
+    Derivatives of the model states with respect to each parameter at each time, with the shape
+    (time, state, parameter), if they were requested.
+    """
+
+class SobolIndices:
+    """
+    Variance-based (Sobol) sensitivity indices of an output, with each parameter uniformly
+    distributed over its range. The first order index of a parameter is the fraction of the
+    output's variance it causes on its own, and the total order index adds its interactions with
+    the other parameters.
+    """
+
+    parameters: list[DispersedParameter]
+    """
+    The parameter behind each index.
+    """
+    output: DispersionOutput
+    """
+    The output the indices apportion the variance of.
+    """
+    variance: float
+    """
+    Variance of the output over the parameter ranges.
+    """
+    confidence_level: float
+    """
+    Confidence level of the intervals.
+    """
+    num_samples: int
+    """
+    Number of base samples whose runs all completed, which the indices are estimated from.
+    """
+    failed_samples: int
+    """
+    Number of base samples left out because one of their runs didn't reach the output.
+    """
+    first_order: np.ndarray
+    """
+    First order index of each parameter.
+    """
+    total_order: np.ndarray
+    """
+    Total order index of each parameter.
+    """
+    first_order_interval: np.ndarray
+    """
+    Bootstrap confidence interval of each first order index, with a (lower, upper) row per
+    parameter.
+    """
+    total_order_interval: np.ndarray
+    """
+    Bootstrap confidence interval of each total order index, with a (lower, upper) row per
+    parameter.
+    """
+
+class NoseShape(Enum):
+    """
+    Profile shapes supported for the nose cone.
+    """
+
+    Conical = 0
+    """
+    Straight cone.
+    """
+
+    Ogive = 1
+    """
+    Tangent ogive.
+    """
+
+    Parabolic = 2
+    """
+    Paraboloid (1/2 power series).
+    """
+
+    Elliptical = 3
+    """
+    Half ellipse.
+    """
+
+    VonKarman = 4
+    """
+    LD-Haack (Von Karman) series.
+    """
+
+class NoseCone:
+    """
+    Nose cone of the rocket. Its base diameter is used as the reference diameter.
+    """
+
+    shape: NoseShape
+    length: float
+    """
+    Length of the nose cone in meters (m).
+    """
+    diameter: float
+    """
+    Base diameter of the nose cone in meters (m).
+    """
+
+    def __init__(self, shape: NoseShape, length: float, diameter: float) -> None: ...
+
+class BodyTube:
+    """
+    Cylindrical section of the rocket body.
+    """
+
+    length: float
+    """
+    Length of the tube in meters (m).
+    """
+    diameter: float
+    """
+    Outer diameter of the tube in meters (m).
+    """
+
+    def __init__(self, length: float, diameter: float) -> None: ...
+
+class Transition:
+    """
+    Conical change in body diameter, either a shoulder (growing) or a boattail (shrinking).
+    """
+
+    length: float
+    """
+    Length of the transition in meters (m).
+    """
+    fore_diameter: float
+    """
+    Diameter at the front of the transition in meters (m).
+    """
+    aft_diameter: float
+    """
+    Diameter at the back of the transition in meters (m).
+    """
+
+    def __init__(self, length: float, fore_diameter: float, aft_diameter: float) -> None: ...
+
+class FinSet:
+    """
+    A set of identical trapezoidal fins, evenly spaced around the body.
+    """
+
+    count: int
+    """
+    Number of fins.
+    """
+    root_chord: float
+    """
+    Root chord length in meters (m).
+    """
+    tip_chord: float
+    """
+    Tip chord length in meters (m).
+    """
+    span: float
+    """
+    Semi-span from the body surface to the fin tip in meters (m).
+    """
+    sweep_length: float
+    """
+    Axial distance from the root leading edge to the tip leading edge in meters (m).
+    """
+    thickness: float
+    """
+    Fin thickness in meters (m).
+    """
+    position: float
+    """
+    Distance from the nose tip to the root leading edge in meters (m).
+    """
+
+    def __init__(
+        self,
+        count: int,
+        root_chord: float,
+        tip_chord: float,
+        span: float,
+        sweep_length: float,
+        thickness: float,
+        position: float,
+    ) -> None: ...
+
+class LaunchLug:
+    """
+    A launch lug or rail button modelled as a short tube in the free stream. Only adds drag.
+    """
+
+    length: float
+    """
+    Length of the lug in meters (m).
+    """
+    outer_diameter: float
+    """
+    Outer diameter of the lug in meters (m).
+    """
+    inner_diameter: float
+    """
+    Inner diameter of the lug in meters (m).
+    """
+
+    def __init__(self, length: float, outer_diameter: float, inner_diameter: float) -> None: ...
+
+class AerodynamicEstimate:
+    """
+    Aerodynamic coefficients estimated from a RocketGeometry. All coefficients are referenced to
+    reference_area and reference_length (the nose base area and diameter).
+    """
+
+    cn_alpha: float
+    """
+    Normal force coefficient slope per radian (1/rad).
+    """
+    cp_location: float
+    """
+    Distance from the nose tip to the center of pressure in meters (m).
+    """
+    cg_location: float
+    """
+    Distance from the nose tip to the center of gravity in meters (m).
+    """
+    stability_margin: float
+    """
+    Static stability margin (CP - CG) in meters (m).
+    """
+    stability_calibers: float
+    """
+    Static stability margin in body calibers.
+    """
+    cmq: float
+    """
+    Pitch damping moment coefficient derivative per radian (1/rad).
+    """
+    cd: float
+    """
+    Total zero angle of attack drag coefficient.
+    """
+    cd_friction: float
+    """
+    Skin friction part of the drag coefficient.
+    """
+    cd_pressure: float
+    """
+    Nose, transition, fin and launch lug pressure part of the drag coefficient.
+    """
+    cd_base: float
+    """
+    Base drag part of the drag coefficient.
+    """
+    cd_interference: float
+    """
+    Fin-body interference part of the drag coefficient.
+    """
+    reference_area: float
+    """
+    Reference area in square meters (m²).
+    """
+    reference_length: float
+    """
+    Reference length in meters (m).
+    """
+
+class RocketGeometry:
+    """
+    Geometric description of a rocket, used to estimate its aerodynamic coefficients with the
+    Barrowman method and a component buildup of the drag.
+    """
+
+    nose_cone: NoseCone
+    body: list[BodyTube | Transition]
+    """
+    Body tubes and transitions behind the nose cone, from front to back.
+    """
+    fins: Optional[FinSet]
+    launch_lugs: list[LaunchLug]
+    surface_roughness: float
+    """
+    Surface roughness height in meters (m).
+    """
+
+    def __init__(
+        self,
+        nose_cone: NoseCone,
+        body: list[BodyTube | Transition],
+        fins: Optional[FinSet] = None,
+        launch_lugs: list[LaunchLug] = [],
+        surface_roughness: float = 20e-6,
+    ) -> None: ...
+    def length(self) -> float:
+        """
+        Total length of the rocket from the nose tip to the aft end of the body in meters (m).
+        """
+        ...
+
+    def reference_diameter(self) -> float:
+        """
+        Reference diameter used for all coefficients in meters (m).
+        """
+        ...
+
+    def reference_area(self) -> float:
+        """
+        Reference area used for all coefficients in square meters (m²).
+        """
+        ...
+
+    def skin_friction(self) -> SkinFriction:
+        """
+        Reynolds number dependent skin friction model for this geometry, matching the friction and
+        interference parts of estimate().
+        """
+        ...
+
+    def estimate(self, cg_location: float, velocity: float = 100.0) -> AerodynamicEstimate:
+        """
+        Estimate the aerodynamic coefficients of the rocket.
+
+        :param cg_location: Distance from the nose tip to the center of gravity in meters (m).
+        :param velocity: Airspeed at which the drag is evaluated at sea level in meters per
+            second (m/s).
+        :return: The estimated coefficients.
+        """
+        ...
+
+class RocketProperties:
+    """
+    Internal physical property group for the rocket.
+    Accessible from Python for reading or updating fields dynamically.
+    """
+
+    mass: float
+    """
+    Mass of the rocket in kilograms (kg).
+    """
+
+    cd: float
+    """
+    Drag coefficient (dimensionless).
+    """
+
+    area_drag: float
+    """
+    Reference area for drag in square meters (m²).
+    """
+
+    area_lift: float
+    """
+    Reference area for lift, normal force and pitch damping moment in square meters (m²). Only
+    used by the 3-DOF model.
+    """
+
+    moment_of_inertia: float
+    """
+    Moment of inertia about the z-axis in kilogram square meters (kg·m²).
+    """
+
+    stab_margin_dimensional: float
+    """
+    Static stability margin in meters (m).
+    """
+
+    cl_a: float
+    """
+    Lift coefficient slope per radian (1/rad).
+    """
+
+    cmq: float
+    """
+    Pitch damping moment coefficient derivative Cmq per radian (1/rad). Negative values damp
+    the pitch rate.
+    """
+
+    reference_length: float
+    """
+    Reference length for non-dimensional moment coefficients such as cmq in meters (m),
+    typically the body diameter.
+    """
+
+    mass_flow_rate: float
+    """
+    Propellant mass flow rate used for thrust-jet damping while the motor burns in kilograms per
+    second (kg/s).
+    """
+
+    burn_time: float
+    """
+    Time from the start of the simulation to motor burnout in seconds (s). Thrust-jet damping only
+    acts before it, so it stays 0 for simulations which start at burnout.
+    """
+
+    nozzle_distance: float
+    """
+    Distance from the center of gravity to the nozzle exit in meters (m).
+    """
+
+    skin_friction: Optional[SkinFriction]
+    """
+    Optional Reynolds number dependent skin friction model. When set, cd only represents the
+    pressure and base drag.
+    """
+
+    aero_table: Optional[AeroTable]
+    """
+    Optional Mach and angle of attack dependent aerodynamic tables. When set, they replace cd,
+    cl_a and stab_margin_dimensional.
+    """
+
+    cg_location: float
+    """
+    Distance from the nose tip to the center of gravity in meters (m), used with the CP
+    locations of aero_table.
+    """
+
+    airbrake: Optional[Airbrake]
+    """
+    Optional airbrake add-on which increases the drag as it deploys.
+    """
+
+    wind_speed: float
+    """
+    Horizontal wind speed in meters per second (m/s), blowing towards +x. Only the 3-DOF model
+    sees the wind.
+    """
+
+class Rocket:
+    """
+    The main class for simulating rocket flight. Contains methods for 1-DOF and 3-DOF simulations,
+    as well as apogee predictions.
+
+    Simulations and predictions emit a UserWarning for every field that affects the selected model
+    but was left at zero, e.g. area_lift for the 3-DOF model.
+    """
+
+    rocket_properties: RocketProperties
+    """
+    The physical properties of the rocket.
+    """
+
+    def __init__(
+        self,
+        mass: float,
+        cd: float,
+        area_drag: float,
+        area_lift: float,
+        moment_of_inertia: float,
+        stab_margin_dimensional: float,
+        cl_a: float,
+        cmq: float = 0.0,
+        reference_length: float = 0.0,
+        mass_flow_rate: float = 0.0,
+        nozzle_distance: float = 0.0,
+        skin_friction: Optional[SkinFriction] = None,
+        aero_table: Optional[AeroTable] = None,
+        cg_location: float = 0.0,
+        airbrake: Optional[Airbrake] = None,
+        wind_speed: float = 0.0,
+        burn_time: float = 0.0,
+    ) -> None:
+        """
+        Creates a new Rocket instance and initializes its underlying RocketProperties group.
+
+        :param mass: Mass of the rocket in kilograms.
+        :param cd: Drag coefficient.
+        :param area_drag: Reference area for drag in square meters.
+        :param area_lift: Reference area for lift, normal force and pitch damping in square meters.
+        :param moment_of_inertia: Moment of inertia about the z-axis in kg·m².
+        :param stab_margin_dimensional: Static stability margin in meters.
+        :param cl_a: Lift coefficient slope per radian.
+        :param cmq: Pitch damping moment coefficient derivative per radian (3-DOF only).
+        :param reference_length: Reference length for the pitch damping coefficient in meters.
+        :param mass_flow_rate: Propellant mass flow rate for thrust-jet damping in kg/s.
+        :param nozzle_distance: Distance from the center of gravity to the nozzle exit in meters.
+        :param skin_friction: Optional Reynolds number dependent skin friction model. When given,
+            cd should only contain the pressure and base drag.
+        :param aero_table: Optional Mach and angle of attack dependent aerodynamic tables which
+            replace cd, cl_a and stab_margin_dimensional.
+        :param cg_location: Distance from the nose tip to the center of gravity in meters, used
+            with the CP locations of aero_table.
+        :param airbrake: Optional airbrake add-on which increases the drag as it deploys.
+        :param wind_speed: Horizontal wind speed in m/s, blowing towards +x (3-DOF only).
+        :param burn_time: Time from the start of the simulation to motor burnout in seconds. The
+            thrust-jet damping of mass_flow_rate only acts before it (3-DOF only).
+        """
+        ...
+
+    @staticmethod
+    def from_geometry(
+        geometry: RocketGeometry,
+        mass: float,
+        moment_of_inertia: float,
+        cg_location: float,
+        velocity: float = 100.0,
+        reynolds_dependent_friction: bool = False,
+    ) -> Rocket:
+        """
+        Create a rocket whose drag coefficient, lift slope, stability margin, pitch damping and
+        reference area are estimated from its geometry with the Barrowman method.
+
+        :param geometry: The rocket geometry.
+        :param mass: Mass of the rocket in kilograms.
+        :param moment_of_inertia: Moment of inertia about the z-axis in kg·m².
+        :param cg_location: Distance from the nose tip to the center of gravity in meters.
+        :param velocity: Airspeed at which the drag is evaluated in meters per second.
+        :param reynolds_dependent_friction: Split the friction drag off of cd into a skin friction
+            model, so it varies with the Reynolds number during the flight.
+        """
+        ...
+
+    def simulate_flight_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        derived_channels: Optional[list[DerivedChannel]] = None,
+    ) -> SimulationResult:
+        """
+        Simulate the rocket's flight using a 1-DOF model (vertical motion only).
+
+        :param initial_state: The initial height and velocity of the rocket.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param max_iterations: Maximum integration iterations allowed.
+        :param print_output: Whether to print simulation progress to stdout.
+        :param derived_channels: Derived quantities to log alongside the states, e.g. the Mach
+            number and dynamic pressure.
+        :return: The logged flight and its summary, with the altitude, vertical velocity and
+            vertical acceleration columns.
+        """
+        ...
+
+    def simulate_flight_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        derived_channels: Optional[list[DerivedChannel]] = None,
+    ) -> SimulationResult:
+        """
+        Simulate the rocket's flight using a 3-DOF model (2D translation and rotation).
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param max_iterations: Maximum integration iterations allowed.
+        :param print_output: Whether to print simulation progress to stdout.
+        :param derived_channels: Derived quantities to log alongside the states, e.g. the Mach
+            number and dynamic pressure.
+        :return: The logged flight and its summary, with the position, angle, velocity, angular
+            rate and acceleration columns.
+        """
+        ...
+
+    def simulate_controlled_flight_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        integration_method: OdeMethod,
+        controller: Controller,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        derived_channels: Optional[list[DerivedChannel]] = None,
+    ) -> SimulationResult:
+        """
+        Simulate the rocket's flight using a 1-DOF model with a closed-loop airbrake controller.
+        The rocket must have an airbrake.
+
+        :param initial_state: The initial height and velocity of the rocket.
+        :param integration_method: Numerical integration method to use.
+        :param controller: Controller which commands the airbrake deployment.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+            The controller is updated at most once per time step, so the time step should not be
+            larger than the controller period.
+        :param max_iterations: Maximum integration iterations allowed.
+        :param print_output: Whether to print simulation progress to stdout.
+        :param derived_channels: Derived quantities to log alongside the states, e.g. the Mach
+            number and dynamic pressure.
+        :return: The logged flight and its summary, whose control log has one (time, predicted
+            apogee, command, actual deployment) row per controller update.
+        :raises RuntimeError: If a Python controller raises an exception.
+        """
+        ...
+
+    def simulate_controlled_flight_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        integration_method: OdeMethod,
+        controller: Controller,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        derived_channels: Optional[list[DerivedChannel]] = None,
+    ) -> SimulationResult:
+        """
+        Simulate the rocket's flight using a 3-DOF model with a closed-loop airbrake controller.
+        The rocket must have an airbrake. The controller's apogee prediction uses the 1-DOF model
+        from the current altitude and vertical velocity.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param integration_method: Numerical integration method to use.
+        :param controller: Controller which commands the airbrake deployment.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+            The controller is updated at most once per time step, so the time step should not be
+            larger than the controller period.
+        :param max_iterations: Maximum integration iterations allowed.
+        :param print_output: Whether to print simulation progress to stdout.
+        :param derived_channels: Derived quantities to log alongside the states, e.g. the Mach
+            number and dynamic pressure.
+        :return: The logged flight and its summary, whose control log has one (time, predicted
+            apogee, command, actual deployment) row per controller update.
+        :raises RuntimeError: If a Python controller raises an exception.
+        """
+        ...
+
+    def predict_apogee_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        airbrake_deployment: Optional[float] = None,
+        deadline: Optional[float] = None,
+    ) -> float:
+        """
+        Predict the apogee (maximum altitude) using a 1-DOF model. If the deadline passes or
+        max_iterations runs out before apogee, the best estimate so far is returned. Use
+        predict_apogee_1dof_with_status to find out whether that happened.
+
+        :param initial_state: The initial height and velocity of the rocket.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param max_iterations: Maximum integration iterations allowed.
+        :param print_output: Whether to print simulation progress to stdout.
+        :param airbrake_deployment: Overrides the deployment of the rocket's airbrakes for this
+            prediction. The rocket must have an airbrake.
+        :param deadline: Wall-clock time the prediction may take in seconds (s), or None for no
+            limit.
+        :return: Maximum altitude reached in meters.
+        """
+        ...
+
+    def predict_apogee_1dof_with_status(
+        self,
+        initial_state: InitialState1DOF,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        airbrake_deployment: Optional[float] = None,
+        deadline: Optional[float] = None,
+    ) -> ApogeePrediction:
+        """
+        Same as predict_apogee_1dof, but also reports whether the prediction reached apogee, ran
+        out of its budget or diverged.
+
+        :return: The apogee together with how the prediction ended.
+        """
+        ...
+
+    def predict_apogee_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        airbrake_deployment: Optional[float] = None,
+        deadline: Optional[float] = None,
+    ) -> float:
+        """
+        Predict the apogee (maximum altitude) using a 3-DOF model. If the deadline passes or
+        max_iterations runs out before apogee, the best estimate so far is returned. Use
+        predict_apogee_3dof_with_status to find out whether that happened.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param max_iterations: Maximum integration iterations allowed.
+        :param print_output: Whether to print simulation progress to stdout.
+        :param airbrake_deployment: Overrides the deployment of the rocket's airbrakes for this
+            prediction. The rocket must have an airbrake.
+        :param deadline: Wall-clock time the prediction may take in seconds (s), or None for no
+            limit.
+        :return: Maximum altitude reached in meters.
+        """
+        ...
+
+    def predict_apogee_3dof_with_status(
+        self,
+        initial_state: InitialState3DOF,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        max_iterations: int = 100000,
+        print_output: bool = False,
+        airbrake_deployment: Optional[float] = None,
+        deadline: Optional[float] = None,
+    ) -> ApogeePrediction:
+        """
+        Same as predict_apogee_3dof, but also reports whether the prediction reached apogee, ran
+        out of its budget or diverged.
+
+        :return: The apogee together with how the prediction ended.
+        """
+        ...
+
+    def predict_apogee_1dof_batch(
+        self,
+        initial_states: np.ndarray,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        parameters: Optional[list[DispersedParameter]] = None,
+        values: Optional[np.ndarray] = None,
+        num_threads: Optional[int] = None,
+        max_iterations: int = 100000,
+    ) -> np.ndarray:
+        """
+        Predict the apogee with the 1-DOF model for a batch of flights, e.g. a parameter sweep.
+        The predictions run on a pool of threads with the GIL released.
+
+        Either initial_states or values may have a single row, which is then shared by the whole
+        batch. Otherwise they must have the same number of rows.
+
+        :param initial_states: Array of shape (n, 2) with an initial state (altitude, vertical velocity) per row.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param parameters: Rocket properties or initial state components which are set per flight.
+        :param values: Array with a row per flight holding the value of each parameter.
+        :param num_threads: Number of threads, or None for one per available core.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :return: Array with the apogee of each flight in meters (m).
+        :raises ValueError: If the shapes don't match, or a parameter doesn't apply to the model.
+        """
+        ...
+
+    def predict_apogee_3dof_batch(
+        self,
+        initial_states: np.ndarray,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        parameters: Optional[list[DispersedParameter]] = None,
+        values: Optional[np.ndarray] = None,
+        num_threads: Optional[int] = None,
+        max_iterations: int = 100000,
+    ) -> np.ndarray:
+        """
+        Predict the apogee with the 3-DOF model for a batch of flights, e.g. a parameter sweep.
+        The predictions run on a pool of threads with the GIL released.
+
+        Either initial_states or values may have a single row, which is then shared by the whole
+        batch. Otherwise they must have the same number of rows.
+
+        :param initial_states: Array of shape (n, 6) with an initial state (x, y, angle, vx, vy, angular rate) per row.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param parameters: Rocket properties or initial state components which are set per flight.
+        :param values: Array with a row per flight holding the value of each parameter.
+        :param num_threads: Number of threads, or None for one per available core.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :return: Array with the apogee of each flight in meters (m).
+        :raises ValueError: If the shapes don't match, or a parameter doesn't apply to the model.
+        """
+        ...
+
+    def deployment_for_apogee_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        target_apogee: float,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        tolerance: float = 0.5,
+        max_evaluations: int = 20,
+        max_iterations: int = 100000,
+    ) -> SolveReport:
+        """
+        Find the constant airbrake deployment (0 to 1) which hits the target apogee with the
+        1-DOF model, by root-finding over repeated apogee predictions. The rocket must have an
+        airbrake.
+
+        :param initial_state: The initial height and velocity of the rocket.
+        :param target_apogee: Target apogee in meters (m).
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param tolerance: Allowed apogee error in meters (m).
+        :param max_evaluations: Maximum number of apogee predictions, which bounds the run time.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :return: The deployment and the apogee it achieves.
+        """
+        ...
+
+    def deployment_time_for_apogee_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        target_apogee: float,
+        integration_method: OdeMethod,
+        deployment: float = 1.0,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        tolerance: float = 0.5,
+        max_evaluations: int = 20,
+        max_iterations: int = 100000,
+    ) -> SolveReport:
+        """
+        Find the time at which deploying the airbrakes hits the target apogee with the 1-DOF
+        model, with the airbrakes retracted before then. The time is in seconds from the initial
+        state. The coasting flight is simulated once, and is not counted as an evaluation.
+
+        :param initial_state: The initial height and velocity of the rocket.
+        :param target_apogee: Target apogee in meters (m).
+        :param integration_method: Numerical integration method to use.
+        :param deployment: Deployment (0 to 1) from the deployment time onwards.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param tolerance: Allowed apogee error in meters (m).
+        :param max_evaluations: Maximum number of apogee predictions, which bounds the run time.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :return: The deployment time and the apogee it achieves.
+        """
+        ...
+
+    def deployment_for_apogee_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        target_apogee: float,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        tolerance: float = 0.5,
+        max_evaluations: int = 20,
+        max_iterations: int = 100000,
+    ) -> SolveReport:
+        """
+        Find the constant airbrake deployment (0 to 1) which hits the target apogee with the
+        3-DOF model, by root-finding over repeated apogee predictions. The rocket must have an
+        airbrake.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param target_apogee: Target apogee in meters (m).
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param tolerance: Allowed apogee error in meters (m).
+        :param max_evaluations: Maximum number of apogee predictions, which bounds the run time.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :return: The deployment and the apogee it achieves.
+        """
+        ...
+
+    def deployment_time_for_apogee_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        target_apogee: float,
+        integration_method: OdeMethod,
+        deployment: float = 1.0,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        tolerance: float = 0.5,
+        max_evaluations: int = 20,
+        max_iterations: int = 100000,
+    ) -> SolveReport:
+        """
+        Find the time at which deploying the airbrakes hits the target apogee with the 3-DOF
+        model, with the airbrakes retracted before then. The time is in seconds from the initial
+        state. The coasting flight is simulated once, and is not counted as an evaluation.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param target_apogee: Target apogee in meters (m).
+        :param integration_method: Numerical integration method to use.
+        :param deployment: Deployment (0 to 1) from the deployment time onwards.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param tolerance: Allowed apogee error in meters (m).
+        :param max_evaluations: Maximum number of apogee predictions, which bounds the run time.
+        :param max_iterations: Maximum integration iterations allowed per prediction.
+        :return: The deployment time and the apogee it achieves.
+        """
+        ...
+    def parameter_for_target_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        parameter: DispersedParameter,
+        bounds: tuple[float, float],
+        target: float,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        output: DispersionOutput = DispersionOutput.Apogee,
+        tolerance: float = 0.5,
+        max_evaluations: int = 20,
+        max_iterations: int = 100000,
+    ) -> SolveReport:
+        """
+        Inverse design with the 1-DOF model: find the value of a parameter at which an output
+        of the flight hits a target, e.g. the ballast mass or drag coefficient giving a target
+        apogee. Each evaluation simulates the flight from the initial state to apogee and on to
+        the ground.
+
+        :param initial_state: The initial 1-DOF condition of the rocket.
+        :param parameter: The parameter to solve for.
+        :param bounds: (lower, upper) range to search, whose outputs must bracket the target.
+        :param target: Target value of the output.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param output: The output of the flight to target.
+        :param tolerance: Allowed error of the output, in its units.
+        :param max_evaluations: Maximum number of simulations, which bounds the run time.
+        :param max_iterations: Maximum integration iterations allowed per simulation phase.
+        :return: The parameter value and the output it achieves.
+        :raises ValueError: If the bounds are invalid, the parameter doesn't apply to the model,
+            or the flight doesn't reach the output.
+        """
+        ...
+    def parameter_for_target_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        parameter: DispersedParameter,
+        bounds: tuple[float, float],
+        target: float,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        output: DispersionOutput = DispersionOutput.Apogee,
+        tolerance: float = 0.5,
+        max_evaluations: int = 20,
+        max_iterations: int = 100000,
+    ) -> SolveReport:
+        """
+        Inverse design with the 3-DOF model: find the value of a parameter at which an output
+        of the flight hits a target, e.g. the ballast mass or drag coefficient giving a target
+        apogee. Each evaluation simulates the flight from the initial state to apogee and on to
+        the ground.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param parameter: The parameter to solve for.
+        :param bounds: (lower, upper) range to search, whose outputs must bracket the target.
+        :param target: Target value of the output.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param output: The output of the flight to target.
+        :param tolerance: Allowed error of the output, in its units.
+        :param max_evaluations: Maximum number of simulations, which bounds the run time.
+        :param max_iterations: Maximum integration iterations allowed per simulation phase.
+        :return: The parameter value and the output it achieves.
+        :raises ValueError: If the bounds are invalid, the parameter doesn't apply to the model,
+            or the flight doesn't reach the output.
+        """
+        ...
+    def optimize_launch_angle_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        objective: LaunchObjective,
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        target_downrange: float = 0.0,
+        max_rail_angle: float = 0.349,
+        tolerance: float = 1e-3,
+        max_evaluations: int = 20,
+        max_iterations: int = 100000,
+    ) -> LaunchOptimum:
+        """
+        Find the launch angle which optimizes an objective with the 3-DOF model, by golden section
+        search over flights simulated to the ground. The rocket leaves the rail at the speed of
+        the initial state, with its velocity along its axis. The model is planar, so the wind
+        blows along the downrange axis and only the tilt in that plane is searched.
+
+        :param initial_state: The initial 6-DOF condition of the rocket, whose angle and velocity
+            direction are replaced by the launch angle.
+        :param objective: What to optimize.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param target_downrange: Downrange position to land at in meters (m), for
+            LaunchObjective.LandingTarget.
+        :param max_rail_angle: Largest tilt of the rail from vertical in radians (rad).
+        :param tolerance: Width in radians (rad) to which the optimal angle is bracketed.
+        :param max_evaluations: Maximum number of simulations, which bounds the run time.
+        :param max_iterations: Maximum integration iterations allowed per simulation phase.
+        :return: The optimal angle and its flight.
+        :raises ValueError: If max_rail_angle isn't between 0 and pi/2, or no flight lands.
+        """
+        ...
+    def fit_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        time: list[float],
+        altitude: list[float],
+        parameters: list[FitParameter],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        acceleration: Optional[list[float]] = None,
+        altitude_std: float = 1.0,
+        acceleration_std: float = 1.0,
+        max_fit_iterations: int = 50,
+        tolerance: float = 1e-8,
+        max_iterations: int = 100000,
+    ) -> FitResult:
+        """
+        Fit rocket properties to a recorded flight with the 1-DOF model, by Levenberg-Marquardt
+        least squares with the simulation as the forward model. The fit starts from the rocket's
+        current properties.
+
+        The simulation starts from initial_state at time[0] and runs to apogee, so the data should
+        cover the coast after burnout. Data after the simulated apogee is compared to the apogee.
+        The Jacobians of the fit are central differences of whole simulations.
+
+        :param initial_state: The initial 1-DOF condition of the rocket at time[0].
+        :param time: Times of the recorded samples in seconds (s), strictly increasing.
+        :param altitude: Recorded altitude at each time in meters (m).
+        :param parameters: The properties to fit.
+        :param integration_method: Numerical integration method to use. A fixed timestep gives
+            smoother residuals than an adaptive one.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param acceleration: Recorded axial accelerometer reading at each time in m/s^2, which is
+            zero in free fall, or None to fit the altitude only.
+        :param altitude_std: Noise of the altitudes in meters (m), which weights their residuals.
+        :param acceleration_std: Noise of the accelerations in m/s^2, which weights their
+            residuals.
+        :param max_fit_iterations: Maximum number of Levenberg-Marquardt iterations.
+        :param tolerance: Relative tolerance on the parameter step and the cost decrease.
+        :param max_iterations: Maximum integration iterations allowed per simulation.
+        :return: The fitted values, residuals and parameter covariance.
+        :raises ValueError: If the data or parameters are invalid, e.g. fitting cd with an aero
+            table.
+        """
+        ...
+    def fit_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        time: list[float],
+        altitude: list[float],
+        parameters: list[FitParameter],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        acceleration: Optional[list[float]] = None,
+        altitude_std: float = 1.0,
+        acceleration_std: float = 1.0,
+        max_fit_iterations: int = 50,
+        tolerance: float = 1e-8,
+        max_iterations: int = 100000,
+    ) -> FitResult:
+        """
+        Fit rocket properties to a recorded flight with the 3-DOF model, by Levenberg-Marquardt
+        least squares with the simulation as the forward model. The fit starts from the rocket's
+        current properties.
+
+        The simulation starts from initial_state at time[0] and runs to apogee, so the data should
+        cover the coast after burnout. Data after the simulated apogee is compared to the apogee.
+        The Jacobians of the fit are central differences of whole simulations.
+
+        :param initial_state: The initial 6-DOF condition of the rocket at time[0].
+        :param time: Times of the recorded samples in seconds (s), strictly increasing.
+        :param altitude: Recorded altitude at each time in meters (m).
+        :param parameters: The properties to fit.
+        :param integration_method: Numerical integration method to use. A fixed timestep gives
+            smoother residuals than an adaptive one.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param acceleration: Recorded axial accelerometer reading at each time in m/s^2, which is
+            zero in free fall, or None to fit the altitude only.
+        :param altitude_std: Noise of the altitudes in meters (m), which weights their residuals.
+        :param acceleration_std: Noise of the accelerations in m/s^2, which weights their
+            residuals.
+        :param max_fit_iterations: Maximum number of Levenberg-Marquardt iterations.
+        :param tolerance: Relative tolerance on the parameter step and the cost decrease.
+        :param max_iterations: Maximum integration iterations allowed per simulation.
+        :return: The fitted values, residuals and parameter covariance.
+        :raises ValueError: If the data or parameters are invalid, e.g. fitting cd with an aero
+            table.
+        """
+        ...
+    def sample_posterior_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        time: list[float],
+        altitude: list[float],
+        parameters: list[FitParameter],
+        priors: list[Distribution],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        acceleration: Optional[list[float]] = None,
+        altitude_std: float = 1.0,
+        acceleration_std: float = 1.0,
+        num_samples: int = 5000,
+        burn_in: int = 1000,
+        seed: int = 0,
+        predictive_samples: int = 200,
+        max_iterations: int = 100000,
+    ) -> PosteriorSamples:
+        """
+        Sample the posterior distribution of rocket properties given a recorded flight with the
+        1-DOF model, using an adaptive Metropolis sampler. The likelihood is Gaussian in the
+        altitudes (and the axial accelerations, if given) with the given noise.
+
+        The chain starts from the least squares fit (see fit_1dof), and its proposal is only
+        adapted during burn-in. Every step runs a simulation, so a chain takes a few seconds.
+
+        :param initial_state: The initial 1-DOF condition of the rocket at time[0].
+        :param time: Times of the recorded samples in seconds (s), strictly increasing.
+        :param altitude: Recorded altitude at each time in meters (m).
+        :param parameters: The properties to sample.
+        :param priors: Prior of each parameter, applied to each of its values.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param acceleration: Recorded axial accelerometer reading at each time in m/s^2, or None.
+        :param altitude_std: Noise of the altitudes in meters (m).
+        :param acceleration_std: Noise of the accelerations in m/s^2.
+        :param num_samples: Number of samples kept after burn-in.
+        :param burn_in: Number of initial steps which are discarded.
+        :param seed: Seed of the random number generator, so runs are reproducible.
+        :param predictive_samples: Number of evenly spaced samples the apogee is predicted for.
+        :param max_iterations: Maximum integration iterations allowed per simulation.
+        :return: The posterior samples and the posterior predictive apogee.
+        :raises ValueError: If the data, parameters or priors are invalid, or the posterior
+            density is zero at the fitted parameters.
+        """
+        ...
+    def sample_posterior_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        time: list[float],
+        altitude: list[float],
+        parameters: list[FitParameter],
+        priors: list[Distribution],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        acceleration: Optional[list[float]] = None,
+        altitude_std: float = 1.0,
+        acceleration_std: float = 1.0,
+        num_samples: int = 5000,
+        burn_in: int = 1000,
+        seed: int = 0,
+        predictive_samples: int = 200,
+        max_iterations: int = 100000,
+    ) -> PosteriorSamples:
+        """
+        Sample the posterior distribution of rocket properties given a recorded flight with the
+        3-DOF model, using an adaptive Metropolis sampler. The likelihood is Gaussian in the
+        altitudes (and the axial accelerations, if given) with the given noise.
+
+        The chain starts from the least squares fit (see fit_3dof), and its proposal is only
+        adapted during burn-in. Every step runs a simulation, so a chain takes a few seconds.
+
+        :param initial_state: The initial 6-DOF condition of the rocket at time[0].
+        :param time: Times of the recorded samples in seconds (s), strictly increasing.
+        :param altitude: Recorded altitude at each time in meters (m).
+        :param parameters: The properties to sample.
+        :param priors: Prior of each parameter, applied to each of its values.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param acceleration: Recorded axial accelerometer reading at each time in m/s^2, or None.
+        :param altitude_std: Noise of the altitudes in meters (m).
+        :param acceleration_std: Noise of the accelerations in m/s^2.
+        :param num_samples: Number of samples kept after burn-in.
+        :param burn_in: Number of initial steps which are discarded.
+        :param seed: Seed of the random number generator, so runs are reproducible.
+        :param predictive_samples: Number of evenly spaced samples the apogee is predicted for.
+        :param max_iterations: Maximum integration iterations allowed per simulation.
+        :return: The posterior samples and the posterior predictive apogee.
+        :raises ValueError: If the data, parameters or priors are invalid, or the posterior
+            density is zero at the fitted parameters.
+        """
+        ...
+    def monte_carlo_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        parameters: list[DispersedParameter],
+        distributions: list[Distribution],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        num_runs: int = 1000,
+        seed: int = 0,
+        num_threads: Optional[int] = None,
+        max_iterations: int = 100000,
+    ) -> MonteCarloResult:
+        """
+        Run a Monte Carlo analysis with the 1-DOF model. Every run draws each parameter from its
+        distribution and is simulated from the initial state to apogee and on to the ground.
+
+        The runs are spread over several threads, with the GIL released. All the dispersed values
+        are drawn from a single seeded generator, so the results don't depend on the number of
+        threads.
+
+        :param initial_state: The initial 1-DOF condition of the rocket.
+        :param parameters: The parameters to disperse.
+        :param distributions: Distribution of each parameter.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param num_runs: Number of simulations.
+        :param seed: Seed of the random number generator, so runs are reproducible.
+        :param num_threads: Number of threads, or None for one per available core.
+        :param max_iterations: Maximum integration iterations allowed per simulation phase.
+        :return: The dispersed values and the outputs of every run.
+        :raises ValueError: If there isn't one distribution per parameter, a parameter doesn't
+            apply to the model, or num_runs or num_threads is zero.
+        """
+        ...
+    def monte_carlo_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        parameters: list[DispersedParameter],
+        distributions: list[Distribution],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        num_runs: int = 1000,
+        seed: int = 0,
+        num_threads: Optional[int] = None,
+        max_iterations: int = 100000,
+    ) -> MonteCarloResult:
+        """
+        Run a Monte Carlo analysis with the 3-DOF model. Every run draws each parameter from its
+        distribution and is simulated from the initial state to apogee and on to the ground.
+
+        The runs are spread over several threads, with the GIL released. All the dispersed values
+        are drawn from a single seeded generator, so the results don't depend on the number of
+        threads.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param parameters: The parameters to disperse.
+        :param distributions: Distribution of each parameter.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param num_runs: Number of simulations.
+        :param seed: Seed of the random number generator, so runs are reproducible.
+        :param num_threads: Number of threads, or None for one per available core.
+        :param max_iterations: Maximum integration iterations allowed per simulation phase.
+        :return: The dispersed values and the outputs of every run.
+        :raises ValueError: If there isn't one distribution per parameter, a parameter doesn't
+            apply to the model, or num_runs or num_threads is zero.
+        """
+        ...
+
+    def apogee_sensitivity_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        parameters: list[DispersedParameter],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        method: SensitivityMethod = SensitivityMethod.Forward,
+        trajectory: bool = False,
+        relative_step: float = 1e-5,
+        max_iterations: int = 100000,
+    ) -> Sensitivity:
+        """
+        Compute the local sensitivity of the apogee with the 1-DOF model to each parameter, about
+        the rocket's properties and the initial state.
+
+        Forward repeats each solver step with dual numbers, which carries the derivatives of the
+        state through the flight exactly. FiniteDifference differences whole simulations, with each
+        parameter displaced by relative_step times its magnitude, or times 1 for smaller magnitudes.
+
+        :param initial_state: The initial 1-DOF condition of the rocket.
+        :param parameters: The parameters to differentiate with respect to.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param method: Method of computing the derivatives.
+        :param trajectory: Whether to also return the sensitivities of the states along the flight.
+        :param relative_step: Step of each parameter for FiniteDifference, relative to its
+            magnitude.
+        :param max_iterations: Maximum integration iterations allowed per simulation.
+        :return: The apogee and its derivatives.
+        :raises ValueError: If a parameter doesn't apply to the model, relative_step isn't
+            positive, or a simulation diverges or doesn't reach apogee.
+        """
+        ...
+
+    def apogee_sensitivity_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        parameters: list[DispersedParameter],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        method: SensitivityMethod = SensitivityMethod.Forward,
+        trajectory: bool = False,
+        relative_step: float = 1e-5,
+        max_iterations: int = 100000,
+    ) -> Sensitivity:
+        """
+        Compute the local sensitivity of the apogee with the 3-DOF model to each parameter, about
+        the rocket's properties and the initial state.
+
+        Forward repeats each solver step with dual numbers, which carries the derivatives of the
+        state through the flight exactly. FiniteDifference differences whole simulations, with each
+        parameter displaced by relative_step times its magnitude, or times 1 for smaller magnitudes.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param parameters: The parameters to differentiate with respect to.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param method: Method of computing the derivatives.
+        :param trajectory: Whether to also return the sensitivities of the states along the flight.
+        :param relative_step: Step of each parameter for FiniteDifference, relative to its
+            magnitude.
+        :param max_iterations: Maximum integration iterations allowed per simulation.
+        :return: The apogee and its derivatives.
+        :raises ValueError: If a parameter doesn't apply to the model, relative_step isn't
+            positive, or a simulation diverges or doesn't reach apogee.
+        """
+        ...
+
+    def sobol_indices_1dof(
+        self,
+        initial_state: InitialState1DOF,
+        parameters: list[DispersedParameter],
+        bounds: list[tuple[float, float]],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        output: DispersionOutput = DispersionOutput.Apogee,
+        num_samples: int = 512,
+        num_resamples: int = 200,
+        confidence_level: float = 0.95,
+        seed: int = 0,
+        num_threads: Optional[int] = None,
+        max_iterations: int = 100000,
+    ) -> SobolIndices:
+        """
+        Global sensitivity analysis with the 1-DOF model: first and total order Sobol indices of
+        an output, with each parameter uniformly distributed over its range.
+
+        Saltelli's scheme runs num_samples * (len(parameters) + 2) simulations, spread over
+        several threads with the GIL released. The samples and the bootstrap resamples are drawn
+        from a single seeded generator, so the results don't depend on the number of threads.
+
+        :param initial_state: The initial 1-DOF condition of the rocket.
+        :param parameters: The parameters to vary.
+        :param bounds: (lower, upper) range of each parameter.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param output: The output of the flight to analyse.
+        :param num_samples: Number of base samples.
+        :param num_resamples: Number of bootstrap resamples for the confidence intervals.
+        :param confidence_level: Confidence level of the intervals, between 0 and 1.
+        :param seed: Seed of the random number generator, so analyses are reproducible.
+        :param num_threads: Number of threads, or None for one per available core.
+        :param max_iterations: Maximum integration iterations allowed per simulation phase.
+        :return: The indices and their confidence intervals.
+        :raises ValueError: If there isn't one finite, non-empty range per parameter, a parameter
+            doesn't apply to the model, the sample sizes or confidence level are invalid, or the
+            output doesn't vary.
+        """
+        ...
+    def sobol_indices_3dof(
+        self,
+        initial_state: InitialState3DOF,
+        parameters: list[DispersedParameter],
+        bounds: list[tuple[float, float]],
+        integration_method: OdeMethod,
+        timestep_config: Optional[FixedTimeStep | AdaptiveTimeStep] = None,
+        output: DispersionOutput = DispersionOutput.Apogee,
+        num_samples: int = 512,
+        num_resamples: int = 200,
+        confidence_level: float = 0.95,
+        seed: int = 0,
+        num_threads: Optional[int] = None,
+        max_iterations: int = 100000,
+    ) -> SobolIndices:
+        """
+        Global sensitivity analysis with the 3-DOF model: first and total order Sobol indices of
+        an output, with each parameter uniformly distributed over its range.
+
+        Saltelli's scheme runs num_samples * (len(parameters) + 2) simulations, spread over
+        several threads with the GIL released. The samples and the bootstrap resamples are drawn
+        from a single seeded generator, so the results don't depend on the number of threads.
+
+        :param initial_state: The initial 6-DOF condition of the rocket.
+        :param parameters: The parameters to vary.
+        :param bounds: (lower, upper) range of each parameter.
+        :param integration_method: Numerical integration method to use.
+        :param timestep_config: Time step configuration (fixed or adaptive), or None for defaults.
+        :param output: The output of the flight to analyse.
+        :param num_samples: Number of base samples.
+        :param num_resamples: Number of bootstrap resamples for the confidence intervals.
+        :param confidence_level: Confidence level of the intervals, between 0 and 1.
+        :param seed: Seed of the random number generator, so analyses are reproducible.
+        :param num_threads: Number of threads, or None for one per available core.
+        :param max_iterations: Maximum integration iterations allowed per simulation phase.
+        :return: The indices and their confidence intervals.
+        :raises ValueError: If there isn't one finite, non-empty range per parameter, a parameter
+            doesn't apply to the model, the sample sizes or confidence level are invalid, or the
+            output doesn't vary.
+        """
+        ...
+    def state_jacobian_1dof(self, initial_state: InitialState1DOF) -> np.ndarray:
+        """
+        Compute the Jacobian of the time derivative of the 1-DOF state with respect to the state.
+
+        The models are evaluated with forward-mode dual numbers, so the Jacobian is exact rather
+        than a finite-difference approximation. Airbrakes are at their initial deployment, and
+        actuator states are held fixed.
+
+        :param initial_state: The 1-DOF state of the rocket.
+        :return: The (2, 2) Jacobian, with a row per state derivative.
+        """
+        ...
+
+    def state_jacobian_3dof(self, initial_state: InitialState3DOF) -> np.ndarray:
+        """
+        Compute the Jacobian of the time derivative of the 3-DOF state with respect to the state.
+
+        The models are evaluated with forward-mode dual numbers, so the Jacobian is exact rather
+        than a finite-difference approximation. Airbrakes are at their initial deployment, and
+        actuator states are held fixed.
+
+        :param initial_state: The 3-DOF state of the rocket, which has six components.
+        :return: The (6, 6) Jacobian, with a row per state derivative.
+        """
+        ...
@@ -0,0 +1,234 @@
+use crate::state::state_vector::AuxVector;
+use pyo3::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ActuatorModel {
+    /// Position follows the command with a first-order lag. States: [position]
+    FirstOrder { time_constant: f64 },
+    /// Second-order servo with rate saturation. States: [position, rate]
+    SecondOrder {
+        natural_frequency: f64,
+        damping_ratio: f64,
+        rate_limit: f64,
+    },
+    /// Proportionally controlled DC motor driving the mechanism through a gearbox.
+    /// States: [motor angle, motor speed, current]
+    DcMotor {
+        resistance: f64,
+        inductance: f64,
+        torque_constant: f64,
+        inertia: f64,
+        viscous_damping: f64,
+        travel: f64,
+        position_gain: f64,
+        max_voltage: f64,
+    },
+}
+
+/// Actuator dynamics for add-ons like airbrakes. The actuator's states are integrated together
+/// with the flight dynamics, and its position (a fraction from 0 to 1) lags behind the commanded
+/// position. The position is saturated to [0, 1].
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Actuator {
+    model: ActuatorModel,
+    /// Position of the actuator at the start of the simulation
+    #[pyo3(get, set)]
+    pub initial_position: f64,
+}
+
+#[pymethods]
+impl Actuator {
+    /// First-order lag, dx/dt = (command - x) / time_constant.
+    #[staticmethod]
+    #[pyo3(signature = (time_constant, initial_position=0.0))]
+    pub fn first_order(time_constant: f64, initial_position: f64) -> Self {
+        Self {
+            model: ActuatorModel::FirstOrder { time_constant },
+            initial_position,
+        }
+    }
+
+    /// Second-order servo with the given natural frequency (rad/s) and damping ratio, whose rate
+    /// is saturated at `rate_limit` (1/s).
+    #[staticmethod]
+    #[pyo3(signature = (natural_frequency, damping_ratio, rate_limit=f64::INFINITY, initial_position=0.0))]
+    pub fn second_order(
+        natural_frequency: f64,
+        damping_ratio: f64,
+        rate_limit: f64,
+        initial_position: f64,
+    ) -> Self {
+        Self {
+            model: ActuatorModel::SecondOrder {
+                natural_frequency,
+                damping_ratio,
+                rate_limit,
+            },
+            initial_position,
+        }
+    }
+
+    /// DC motor with armature resistance (Ohm) and inductance (H), torque constant (N*m/A, equal
+    /// to the back-EMF constant), rotor inertia (kg*m^2) and viscous damping (N*m*s/rad), all
+    /// referred to the output shaft. `travel` is the shaft angle for full deployment (rad). The
+    /// motor voltage is `position_gain` (V/rad) times the angle error, saturated at `max_voltage`.
+    #[staticmethod]
+    #[pyo3(signature = (resistance, inductance, torque_constant, inertia, viscous_damping, travel, position_gain, max_voltage, initial_position=0.0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn dc_motor(
+        resistance: f64,
+        inductance: f64,
+        torque_constant: f64,
+        inertia: f64,
+        viscous_damping: f64,
+        travel: f64,
+        position_gain: f64,
+        max_voltage: f64,
+        initial_position: f64,
+    ) -> Self {
+        Self {
+            model: ActuatorModel::DcMotor {
+                resistance,
+                inductance,
+                torque_constant,
+                inertia,
+                viscous_damping,
+                travel,
+                position_gain,
+                max_voltage,
+            },
+            initial_position,
+        }
+    }
+}
+
+impl Actuator {
+    /// Auxiliary states of the actuator resting at its initial position.
+    pub(crate) fn initial_state(&self) -> AuxVector {
+        let position = self.initial_position.clamp(0.0, 1.0);
+        match self.model {
+            ActuatorModel::DcMotor { travel, .. } => AuxVector::new(position * travel, 0.0, 0.0),
+            _ => AuxVector::new(position, 0.0, 0.0),
+        }
+    }
+
+    /// Position of the actuator (0 to 1) from its auxiliary states.
+    pub(crate) fn position(&self, aux: &AuxVector) -> f64 {
+        let position = match self.model {
+            ActuatorModel::DcMotor { travel, .. } => aux[0] / travel,
+            _ => aux[0],
+        };
+        position.clamp(0.0, 1.0)
+    }
+
+    /// Time derivatives of the auxiliary states for the given command (0 to 1).
+    pub(crate) fn derivatives(&self, aux: &AuxVector, command: f64) -> AuxVector {
+        let command = command.clamp(0.0, 1.0);
+        match self.model {
+            ActuatorModel::FirstOrder { time_constant } => {
+                let rate = (command - aux[0]) / time_constant;
+                AuxVector::new(Self::stop(aux[0], rate, 1.0), 0.0, 0.0)
+            }
+            ActuatorModel::SecondOrder {
+                natural_frequency,
+                damping_ratio,
+                rate_limit,
+            } => {
+                let rate = aux[1].clamp(-rate_limit, rate_limit);
+                let accel = natural_frequency.powi(2) * (command - aux[0])
+                    - 2.0 * damping_ratio * natural_frequency * aux[1];
+                // Keep the rate state from winding up past the limit
+                let accel = if aux[1].abs() >= rate_limit && accel * aux[1] > 0.0 {
+                    0.0
+                } else {
+                    accel
+                };
+                AuxVector::new(Self::stop(aux[0], rate, 1.0), accel, 0.0)
+            }
+            ActuatorModel::DcMotor {
+                resistance,
+                inductance,
+                torque_constant,
+                inertia,
+                viscous_damping,
+                travel,
+                position_gain,
+                max_voltage,
+            } => {
+                let (angle, speed, current) = (aux[0], aux[1], aux[2]);
+                let voltage =
+                    (position_gain * (command * travel - angle)).clamp(-max_voltage, max_voltage);
+                let dcurrent =
+                    (voltage - resistance * current - torque_constant * speed) / inductance;
+                let dspeed = (torque_constant * current - viscous_damping * speed) / inertia;
+                AuxVector::new(Self::stop(angle, speed, travel), dspeed, dcurrent)
+            }
+        }
+    }
+
+    /// Applies the hard stops at 0 and `max` to a position rate.
+    fn stop(position: f64, rate: f64, max: f64) -> f64 {
+        if (position <= 0.0 && rate < 0.0) || (position >= max && rate > 0.0) {
+            0.0
+        } else {
+            rate
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// Integrates the actuator with explicit Euler for `duration` seconds.
+    fn run(actuator: &Actuator, command: f64, duration: f64) -> AuxVector {
+        let dt = 1e-5;
+        let mut aux = actuator.initial_state();
+        for _ in 0..(duration / dt) as usize {
+            aux += actuator.derivatives(&aux, command) * dt;
+        }
+        aux
+    }
+
+    #[test]
+    fn first_order_lag_reaches_63_percent_after_one_time_constant() {
+        let actuator = Actuator::first_order(0.2, 0.0);
+        let aux = run(&actuator, 1.0, 0.2);
+        assert_relative_eq!(
+            actuator.position(&aux),
+            1.0 - (-1.0_f64).exp(),
+            epsilon = 1e-3
+        );
+    }
+
+    #[test]
+    fn second_order_servo_respects_rate_limit() {
+        let actuator = Actuator::second_order(50.0, 0.7, 2.0, 0.0);
+
+        // Moving the full range at 2/s takes at least 0.5 s
+        let aux = run(&actuator, 1.0, 0.25);
+        assert!(actuator.position(&aux) <= 0.5 + 1e-6);
+        assert!(actuator.position(&aux) > 0.4);
+
+        let aux = run(&actuator, 1.0, 1.0);
+        assert_relative_eq!(actuator.position(&aux), 1.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn dc_motor_settles_at_commanded_position() {
+        let actuator = Actuator::dc_motor(2.0, 1e-3, 0.5, 1e-3, 1e-3, 1.5, 20.0, 12.0, 0.0);
+        let aux = run(&actuator, 0.6, 2.0);
+        assert_relative_eq!(actuator.position(&aux), 0.6, epsilon = 1e-3);
+        assert_relative_eq!(aux[1], 0.0, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn position_is_saturated() {
+        let actuator = Actuator::first_order(0.1, 1.0);
+        assert_eq!(actuator.derivatives(&actuator.initial_state(), 2.0)[0], 0.0);
+        assert_eq!(actuator.position(&AuxVector::new(1.3, 0.0, 0.0)), 1.0);
+        assert_eq!(actuator.position(&AuxVector::new(-0.1, 0.0, 0.0)), 0.0);
+    }
+}
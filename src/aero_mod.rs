@@ -0,0 +1,505 @@
+use crate::constants::aero_constants::DEFAULT_TRANSITION_REYNOLDS;
+use crate::dual_mod::Real;
+use crate::physics_mod;
+use numpy::{PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// Reynolds number dependent skin friction drag. When a rocket has a skin friction model, its `cd`
+/// only represents the pressure and base drag, and the friction drag is computed from the local
+/// Reynolds number on top of it. The Reynolds number uses the standard atmosphere density and
+/// viscosity at the rocket's altitude, so it falls as the air thins on the way to apogee.
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkinFriction {
+    /// Wetted area of the rocket, including any form factors (m^2)
+    pub wetted_area: f64,
+    /// Length used for the Reynolds number, usually the length of the rocket (m)
+    pub length: f64,
+    /// Surface roughness height (m)
+    pub roughness: f64,
+    /// Reynolds number at which the boundary layer becomes turbulent, 0 for fully turbulent flow
+    pub transition_reynolds: f64,
+}
+
+#[pymethods]
+impl SkinFriction {
+    #[new]
+    #[pyo3(signature = (wetted_area, length, roughness=0.0, transition_reynolds=DEFAULT_TRANSITION_REYNOLDS))]
+    pub fn new(wetted_area: f64, length: f64, roughness: f64, transition_reynolds: f64) -> Self {
+        Self {
+            wetted_area,
+            length,
+            roughness,
+            transition_reynolds,
+        }
+    }
+
+    /// Friction drag coefficient at the given airspeed and altitude (m), referenced to
+    /// `reference_area`.
+    #[pyo3(signature = (velocity, reference_area, altitude=0.0))]
+    pub fn drag_coefficient(&self, velocity: f64, reference_area: f64, altitude: f64) -> f64 {
+        self.friction_drag_coefficient(velocity, reference_area, altitude)
+    }
+}
+
+impl SkinFriction {
+    pub(crate) fn friction_drag_coefficient<T: Real>(
+        &self,
+        velocity: T,
+        reference_area: T,
+        altitude: T,
+    ) -> T {
+        let reynolds = physics_mod::calc_reynolds_number(velocity, self.length, altitude);
+        let cf = physics_mod::skin_friction_coefficient(
+            reynolds,
+            self.roughness / self.length,
+            self.transition_reynolds,
+        );
+        cf * self.wetted_area / reference_area
+    }
+}
+
+/// Aerodynamic coefficients looked up from an `AeroTable`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct AeroCoefficients<T = f64> {
+    /// Axial force coefficient
+    pub(crate) ca: T,
+    /// Normal force coefficient
+    pub(crate) cn: T,
+    /// Distance from the nose tip to the center of pressure (m)
+    pub(crate) cp: T,
+}
+
+/// Tables of the axial force coefficient, normal force coefficient and center of pressure location
+/// as a function of Mach number and angle of attack, e.g. exported from RASAero II or CFD. The
+/// tables are interpolated bilinearly and clamped at their edges. The angle of attack is in
+/// radians and only the positive half is tabulated, since the rocket is assumed to be symmetric.
+///
+/// The tables are shared between clones, so copying the rocket properties into every integrator
+/// stage stays cheap.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AeroTable {
+    mach: Arc<[f64]>,
+    alpha: Arc<[f64]>,
+    /// Row major tables, indexed as [mach][alpha]
+    ca: Arc<[f64]>,
+    cn: Arc<[f64]>,
+    cp: Arc<[f64]>,
+}
+
+#[pymethods]
+impl AeroTable {
+    /// Creates a table from 1D Mach and angle of attack breakpoints and 2D (Mach x alpha)
+    /// coefficient arrays.
+    #[new]
+    pub fn new(
+        mach: PyReadonlyArray1<f64>,
+        alpha: PyReadonlyArray1<f64>,
+        ca: PyReadonlyArray2<f64>,
+        cn: PyReadonlyArray2<f64>,
+        cp: PyReadonlyArray2<f64>,
+    ) -> PyResult<Self> {
+        let flatten = |table: PyReadonlyArray2<f64>| -> (Vec<f64>, usize, usize) {
+            let view = table.as_array();
+            (view.iter().copied().collect(), view.nrows(), view.ncols())
+        };
+        let mach = mach.as_array().to_vec();
+        let alpha = alpha.as_array().to_vec();
+        let (ca, ca_rows, ca_cols) = flatten(ca);
+        let (cn, cn_rows, cn_cols) = flatten(cn);
+        let (cp, cp_rows, cp_cols) = flatten(cp);
+
+        for (rows, cols) in [(ca_rows, ca_cols), (cn_rows, cn_cols), (cp_rows, cp_cols)] {
+            if rows != mach.len() || cols != alpha.len() {
+                return Err(PyValueError::new_err(
+                    "Coefficient tables must have shape (len(mach), len(alpha))",
+                ));
+            }
+        }
+        Self::from_vecs(mach, alpha, ca, cn, cp).map_err(PyValueError::new_err)
+    }
+
+    /// Loads a table from a long format CSV file with one row per (Mach, alpha) pair, like the
+    /// aerodynamic exports of RASAero II. Column names are matched exactly. Angles are converted
+    /// from degrees if `alpha_in_degrees` is set, and CP locations are multiplied by `cp_scale`
+    /// (e.g. 0.0254 for inches).
+    #[staticmethod]
+    #[pyo3(signature = (path, mach_column="Mach", alpha_column="Alpha", ca_column="CA", cn_column="CN", cp_column="CP", alpha_in_degrees=true, cp_scale=1.0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_csv(
+        path: &str,
+        mach_column: &str,
+        alpha_column: &str,
+        ca_column: &str,
+        cn_column: &str,
+        cp_column: &str,
+        alpha_in_degrees: bool,
+        cp_scale: f64,
+    ) -> PyResult<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| PyIOError::new_err(format!("Could not read {path}: {e}")))?;
+        let columns = [mach_column, alpha_column, ca_column, cn_column, cp_column];
+        Self::from_csv_str(&contents, columns, alpha_in_degrees, cp_scale)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Looks up (CA, CN, CP) at the given Mach number and angle of attack (rad).
+    pub fn lookup(&self, mach: f64, alpha: f64) -> (f64, f64, f64) {
+        let coeffs = self.interpolate(mach, alpha);
+        (coeffs.ca, coeffs.cn, coeffs.cp)
+    }
+
+    #[getter]
+    pub fn mach(&self) -> Vec<f64> {
+        self.mach.to_vec()
+    }
+
+    #[getter]
+    pub fn alpha(&self) -> Vec<f64> {
+        self.alpha.to_vec()
+    }
+}
+
+impl AeroTable {
+    /// Creates a table from already flattened, row major (Mach x alpha) coefficient tables.
+    pub(crate) fn from_vecs(
+        mach: Vec<f64>,
+        alpha: Vec<f64>,
+        ca: Vec<f64>,
+        cn: Vec<f64>,
+        cp: Vec<f64>,
+    ) -> Result<Self, String> {
+        for (name, breakpoints) in [("mach", &mach), ("alpha", &alpha)] {
+            if breakpoints.is_empty() {
+                return Err(format!("{name} breakpoints must not be empty"));
+            }
+            if breakpoints.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(format!("{name} breakpoints must be strictly increasing"));
+            }
+        }
+        let size = mach.len() * alpha.len();
+        if ca.len() != size || cn.len() != size || cp.len() != size {
+            return Err("Coefficient tables must have len(mach) * len(alpha) entries".to_string());
+        }
+        if ca.iter().chain(&cn).chain(&cp).any(|c| !c.is_finite()) {
+            return Err("Coefficient tables must only contain finite values".to_string());
+        }
+        Ok(Self {
+            mach: mach.into(),
+            alpha: alpha.into(),
+            ca: ca.into(),
+            cn: cn.into(),
+            cp: cp.into(),
+        })
+    }
+
+    /// Parses a long format CSV table. `columns` holds the names of the Mach, alpha, CA, CN and CP
+    /// columns, in that order.
+    pub(crate) fn from_csv_str(
+        contents: &str,
+        columns: [&str; 5],
+        alpha_in_degrees: bool,
+        cp_scale: f64,
+    ) -> Result<Self, String> {
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let header: Vec<&str> = lines
+            .next()
+            .ok_or("CSV file is empty")?
+            .split(',')
+            .map(|name| name.trim())
+            .collect();
+        let mut indices = [0; 5];
+        for (index, name) in indices.iter_mut().zip(columns) {
+            *index = header
+                .iter()
+                .position(|h| *h == name)
+                .ok_or(format!("Column '{name}' not found in CSV header"))?;
+        }
+
+        let mut rows = Vec::new();
+        for (line_number, line) in lines.enumerate() {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            let mut row = [0.0; 5];
+            for (value, index) in row.iter_mut().zip(indices) {
+                *value = fields
+                    .get(index)
+                    .and_then(|f| f.parse::<f64>().ok())
+                    .ok_or(format!("Invalid number on data row {}", line_number + 1))?;
+            }
+            if alpha_in_degrees {
+                row[1] = row[1].to_radians();
+            }
+            row[4] *= cp_scale;
+            rows.push(row);
+        }
+
+        Self::from_rows(&rows)
+    }
+
+    /// Builds a table from (Mach, alpha, CA, CN, CP) rows which must cover the full grid of Mach
+    /// and alpha breakpoints.
+    pub(crate) fn from_rows(rows: &[[f64; 5]]) -> Result<Self, String> {
+        let breakpoints = |col: usize| {
+            let mut values: Vec<f64> = rows.iter().map(|row| row[col]).collect();
+            values.sort_by(|a, b| a.total_cmp(b));
+            values.dedup();
+            values
+        };
+        let mach = breakpoints(0);
+        let alpha = breakpoints(1);
+
+        let size = mach.len() * alpha.len();
+        if rows.len() != size {
+            return Err(format!(
+                "Table rows must cover the full Mach x alpha grid ({} x {}), found {} rows",
+                mach.len(),
+                alpha.len(),
+                rows.len()
+            ));
+        }
+
+        let (mut ca, mut cn, mut cp) = (
+            vec![f64::NAN; size],
+            vec![f64::NAN; size],
+            vec![f64::NAN; size],
+        );
+        for row in rows {
+            let i = mach.partition_point(|m| *m < row[0]);
+            let j = alpha.partition_point(|a| *a < row[1]);
+            let index = i * alpha.len() + j;
+            ca[index] = row[2];
+            cn[index] = row[3];
+            cp[index] = row[4];
+        }
+        if ca.iter().any(|c| c.is_nan()) {
+            return Err("Table rows contain duplicate (Mach, alpha) pairs".to_string());
+        }
+        Self::from_vecs(mach, alpha, ca, cn, cp)
+    }
+
+    /// Axial force coefficient at zero angle of attack at each Mach breakpoint, i.e. the knots of
+    /// the drag curve.
+    pub(crate) fn axial_force_knots(&self) -> Vec<f64> {
+        let n = self.alpha.len();
+        (0..self.mach.len()).map(|i| self.ca[i * n]).collect()
+    }
+
+    /// Copies the table with the axial force coefficient at zero angle of attack set to `knots`
+    /// at each Mach breakpoint. The rest of each Mach row is shifted by the same amount, so the
+    /// increase with angle of attack is kept.
+    pub(crate) fn with_axial_force_knots(&self, knots: &[f64]) -> Self {
+        let n = self.alpha.len();
+        let ca: Vec<f64> = self
+            .ca
+            .iter()
+            .enumerate()
+            .map(|(index, ca)| ca + knots[index / n] - self.ca[index / n * n])
+            .collect();
+        Self {
+            ca: ca.into(),
+            ..self.clone()
+        }
+    }
+
+    /// Bilinearly interpolates the coefficients at the given Mach number and angle of attack.
+    /// Negative angles of attack use the table for the positive angle, and both inputs are
+    /// clamped to the range of the table.
+    pub(crate) fn interpolate<T: Real>(&self, mach: T, alpha: T) -> AeroCoefficients<T> {
+        let (i, fm) = bracket(&self.mach, mach);
+        let (j, fa) = bracket(&self.alpha, alpha.abs());
+        let (i1, j1) = (
+            (i + 1).min(self.mach.len() - 1),
+            (j + 1).min(self.alpha.len() - 1),
+        );
+        let n = self.alpha.len();
+
+        let bilinear = |table: &[f64]| {
+            let low = lerp(table[i * n + j], table[i * n + j1], fa);
+            let high = lerp(table[i1 * n + j], table[i1 * n + j1], fa);
+            (T::constant(1.0) - fm) * low + fm * high
+        };
+
+        AeroCoefficients {
+            ca: bilinear(&self.ca),
+            cn: bilinear(&self.cn),
+            cp: bilinear(&self.cp),
+        }
+    }
+}
+
+/// Linear interpolation from `low` to `high` by `fraction`.
+fn lerp<T: Real>(low: f64, high: f64, fraction: T) -> T {
+    (T::constant(1.0) - fraction) * low + fraction * high
+}
+
+/// Finds the lower breakpoint index and the interpolation fraction for the given value, clamping
+/// it to the range of the breakpoints.
+fn bracket<T: Real>(breakpoints: &[f64], value: T) -> (usize, T) {
+    let last = breakpoints.len() - 1;
+    if last == 0 || value.value() <= breakpoints[0] {
+        return (0, T::constant(0.0));
+    }
+    if value.value() >= breakpoints[last] {
+        return (last, T::constant(0.0));
+    }
+    let i = breakpoints.partition_point(|b| *b <= value.value()) - 1;
+    let fraction = (value - breakpoints[i]) / (breakpoints[i + 1] - breakpoints[i]);
+    (i, fraction)
+}
+
+/// Linearly interpolates a 1D table, holding the end values outside of its range.
+pub(crate) fn interpolate_1d<T: Real>(breakpoints: &[f64], values: &[f64], value: T) -> T {
+    let (i, fraction) = bracket(breakpoints, value);
+    let i1 = (i + 1).min(breakpoints.len() - 1);
+    lerp(values[i], values[i1], fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn friction_drag_falls_with_airspeed() {
+        let skin_friction = SkinFriction::new(1.2, 2.5, 0.0, DEFAULT_TRANSITION_REYNOLDS);
+        let area = 0.0182;
+
+        // A smooth rocket loses friction drag coefficient as the Reynolds number grows
+        let slow = skin_friction.drag_coefficient(30.0, area, 0.0);
+        let fast = skin_friction.drag_coefficient(250.0, area, 0.0);
+        assert!(slow > fast);
+        assert!(fast > 0.0);
+    }
+
+    #[test]
+    fn friction_drag_rises_with_altitude() {
+        let skin_friction = SkinFriction::new(1.2, 2.5, 0.0, 0.0);
+        let area = 0.0182;
+
+        // The lower Reynolds number in the thinner air aloft raises the turbulent friction
+        let pad = skin_friction.drag_coefficient(200.0, area, 0.0);
+        let aloft = skin_friction.drag_coefficient(200.0, area, 3000.0);
+        assert!(aloft > pad);
+
+        let reynolds = physics_mod::calc_reynolds_number(200.0, 2.5, 3000.0);
+        let cf = physics_mod::skin_friction_coefficient(reynolds, 0.0, 0.0);
+        assert_relative_eq!(aloft, cf * 1.2 / area, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn drag_coefficient_matches_skin_friction_coefficient() {
+        let skin_friction = SkinFriction::new(1.2, 2.5, 50e-6, 0.0);
+        let velocity = 120.0;
+        let area = 0.0182;
+
+        let reynolds = physics_mod::calc_reynolds_number(velocity, 2.5, 0.0);
+        let cf = physics_mod::skin_friction_coefficient(reynolds, 50e-6 / 2.5, 0.0);
+        assert_relative_eq!(
+            skin_friction.drag_coefficient(velocity, area, 0.0),
+            cf * 1.2 / area,
+            epsilon = 1e-12
+        );
+    }
+
+    /// 3 Mach x 2 alpha table where every coefficient is a linear function of Mach and alpha, so
+    /// bilinear interpolation should reproduce it exactly.
+    fn make_table() -> AeroTable {
+        let mach = vec![0.0, 0.5, 1.0];
+        let alpha = vec![0.0, 0.2];
+        let f = |m: f64, a: f64, c: f64| c + m + 10.0 * a;
+        let mut ca = Vec::new();
+        let mut cn = Vec::new();
+        let mut cp = Vec::new();
+        for m in &mach {
+            for a in &alpha {
+                ca.push(f(*m, *a, 0.4));
+                cn.push(f(*m, *a, 0.0));
+                cp.push(f(*m, *a, 1.5));
+            }
+        }
+        AeroTable::from_vecs(mach, alpha, ca, cn, cp).unwrap()
+    }
+
+    #[test]
+    fn interpolate_is_exact_on_and_between_breakpoints() {
+        let table = make_table();
+
+        let on_grid = table.interpolate(0.5, 0.2);
+        assert_relative_eq!(on_grid.ca, 0.4 + 0.5 + 2.0, epsilon = 1e-12);
+        assert_relative_eq!(on_grid.cn, 0.5 + 2.0, epsilon = 1e-12);
+        assert_relative_eq!(on_grid.cp, 1.5 + 0.5 + 2.0, epsilon = 1e-12);
+
+        let between = table.interpolate(0.8, 0.05);
+        assert_relative_eq!(between.ca, 0.4 + 0.8 + 0.5, epsilon = 1e-12);
+        assert_relative_eq!(between.cn, 0.8 + 0.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn interpolate_clamps_and_mirrors_alpha() {
+        let table = make_table();
+
+        // Negative angles of attack look up the positive angle
+        assert_eq!(table.interpolate(0.3, -0.1), table.interpolate(0.3, 0.1));
+
+        // Outside the table the edge values are held
+        assert_eq!(table.interpolate(3.0, 0.1), table.interpolate(1.0, 0.1));
+        assert_eq!(table.interpolate(0.3, 1.0), table.interpolate(0.3, 0.2));
+        assert_eq!(table.interpolate(-1.0, 0.0), table.interpolate(0.0, 0.0));
+    }
+
+    #[test]
+    fn single_breakpoint_tables_are_constant_in_that_direction() {
+        let table = AeroTable::from_vecs(
+            vec![0.3],
+            vec![0.0, 0.1],
+            vec![0.5, 0.7],
+            vec![0.0, 1.0],
+            vec![1.0, 1.0],
+        )
+        .unwrap();
+        let coeffs = table.interpolate(0.9, 0.05);
+        assert_relative_eq!(coeffs.ca, 0.6, epsilon = 1e-12);
+        assert_relative_eq!(coeffs.cn, 0.5, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn from_vecs_rejects_invalid_tables() {
+        let ok = |m: Vec<f64>, a: Vec<f64>, n: usize| {
+            AeroTable::from_vecs(m, a, vec![0.5; n], vec![0.0; n], vec![1.0; n])
+        };
+        assert!(ok(vec![0.0, 0.5], vec![0.0, 0.1], 4).is_ok());
+        assert!(ok(vec![0.5, 0.0], vec![0.0, 0.1], 4).is_err());
+        assert!(ok(vec![0.0, 0.5], vec![0.0, 0.0], 4).is_err());
+        assert!(ok(vec![0.0, 0.5], vec![0.0, 0.1], 3).is_err());
+        assert!(ok(vec![], vec![0.0], 0).is_err());
+    }
+
+    #[test]
+    fn from_csv_str_parses_long_format_tables() {
+        let csv = "Mach,Alpha,CA,CN,CP
+            0.1,0,0.45,0.0,40
+            0.1,4,0.50,0.8,41
+            0.5,4,0.55,0.9,42
+            0.5,0,0.47,0.0,39
+            ";
+        let columns = ["Mach", "Alpha", "CA", "CN", "CP"];
+        let table = AeroTable::from_csv_str(csv, columns, true, 0.0254).unwrap();
+
+        assert_eq!(*table.mach, [0.1, 0.5]);
+        assert_relative_eq!(table.alpha[1], 4.0_f64.to_radians(), epsilon = 1e-12);
+
+        let coeffs = table.interpolate(0.5, 4.0_f64.to_radians());
+        assert_relative_eq!(coeffs.ca, 0.55, epsilon = 1e-12);
+        assert_relative_eq!(coeffs.cn, 0.9, epsilon = 1e-12);
+        assert_relative_eq!(coeffs.cp, 42.0 * 0.0254, epsilon = 1e-12);
+
+        // Missing columns and incomplete grids are rejected
+        let missing = ["Mach", "Alpha", "CA Power-Off", "CN", "CP"];
+        assert!(AeroTable::from_csv_str(csv, missing, true, 1.0).is_err());
+        let incomplete = "Mach,Alpha,CA,CN,CP\n0.1,0,0.45,0.0,40\n0.5,4,0.55,0.9,42\n";
+        assert!(AeroTable::from_csv_str(incomplete, columns, true, 1.0).is_err());
+    }
+}
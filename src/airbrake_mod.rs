@@ -0,0 +1,233 @@
+use crate::actuator_mod::Actuator;
+use crate::aero_mod;
+use crate::dual_mod::Real;
+use crate::state::state_vector::AuxVector;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+#[derive(Clone, Debug, PartialEq)]
+enum CurveKind {
+    /// Piecewise linear table, clamped at its ends
+    Table { x: Arc<[f64]>, y: Arc<[f64]> },
+    /// Polynomial coefficients in increasing order of power
+    Polynomial(Arc<[f64]>),
+}
+
+/// A 1D function, given either as a piecewise linear table or as a polynomial. Used for the
+/// deployment and Mach number dependence of add-ons like airbrakes.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    kind: CurveKind,
+}
+
+#[pymethods]
+impl Curve {
+    /// Piecewise linear table through the points (x, y), holding the end values outside of it.
+    #[staticmethod]
+    pub fn table(x: Vec<f64>, y: Vec<f64>) -> PyResult<Self> {
+        Self::from_table(x, y).map_err(PyValueError::new_err)
+    }
+
+    /// Polynomial c0 + c1*x + c2*x^2 + ..., with the coefficients in increasing order of power.
+    #[staticmethod]
+    pub fn polynomial(coefficients: Vec<f64>) -> Self {
+        Self {
+            kind: CurveKind::Polynomial(coefficients.into()),
+        }
+    }
+
+    /// Constant function.
+    #[staticmethod]
+    pub fn constant(value: f64) -> Self {
+        Self::polynomial(vec![value])
+    }
+
+    /// Evaluates the curve at `x`.
+    pub fn evaluate(&self, x: f64) -> f64 {
+        self.value_at(x)
+    }
+}
+
+impl Curve {
+    pub(crate) fn value_at<T: Real>(&self, x: T) -> T {
+        match &self.kind {
+            CurveKind::Table { x: xs, y: ys } => aero_mod::interpolate_1d(xs, ys, x),
+            // Horner's method
+            CurveKind::Polynomial(coefficients) => coefficients
+                .iter()
+                .rev()
+                .fold(T::constant(0.0), |acc, c| acc * x + *c),
+        }
+    }
+
+    pub(crate) fn from_table(x: Vec<f64>, y: Vec<f64>) -> Result<Self, String> {
+        if x.is_empty() || x.len() != y.len() {
+            return Err(
+                "Curve tables need the same, non-zero number of x and y values".to_string(),
+            );
+        }
+        if x.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("Curve table x values must be strictly increasing".to_string());
+        }
+        Ok(Self {
+            kind: CurveKind::Table {
+                x: x.into(),
+                y: y.into(),
+            },
+        })
+    }
+}
+
+/// Airbrake add-on which increases the drag of the rocket as it deploys. The deployment fraction
+/// goes from 0 (retracted) to 1 (fully deployed), and sets both a drag coefficient increment and
+/// extra reference area. With deployment d and Mach number M, the rocket's drag becomes
+///
+/// `D = q * (cd + cd_increment(d) * mach_factor(M)) * (area_drag + area_increment(d))`
+///
+/// Without an actuator the airbrakes are instantly at `deployment`. With an actuator,
+/// `deployment` is the commanded position and the actual deployment follows the actuator dynamics.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Airbrake {
+    /// Deployment fraction, clamped to [0, 1] when used
+    #[pyo3(get, set)]
+    pub deployment: f64,
+    /// Drag coefficient increment as a function of the deployment fraction
+    #[pyo3(get)]
+    pub cd_increment: Curve,
+    /// Extra reference area as a function of the deployment fraction (m^2)
+    #[pyo3(get)]
+    pub area_increment: Option<Curve>,
+    /// Optional factor on the drag coefficient increment as a function of Mach number
+    #[pyo3(get)]
+    pub mach_factor: Option<Curve>,
+    /// Optional actuator dynamics between the commanded and actual deployment
+    #[pyo3(get)]
+    pub actuator: Option<Actuator>,
+}
+
+#[pymethods]
+impl Airbrake {
+    #[new]
+    #[pyo3(signature = (cd_increment, area_increment=None, mach_factor=None, deployment=0.0, actuator=None))]
+    pub fn new(
+        cd_increment: Curve,
+        area_increment: Option<Curve>,
+        mach_factor: Option<Curve>,
+        deployment: f64,
+        actuator: Option<Actuator>,
+    ) -> Self {
+        Self {
+            deployment,
+            cd_increment,
+            area_increment,
+            mach_factor,
+            actuator,
+        }
+    }
+
+    /// Drag coefficient and reference area increments at `deployment` and the given Mach number.
+    pub fn increments(&self, mach: f64) -> (f64, f64) {
+        self.increments_at(self.deployment, mach)
+    }
+}
+
+impl Airbrake {
+    /// Drag coefficient and reference area increments at the given deployment and Mach number.
+    pub(crate) fn increments_at<T: Real>(&self, deployment: f64, mach: T) -> (T, f64) {
+        let deployment = deployment.clamp(0.0, 1.0);
+        let factor = self
+            .mach_factor
+            .as_ref()
+            .map_or(T::constant(1.0), |f| f.value_at(mach));
+        let cd = factor * self.cd_increment.evaluate(deployment);
+        let area = self
+            .area_increment
+            .as_ref()
+            .map_or(0.0, |a| a.evaluate(deployment));
+        (cd, area)
+    }
+
+    /// Actual deployment, taken from the actuator's auxiliary states if there is an actuator.
+    pub(crate) fn actual_deployment(&self, aux: &AuxVector) -> f64 {
+        match &self.actuator {
+            Some(actuator) => actuator.position(aux),
+            None => self.deployment,
+        }
+    }
+
+    /// Applies the airbrake to the rocket's drag coefficient and reference area.
+    pub(crate) fn apply<T: Real>(&self, cd: T, area: T, mach: T, aux: &AuxVector) -> (T, T) {
+        let (cd_increment, area_increment) = self.increments_at(self.actual_deployment(aux), mach);
+        (cd + cd_increment, area + area_increment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn curves_evaluate_tables_and_polynomials() {
+        let table = Curve::from_table(vec![0.0, 0.5, 1.0], vec![0.0, 0.2, 0.6]).unwrap();
+        assert_relative_eq!(table.evaluate(0.25), 0.1, epsilon = 1e-12);
+        assert_relative_eq!(table.evaluate(0.75), 0.4, epsilon = 1e-12);
+        assert_relative_eq!(table.evaluate(2.0), 0.6, epsilon = 1e-12);
+
+        let polynomial = Curve::polynomial(vec![1.0, 2.0, 3.0]);
+        assert_relative_eq!(polynomial.evaluate(2.0), 1.0 + 4.0 + 12.0, epsilon = 1e-12);
+        assert_relative_eq!(Curve::constant(0.7).evaluate(5.0), 0.7, epsilon = 1e-12);
+
+        assert!(Curve::from_table(vec![0.0, 0.0], vec![1.0, 2.0]).is_err());
+        assert!(Curve::from_table(vec![0.0], vec![]).is_err());
+    }
+
+    #[test]
+    fn airbrake_increments_follow_deployment_and_mach() {
+        let mut airbrake = Airbrake::new(
+            Curve::polynomial(vec![0.0, 0.8]),
+            Some(Curve::polynomial(vec![0.0, 0.01])),
+            Some(Curve::from_table(vec![0.3, 0.8], vec![1.0, 0.5]).unwrap()),
+            0.0,
+            None,
+        );
+        let aux = AuxVector::zeros();
+
+        // Retracted airbrakes do nothing
+        assert_eq!(airbrake.apply(0.5, 0.02, 0.1, &aux), (0.5, 0.02));
+
+        airbrake.deployment = 0.5;
+        let (cd, area) = airbrake.apply(0.5, 0.02, 0.1, &aux);
+        assert_relative_eq!(cd, 0.5 + 0.4, epsilon = 1e-12);
+        assert_relative_eq!(area, 0.02 + 0.005, epsilon = 1e-12);
+
+        // The Mach factor only scales the drag coefficient increment
+        let (cd, area) = airbrake.apply(0.5, 0.02, 0.8, &aux);
+        assert_relative_eq!(cd, 0.5 + 0.2, epsilon = 1e-12);
+        assert_relative_eq!(area, 0.02 + 0.005, epsilon = 1e-12);
+
+        // Deployment is clamped to [0, 1]
+        airbrake.deployment = 1.5;
+        assert_eq!(airbrake.increments(0.1), (0.8, 0.01));
+    }
+
+    #[test]
+    fn actuated_airbrake_uses_the_actuator_position() {
+        let mut airbrake = Airbrake::new(
+            Curve::polynomial(vec![0.0, 1.0]),
+            None,
+            None,
+            1.0,
+            Some(Actuator::first_order(0.1, 0.0)),
+        );
+        let aux = AuxVector::new(0.25, 0.0, 0.0);
+        assert_eq!(airbrake.actual_deployment(&aux), 0.25);
+        assert_eq!(airbrake.apply(0.5, 0.02, 0.1, &aux), (0.75, 0.02));
+
+        airbrake.actuator = None;
+        assert_eq!(airbrake.actual_deployment(&aux), 1.0);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::dispersion_mod::{apply_parameters, validate_parameters, DispersedParameter};
+use crate::ode::OdeSolver;
+use crate::rocket::RocketProperties;
+use crate::simulation;
+use crate::state::InitialCondition;
+
+/// Maps `f` over the items on `num_threads` scoped threads, or one per available core, keeping
+/// the order of the items. A panic on any thread is resumed on the calling thread.
+pub(crate) fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    num_threads: Option<usize>,
+    f: impl Fn(&T) -> R + Sync,
+) -> Result<Vec<R>, String> {
+    let num_threads = num_threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |threads| threads.get()));
+    if num_threads == 0 {
+        return Err("num_threads must be positive".to_string());
+    }
+
+    let chunk_size = items.len().div_ceil(num_threads).max(1);
+    let f = &f;
+    Ok(std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    }))
+}
+
+/// Apogee predictions for a batch of initial conditions and/or values of rocket parameters.
+/// Either may have a single entry, which is then shared by the whole batch.
+#[derive(Clone, Debug)]
+pub(crate) struct ApogeeBatch {
+    pub(crate) rocket_properties: RocketProperties,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) max_iterations: u64,
+    pub(crate) initial_conditions: Vec<InitialCondition>,
+    pub(crate) parameters: Vec<DispersedParameter>,
+    /// Row of parameter values per prediction
+    pub(crate) values: Vec<Vec<f64>>,
+}
+
+impl ApogeeBatch {
+    /// Number of predictions, after broadcasting single entries.
+    fn len(&self) -> Result<usize, String> {
+        if self.initial_conditions.is_empty() {
+            return Err("There must be at least one initial state".to_string());
+        }
+        if self
+            .values
+            .iter()
+            .any(|row| row.len() != self.parameters.len())
+            || (self.values.is_empty() && !self.parameters.is_empty())
+        {
+            return Err("There must be one value per parameter in each row".to_string());
+        }
+        match (self.initial_conditions.len(), self.values.len()) {
+            (n, 0 | 1) | (1, n) => Ok(n),
+            (n, m) if n == m => Ok(n),
+            (n, m) => Err(format!(
+                "{n} initial states and {m} rows of values can't be broadcast together"
+            )),
+        }
+    }
+
+    /// Predicts every apogee on `num_threads` threads, or one per available core. Predictions
+    /// which don't reach apogee give the best estimate, like a single prediction.
+    pub(crate) fn run(&self, num_threads: Option<usize>) -> Result<Vec<f64>, String> {
+        let len = self.len()?;
+        for initial_condition in &self.initial_conditions {
+            validate_parameters(&self.parameters, initial_condition)?;
+        }
+
+        let indices: Vec<usize> = (0..len).collect();
+        parallel_map(&indices, num_threads, |&i| {
+            let initial_condition =
+                self.initial_conditions[i.min(self.initial_conditions.len() - 1)];
+            let values = self
+                .values
+                .get(i.min(self.values.len().saturating_sub(1)))
+                .map_or(&[][..], Vec::as_slice);
+            let (rocket_properties, initial_condition) = apply_parameters(
+                &self.parameters,
+                values,
+                &self.rocket_properties,
+                initial_condition,
+            );
+            simulation::apogee(
+                initial_condition.to_state(rocket_properties),
+                self.ode_solver.clone(),
+                self.max_iterations,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::FixedTimeStep;
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+
+    fn make_batch(
+        initial_conditions: Vec<InitialCondition>,
+        parameters: Vec<DispersedParameter>,
+        values: Vec<Vec<f64>>,
+    ) -> ApogeeBatch {
+        ApogeeBatch {
+            rocket_properties: RocketProperties::new(10.0, 0.5, 0.0182, 0.0182, 5.0, 0.3, 2.0),
+            ode_solver: OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            max_iterations: 100_000,
+            initial_conditions,
+            parameters,
+            values,
+        }
+    }
+
+    fn one_dof(velocity: f64) -> InitialCondition {
+        InitialCondition::OneDOF(InitialState1DOF::new(0.0, velocity))
+    }
+
+    #[test]
+    fn parallel_map_keeps_the_order() {
+        let items: Vec<u32> = (0..103).collect();
+        for num_threads in [1, 4, 200] {
+            let squares = parallel_map(&items, Some(num_threads), |x| x * x).unwrap();
+            assert_eq!(squares, items.iter().map(|x| x * x).collect::<Vec<_>>());
+        }
+        assert!(parallel_map(&items, Some(0), |x| *x).is_err());
+        assert!(parallel_map(&[] as &[u32], None, |x| *x)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn batch_matches_single_predictions() {
+        let batch = make_batch(
+            vec![one_dof(100.0), one_dof(150.0), one_dof(200.0)],
+            vec![],
+            vec![],
+        );
+        let apogees = batch.run(Some(2)).unwrap();
+        for (initial_condition, apogee) in batch.initial_conditions.iter().zip(&apogees) {
+            let expected = simulation::apogee(
+                initial_condition.to_state(batch.rocket_properties.clone()),
+                batch.ode_solver.clone(),
+                batch.max_iterations,
+            );
+            assert_eq!(*apogee, expected);
+        }
+        assert!(apogees.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn single_entries_are_broadcast() {
+        // One initial state swept over drag coefficients
+        let batch = make_batch(
+            vec![one_dof(150.0)],
+            vec![DispersedParameter::Cd],
+            vec![vec![0.3], vec![0.5], vec![0.7]],
+        );
+        let apogees = batch.run(None).unwrap();
+        assert_eq!(apogees.len(), 3);
+        assert!(apogees.windows(2).all(|pair| pair[0] > pair[1]));
+        // cd = 0.5 is the rocket's own drag coefficient
+        let nominal = make_batch(vec![one_dof(150.0)], vec![], vec![]);
+        assert_abs_diff_eq!(apogees[1], nominal.run(None).unwrap()[0]);
+
+        // Several initial states with the same values
+        let batch = make_batch(
+            vec![one_dof(150.0), one_dof(160.0)],
+            vec![DispersedParameter::Mass],
+            vec![vec![12.0]],
+        );
+        assert_eq!(batch.run(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn invalid_batches_are_rejected() {
+        let mismatched = make_batch(
+            vec![one_dof(150.0), one_dof(160.0)],
+            vec![DispersedParameter::Cd],
+            vec![vec![0.3], vec![0.5], vec![0.7]],
+        );
+        assert!(mismatched.run(None).is_err());
+        let short_row = make_batch(
+            vec![one_dof(150.0)],
+            vec![DispersedParameter::Cd],
+            vec![vec![]],
+        );
+        assert!(short_row.run(None).is_err());
+        let no_values = make_batch(vec![one_dof(150.0)], vec![DispersedParameter::Cd], vec![]);
+        assert!(no_values.run(None).is_err());
+        assert!(make_batch(vec![], vec![], vec![]).run(None).is_err());
+        let wind = make_batch(
+            vec![one_dof(150.0)],
+            vec![DispersedParameter::WindSpeed],
+            vec![vec![5.0]],
+        );
+        assert!(wind.run(None).is_err());
+        let three_dof =
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.0, 0.0, 150.0, 0.0));
+        let wind = ApogeeBatch {
+            initial_conditions: vec![three_dof],
+            ..wind
+        };
+        assert_eq!(wind.run(None).unwrap().len(), 1);
+    }
+}
@@ -16,15 +16,71 @@ pub mod physics_constants {
     pub const GRAVITY_M_S_2: f64 = 9.80665;
     /// ISA air density at sea level in kg/m^3
     pub const SEA_LEVEL_AIR_DENSITY_KG_M_3: f64 = 1.225;
+    /// ISA dynamic viscosity of air at sea level in Pa*s
+    pub const SEA_LEVEL_DYNAMIC_VISCOSITY_PA_S: f64 = 1.789e-5;
+    /// ISA speed of sound at sea level in m/s
+    pub const SEA_LEVEL_SPEED_OF_SOUND_M_S: f64 = 340.294;
+    /// ISA air temperature at sea level in K
+    pub const SEA_LEVEL_TEMPERATURE_K: f64 = 288.15;
+    /// ISA temperature lapse rate of the troposphere in K/m
+    pub const TROPOSPHERE_LAPSE_RATE_K_M: f64 = 0.0065;
+    /// ISA altitude of the tropopause, above which the temperature is constant, in m
+    pub const TROPOPAUSE_ALTITUDE_M: f64 = 11_000.0;
+    /// Specific gas constant of dry air in J/(kg*K)
+    pub const AIR_GAS_CONSTANT_J_KG_K: f64 = 287.053;
+    /// Sutherland's constant for the viscosity of air in K
+    pub const SUTHERLAND_TEMPERATURE_K: f64 = 110.4;
+}
+
+pub mod aero_constants {
+    /// The default airspeed (m/s) at which the geometry based drag buildup is evaluated
+    pub const DEFAULT_ESTIMATE_VELOCITY_M_S: f64 = 100.0;
+    /// The default surface roughness height (m), roughly that of a smooth painted surface
+    pub const DEFAULT_SURFACE_ROUGHNESS_M: f64 = 20e-6;
+    /// The default Reynolds number at which the boundary layer transitions to turbulent
+    pub const DEFAULT_TRANSITION_REYNOLDS: f64 = 5.0e5;
+    /// Number of segments used to numerically integrate nose cone profiles
+    pub const NOSE_PROFILE_SEGMENTS: usize = 200;
+}
+
+pub mod control_constants {
+    /// The default rate (Hz) at which closed-loop controllers are updated
+    pub const DEFAULT_CONTROL_RATE_HZ: f64 = 100.0;
+}
+
+pub mod solver_constants {
+    /// The default tolerance (m) on the apogee error of targeting searches
+    pub const DEFAULT_APOGEE_TOLERANCE_M: f64 = 0.5;
+    /// The default maximum number of simulations a targeting search may run
+    pub const DEFAULT_MAX_EVALUATIONS: u32 = 20;
+    /// The default maximum number of Levenberg-Marquardt iterations when fitting flight data
+    pub const DEFAULT_FIT_ITERATIONS: u32 = 50;
+    /// The default relative tolerance on the parameter step and cost change of a fit
+    pub const DEFAULT_FIT_TOLERANCE: f64 = 1e-8;
+    /// The default step of a parameter in a sensitivity analysis, relative to its magnitude
+    pub const DEFAULT_SENSITIVITY_STEP: f64 = 1e-5;
+    /// The default width (rad) to which launch angle searches bracket the optimal angle
+    pub const DEFAULT_ANGLE_TOLERANCE_RAD: f64 = 1e-3;
+    /// The default largest tilt (rad) of the launch rail from vertical, 20 degrees
+    pub const DEFAULT_MAX_RAIL_ANGLE_RAD: f64 = 0.349;
 }
 
 pub mod simulation_constants {
     /// Number of data columns in SimulationData (matches StateVector length)
     pub const DATA_LENGTH: usize = 9; // TODO: change to 18 when 6DOF is implemented
+    /// Number of derived channels SimulationData can record (one per DerivedChannel)
+    pub const DERIVED_DATA_LENGTH: usize = 7;
+    /// Number of data columns in the control log of SimulationData
+    /// (time, predicted apogee, command, actual deployment)
+    pub const CONTROL_DATA_LENGTH: usize = 4;
     /// The initial number of rows to pre-allocate in SimulationData
     pub const INITIAL_DATA_CAPACITY: usize = 1000;
     /// The velocity threshold (m/s) below which the rocket is considered to have reached apogee
     pub const APOGEE_VELOCITY_THRESHOLD_M_S: f64 = 0.5;
+    /// The time (s) to which the peaks of a flight are located between timesteps
+    pub const EVENT_TIME_TOLERANCE_S: f64 = 1e-6;
+    /// The maximum number of repeated timesteps taken to locate a peak of a flight
+    pub const MAX_EVENT_EVALUATIONS: u32 = 60;
     /// The default number of iterations to run the simulation for if no stopping condition is met
     pub const MAX_ITERATIONS: u64 = 1e5 as u64;
 }
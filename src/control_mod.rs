@@ -0,0 +1,408 @@
+use crate::constants::control_constants::DEFAULT_CONTROL_RATE_HZ;
+use crate::simdata_mod::SimulationData;
+use crate::simulation;
+use crate::state::State;
+use pyo3::prelude::*;
+use std::fmt;
+use std::sync::Arc;
+
+/// Custom control law, called with (time, state log row, predicted apogee) and returning the
+/// command.
+type CustomLaw = dyn Fn(f64, &[f64], f64) -> Result<f64, String> + Send + Sync;
+
+#[derive(Clone)]
+enum ControlLaw {
+    /// PID on the predicted apogee error
+    Pid {
+        target_apogee: f64,
+        kp: f64,
+        ki: f64,
+        kd: f64,
+    },
+    /// Fully deploys when the predicted apogee is above the target, retracts when it is below
+    BangBang { target_apogee: f64, deadband: f64 },
+    /// User supplied control law, e.g. a Python function
+    Custom(Arc<CustomLaw>),
+}
+
+impl fmt::Debug for ControlLaw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlLaw::Pid {
+                target_apogee,
+                kp,
+                ki,
+                kd,
+            } => f
+                .debug_struct("Pid")
+                .field("target_apogee", target_apogee)
+                .field("kp", kp)
+                .field("ki", ki)
+                .field("kd", kd)
+                .finish(),
+            ControlLaw::BangBang {
+                target_apogee,
+                deadband,
+            } => f
+                .debug_struct("BangBang")
+                .field("target_apogee", target_apogee)
+                .field("deadband", deadband)
+                .finish(),
+            ControlLaw::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// Closed-loop airbrake controller which runs inside the simulation. At every update it sees the
+/// simulated state and the apogee predicted with the current command held, and outputs a new
+/// airbrake deployment command (0 to 1). The command is held constant between updates, and the
+/// timesteps of the simulation end at the update times so that every update is run.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Controller {
+    law: ControlLaw,
+    /// Update rate of the controller (Hz)
+    #[pyo3(get, set)]
+    pub rate_hz: f64,
+}
+
+#[pymethods]
+impl Controller {
+    /// PID controller on the apogee error (predicted - target, in m). The integral is only
+    /// accumulated while the command is not saturated, to avoid windup.
+    #[staticmethod]
+    #[pyo3(signature = (target_apogee, kp, ki=0.0, kd=0.0, rate_hz=DEFAULT_CONTROL_RATE_HZ))]
+    pub fn pid(target_apogee: f64, kp: f64, ki: f64, kd: f64, rate_hz: f64) -> Self {
+        Self {
+            law: ControlLaw::Pid {
+                target_apogee,
+                kp,
+                ki,
+                kd,
+            },
+            rate_hz,
+        }
+    }
+
+    /// Bang-bang controller which fully deploys when the predicted apogee is more than
+    /// `deadband` (m) above the target, retracts when it is more than `deadband` below, and holds
+    /// the command in between.
+    #[staticmethod]
+    #[pyo3(signature = (target_apogee, deadband=0.0, rate_hz=DEFAULT_CONTROL_RATE_HZ))]
+    pub fn bang_bang(target_apogee: f64, deadband: f64, rate_hz: f64) -> Self {
+        Self {
+            law: ControlLaw::BangBang {
+                target_apogee,
+                deadband,
+            },
+            rate_hz,
+        }
+    }
+
+    /// Controller which calls a Python function as `callback(time, state, predicted_apogee)`,
+    /// where `state` is the current row of the state log, and uses its return value as the
+    /// command.
+    #[staticmethod]
+    #[pyo3(signature = (callback, rate_hz=DEFAULT_CONTROL_RATE_HZ))]
+    pub fn python(callback: Py<PyAny>, rate_hz: f64) -> Self {
+        let law = move |time: f64, state: &[f64], predicted_apogee: f64| {
+            Python::attach(|py| {
+                callback
+                    .call1(py, (time, state.to_vec(), predicted_apogee))
+                    .and_then(|command| command.bind(py).extract::<f64>())
+                    .map_err(|e| e.to_string())
+            })
+        };
+        Self {
+            law: ControlLaw::Custom(Arc::new(law)),
+            rate_hz,
+        }
+    }
+}
+
+impl Controller {
+    /// Controller which uses a Rust closure as its control law.
+    #[cfg(test)]
+    pub(crate) fn custom(
+        law: impl Fn(f64, &[f64], f64) -> Result<f64, String> + Send + Sync + 'static,
+        rate_hz: f64,
+    ) -> Self {
+        Self {
+            law: ControlLaw::Custom(Arc::new(law)),
+            rate_hz,
+        }
+    }
+}
+
+/// Runs a `Controller` during a simulation, holding its internal state (e.g. the PID integral)
+/// and the zero-order hold between updates.
+#[derive(Clone, Debug)]
+pub(crate) struct ControlLoop {
+    controller: Controller,
+    next_update: f64,
+    command: f64,
+    integral: f64,
+    /// Time and apogee error of the previous update
+    previous_error: Option<(f64, f64)>,
+    /// Error message of a failed custom control law, which stops the simulation
+    pub(crate) error: Option<String>,
+}
+
+impl ControlLoop {
+    /// Creates a control loop for the given initial state. The rocket must have an airbrake.
+    pub(crate) fn new(controller: Controller, state: &State) -> Result<Self, String> {
+        if controller.rate_hz <= 0.0 || !controller.rate_hz.is_finite() {
+            return Err("Controller rate_hz must be positive".to_string());
+        }
+        let command = state
+            .get_rocket_properties()
+            .airbrake
+            .as_ref()
+            .ok_or("Closed-loop control requires the rocket to have an airbrake")?
+            .deployment;
+
+        Ok(Self {
+            controller,
+            next_update: state.get_time(),
+            command,
+            integral: 0.0,
+            previous_error: None,
+            error: None,
+        })
+    }
+
+    /// Updates the command if an update is due, applies it to the state and logs the update.
+    pub(crate) fn update(&mut self, state: &mut State, log: &mut SimulationData) {
+        let time = state.get_time();
+        let period = 1.0 / self.controller.rate_hz;
+        // Allow for round-off in the accumulated time of fixed timesteps
+        let tolerance = 1e-6 * period;
+        if time < self.next_update - tolerance || self.error.is_some() {
+            return;
+        }
+        // The timesteps end at the update times, so this only moves past the current update
+        while self.next_update <= time + tolerance {
+            self.next_update += period;
+        }
+
+        let predicted_apogee = self.predict_apogee(state);
+        match self.control_law(time, state, predicted_apogee) {
+            Ok(command) => self.command = command.clamp(0.0, 1.0),
+            Err(error) => {
+                self.error = Some(error);
+                return;
+            }
+        }
+        state.set_airbrake_command(self.command);
+
+        let deployment = state.get_airbrake_deployment().unwrap_or(f64::NAN);
+        log.add_control_row([time, predicted_apogee, self.command, deployment]);
+    }
+
+    /// Time (s) from `time` to the next update, which the next timestep must not go past.
+    pub(crate) fn time_to_update(&self, time: f64) -> f64 {
+        self.next_update - time
+    }
+
+    /// Apogee predicted with the model of the simulated flight, assuming the current command is
+    /// held and the airbrakes instantly move to it.
+    fn predict_apogee(&self, state: &State) -> f64 {
+        let mut rocket_properties = state.get_rocket_properties().clone();
+        if let Some(airbrake) = rocket_properties.airbrake.as_mut() {
+            airbrake.deployment = self.command;
+            airbrake.actuator = None;
+        }
+        simulation::predict_apogee(state.with_rocket_properties(rocket_properties))
+    }
+
+    fn control_law(
+        &mut self,
+        time: f64,
+        state: &State,
+        predicted_apogee: f64,
+    ) -> Result<f64, String> {
+        match &self.controller.law {
+            ControlLaw::Pid {
+                target_apogee,
+                kp,
+                ki,
+                kd,
+            } => {
+                let error = predicted_apogee - target_apogee;
+                let (integral, derivative) = match self.previous_error {
+                    Some((previous_time, previous_error)) if time > previous_time => {
+                        let dt = time - previous_time;
+                        (self.integral + error * dt, (error - previous_error) / dt)
+                    }
+                    _ => (self.integral, 0.0),
+                };
+                self.previous_error = Some((time, error));
+
+                let command = kp * error + ki * integral + kd * derivative;
+                if (0.0..=1.0).contains(&command) {
+                    self.integral = integral;
+                }
+                Ok(command)
+            }
+            ControlLaw::BangBang {
+                target_apogee,
+                deadband,
+            } => {
+                let error = predicted_apogee - target_apogee;
+                Ok(if error > *deadband {
+                    1.0
+                } else if error < -deadband {
+                    0.0
+                } else {
+                    self.command
+                })
+            }
+            ControlLaw::Custom(law) => law(time, state.get_row_log().as_array(), predicted_apogee),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actuator_mod::Actuator;
+    use crate::airbrake_mod::{Airbrake, Curve};
+    use crate::ode::{AdaptiveTimeStep, FixedTimeStep, OdeSolver};
+    use crate::rocket::{run_controlled_simulation, RocketProperties};
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+
+    /// Rocket with airbrakes which reaches roughly 830 m from 150 m/s when retracted.
+    fn make_state(actuator: Option<Actuator>) -> State {
+        let mut rocket_properties = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        rocket_properties.airbrake = Some(Airbrake::new(
+            Curve::polynomial(vec![0.0, 1.0]),
+            None,
+            None,
+            0.0,
+            actuator,
+        ));
+        State::new_1dof(rocket_properties, InitialState1DOF::new(0.0, 150.0))
+    }
+
+    fn run(state: State, controller: Controller) -> Result<SimulationData, String> {
+        let solver = OdeSolver::RK3(FixedTimeStep::new(0.01));
+        run_controlled_simulation(state, solver, controller, 100_000, false, Vec::new())
+            .map(|(_, log)| log)
+    }
+
+    fn apogee(log: &SimulationData) -> f64 {
+        log.get_val(log.time_log.len() - 1, 1)
+    }
+
+    #[test]
+    fn pid_controller_reaches_target_apogee() {
+        let target = 780.0;
+        let log = run(
+            make_state(None),
+            Controller::pid(target, 0.05, 0.01, 0.0, 100.0),
+        )
+        .unwrap();
+        assert_abs_diff_eq!(apogee(&log), target, epsilon = 2.0);
+
+        // The control log has one row per update at 100 Hz
+        assert!(log.control_log.len() > 100);
+        assert_abs_diff_eq!(
+            log.control_log[1][0] - log.control_log[0][0],
+            0.01,
+            epsilon = 1e-9
+        );
+        assert!(log
+            .control_log
+            .iter()
+            .all(|row| (0.0..=1.0).contains(&row[2])));
+    }
+
+    #[test]
+    fn bang_bang_controller_reaches_target_apogee_with_actuator_lag() {
+        let target = 780.0;
+        let state = make_state(Some(Actuator::first_order(0.1, 0.0)));
+        let log = run(state, Controller::bang_bang(target, 1.0, 100.0)).unwrap();
+        assert_abs_diff_eq!(apogee(&log), target, epsilon = 5.0);
+
+        // The actual deployment lags behind the command
+        let first_deploy = log.control_log.iter().find(|row| row[2] == 1.0).unwrap();
+        assert!(first_deploy[3] < 1.0);
+    }
+
+    #[test]
+    fn commands_are_held_between_updates() {
+        let controller = Controller::custom(|time, _state, _apogee| Ok(time), 10.0);
+        let log = run(make_state(None), controller).unwrap();
+
+        for pair in log.control_log.windows(2) {
+            assert_abs_diff_eq!(pair[1][0] - pair[0][0], 0.1, epsilon = 1e-6);
+        }
+        // The command is clamped to [0, 1]
+        assert_eq!(log.control_log.last().unwrap()[2], 1.0);
+    }
+
+    #[test]
+    fn adaptive_steps_end_at_every_update() {
+        // RK45 would take steps of up to 0.1 s by itself, which would step over most updates
+        let solver = OdeSolver::RK45(AdaptiveTimeStep::default());
+        let controller = Controller::bang_bang(780.0, 1.0, 100.0);
+        let (_, log) =
+            run_controlled_simulation(make_state(None), solver, controller, 100_000, false, vec![])
+                .unwrap();
+
+        // One update every 0.01 s, up to the start of the last timestep
+        assert_eq!(log.control_log[0][0], 0.0);
+        for pair in log.control_log.windows(2) {
+            assert_abs_diff_eq!(pair[1][0] - pair[0][0], 0.01, epsilon = 1e-9);
+        }
+        let last_update = log.control_log.last().unwrap()[0];
+        let last_step = *log.time_log.last().unwrap();
+        assert!(last_step - last_update < 0.01);
+        assert_abs_diff_eq!(apogee(&log), 780.0, epsilon = 5.0);
+    }
+
+    #[test]
+    fn apogee_is_predicted_with_the_model_of_the_flight() {
+        // A tilted 3DOF flight doesn't climb as high as the 1DOF model would predict from the
+        // same vertical velocity
+        let rocket_properties = make_state(None).get_rocket_properties().clone();
+        let initial_state = InitialState3DOF::new(0.0, 0.0, -0.4, 60.0, 140.0, 0.0);
+        let state = State::new_3dof(rocket_properties.clone(), initial_state);
+        let control_loop = ControlLoop::new(Controller::bang_bang(0.0, 0.0, 1.0), &state).unwrap();
+
+        let expected = simulation::apogee(
+            state.clone(),
+            OdeSolver::RK45(AdaptiveTimeStep::default()),
+            100_000,
+        );
+        let predicted = control_loop.predict_apogee(&state);
+        assert_abs_diff_eq!(predicted, expected, epsilon = 1e-9);
+
+        let vertical = State::new_1dof(rocket_properties, InitialState1DOF::new(0.0, 140.0));
+        assert!((control_loop.predict_apogee(&vertical) - predicted).abs() > 10.0);
+    }
+
+    #[test]
+    fn controller_errors_stop_the_simulation() {
+        let controller = Controller::custom(
+            |time, _state, _apogee| {
+                if time > 1.0 {
+                    Err("sensor fault".to_string())
+                } else {
+                    Ok(0.0)
+                }
+            },
+            100.0,
+        );
+        let error = run(make_state(None), controller).unwrap_err();
+        assert!(error.contains("sensor fault"));
+
+        // Control needs airbrakes
+        let state = State::new_1dof(
+            RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2),
+            InitialState1DOF::new(0.0, 150.0),
+        );
+        assert!(run(state, Controller::bang_bang(780.0, 0.0, 100.0)).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::physics_mod;
+use crate::rocket::RocketProperties;
+use pyo3::prelude::*;
+use std::f64::consts::PI;
+
+/// Quantity derived from the state of a flight, which can be logged alongside the states.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DerivedChannel {
+    /// Airspeed divided by the speed of sound.
+    Mach,
+    /// Dynamic pressure 1/2 * rho * V^2 of the airspeed (Pa).
+    DynamicPressure,
+    /// Angle between the rocket's axis and the airspeed (rad), positive with the rocket pointing
+    /// counter-clockwise of it. Always zero for the 1DOF model.
+    AngleOfAttack,
+    /// Aerodynamic force opposing the airspeed (N).
+    DragForce,
+    /// Aerodynamic force perpendicular to the airspeed, towards the side the nose points to (N).
+    /// Always zero for the 1DOF model.
+    LiftForce,
+    /// Air density (kg/m^3).
+    Density,
+    /// Static stability margin in body diameters (calibers).
+    StabilityCalibers,
+}
+
+impl DerivedChannel {
+    /// Name of the channel's column in the simulation result.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            DerivedChannel::Mach => "mach",
+            DerivedChannel::DynamicPressure => "dynamic_pressure",
+            DerivedChannel::AngleOfAttack => "angle_of_attack",
+            DerivedChannel::DragForce => "drag_force",
+            DerivedChannel::LiftForce => "lift_force",
+            DerivedChannel::Density => "density",
+            DerivedChannel::StabilityCalibers => "stability_calibers",
+        }
+    }
+
+    pub(crate) fn unit(self) -> &'static str {
+        match self {
+            DerivedChannel::Mach => "-",
+            DerivedChannel::DynamicPressure => "Pa",
+            DerivedChannel::AngleOfAttack => "rad",
+            DerivedChannel::DragForce | DerivedChannel::LiftForce => "N",
+            DerivedChannel::Density => "kg/m^3",
+            DerivedChannel::StabilityCalibers => "cal",
+        }
+    }
+
+    /// Value of the channel among the flight quantities.
+    pub(crate) fn value(self, quantities: &FlightQuantities) -> f64 {
+        match self {
+            DerivedChannel::Mach => quantities.mach,
+            DerivedChannel::DynamicPressure => quantities.dynamic_pressure,
+            DerivedChannel::AngleOfAttack => quantities.angle_of_attack,
+            DerivedChannel::DragForce => quantities.drag_force,
+            DerivedChannel::LiftForce => quantities.lift_force,
+            DerivedChannel::Density => quantities.density,
+            DerivedChannel::StabilityCalibers => quantities.stability_calibers,
+        }
+    }
+}
+
+/// Quantities the models work out on the way to the state derivatives, which aren't states
+/// themselves.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct FlightQuantities {
+    /// Air density (kg/m^3)
+    pub(crate) density: f64,
+    pub(crate) mach: f64,
+    /// Dynamic pressure (Pa)
+    pub(crate) dynamic_pressure: f64,
+    /// Angle of attack (rad)
+    pub(crate) angle_of_attack: f64,
+    /// Aerodynamic force opposing the airspeed (N)
+    pub(crate) drag_force: f64,
+    /// Aerodynamic force perpendicular to the airspeed (N)
+    pub(crate) lift_force: f64,
+    /// Static stability margin in body diameters
+    pub(crate) stability_calibers: f64,
+}
+
+impl FlightQuantities {
+    /// Collects the quantities at the given airspeed (m/s), from the aerodynamic forces (N) and
+    /// the stability margin (m) of the rocket.
+    pub(crate) fn new(
+        rocket_properties: &RocketProperties,
+        airspeed: f64,
+        angle_of_attack: f64,
+        drag_force: f64,
+        lift_force: f64,
+        stability_margin: f64,
+    ) -> Self {
+        let density = physics_mod::density();
+        Self {
+            density,
+            mach: airspeed / physics_mod::speed_of_sound(),
+            dynamic_pressure: 0.5 * density * airspeed.powi(2),
+            angle_of_attack,
+            drag_force,
+            lift_force,
+            stability_calibers: stability_margin / reference_diameter(rocket_properties),
+        }
+    }
+}
+
+/// Body diameter the stability margin is measured in: the reference length, or the diameter of
+/// the drag reference area if the reference length isn't set.
+fn reference_diameter(rocket_properties: &RocketProperties) -> f64 {
+    if rocket_properties.reference_length > 0.0 {
+        rocket_properties.reference_length
+    } else {
+        (4.0 * rocket_properties.area_drag / PI).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn quantities_follow_from_airspeed_and_margin() {
+        let mut rocket_properties =
+            RocketProperties::new(10.0, 0.5, PI * 0.01, 0.03, 2.0, 0.3, 5.0);
+        let quantities = FlightQuantities::new(&rocket_properties, 170.147, 0.1, 200.0, 30.0, 0.3);
+        assert_relative_eq!(quantities.mach, 0.5);
+        assert_relative_eq!(
+            quantities.dynamic_pressure,
+            0.5 * 1.225 * 170.147_f64.powi(2)
+        );
+        // The diameter of the drag reference area is 0.2 m
+        assert_relative_eq!(quantities.stability_calibers, 1.5);
+        assert_eq!(DerivedChannel::LiftForce.value(&quantities), 30.0);
+
+        rocket_properties.reference_length = 0.15;
+        let quantities = FlightQuantities::new(&rocket_properties, 0.0, 0.0, 0.0, 0.0, 0.3);
+        assert_relative_eq!(quantities.stability_calibers, 2.0);
+        assert_eq!(quantities.dynamic_pressure, 0.0);
+    }
+}
@@ -0,0 +1,688 @@
+use crate::batch_mod::parallel_map;
+use crate::filter_mod::matrix_to_pyarray;
+use crate::ode::OdeSolver;
+use crate::random_mod::{Distribution, Rng};
+use crate::rocket::{ModelParameters, RocketProperties};
+use crate::simdata_mod::SimulationData;
+use crate::simulation::{PredictionStatus, Simulation, SimulationExitCondition};
+use crate::state::InitialCondition;
+use nalgebra::DMatrix;
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Default number of runs of a Monte Carlo analysis
+pub(crate) const DEFAULT_MONTE_CARLO_RUNS: usize = 1000;
+
+/// Input of the simulation which can be varied, e.g. dispersed in a Monte Carlo analysis or
+/// differentiated in a sensitivity analysis. The rocket properties and the wind speed replace the
+/// rocket's value, the initial state components replace the initial state's value.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DispersedParameter {
+    Mass,
+    Cd,
+    AreaDrag,
+    AreaLift,
+    MomentOfInertia,
+    StabilityMargin,
+    ClA,
+    Cmq,
+    ReferenceLength,
+    MassFlowRate,
+    NozzleDistance,
+    CgLocation,
+    /// Horizontal wind speed (3DOF only)
+    WindSpeed,
+    /// Deviation of the motor's total impulse from its nominal value (N*s). The simulations start
+    /// at burnout, so the deviation changes the burnout speed along the rocket's axis by the
+    /// deviation over the mass.
+    ImpulseDeviation,
+    /// Altitude of the 1DOF initial state, or y of the 3DOF initial state
+    InitialAltitude,
+    /// Velocity of the 1DOF initial state, or vy of the 3DOF initial state
+    InitialVerticalVelocity,
+    /// x of the 3DOF initial state
+    InitialDownrange,
+    /// vx of the 3DOF initial state
+    InitialHorizontalVelocity,
+    /// Angle of the 3DOF initial state
+    InitialAngle,
+    /// Angular rate of the 3DOF initial state
+    InitialAngularRate,
+}
+
+impl DispersedParameter {
+    /// Whether the parameter only exists in the 3DOF model.
+    fn is_3dof_only(self) -> bool {
+        matches!(
+            self,
+            DispersedParameter::WindSpeed
+                | DispersedParameter::InitialDownrange
+                | DispersedParameter::InitialHorizontalVelocity
+                | DispersedParameter::InitialAngle
+                | DispersedParameter::InitialAngularRate
+        )
+    }
+
+    /// Value of the parameter for the rocket properties and initial conditions. The impulse
+    /// deviation is zero, its nominal value.
+    pub(crate) fn value(
+        self,
+        rocket_properties: &RocketProperties,
+        initial_condition: &InitialCondition,
+    ) -> f64 {
+        match (self, initial_condition) {
+            (DispersedParameter::Mass, _) => rocket_properties.mass,
+            (DispersedParameter::Cd, _) => rocket_properties.cd,
+            (DispersedParameter::AreaDrag, _) => rocket_properties.area_drag,
+            (DispersedParameter::AreaLift, _) => rocket_properties.area_lift,
+            (DispersedParameter::MomentOfInertia, _) => rocket_properties.moment_of_inertia,
+            (DispersedParameter::StabilityMargin, _) => rocket_properties.stab_margin_dimensional,
+            (DispersedParameter::ClA, _) => rocket_properties.cl_a,
+            (DispersedParameter::Cmq, _) => rocket_properties.cmq,
+            (DispersedParameter::ReferenceLength, _) => rocket_properties.reference_length,
+            (DispersedParameter::MassFlowRate, _) => rocket_properties.mass_flow_rate,
+            (DispersedParameter::NozzleDistance, _) => rocket_properties.nozzle_distance,
+            (DispersedParameter::CgLocation, _) => rocket_properties.cg_location,
+            (DispersedParameter::WindSpeed, _) => rocket_properties.wind_speed,
+            (DispersedParameter::ImpulseDeviation, _) => 0.0,
+            (DispersedParameter::InitialAltitude, InitialCondition::OneDOF(state)) => {
+                state.initial_height
+            }
+            (DispersedParameter::InitialAltitude, InitialCondition::ThreeDOF(state)) => state.y,
+            (DispersedParameter::InitialVerticalVelocity, InitialCondition::OneDOF(state)) => {
+                state.initial_velocity
+            }
+            (DispersedParameter::InitialVerticalVelocity, InitialCondition::ThreeDOF(state)) => {
+                state.vy
+            }
+            (DispersedParameter::InitialDownrange, InitialCondition::ThreeDOF(state)) => state.x,
+            (DispersedParameter::InitialHorizontalVelocity, InitialCondition::ThreeDOF(state)) => {
+                state.vx
+            }
+            (DispersedParameter::InitialAngle, InitialCondition::ThreeDOF(state)) => state.angle,
+            (DispersedParameter::InitialAngularRate, InitialCondition::ThreeDOF(state)) => {
+                state.angular_rate
+            }
+            // Rejected by `validate_parameters`
+            (_, InitialCondition::OneDOF(_)) => 0.0,
+        }
+    }
+
+    /// Sets the parameter of a run. The impulse deviation is applied separately, once the mass is
+    /// known.
+    fn apply(
+        self,
+        value: f64,
+        rocket_properties: &mut RocketProperties,
+        initial_condition: &mut InitialCondition,
+    ) {
+        match (self, initial_condition) {
+            (DispersedParameter::Mass, _) => rocket_properties.mass = value,
+            (DispersedParameter::Cd, _) => rocket_properties.cd = value,
+            (DispersedParameter::AreaDrag, _) => rocket_properties.area_drag = value,
+            (DispersedParameter::AreaLift, _) => rocket_properties.area_lift = value,
+            (DispersedParameter::MomentOfInertia, _) => rocket_properties.moment_of_inertia = value,
+            (DispersedParameter::StabilityMargin, _) => {
+                rocket_properties.stab_margin_dimensional = value
+            }
+            (DispersedParameter::ClA, _) => rocket_properties.cl_a = value,
+            (DispersedParameter::Cmq, _) => rocket_properties.cmq = value,
+            (DispersedParameter::ReferenceLength, _) => rocket_properties.reference_length = value,
+            (DispersedParameter::MassFlowRate, _) => rocket_properties.mass_flow_rate = value,
+            (DispersedParameter::NozzleDistance, _) => rocket_properties.nozzle_distance = value,
+            (DispersedParameter::CgLocation, _) => rocket_properties.cg_location = value,
+            (DispersedParameter::WindSpeed, _) => rocket_properties.wind_speed = value,
+            (DispersedParameter::ImpulseDeviation, _) => {}
+            (DispersedParameter::InitialAltitude, InitialCondition::OneDOF(state)) => {
+                state.initial_height = value
+            }
+            (DispersedParameter::InitialAltitude, InitialCondition::ThreeDOF(state)) => {
+                state.y = value
+            }
+            (DispersedParameter::InitialVerticalVelocity, InitialCondition::OneDOF(state)) => {
+                state.initial_velocity = value
+            }
+            (DispersedParameter::InitialVerticalVelocity, InitialCondition::ThreeDOF(state)) => {
+                state.vy = value
+            }
+            (DispersedParameter::InitialDownrange, InitialCondition::ThreeDOF(state)) => {
+                state.x = value
+            }
+            (DispersedParameter::InitialHorizontalVelocity, InitialCondition::ThreeDOF(state)) => {
+                state.vx = value
+            }
+            (DispersedParameter::InitialAngle, InitialCondition::ThreeDOF(state)) => {
+                state.angle = value
+            }
+            (DispersedParameter::InitialAngularRate, InitialCondition::ThreeDOF(state)) => {
+                state.angular_rate = value
+            }
+            // Rejected by `validate_parameters`
+            (_, InitialCondition::OneDOF(_)) => {}
+        }
+    }
+
+    /// The scalar rocket property the parameter sets, if it sets one of those the models depend
+    /// on, e.g. to seed its derivative.
+    pub(crate) fn model_parameter<T>(self, parameters: &mut ModelParameters<T>) -> Option<&mut T> {
+        match self {
+            DispersedParameter::Mass => Some(&mut parameters.mass),
+            DispersedParameter::Cd => Some(&mut parameters.cd),
+            DispersedParameter::AreaDrag => Some(&mut parameters.area_drag),
+            DispersedParameter::AreaLift => Some(&mut parameters.area_lift),
+            DispersedParameter::MomentOfInertia => Some(&mut parameters.moment_of_inertia),
+            DispersedParameter::StabilityMargin => Some(&mut parameters.stab_margin_dimensional),
+            DispersedParameter::ClA => Some(&mut parameters.cl_a),
+            DispersedParameter::Cmq => Some(&mut parameters.cmq),
+            DispersedParameter::ReferenceLength => Some(&mut parameters.reference_length),
+            DispersedParameter::MassFlowRate => Some(&mut parameters.mass_flow_rate),
+            DispersedParameter::NozzleDistance => Some(&mut parameters.nozzle_distance),
+            DispersedParameter::CgLocation => Some(&mut parameters.cg_location),
+            DispersedParameter::WindSpeed => Some(&mut parameters.wind_speed),
+            _ => None,
+        }
+    }
+}
+
+/// Output of each run of a Monte Carlo analysis.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DispersionOutput {
+    Apogee,
+    TimeToApogee,
+    /// Horizontal distance from the origin at which the rocket lands, which is always zero for the
+    /// 1DOF model
+    LandingDownrange,
+}
+
+/// Summary statistics of an output over the runs which completed it. Every statistic is NaN if no
+/// run completed the output.
+#[pyclass(get_all)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DispersionStatistics {
+    pub mean: f64,
+    /// Sample standard deviation, which is 0 if only one run completed the output
+    pub std: f64,
+    pub min: f64,
+    pub max: f64,
+    /// 5th percentile
+    pub p5: f64,
+    /// Median
+    pub p50: f64,
+    /// 95th percentile
+    pub p95: f64,
+}
+
+/// Outputs of the runs of a Monte Carlo analysis. Outputs a run didn't reach, e.g. because it
+/// diverged or ran out of iterations, are NaN and left out of the statistics.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct MonteCarloResult {
+    /// The parameter behind each column of `samples`
+    #[pyo3(get)]
+    pub parameters: Vec<DispersedParameter>,
+    /// Row per run
+    samples: DMatrix<f64>,
+    apogee: Vec<f64>,
+    time_to_apogee: Vec<f64>,
+    landing_downrange: Vec<f64>,
+}
+
+#[pymethods]
+impl MonteCarloResult {
+    /// Dispersed values of the parameters, with a row per run.
+    #[getter]
+    pub fn samples<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.samples)
+    }
+
+    /// Apogee of each run (m).
+    #[getter]
+    pub fn apogee<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.apogee.to_pyarray(py)
+    }
+
+    /// Time from the start of each run to its apogee (s).
+    #[getter]
+    pub fn time_to_apogee<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.time_to_apogee.to_pyarray(py)
+    }
+
+    /// Horizontal distance from the origin at which each run lands (m).
+    #[getter]
+    pub fn landing_downrange<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.landing_downrange.to_pyarray(py)
+    }
+
+    /// Number of runs which didn't complete every output.
+    #[getter]
+    pub fn failed_runs(&self) -> usize {
+        (0..self.apogee.len())
+            .filter(|&i| {
+                [&self.apogee, &self.time_to_apogee, &self.landing_downrange]
+                    .iter()
+                    .any(|output| !output[i].is_finite())
+            })
+            .count()
+    }
+
+    /// Summary statistics of an output.
+    pub fn statistics(&self, output: DispersionOutput) -> DispersionStatistics {
+        let values = self.finite_sorted(output);
+        let (mean, std) = match values.len() {
+            0 => (f64::NAN, f64::NAN),
+            1 => (values[0], 0.0),
+            n => {
+                let mean = values.iter().sum::<f64>() / n as f64;
+                let variance =
+                    values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+                (mean, variance.sqrt())
+            }
+        };
+        DispersionStatistics {
+            mean,
+            std,
+            min: values.first().copied().unwrap_or(f64::NAN),
+            max: values.last().copied().unwrap_or(f64::NAN),
+            p5: percentile(&values, 5.0),
+            p50: percentile(&values, 50.0),
+            p95: percentile(&values, 95.0),
+        }
+    }
+
+    /// Percentile `q` (0 to 100) of an output, interpolated linearly between the runs.
+    pub fn percentile(&self, output: DispersionOutput, q: f64) -> PyResult<f64> {
+        self.output_percentile(output, q)
+            .map_err(PyValueError::new_err)
+    }
+}
+
+impl MonteCarloResult {
+    pub(crate) fn output_percentile(
+        &self,
+        output: DispersionOutput,
+        q: f64,
+    ) -> Result<f64, String> {
+        if !(0.0..=100.0).contains(&q) {
+            return Err("q must be between 0 and 100".to_string());
+        }
+        Ok(percentile(&self.finite_sorted(output), q))
+    }
+
+    fn output(&self, output: DispersionOutput) -> &[f64] {
+        match output {
+            DispersionOutput::Apogee => &self.apogee,
+            DispersionOutput::TimeToApogee => &self.time_to_apogee,
+            DispersionOutput::LandingDownrange => &self.landing_downrange,
+        }
+    }
+
+    /// Values of an output the runs completed, in ascending order.
+    fn finite_sorted(&self, output: DispersionOutput) -> Vec<f64> {
+        let mut values: Vec<f64> = self
+            .output(output)
+            .iter()
+            .copied()
+            .filter(|x| x.is_finite())
+            .collect();
+        values.sort_by(f64::total_cmp);
+        values
+    }
+}
+
+/// Percentile `q` of sorted values, interpolated linearly between the closest ranks. NaN without
+/// values.
+pub(crate) fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let rank = q / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Checks that every parameter applies to the model of the initial conditions.
+pub(crate) fn validate_parameters(
+    parameters: &[DispersedParameter],
+    initial_condition: &InitialCondition,
+) -> Result<(), String> {
+    if let InitialCondition::OneDOF(_) = initial_condition {
+        if let Some(parameter) = parameters.iter().find(|p| p.is_3dof_only()) {
+            return Err(format!("{parameter:?} only applies to the 3DOF model"));
+        }
+    }
+    Ok(())
+}
+
+/// Rocket properties and initial conditions with the parameters set to the given values.
+pub(crate) fn apply_parameters(
+    parameters: &[DispersedParameter],
+    values: &[f64],
+    rocket_properties: &RocketProperties,
+    initial_condition: InitialCondition,
+) -> (RocketProperties, InitialCondition) {
+    let mut rocket_properties = rocket_properties.clone();
+    let mut initial_condition = initial_condition;
+    for (parameter, value) in parameters.iter().zip(values) {
+        parameter.apply(*value, &mut rocket_properties, &mut initial_condition);
+    }
+
+    let impulse: f64 = parameters
+        .iter()
+        .zip(values)
+        .filter(|(parameter, _)| **parameter == DispersedParameter::ImpulseDeviation)
+        .map(|(_, value)| value)
+        .sum();
+    let dv = impulse / rocket_properties.mass;
+    match &mut initial_condition {
+        InitialCondition::OneDOF(state) => state.initial_velocity += dv,
+        InitialCondition::ThreeDOF(state) => {
+            // Same orientation convention as the 3DOF model
+            state.vx -= dv * state.angle.sin();
+            state.vy += dv * state.angle.cos();
+        }
+    }
+    (rocket_properties, initial_condition)
+}
+
+/// Outputs of a single run, NaN where the run didn't reach them.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RunOutputs {
+    apogee: f64,
+    time_to_apogee: f64,
+    landing_downrange: f64,
+}
+
+impl RunOutputs {
+    pub(crate) fn get(&self, output: DispersionOutput) -> f64 {
+        match output {
+            DispersionOutput::Apogee => self.apogee,
+            DispersionOutput::TimeToApogee => self.time_to_apogee,
+            DispersionOutput::LandingDownrange => self.landing_downrange,
+        }
+    }
+}
+
+/// Simulates a run with the parameters set to the given values to apogee, then on to the ground.
+pub(crate) fn simulate_run(
+    rocket_properties: &RocketProperties,
+    initial_condition: InitialCondition,
+    ode_solver: &OdeSolver,
+    max_iterations: u64,
+    parameters: &[DispersedParameter],
+    values: &[f64],
+) -> RunOutputs {
+    let (rocket_properties, initial_condition) =
+        apply_parameters(parameters, values, rocket_properties, initial_condition);
+    let mut outputs = RunOutputs {
+        apogee: f64::NAN,
+        time_to_apogee: f64::NAN,
+        landing_downrange: f64::NAN,
+    };
+
+    let mut ascent = Simulation::new(
+        initial_condition.to_state(rocket_properties),
+        ode_solver.clone(),
+        SimulationExitCondition::ApogeeReached,
+        max_iterations,
+    );
+    ascent.run(&mut SimulationData::new(), false, false);
+    if ascent.status() != PredictionStatus::Converged {
+        return outputs;
+    }
+    outputs.apogee = ascent.state().get_altitude();
+    outputs.time_to_apogee = ascent.state().get_time();
+
+    let mut descent = Simulation::new(
+        ascent.state().clone(),
+        ode_solver.clone(),
+        SimulationExitCondition::GroundHit,
+        max_iterations,
+    );
+    descent.run(&mut SimulationData::new(), false, false);
+    if descent.status() == PredictionStatus::Converged {
+        outputs.landing_downrange = descent.state().get_downrange();
+    }
+    outputs
+}
+
+/// Monte Carlo analysis of a flight, with the given parameters dispersed.
+#[derive(Clone, Debug)]
+pub(crate) struct DispersionProblem {
+    pub(crate) rocket_properties: RocketProperties,
+    pub(crate) initial_condition: InitialCondition,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) parameters: Vec<DispersedParameter>,
+    pub(crate) distributions: Vec<Distribution>,
+    pub(crate) max_iterations: u64,
+}
+
+impl DispersionProblem {
+    fn validate(&self) -> Result<(), String> {
+        if self.parameters.len() != self.distributions.len() {
+            return Err("There must be one distribution per parameter".to_string());
+        }
+        validate_parameters(&self.parameters, &self.initial_condition)
+    }
+
+    /// Runs `num_runs` simulations on `num_threads` threads, or one per available core. All the
+    /// dispersed values are drawn up front from a single generator, so the results only depend on
+    /// the seed and not on the number of threads.
+    pub(crate) fn run(
+        &self,
+        num_runs: usize,
+        seed: u64,
+        num_threads: Option<usize>,
+    ) -> Result<MonteCarloResult, String> {
+        self.validate()?;
+        if num_runs == 0 {
+            return Err("num_runs must be positive".to_string());
+        }
+
+        let mut rng = Rng::new(seed);
+        let samples = DMatrix::from_fn(num_runs, self.parameters.len(), |_, j| {
+            self.distributions[j].sample(&mut rng)
+        });
+        let rows: Vec<Vec<f64>> = samples
+            .row_iter()
+            .map(|row| row.iter().copied().collect())
+            .collect();
+        let outputs = parallel_map(&rows, num_threads, |row| self.simulate(row))?;
+
+        Ok(MonteCarloResult {
+            parameters: self.parameters.clone(),
+            samples,
+            apogee: outputs.iter().map(|o| o.apogee).collect(),
+            time_to_apogee: outputs.iter().map(|o| o.time_to_apogee).collect(),
+            landing_downrange: outputs.iter().map(|o| o.landing_downrange).collect(),
+        })
+    }
+
+    fn simulate(&self, values: &[f64]) -> RunOutputs {
+        simulate_run(
+            &self.rocket_properties,
+            self.initial_condition,
+            &self.ode_solver,
+            self.max_iterations,
+            &self.parameters,
+            values,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::FixedTimeStep;
+    use crate::simulation;
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+
+    fn make_problem(
+        initial_condition: InitialCondition,
+        parameters: Vec<DispersedParameter>,
+        distributions: Vec<Distribution>,
+    ) -> DispersionProblem {
+        let mut rocket_properties = RocketProperties::new(10.0, 0.5, 0.0182, 0.0182, 5.0, 0.3, 2.0);
+        rocket_properties.cmq = -20.0;
+        rocket_properties.reference_length = 0.15;
+        DispersionProblem {
+            rocket_properties,
+            initial_condition,
+            ode_solver: OdeSolver::RK3(FixedTimeStep::new(0.05)),
+            parameters,
+            distributions,
+            max_iterations: 100_000,
+        }
+    }
+
+    fn one_dof() -> InitialCondition {
+        InitialCondition::OneDOF(InitialState1DOF::new(0.0, 150.0))
+    }
+
+    fn three_dof() -> InitialCondition {
+        InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.1, -15.0, 150.0, 0.0))
+    }
+
+    #[test]
+    fn runs_are_reproducible_and_independent_of_threads() {
+        let problem = make_problem(
+            three_dof(),
+            vec![DispersedParameter::Cd, DispersedParameter::WindSpeed],
+            vec![
+                Distribution::new_normal(0.5, 0.05).unwrap(),
+                Distribution::new_uniform(0.0, 10.0).unwrap(),
+            ],
+        );
+        let a = problem.run(40, 3, Some(1)).unwrap();
+        let b = problem.run(40, 3, Some(4)).unwrap();
+        let c = problem.run(40, 4, Some(4)).unwrap();
+
+        assert_eq!(a.samples, b.samples);
+        assert_eq!(a.apogee, b.apogee);
+        assert_eq!(a.landing_downrange, b.landing_downrange);
+        assert_ne!(a.samples, c.samples);
+        assert_eq!(a.failed_runs(), 0);
+
+        // Each run matches a simulation with its dispersed values
+        let (rocket_properties, initial_condition) = apply_parameters(
+            &problem.parameters,
+            &[0.55, 5.0],
+            &problem.rocket_properties,
+            problem.initial_condition,
+        );
+        let apogee = simulation::apogee(
+            initial_condition.to_state(rocket_properties),
+            problem.ode_solver.clone(),
+            problem.max_iterations,
+        );
+        assert_abs_diff_eq!(problem.simulate(&[0.55, 5.0]).apogee, apogee);
+    }
+
+    #[test]
+    fn outputs_follow_the_dispersions() {
+        let problem = make_problem(
+            one_dof(),
+            vec![DispersedParameter::Cd],
+            vec![Distribution::new_uniform(0.3, 0.7).unwrap()],
+        );
+        let result = problem.run(50, 0, Some(2)).unwrap();
+
+        // More drag gives a lower apogee, sooner
+        for i in 0..49 {
+            let (cd, next_cd) = (result.samples[(i, 0)], result.samples[(i + 1, 0)]);
+            let lower = result.apogee[i + 1] < result.apogee[i];
+            assert_eq!(lower, next_cd > cd);
+            assert_eq!(
+                result.time_to_apogee[i + 1] < result.time_to_apogee[i],
+                lower
+            );
+        }
+        // The 1DOF model lands where it launched
+        assert!(result.landing_downrange.iter().all(|x| *x == 0.0));
+
+        let statistics = result.statistics(DispersionOutput::Apogee);
+        assert!(statistics.min < statistics.p5 && statistics.p5 < statistics.p50);
+        assert!(statistics.p50 < statistics.p95 && statistics.p95 < statistics.max);
+        assert!(statistics.std > 0.0);
+        assert_eq!(
+            result
+                .output_percentile(DispersionOutput::Apogee, 0.0)
+                .unwrap(),
+            statistics.min
+        );
+        assert!(result
+            .output_percentile(DispersionOutput::Apogee, 101.0)
+            .is_err());
+    }
+
+    #[test]
+    fn wind_and_impulse_move_the_flight() {
+        let calm = make_problem(three_dof(), vec![], vec![]).simulate(&[]);
+        let windy = make_problem(
+            three_dof(),
+            vec![DispersedParameter::WindSpeed],
+            vec![Distribution::new_normal(0.0, 1.0).unwrap()],
+        )
+        .simulate(&[8.0]);
+        // The rocket weathercocks into the wind while climbing, then drifts with it once falling
+        assert!(windy.apogee < calm.apogee);
+        assert!(windy.landing_downrange != calm.landing_downrange);
+
+        // Extra impulse raises the burnout speed by the impulse over the mass
+        let problem = make_problem(
+            one_dof(),
+            vec![DispersedParameter::ImpulseDeviation],
+            vec![Distribution::new_normal(0.0, 10.0).unwrap()],
+        );
+        let (_, initial_condition) = apply_parameters(
+            &problem.parameters,
+            &[50.0],
+            &problem.rocket_properties,
+            problem.initial_condition,
+        );
+        let InitialCondition::OneDOF(state) = initial_condition else {
+            unreachable!()
+        };
+        assert_abs_diff_eq!(state.initial_velocity, 155.0);
+    }
+
+    #[test]
+    fn statistics_of_few_completed_runs() {
+        let result = MonteCarloResult {
+            parameters: vec![DispersedParameter::Cd],
+            samples: DMatrix::zeros(2, 1),
+            apogee: vec![f64::NAN, 800.0],
+            time_to_apogee: vec![f64::NAN, f64::NAN],
+            landing_downrange: vec![0.0, 0.0],
+        };
+
+        // A single completed run has no spread
+        let statistics = result.statistics(DispersionOutput::Apogee);
+        assert_eq!(statistics.mean, 800.0);
+        assert_eq!(statistics.std, 0.0);
+        assert_eq!(statistics.p95, 800.0);
+
+        let statistics = result.statistics(DispersionOutput::TimeToApogee);
+        assert!(statistics.mean.is_nan());
+        assert!(statistics.std.is_nan());
+        assert!(statistics.min.is_nan());
+
+        let statistics = result.statistics(DispersionOutput::LandingDownrange);
+        assert_eq!((statistics.mean, statistics.std), (0.0, 0.0));
+    }
+
+    #[test]
+    fn invalid_dispersions_are_rejected() {
+        let normal = Distribution::new_normal(0.0, 1.0).unwrap();
+        let problem = make_problem(one_dof(), vec![DispersedParameter::Cd], vec![]);
+        assert!(problem.run(10, 0, Some(1)).is_err());
+        let problem = make_problem(one_dof(), vec![DispersedParameter::WindSpeed], vec![normal]);
+        assert!(problem.run(10, 0, Some(1)).is_err());
+        let problem = make_problem(one_dof(), vec![], vec![]);
+        assert!(problem.run(0, 0, Some(1)).is_err());
+        assert!(problem.run(10, 0, Some(0)).is_err());
+    }
+}
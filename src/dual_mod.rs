@@ -0,0 +1,307 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Scalar type the physics and the models can be evaluated with: `f64`, or a dual number to
+/// differentiate them. Operations with `f64` constants are written with the constant on the right.
+pub(crate) trait Real:
+    Copy
+    + Debug
+    + PartialEq
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + Add<f64, Output = Self>
+    + Sub<f64, Output = Self>
+    + Mul<f64, Output = Self>
+    + Div<f64, Output = Self>
+{
+    /// A value which doesn't depend on any of the inputs being differentiated.
+    fn constant(value: f64) -> Self;
+    /// The value, without any derivatives.
+    fn value(self) -> f64;
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    /// Four-quadrant arctangent of `self / x`.
+    fn atan2(self, x: Self) -> Self;
+    fn ln(self) -> Self;
+    fn ln_1p(self) -> Self;
+    fn exp(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn powf(self, n: f64) -> Self;
+
+    fn abs(self) -> Self {
+        if self.value() < 0.0 {
+            -self
+        } else {
+            self
+        }
+    }
+
+    /// Sign of the value, which is a constant like `f64::signum`.
+    fn signum(self) -> Self {
+        Self::constant(self.value().signum())
+    }
+
+    /// The larger of the two, compared by value.
+    fn max(self, other: Self) -> Self {
+        if other.value() > self.value() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl Real for f64 {
+    fn constant(value: f64) -> Self {
+        value
+    }
+    fn value(self) -> f64 {
+        self
+    }
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+    fn sin(self) -> Self {
+        f64::sin(self)
+    }
+    fn cos(self) -> Self {
+        f64::cos(self)
+    }
+    fn atan2(self, x: Self) -> Self {
+        f64::atan2(self, x)
+    }
+    fn ln(self) -> Self {
+        f64::ln(self)
+    }
+    fn ln_1p(self) -> Self {
+        f64::ln_1p(self)
+    }
+    fn exp(self) -> Self {
+        f64::exp(self)
+    }
+    fn powi(self, n: i32) -> Self {
+        f64::powi(self, n)
+    }
+    fn powf(self, n: f64) -> Self {
+        f64::powf(self, n)
+    }
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+}
+
+/// Forward-mode dual number: a value and its derivatives with respect to `N` inputs, which are
+/// carried through every operation by the chain rule.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Dual<const N: usize> {
+    pub(crate) value: f64,
+    pub(crate) derivatives: [f64; N],
+}
+
+impl<const N: usize> Dual<N> {
+    /// The `i`th of the inputs being differentiated with respect to.
+    pub(crate) fn variable(value: f64, i: usize) -> Self {
+        let mut derivatives = [0.0; N];
+        derivatives[i] = 1.0;
+        Self { value, derivatives }
+    }
+
+    /// Applies a function with the value `value` and derivative `slope` at `self.value`.
+    fn chain(self, value: f64, slope: f64) -> Self {
+        Self {
+            value,
+            derivatives: self.derivatives.map(|d| d * slope),
+        }
+    }
+}
+
+impl<const N: usize> Real for Dual<N> {
+    fn constant(value: f64) -> Self {
+        Self {
+            value,
+            derivatives: [0.0; N],
+        }
+    }
+    fn value(self) -> f64 {
+        self.value
+    }
+    fn sqrt(self) -> Self {
+        let root = self.value.sqrt();
+        self.chain(root, 0.5 / root)
+    }
+    fn sin(self) -> Self {
+        self.chain(self.value.sin(), self.value.cos())
+    }
+    fn cos(self) -> Self {
+        self.chain(self.value.cos(), -self.value.sin())
+    }
+    fn atan2(self, x: Self) -> Self {
+        let radius_squared = self.value.powi(2) + x.value.powi(2);
+        Self {
+            value: self.value.atan2(x.value),
+            derivatives: std::array::from_fn(|i| {
+                (x.value * self.derivatives[i] - self.value * x.derivatives[i]) / radius_squared
+            }),
+        }
+    }
+    fn ln(self) -> Self {
+        self.chain(self.value.ln(), 1.0 / self.value)
+    }
+    fn ln_1p(self) -> Self {
+        self.chain(self.value.ln_1p(), 1.0 / (1.0 + self.value))
+    }
+    fn exp(self) -> Self {
+        let exp = self.value.exp();
+        self.chain(exp, exp)
+    }
+    fn powi(self, n: i32) -> Self {
+        let slope = match n {
+            0 => 0.0,
+            n => n as f64 * self.value.powi(n - 1),
+        };
+        self.chain(self.value.powi(n), slope)
+    }
+    fn powf(self, n: f64) -> Self {
+        self.chain(self.value.powf(n), n * self.value.powf(n - 1.0))
+    }
+}
+
+impl<const N: usize> Add for Dual<N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            value: self.value + rhs.value,
+            derivatives: std::array::from_fn(|i| self.derivatives[i] + rhs.derivatives[i]),
+        }
+    }
+}
+
+impl<const N: usize> Sub for Dual<N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<const N: usize> Mul for Dual<N> {
+    type Output = Self;
+    // Product rule
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            value: self.value * rhs.value,
+            derivatives: std::array::from_fn(|i| {
+                self.derivatives[i] * rhs.value + self.value * rhs.derivatives[i]
+            }),
+        }
+    }
+}
+
+impl<const N: usize> Div for Dual<N> {
+    type Output = Self;
+    // Quotient rule
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self {
+        Self {
+            value: self.value / rhs.value,
+            derivatives: std::array::from_fn(|i| {
+                (self.derivatives[i] * rhs.value - self.value * rhs.derivatives[i])
+                    / rhs.value.powi(2)
+            }),
+        }
+    }
+}
+
+impl<const N: usize> Neg for Dual<N> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.chain(-self.value, -1.0)
+    }
+}
+
+impl<const N: usize> Add<f64> for Dual<N> {
+    type Output = Self;
+    fn add(self, rhs: f64) -> Self {
+        Self {
+            value: self.value + rhs,
+            ..self
+        }
+    }
+}
+
+impl<const N: usize> Sub<f64> for Dual<N> {
+    type Output = Self;
+    fn sub(self, rhs: f64) -> Self {
+        self + -rhs
+    }
+}
+
+impl<const N: usize> Mul<f64> for Dual<N> {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self {
+        self.chain(self.value * rhs, rhs)
+    }
+}
+
+impl<const N: usize> Div<f64> for Dual<N> {
+    type Output = Self;
+    fn div(self, rhs: f64) -> Self {
+        self.chain(self.value / rhs, 1.0 / rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// Checks the derivative of `f` at `x` against a central difference.
+    fn check(f: impl Fn(Dual<1>) -> Dual<1>, x: f64) {
+        let dual = f(Dual::variable(x, 0));
+        let h = 1e-6;
+        let value = |x: f64| f(Dual::constant(x)).value;
+        assert_relative_eq!(dual.value, value(x));
+        assert_relative_eq!(
+            dual.derivatives[0],
+            (value(x + h) - value(x - h)) / (2.0 * h),
+            max_relative = 1e-6
+        );
+    }
+
+    #[test]
+    fn derivatives_follow_the_chain_rule() {
+        check(|x| x * x * 3.0 - x / 2.0 + 1.0, 1.7);
+        check(|x| (x * x + 1.0).sqrt() / x, 0.8);
+        check(|x| x.sin() * x.cos() - x.atan2(Dual::constant(-0.4)), 0.3);
+        check(|x| Dual::constant(0.7).atan2(x * x), 1.2);
+        check(|x| x.ln() + (x * 2.0).ln_1p(), 2.5);
+        check(|x| x.powi(3) - x.powi(-2) + x.powi(0), 1.3);
+        check(|x| x.powf(4.26) + (x * -0.3).exp(), 0.75);
+        check(|x| (-x).abs() * x.max(Dual::constant(0.5)), 0.9);
+        check(|x| Dual::constant(2.0) / (x - 3.0), 1.1);
+    }
+
+    #[test]
+    fn partial_derivatives_are_tracked_separately() {
+        let x = Dual::<2>::variable(3.0, 0);
+        let y = Dual::<2>::variable(4.0, 1);
+        let r = (x * x + y * y).sqrt();
+        assert_relative_eq!(r.value, 5.0);
+        assert_relative_eq!(r.derivatives[0], 0.6);
+        assert_relative_eq!(r.derivatives[1], 0.8);
+
+        // Piecewise functions take the derivative of the branch they're on
+        assert_eq!(x.max(Dual::constant(5.0)).derivatives, [0.0, 0.0]);
+        assert_eq!(x.max(Dual::constant(2.0)).derivatives, [1.0, 0.0]);
+        assert_eq!((-x).abs().derivatives, [1.0, 0.0]);
+        assert_eq!(x.signum().derivatives, [0.0, 0.0]);
+    }
+}
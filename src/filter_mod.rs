@@ -0,0 +1,711 @@
+pub(crate) mod particle;
+pub(crate) mod unscented;
+
+use crate::constants::simulation_constants::{APOGEE_VELOCITY_THRESHOLD_M_S, MAX_ITERATIONS};
+use crate::dual_mod::Dual;
+use crate::ode::{AdaptiveTimeStep, FixedTimeStep, OdeMethod, OdeSolver};
+use crate::rocket::{ModelParameters, Rocket, RocketProperties};
+use crate::simulation;
+use crate::state::{InitialCondition, InitialState1DOF, InitialState3DOF, State};
+use nalgebra::{DMatrix, DVector};
+use numpy::{ndarray::Array2, PyArray1, PyArray2, ToPyArray};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Default length (s) of the integration steps a filter prediction is split into
+pub(crate) const DEFAULT_FILTER_STEP: f64 = 0.01;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MeasurementKind {
+    /// Altitude from a barometer (m)
+    BarometricAltitude,
+    /// Specific force along the rocket's axis from an accelerometer (m/s^2). It reads 0 in free
+    /// fall, and the drag deceleration while coasting.
+    AxialAcceleration,
+    /// Position from a GPS receiver: altitude, plus the downrange position with the 3DOF model
+    Gps,
+}
+
+/// A sensor reading, together with the model which predicts it from the filter's state.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    kind: MeasurementKind,
+    values: Vec<f64>,
+    /// Standard deviation of the sensor noise
+    #[pyo3(get, set)]
+    pub noise_std: f64,
+}
+
+#[pymethods]
+impl Measurement {
+    /// Altitude from a barometer (m).
+    #[staticmethod]
+    pub fn barometric_altitude(altitude: f64, noise_std: f64) -> Self {
+        Self {
+            kind: MeasurementKind::BarometricAltitude,
+            values: vec![altitude],
+            noise_std,
+        }
+    }
+
+    /// Specific force along the rocket's axis from an accelerometer (m/s^2), which is 0 in free
+    /// fall. With the 1DOF model, the rocket's axis is vertical.
+    #[staticmethod]
+    pub fn axial_acceleration(acceleration: f64, noise_std: f64) -> Self {
+        Self {
+            kind: MeasurementKind::AxialAcceleration,
+            values: vec![acceleration],
+            noise_std,
+        }
+    }
+
+    /// Position from a GPS receiver (m). `downrange` is the horizontal position, and can only be
+    /// used with the 3DOF model.
+    #[staticmethod]
+    #[pyo3(signature = (altitude, noise_std, downrange=None))]
+    pub fn gps(altitude: f64, noise_std: f64, downrange: Option<f64>) -> Self {
+        let values = match downrange {
+            Some(x) => vec![altitude, x],
+            None => vec![altitude],
+        };
+        Self {
+            kind: MeasurementKind::Gps,
+            values,
+            noise_std,
+        }
+    }
+}
+
+impl Measurement {
+    pub(crate) fn values(&self) -> DVector<f64> {
+        DVector::from_column_slice(&self.values)
+    }
+
+    /// Measurement noise covariance.
+    pub(crate) fn covariance(&self) -> DMatrix<f64> {
+        DMatrix::from_diagonal_element(self.values.len(), self.values.len(), self.noise_std.powi(2))
+    }
+}
+
+/// Process model of the state estimators: one of the rocket models, with its state optionally
+/// augmented by a bias on the drag coefficient which is estimated alongside it. The state is
+/// (model state..., cd bias), with the model state laid out as in `InitialState1DOF` or
+/// `InitialState3DOF`.
+#[derive(Clone, Debug)]
+pub(crate) struct ProcessModel {
+    rocket_properties: RocketProperties,
+    /// Template of the model's initial conditions, which the state vector is written into
+    initial_condition: InitialCondition,
+    method: OdeMethod,
+    max_step: f64,
+    estimate_cd_bias: bool,
+}
+
+impl ProcessModel {
+    /// Creates the process model. The airbrakes are treated as held at their current deployment,
+    /// and the cd bias can't be estimated when the drag comes from an aero table.
+    pub(crate) fn new(
+        mut rocket_properties: RocketProperties,
+        initial_condition: InitialCondition,
+        method: OdeMethod,
+        max_step: f64,
+        estimate_cd_bias: bool,
+    ) -> Result<Self, String> {
+        if method == OdeMethod::RK45 {
+            return Err("State estimators need a fixed timestep integration method".to_string());
+        }
+        if max_step <= 0.0 {
+            return Err("max_step must be positive".to_string());
+        }
+        if estimate_cd_bias && rocket_properties.aero_table.is_some() {
+            return Err(
+                "The cd bias can't be estimated for a rocket with an aero table".to_string(),
+            );
+        }
+        if let Some(airbrake) = rocket_properties.airbrake.as_mut() {
+            airbrake.actuator = None;
+        }
+        Ok(Self {
+            rocket_properties,
+            initial_condition,
+            method,
+            max_step,
+            estimate_cd_bias,
+        })
+    }
+
+    /// Number of states of the rocket model.
+    fn model_dim(&self) -> usize {
+        match self.initial_condition {
+            InitialCondition::OneDOF(_) => 2,
+            InitialCondition::ThreeDOF(_) => 6,
+        }
+    }
+
+    /// Number of states, including the cd bias.
+    pub(crate) fn dim(&self) -> usize {
+        self.model_dim() + usize::from(self.estimate_cd_bias)
+    }
+
+    /// State vector at the initial conditions, with a zero cd bias.
+    pub(crate) fn initial_state(&self) -> DVector<f64> {
+        let model_state = match self.initial_condition {
+            InitialCondition::OneDOF(s) => vec![s.initial_height, s.initial_velocity],
+            InitialCondition::ThreeDOF(s) => vec![s.x, s.y, s.angle, s.vx, s.vy, s.angular_rate],
+        };
+        let mut x = DVector::zeros(self.dim());
+        x.rows_mut(0, self.model_dim())
+            .copy_from_slice(&model_state);
+        x
+    }
+
+    /// Model state for the given state vector.
+    fn state(&self, x: &DVector<f64>) -> State {
+        let mut rocket_properties = self.rocket_properties.clone();
+        if self.estimate_cd_bias {
+            rocket_properties.cd += x[self.model_dim()];
+        }
+        self.initial_condition
+            .with_row(x.as_slice())
+            .to_state(rocket_properties)
+    }
+
+    /// Solver and number of steps a prediction of `dt` seconds is split into.
+    fn solver(&self, dt: f64) -> (OdeSolver, usize) {
+        let steps = (dt / self.max_step).ceil().max(1.0);
+        let step = FixedTimeStep::new(dt / steps);
+        let ode = match self.method {
+            OdeMethod::Euler => OdeSolver::Euler(step),
+            _ => OdeSolver::RK3(step),
+        };
+        (ode, steps as usize)
+    }
+
+    /// State vector with the model state of `state` and the cd bias of `x`.
+    fn with_model_state(&self, x: &DVector<f64>, state: &State) -> DVector<f64> {
+        let mut x_next = x.clone();
+        x_next
+            .rows_mut(0, self.model_dim())
+            .copy_from_slice(&state.get_state_vec().as_array()[..self.model_dim()]);
+        x_next
+    }
+
+    /// Scalar rocket properties of a model state as dual numbers, with the derivative of the cd in
+    /// the direction `tangent` of the state vector.
+    fn parameters(&self, state: &State, tangent: &[f64]) -> ModelParameters<Dual<1>> {
+        let mut parameters = state.get_rocket_properties().parameters::<Dual<1>>();
+        if self.estimate_cd_bias {
+            parameters.cd.derivatives = [tangent[self.model_dim()]];
+        }
+        parameters
+    }
+
+    /// Propagates the state vector `dt` seconds forward. The cd bias is held constant.
+    pub(crate) fn propagate(&self, x: &DVector<f64>, dt: f64) -> DVector<f64> {
+        let mut state = self.state(x);
+        let (mut ode, steps) = self.solver(dt);
+        for _ in 0..steps {
+            ode.timestep(&mut state);
+        }
+        self.with_model_state(x, &state)
+    }
+
+    /// Propagates the state vector like `propagate`, along with the Jacobian of the propagated
+    /// state vector with respect to `x`. Each column is carried through the steps with dual
+    /// numbers, so the Jacobian is exact rather than a finite-difference approximation.
+    pub(crate) fn propagate_with_jacobian(
+        &self,
+        x: &DVector<f64>,
+        dt: f64,
+    ) -> (DVector<f64>, DMatrix<f64>) {
+        let mut state = self.state(x);
+        let (mut ode, steps) = self.solver(dt);
+        let n = self.model_dim();
+        let mut jacobian = DMatrix::identity(self.dim(), self.dim());
+        for _ in 0..steps {
+            let previous = state.clone();
+            ode.timestep(&mut state);
+            for j in 0..self.dim() {
+                let tangent: Vec<f64> = jacobian.column(j).iter().copied().collect();
+                let parameters = self.parameters(&previous, &tangent);
+                let model_tangent =
+                    ode.tangent_step(&previous, &tangent[..n], &parameters, ode.step_size());
+                jacobian
+                    .view_mut((0, j), (n, 1))
+                    .copy_from_slice(&model_tangent);
+            }
+        }
+        (self.with_model_state(x, &state), jacobian)
+    }
+
+    /// Apogee predicted from the given state vector with the default adaptive RK45 solver.
+    pub(crate) fn apogee(&self, x: &DVector<f64>) -> f64 {
+        let state = self.state(x);
+        if state.get_vertical_velocity() < APOGEE_VELOCITY_THRESHOLD_M_S {
+            return state.get_altitude();
+        }
+        simulation::apogee(
+            state,
+            OdeSolver::RK45(AdaptiveTimeStep::default()),
+            MAX_ITERATIONS,
+        )
+    }
+
+    /// Predicted value of a measurement for the given state vector.
+    pub(crate) fn measure(
+        &self,
+        x: &DVector<f64>,
+        measurement: &Measurement,
+    ) -> Result<DVector<f64>, String> {
+        let three_dof = matches!(self.initial_condition, InitialCondition::ThreeDOF(_));
+        // The 3DOF state starts with the downrange position, followed by the altitude
+        let altitude = if three_dof { x[1] } else { x[0] };
+        match (measurement.kind, measurement.values.len()) {
+            (MeasurementKind::BarometricAltitude, _) | (MeasurementKind::Gps, 1) => {
+                Ok(DVector::from_element(1, altitude))
+            }
+            (MeasurementKind::Gps, _) if three_dof => {
+                Ok(DVector::from_column_slice(&[altitude, x[0]]))
+            }
+            (MeasurementKind::Gps, _) => {
+                Err("A GPS downrange position needs the 3DOF model".to_string())
+            }
+            (MeasurementKind::AxialAcceleration, _) => Ok(DVector::from_element(
+                1,
+                self.state(x).get_axial_acceleration(),
+            )),
+        }
+    }
+
+    /// Jacobian of `measure` with respect to the state vector. The positions are components of
+    /// the state, and the axial acceleration is differentiated with dual numbers.
+    pub(crate) fn measurement_jacobian(
+        &self,
+        x: &DVector<f64>,
+        measurement: &Measurement,
+    ) -> Result<DMatrix<f64>, String> {
+        let rows = self.measure(x, measurement)?.len();
+        let mut jacobian = DMatrix::zeros(rows, self.dim());
+        match measurement.kind {
+            MeasurementKind::BarometricAltitude | MeasurementKind::Gps => {
+                // The 3DOF state starts with the downrange position, followed by the altitude
+                let three_dof = matches!(self.initial_condition, InitialCondition::ThreeDOF(_));
+                jacobian[(0, usize::from(three_dof))] = 1.0;
+                if rows == 2 {
+                    jacobian[(1, 0)] = 1.0;
+                }
+            }
+            MeasurementKind::AxialAcceleration => {
+                let state = self.state(x);
+                for j in 0..self.dim() {
+                    let tangent = DVector::from_fn(self.dim(), |i, _| f64::from(i == j));
+                    let u: Vec<Dual<1>> = (0..self.model_dim())
+                        .map(|i| Dual {
+                            value: x[i],
+                            derivatives: [tangent[i]],
+                        })
+                        .collect();
+                    let parameters = self.parameters(&state, tangent.as_slice());
+                    jacobian[(0, j)] =
+                        state.axial_acceleration_with(&u, &parameters).derivatives[0];
+                }
+            }
+        }
+        Ok(jacobian)
+    }
+}
+
+/// Diagonal initial and process noise covariances from their variances, which must have one
+/// entry per state.
+pub(crate) fn diagonal_covariances(
+    n: usize,
+    initial_covariance: &[f64],
+    process_noise: &[f64],
+) -> Result<(DMatrix<f64>, DMatrix<f64>), String> {
+    if initial_covariance.len() != n || process_noise.len() != n {
+        return Err(format!(
+            "initial_covariance and process_noise must both have {n} entries, one per state"
+        ));
+    }
+    Ok((
+        DMatrix::from_diagonal(&DVector::from_column_slice(initial_covariance)),
+        DMatrix::from_diagonal(&DVector::from_column_slice(process_noise)),
+    ))
+}
+
+/// Jacobian of `f` at `x` from central finite differences.
+pub(crate) fn numerical_jacobian(
+    f: impl Fn(&DVector<f64>) -> Result<DVector<f64>, String>,
+    x: &DVector<f64>,
+) -> Result<DMatrix<f64>, String> {
+    let rows = f(x)?.len();
+    let mut jacobian = DMatrix::zeros(rows, x.len());
+    for i in 0..x.len() {
+        let h = 1e-6 * x[i].abs().max(1.0);
+        let (mut x_plus, mut x_minus) = (x.clone(), x.clone());
+        x_plus[i] += h;
+        x_minus[i] -= h;
+        let column = (f(&x_plus)? - f(&x_minus)?) / (2.0 * h);
+        jacobian.set_column(i, &column);
+    }
+    Ok(jacobian)
+}
+
+/// Converts a matrix into a NumPy array.
+pub(crate) fn matrix_to_pyarray<'py>(
+    py: Python<'py>,
+    matrix: &DMatrix<f64>,
+) -> Bound<'py, PyArray2<f64>> {
+    Array2::from_shape_fn(matrix.shape(), |(i, j)| matrix[(i, j)]).to_pyarray(py)
+}
+
+/// Distribution of the apogee predicted from the samples (sigma points or particles) of a state
+/// estimator.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApogeeDistribution {
+    /// Mean apogee (m)
+    #[pyo3(get)]
+    pub mean: f64,
+    /// Standard deviation of the apogee (m)
+    #[pyo3(get)]
+    pub std: f64,
+    pub(crate) samples: Vec<f64>,
+    pub(crate) weights: Vec<f64>,
+}
+
+#[pymethods]
+impl ApogeeDistribution {
+    /// Apogee predicted from each sample (m).
+    #[getter]
+    pub fn samples<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.samples.to_pyarray(py)
+    }
+
+    /// Weight of each sample in the mean.
+    #[getter]
+    pub fn weights<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.weights.to_pyarray(py)
+    }
+}
+
+impl ApogeeDistribution {
+    /// Distribution from weighted samples. The variance uses `covariance_weights`, which differ
+    /// from the mean weights for sigma points.
+    pub(crate) fn from_samples(
+        samples: Vec<f64>,
+        weights: Vec<f64>,
+        covariance_weights: &[f64],
+    ) -> Self {
+        let mean: f64 = samples.iter().zip(&weights).map(|(a, w)| w * a).sum();
+        let variance: f64 = samples
+            .iter()
+            .zip(covariance_weights)
+            .map(|(a, w)| w * (a - mean).powi(2))
+            .sum();
+        Self {
+            mean,
+            std: variance.max(0.0).sqrt(),
+            samples,
+            weights,
+        }
+    }
+}
+
+/// Extended Kalman filter which uses the 1DOF or 3DOF model as its process model, for fusing
+/// sensor data on a flight computer. The Jacobians of the process and measurement models are
+/// exact derivatives from dual numbers.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct ExtendedKalmanFilter {
+    model: ProcessModel,
+    x: DVector<f64>,
+    p: DMatrix<f64>,
+    /// Process noise, added to the covariance per second of prediction
+    q: DMatrix<f64>,
+}
+
+#[pymethods]
+impl ExtendedKalmanFilter {
+    /// Filter which uses the 1DOF model, with the state (altitude, vertical velocity), followed by
+    /// the cd bias if `estimate_cd_bias` is set.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, initial_state, initial_covariance, process_noise, estimate_cd_bias=false, integration_method=OdeMethod::RK3, max_step=DEFAULT_FILTER_STEP))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_1dof(
+        rocket: &Rocket,
+        initial_state: InitialState1DOF,
+        initial_covariance: Vec<f64>,
+        process_noise: Vec<f64>,
+        estimate_cd_bias: bool,
+        integration_method: OdeMethod,
+        max_step: f64,
+    ) -> PyResult<Self> {
+        let model = ProcessModel::new(
+            rocket.rocket_properties.clone(),
+            InitialCondition::OneDOF(initial_state),
+            integration_method,
+            max_step,
+            estimate_cd_bias,
+        )
+        .map_err(PyValueError::new_err)?;
+        Self::new(model, &initial_covariance, &process_noise).map_err(PyValueError::new_err)
+    }
+
+    /// Filter which uses the 3DOF model, with the state (x, altitude, angle, vx, vy, angular
+    /// rate), followed by the cd bias if `estimate_cd_bias` is set.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, initial_state, initial_covariance, process_noise, estimate_cd_bias=false, integration_method=OdeMethod::RK3, max_step=DEFAULT_FILTER_STEP))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_3dof(
+        rocket: &Rocket,
+        initial_state: InitialState3DOF,
+        initial_covariance: Vec<f64>,
+        process_noise: Vec<f64>,
+        estimate_cd_bias: bool,
+        integration_method: OdeMethod,
+        max_step: f64,
+    ) -> PyResult<Self> {
+        let model = ProcessModel::new(
+            rocket.rocket_properties.clone(),
+            InitialCondition::ThreeDOF(initial_state),
+            integration_method,
+            max_step,
+            estimate_cd_bias,
+        )
+        .map_err(PyValueError::new_err)?;
+        Self::new(model, &initial_covariance, &process_noise).map_err(PyValueError::new_err)
+    }
+
+    /// Propagates the estimate `dt` seconds forward with the process model.
+    pub fn predict(&mut self, dt: f64) -> PyResult<()> {
+        self.predict_step(dt).map_err(PyValueError::new_err)
+    }
+
+    /// Corrects the estimate with a measurement.
+    pub fn update(&mut self, measurement: &Measurement) -> PyResult<()> {
+        self.update_step(measurement).map_err(PyValueError::new_err)
+    }
+
+    /// Current state estimate.
+    #[getter]
+    pub fn state<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.x.as_slice().to_pyarray(py)
+    }
+
+    /// Covariance of the current state estimate.
+    #[getter]
+    pub fn covariance<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.p)
+    }
+}
+
+impl ExtendedKalmanFilter {
+    /// Creates a filter with diagonal initial and process noise covariances.
+    pub(crate) fn new(
+        model: ProcessModel,
+        initial_covariance: &[f64],
+        process_noise: &[f64],
+    ) -> Result<Self, String> {
+        let (p, q) = diagonal_covariances(model.dim(), initial_covariance, process_noise)?;
+        Ok(Self {
+            x: model.initial_state(),
+            p,
+            q,
+            model,
+        })
+    }
+
+    pub(crate) fn predict_step(&mut self, dt: f64) -> Result<(), String> {
+        let (x, f) = self.model.propagate_with_jacobian(&self.x, dt);
+        self.x = x;
+        self.p = &f * &self.p * f.transpose() + &self.q * dt;
+        Ok(())
+    }
+
+    pub(crate) fn update_step(&mut self, measurement: &Measurement) -> Result<(), String> {
+        let h = self.model.measurement_jacobian(&self.x, measurement)?;
+        let innovation = measurement.values() - self.model.measure(&self.x, measurement)?;
+        let r = measurement.covariance();
+
+        let s = &h * &self.p * h.transpose() + &r;
+        let s_inv = s
+            .try_inverse()
+            .ok_or("The innovation covariance is singular")?;
+        let k = &self.p * h.transpose() * s_inv;
+
+        self.x += &k * innovation;
+        // Joseph form, which keeps the covariance symmetric and positive definite
+        let i_kh = DMatrix::identity(self.x.len(), self.x.len()) - &k * &h;
+        self.p = &i_kh * &self.p * i_kh.transpose() + &k * r * k.transpose();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simdata_mod::SimulationData;
+    use crate::simulation::{Simulation, SimulationExitCondition};
+    use approx::assert_abs_diff_eq;
+
+    pub(super) fn make_rocket_properties(cd: f64) -> RocketProperties {
+        RocketProperties::new(15.0, cd, 0.0182, 0.05, 5.0, 0.5, 0.2)
+    }
+
+    pub(super) fn one_dof(altitude: f64, velocity: f64) -> InitialCondition {
+        InitialCondition::OneDOF(InitialState1DOF::new(altitude, velocity))
+    }
+
+    /// Altitudes at 10 Hz of a coasting flight with the given cd.
+    pub(super) fn truth(cd: f64) -> SimulationData {
+        let state = one_dof(0.0, 150.0).to_state(make_rocket_properties(cd));
+        let mut simulation = Simulation::new(
+            state,
+            OdeSolver::RK3(FixedTimeStep::new(0.1)),
+            SimulationExitCondition::ApogeeReached,
+            1000,
+        );
+        let mut log = SimulationData::new();
+        simulation.run(&mut log, false, true);
+        log
+    }
+
+    #[test]
+    fn propagate_matches_the_simulation() {
+        let model = ProcessModel::new(
+            make_rocket_properties(0.5),
+            one_dof(0.0, 150.0),
+            OdeMethod::RK3,
+            0.1,
+            true,
+        )
+        .unwrap();
+        let log = truth(0.5);
+        let x = model.propagate(&model.initial_state(), 0.5);
+
+        assert_abs_diff_eq!(x[0], log.state_log[5][0], epsilon = 1e-9);
+        assert_abs_diff_eq!(x[1], log.state_log[5][1], epsilon = 1e-9);
+        assert_eq!(x[2], 0.0);
+    }
+
+    #[test]
+    fn accelerometer_reads_drag_deceleration() {
+        let model = ProcessModel::new(
+            make_rocket_properties(0.5),
+            one_dof(0.0, 0.0),
+            OdeMethod::RK3,
+            0.01,
+            false,
+        )
+        .unwrap();
+        let measurement = Measurement::axial_acceleration(0.0, 0.1);
+
+        // Free fall reads zero
+        let x = DVector::from_column_slice(&[100.0, 0.0]);
+        assert_abs_diff_eq!(model.measure(&x, &measurement).unwrap()[0], 0.0);
+
+        let x = DVector::from_column_slice(&[100.0, 100.0]);
+        let drag = 0.5 * 1.225 * 100.0_f64.powi(2) * 0.5 * 0.0182 / 15.0;
+        assert_abs_diff_eq!(
+            model.measure(&x, &measurement).unwrap()[0],
+            -drag,
+            epsilon = 1e-9
+        );
+
+        // Pointing up in the 3DOF model gives the same reading
+        let model = ProcessModel::new(
+            make_rocket_properties(0.5),
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+            OdeMethod::RK3,
+            0.01,
+            false,
+        )
+        .unwrap();
+        let x = DVector::from_column_slice(&[0.0, 100.0, 0.0, 0.0, 100.0, 0.0]);
+        assert_abs_diff_eq!(
+            model.measure(&x, &measurement).unwrap()[0],
+            -drag,
+            epsilon = 1e-9
+        );
+        assert!(model
+            .measure(&x, &Measurement::gps(100.0, 1.0, Some(0.0)))
+            .is_ok());
+    }
+
+    #[test]
+    fn jacobians_match_finite_differences() {
+        let model = ProcessModel::new(
+            make_rocket_properties(0.5),
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+            OdeMethod::RK3,
+            0.01,
+            true,
+        )
+        .unwrap();
+        let x = DVector::from_column_slice(&[10.0, 500.0, 0.1, -10.0, 120.0, 0.3, 0.05]);
+
+        let (x_next, jacobian) = model.propagate_with_jacobian(&x, 0.1);
+        assert_eq!(x_next, model.propagate(&x, 0.1));
+        let finite = numerical_jacobian(|x| Ok(model.propagate(x, 0.1)), &x).unwrap();
+        assert_abs_diff_eq!(jacobian, finite, epsilon = 1e-6);
+
+        for measurement in [
+            Measurement::axial_acceleration(0.0, 0.1),
+            Measurement::gps(0.0, 1.0, Some(0.0)),
+        ] {
+            let jacobian = model.measurement_jacobian(&x, &measurement).unwrap();
+            let finite = numerical_jacobian(|x| model.measure(x, &measurement), &x).unwrap();
+            assert_abs_diff_eq!(jacobian, finite, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn ekf_estimates_cd_bias_from_altitude() {
+        let log = truth(0.6);
+        let model = ProcessModel::new(
+            make_rocket_properties(0.5),
+            one_dof(0.0, 150.0),
+            OdeMethod::RK3,
+            0.1,
+            true,
+        )
+        .unwrap();
+        let mut ekf =
+            ExtendedKalmanFilter::new(model, &[1.0, 1.0, 0.1], &[1e-4, 1e-4, 1e-6]).unwrap();
+
+        for row in log.state_log.iter().skip(1) {
+            ekf.predict_step(0.1).unwrap();
+            ekf.update_step(&Measurement::barometric_altitude(row[0], 0.5))
+                .unwrap();
+        }
+
+        assert_abs_diff_eq!(ekf.x[2], 0.1, epsilon = 0.01);
+        assert_abs_diff_eq!(ekf.x[1], log.state_log.last().unwrap()[1], epsilon = 1.0);
+        // The covariance shrinks from its initial value
+        assert!(ekf.p[(2, 2)] < 1e-3);
+    }
+
+    #[test]
+    fn invalid_configurations_are_rejected() {
+        let model = |method, bias| {
+            ProcessModel::new(
+                make_rocket_properties(0.5),
+                one_dof(0.0, 150.0),
+                method,
+                0.01,
+                bias,
+            )
+        };
+        assert!(model(OdeMethod::RK45, false).is_err());
+        let model = model(OdeMethod::Euler, false).unwrap();
+        assert!(ExtendedKalmanFilter::new(model.clone(), &[1.0], &[1.0, 1.0]).is_err());
+        assert!(model
+            .measure(
+                &model.initial_state(),
+                &Measurement::gps(0.0, 1.0, Some(0.0))
+            )
+            .is_err());
+    }
+}
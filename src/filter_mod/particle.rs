@@ -0,0 +1,335 @@
+use super::{
+    diagonal_covariances, matrix_to_pyarray, ApogeeDistribution, Measurement, ProcessModel,
+    DEFAULT_FILTER_STEP,
+};
+use crate::ode::OdeMethod;
+use crate::random_mod::Rng;
+use crate::rocket::Rocket;
+use crate::state::{InitialCondition, InitialState1DOF, InitialState3DOF};
+use nalgebra::{DMatrix, DVector};
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Default number of particles of a particle filter
+const DEFAULT_NUM_PARTICLES: usize = 1000;
+
+/// Bootstrap particle filter which uses the 1DOF or 3DOF model as its process model. The
+/// estimate is represented by weighted samples (particles), so it copes with strongly
+/// non-Gaussian uncertainty at the cost of propagating every particle. The particles are
+/// resampled when the effective sample size falls below half the number of particles.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct ParticleFilter {
+    model: ProcessModel,
+    particles: Vec<DVector<f64>>,
+    weights: Vec<f64>,
+    /// Standard deviation of the process noise added to each state per square root second
+    process_std: DVector<f64>,
+    rng: Rng,
+}
+
+#[pymethods]
+impl ParticleFilter {
+    /// Filter which uses the 1DOF model, with the state (altitude, vertical velocity), followed by
+    /// the cd bias if `estimate_cd_bias` is set.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, initial_state, initial_covariance, process_noise, num_particles=DEFAULT_NUM_PARTICLES, seed=0, estimate_cd_bias=false, integration_method=OdeMethod::RK3, max_step=DEFAULT_FILTER_STEP))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_1dof(
+        rocket: &Rocket,
+        initial_state: InitialState1DOF,
+        initial_covariance: Vec<f64>,
+        process_noise: Vec<f64>,
+        num_particles: usize,
+        seed: u64,
+        estimate_cd_bias: bool,
+        integration_method: OdeMethod,
+        max_step: f64,
+    ) -> PyResult<Self> {
+        let model = ProcessModel::new(
+            rocket.rocket_properties.clone(),
+            InitialCondition::OneDOF(initial_state),
+            integration_method,
+            max_step,
+            estimate_cd_bias,
+        )
+        .map_err(PyValueError::new_err)?;
+        Self::new(
+            model,
+            &initial_covariance,
+            &process_noise,
+            num_particles,
+            seed,
+        )
+        .map_err(PyValueError::new_err)
+    }
+
+    /// Filter which uses the 3DOF model, with the state (x, altitude, angle, vx, vy, angular
+    /// rate), followed by the cd bias if `estimate_cd_bias` is set.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, initial_state, initial_covariance, process_noise, num_particles=DEFAULT_NUM_PARTICLES, seed=0, estimate_cd_bias=false, integration_method=OdeMethod::RK3, max_step=DEFAULT_FILTER_STEP))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_3dof(
+        rocket: &Rocket,
+        initial_state: InitialState3DOF,
+        initial_covariance: Vec<f64>,
+        process_noise: Vec<f64>,
+        num_particles: usize,
+        seed: u64,
+        estimate_cd_bias: bool,
+        integration_method: OdeMethod,
+        max_step: f64,
+    ) -> PyResult<Self> {
+        let model = ProcessModel::new(
+            rocket.rocket_properties.clone(),
+            InitialCondition::ThreeDOF(initial_state),
+            integration_method,
+            max_step,
+            estimate_cd_bias,
+        )
+        .map_err(PyValueError::new_err)?;
+        Self::new(
+            model,
+            &initial_covariance,
+            &process_noise,
+            num_particles,
+            seed,
+        )
+        .map_err(PyValueError::new_err)
+    }
+
+    /// Propagates each particle `dt` seconds forward with the process model, and adds process
+    /// noise.
+    pub fn predict(&mut self, dt: f64) {
+        self.predict_step(dt)
+    }
+
+    /// Reweights the particles by the likelihood of a measurement, and resamples them if too few
+    /// carry most of the weight.
+    pub fn update(&mut self, measurement: &Measurement) -> PyResult<()> {
+        self.update_step(measurement).map_err(PyValueError::new_err)
+    }
+
+    /// Distribution of the apogee, predicted from each particle. This runs a simulation per
+    /// particle, so it is much slower than the other methods.
+    pub fn apogee_distribution(&self) -> ApogeeDistribution {
+        self.apogee_samples()
+    }
+
+    /// Weighted mean of the particles.
+    #[getter]
+    pub fn state<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.mean().as_slice().to_pyarray(py)
+    }
+
+    /// Weighted covariance of the particles.
+    #[getter]
+    pub fn covariance<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.covariance_matrix())
+    }
+
+    /// Weight of each particle, which sum to 1.
+    #[getter]
+    pub fn weights<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.weights.to_pyarray(py)
+    }
+
+    /// Effective number of particles, 1 / sum(w^2).
+    #[getter]
+    pub fn effective_sample_size(&self) -> f64 {
+        1.0 / self.weights.iter().map(|w| w * w).sum::<f64>()
+    }
+}
+
+impl ParticleFilter {
+    /// Creates a filter with particles drawn from a normal distribution with diagonal covariance
+    /// about the initial state.
+    pub(crate) fn new(
+        model: ProcessModel,
+        initial_covariance: &[f64],
+        process_noise: &[f64],
+        num_particles: usize,
+        seed: u64,
+    ) -> Result<Self, String> {
+        let (p, q) = diagonal_covariances(model.dim(), initial_covariance, process_noise)?;
+        if num_particles == 0 {
+            return Err("num_particles must be positive".to_string());
+        }
+        if p.diagonal()
+            .iter()
+            .chain(q.diagonal().iter())
+            .any(|v| *v < 0.0)
+        {
+            return Err("The variances must not be negative".to_string());
+        }
+
+        let mut rng = Rng::new(seed);
+        let x = model.initial_state();
+        let initial_std = p.diagonal().map(f64::sqrt);
+        let particles = (0..num_particles)
+            .map(|_| &x + initial_std.component_mul(&rng.normal_vector(x.len())))
+            .collect();
+        Ok(Self {
+            particles,
+            weights: vec![1.0 / num_particles as f64; num_particles],
+            process_std: q.diagonal().map(f64::sqrt),
+            model,
+            rng,
+        })
+    }
+
+    pub(crate) fn predict_step(&mut self, dt: f64) {
+        let noise_std = &self.process_std * dt.abs().sqrt();
+        for particle in self.particles.iter_mut() {
+            let noise = noise_std.component_mul(&self.rng.normal_vector(particle.len()));
+            *particle = self.model.propagate(particle, dt) + noise;
+        }
+    }
+
+    pub(crate) fn update_step(&mut self, measurement: &Measurement) -> Result<(), String> {
+        let r_inv = measurement
+            .covariance()
+            .try_inverse()
+            .ok_or("The measurement covariance is singular")?;
+        let values = measurement.values();
+
+        // Log weights, normalised by the largest to avoid underflow
+        let mut log_weights = Vec::with_capacity(self.particles.len());
+        for (particle, weight) in self.particles.iter().zip(&self.weights) {
+            let residual = &values - self.model.measure(particle, measurement)?;
+            log_weights.push(weight.ln() - 0.5 * (residual.transpose() * &r_inv * &residual)[0]);
+        }
+        let max = log_weights
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if !max.is_finite() {
+            return Err("No particle is consistent with the measurement".to_string());
+        }
+        let weights: Vec<f64> = log_weights.iter().map(|w| (w - max).exp()).collect();
+        let total: f64 = weights.iter().sum();
+        self.weights = weights.iter().map(|w| w / total).collect();
+
+        if self.effective_sample_size() < self.particles.len() as f64 / 2.0 {
+            self.resample();
+        }
+        Ok(())
+    }
+
+    /// Systematic resampling, which draws every particle with a single random offset.
+    fn resample(&mut self) {
+        let n = self.particles.len();
+        let offset = self.rng.uniform();
+        let mut cumulative = self.weights[0];
+        let mut i = 0;
+        let particles = (0..n)
+            .map(|j| {
+                let u = (j as f64 + offset) / n as f64;
+                while cumulative < u && i < n - 1 {
+                    i += 1;
+                    cumulative += self.weights[i];
+                }
+                self.particles[i].clone()
+            })
+            .collect();
+        self.particles = particles;
+        self.weights = vec![1.0 / n as f64; n];
+    }
+
+    pub(crate) fn mean(&self) -> DVector<f64> {
+        self.particles
+            .iter()
+            .zip(&self.weights)
+            .fold(DVector::zeros(self.model.dim()), |sum, (particle, w)| {
+                sum + particle * *w
+            })
+    }
+
+    pub(crate) fn covariance_matrix(&self) -> DMatrix<f64> {
+        let mean = self.mean();
+        self.particles.iter().zip(&self.weights).fold(
+            DMatrix::zeros(mean.len(), mean.len()),
+            |sum, (particle, w)| {
+                let deviation = particle - &mean;
+                sum + &deviation * deviation.transpose() * *w
+            },
+        )
+    }
+
+    pub(crate) fn apogee_samples(&self) -> ApogeeDistribution {
+        let apogees = self
+            .particles
+            .iter()
+            .map(|particle| self.model.apogee(particle))
+            .collect();
+        ApogeeDistribution::from_samples(apogees, self.weights.clone(), &self.weights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter_mod::tests::{make_rocket_properties, one_dof, truth};
+    use approx::assert_abs_diff_eq;
+
+    fn make_filter(num_particles: usize, seed: u64) -> ParticleFilter {
+        let model = ProcessModel::new(
+            make_rocket_properties(0.5),
+            one_dof(0.0, 150.0),
+            OdeMethod::RK3,
+            0.1,
+            true,
+        )
+        .unwrap();
+        ParticleFilter::new(
+            model,
+            &[1.0, 1.0, 0.01],
+            &[1e-2, 1e-2, 1e-6],
+            num_particles,
+            seed,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn particles_are_drawn_from_the_initial_distribution() {
+        let filter = make_filter(20_000, 1);
+        assert_abs_diff_eq!(filter.mean()[1], 150.0, epsilon = 0.05);
+        assert_abs_diff_eq!(filter.covariance_matrix()[(0, 0)], 1.0, epsilon = 0.05);
+        assert_abs_diff_eq!(filter.covariance_matrix()[(2, 2)], 0.01, epsilon = 5e-4);
+
+        // The same seed gives the same particles
+        assert_eq!(make_filter(10, 3).particles, make_filter(10, 3).particles);
+    }
+
+    #[test]
+    fn resampling_keeps_heavy_particles() {
+        let mut filter = make_filter(4, 0);
+        filter.weights = vec![0.0, 1.0, 0.0, 0.0];
+        let heavy = filter.particles[1].clone();
+        filter.resample();
+        assert!(filter.particles.iter().all(|p| *p == heavy));
+        assert_eq!(filter.effective_sample_size(), 4.0);
+    }
+
+    #[test]
+    fn particle_filter_tracks_the_flight() {
+        let log = truth(0.6);
+        let mut filter = make_filter(500, 0);
+
+        for row in log.state_log.iter().skip(1).take(30) {
+            filter.predict_step(0.1);
+            filter
+                .update_step(&Measurement::barometric_altitude(row[0], 0.5))
+                .unwrap();
+        }
+
+        let mean = filter.mean();
+        assert_abs_diff_eq!(mean[0], log.state_log[30][0], epsilon = 1.0);
+        assert_abs_diff_eq!(mean[1], log.state_log[30][1], epsilon = 2.0);
+        let distribution = filter.apogee_samples();
+        assert_eq!(distribution.samples.len(), 500);
+        assert!(distribution.std > 0.0);
+    }
+}
@@ -0,0 +1,313 @@
+use super::{
+    diagonal_covariances, matrix_to_pyarray, ApogeeDistribution, Measurement, ProcessModel,
+    DEFAULT_FILTER_STEP,
+};
+use crate::ode::OdeMethod;
+use crate::rocket::Rocket;
+use crate::state::{InitialCondition, InitialState1DOF, InitialState3DOF};
+use nalgebra::{DMatrix, DVector};
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Unscented Kalman filter which uses the 1DOF or 3DOF model as its process model. Instead of
+/// linearising the models, a set of sigma points is propagated through them, which captures the
+/// nonlinearity of the drag. Uses the scaled unscented transform with parameters alpha, beta and
+/// kappa.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct UnscentedKalmanFilter {
+    model: ProcessModel,
+    x: DVector<f64>,
+    p: DMatrix<f64>,
+    /// Process noise, added to the covariance per second of prediction
+    q: DMatrix<f64>,
+    /// Scale of the sigma points, (n + lambda)
+    scale: f64,
+    mean_weights: Vec<f64>,
+    covariance_weights: Vec<f64>,
+}
+
+#[pymethods]
+impl UnscentedKalmanFilter {
+    /// Filter which uses the 1DOF model, with the state (altitude, vertical velocity), followed by
+    /// the cd bias if `estimate_cd_bias` is set.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, initial_state, initial_covariance, process_noise, estimate_cd_bias=false, integration_method=OdeMethod::RK3, max_step=DEFAULT_FILTER_STEP, alpha=1e-3, beta=2.0, kappa=0.0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_1dof(
+        rocket: &Rocket,
+        initial_state: InitialState1DOF,
+        initial_covariance: Vec<f64>,
+        process_noise: Vec<f64>,
+        estimate_cd_bias: bool,
+        integration_method: OdeMethod,
+        max_step: f64,
+        alpha: f64,
+        beta: f64,
+        kappa: f64,
+    ) -> PyResult<Self> {
+        let model = ProcessModel::new(
+            rocket.rocket_properties.clone(),
+            InitialCondition::OneDOF(initial_state),
+            integration_method,
+            max_step,
+            estimate_cd_bias,
+        )
+        .map_err(PyValueError::new_err)?;
+        Self::new(
+            model,
+            &initial_covariance,
+            &process_noise,
+            (alpha, beta, kappa),
+        )
+        .map_err(PyValueError::new_err)
+    }
+
+    /// Filter which uses the 3DOF model, with the state (x, altitude, angle, vx, vy, angular
+    /// rate), followed by the cd bias if `estimate_cd_bias` is set.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, initial_state, initial_covariance, process_noise, estimate_cd_bias=false, integration_method=OdeMethod::RK3, max_step=DEFAULT_FILTER_STEP, alpha=1e-3, beta=2.0, kappa=0.0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_3dof(
+        rocket: &Rocket,
+        initial_state: InitialState3DOF,
+        initial_covariance: Vec<f64>,
+        process_noise: Vec<f64>,
+        estimate_cd_bias: bool,
+        integration_method: OdeMethod,
+        max_step: f64,
+        alpha: f64,
+        beta: f64,
+        kappa: f64,
+    ) -> PyResult<Self> {
+        let model = ProcessModel::new(
+            rocket.rocket_properties.clone(),
+            InitialCondition::ThreeDOF(initial_state),
+            integration_method,
+            max_step,
+            estimate_cd_bias,
+        )
+        .map_err(PyValueError::new_err)?;
+        Self::new(
+            model,
+            &initial_covariance,
+            &process_noise,
+            (alpha, beta, kappa),
+        )
+        .map_err(PyValueError::new_err)
+    }
+
+    /// Propagates the estimate `dt` seconds forward with the process model.
+    pub fn predict(&mut self, dt: f64) -> PyResult<()> {
+        self.predict_step(dt).map_err(PyValueError::new_err)
+    }
+
+    /// Corrects the estimate with a measurement.
+    pub fn update(&mut self, measurement: &Measurement) -> PyResult<()> {
+        self.update_step(measurement).map_err(PyValueError::new_err)
+    }
+
+    /// Distribution of the apogee, predicted from each sigma point of the current estimate.
+    pub fn apogee_distribution(&self) -> PyResult<ApogeeDistribution> {
+        self.apogee_samples().map_err(PyValueError::new_err)
+    }
+
+    /// Current state estimate.
+    #[getter]
+    pub fn state<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.x.as_slice().to_pyarray(py)
+    }
+
+    /// Covariance of the current state estimate.
+    #[getter]
+    pub fn covariance<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.p)
+    }
+}
+
+impl UnscentedKalmanFilter {
+    /// Creates a filter with diagonal initial and process noise covariances, and the unscented
+    /// transform parameters (alpha, beta, kappa).
+    pub(crate) fn new(
+        model: ProcessModel,
+        initial_covariance: &[f64],
+        process_noise: &[f64],
+        (alpha, beta, kappa): (f64, f64, f64),
+    ) -> Result<Self, String> {
+        let (p, q) = diagonal_covariances(model.dim(), initial_covariance, process_noise)?;
+        let n = model.dim() as f64;
+        let scale = alpha.powi(2) * (n + kappa);
+        if scale <= 0.0 {
+            return Err("alpha and kappa must give a positive sigma point scale".to_string());
+        }
+        let lambda = scale - n;
+
+        let mut mean_weights = vec![1.0 / (2.0 * scale); 2 * model.dim() + 1];
+        let mut covariance_weights = mean_weights.clone();
+        mean_weights[0] = lambda / scale;
+        covariance_weights[0] = lambda / scale + 1.0 - alpha.powi(2) + beta;
+        Ok(Self {
+            x: model.initial_state(),
+            p,
+            q,
+            model,
+            scale,
+            mean_weights,
+            covariance_weights,
+        })
+    }
+
+    /// Sigma points of the current estimate: the mean, followed by the mean plus and minus each
+    /// column of the scaled covariance's Cholesky factor.
+    fn sigma_points(&self) -> Result<Vec<DVector<f64>>, String> {
+        let l = (&self.p * self.scale)
+            .cholesky()
+            .ok_or("The covariance is not positive definite")?
+            .unpack();
+        let mut points = vec![self.x.clone()];
+        points.extend(l.column_iter().map(|column| &self.x + column));
+        points.extend(l.column_iter().map(|column| &self.x - column));
+        Ok(points)
+    }
+
+    /// Weighted mean of the points and their covariance about it.
+    fn moments(&self, points: &[DVector<f64>]) -> (DVector<f64>, DMatrix<f64>) {
+        let mean = points
+            .iter()
+            .zip(&self.mean_weights)
+            .fold(DVector::zeros(points[0].len()), |sum, (point, w)| {
+                sum + point * *w
+            });
+        let covariance = self.cross_covariance(points, &mean, points, &mean);
+        (mean, covariance)
+    }
+
+    fn cross_covariance(
+        &self,
+        a: &[DVector<f64>],
+        a_mean: &DVector<f64>,
+        b: &[DVector<f64>],
+        b_mean: &DVector<f64>,
+    ) -> DMatrix<f64> {
+        a.iter().zip(b).zip(&self.covariance_weights).fold(
+            DMatrix::zeros(a_mean.len(), b_mean.len()),
+            |sum, ((a, b), w)| sum + (a - a_mean) * (b - b_mean).transpose() * *w,
+        )
+    }
+
+    pub(crate) fn predict_step(&mut self, dt: f64) -> Result<(), String> {
+        let points: Vec<_> = self
+            .sigma_points()?
+            .iter()
+            .map(|point| self.model.propagate(point, dt))
+            .collect();
+        let (x, p) = self.moments(&points);
+        self.x = x;
+        self.p = p + &self.q * dt;
+        Ok(())
+    }
+
+    pub(crate) fn update_step(&mut self, measurement: &Measurement) -> Result<(), String> {
+        let points = self.sigma_points()?;
+        let predicted = points
+            .iter()
+            .map(|point| self.model.measure(point, measurement))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (z, p_zz) = self.moments(&predicted);
+        let p_zz = p_zz + measurement.covariance();
+        let p_xz = self.cross_covariance(&points, &self.x, &predicted, &z);
+
+        let k = p_xz
+            * p_zz
+                .clone()
+                .try_inverse()
+                .ok_or("The innovation covariance is singular")?;
+        self.x += &k * (measurement.values() - z);
+        self.p -= &k * p_zz * k.transpose();
+        // Keep the covariance symmetric against rounding
+        self.p = (&self.p + self.p.transpose()) / 2.0;
+        Ok(())
+    }
+
+    pub(crate) fn apogee_samples(&self) -> Result<ApogeeDistribution, String> {
+        let apogees = self
+            .sigma_points()?
+            .iter()
+            .map(|point| self.model.apogee(point))
+            .collect();
+        Ok(ApogeeDistribution::from_samples(
+            apogees,
+            self.mean_weights.clone(),
+            &self.covariance_weights,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter_mod::tests::{make_rocket_properties, one_dof, truth};
+    use crate::filter_mod::ExtendedKalmanFilter;
+    use approx::assert_abs_diff_eq;
+
+    fn make_model() -> ProcessModel {
+        ProcessModel::new(
+            make_rocket_properties(0.5),
+            one_dof(0.0, 150.0),
+            OdeMethod::RK3,
+            0.1,
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn sigma_points_recover_the_moments() {
+        let mut ukf =
+            UnscentedKalmanFilter::new(make_model(), &[4.0, 1.0, 0.01], &[0.0; 3], (0.5, 2.0, 0.0))
+                .unwrap();
+        ukf.x = DVector::from_column_slice(&[10.0, 20.0, 0.1]);
+        let (mean, covariance) = ukf.moments(&ukf.sigma_points().unwrap());
+
+        assert_abs_diff_eq!(mean, ukf.x, epsilon = 1e-9);
+        assert_abs_diff_eq!(covariance, ukf.p, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn ukf_matches_the_ekf_and_estimates_cd_bias() {
+        let log = truth(0.6);
+        let (p0, q) = ([1.0, 1.0, 0.1], [1e-4, 1e-4, 1e-6]);
+        let mut ukf = UnscentedKalmanFilter::new(make_model(), &p0, &q, (1e-3, 2.0, 0.0)).unwrap();
+        let mut ekf = ExtendedKalmanFilter::new(make_model(), &p0, &q).unwrap();
+
+        for row in log.state_log.iter().skip(1).take(30) {
+            let measurement = Measurement::barometric_altitude(row[0], 0.5);
+            ukf.predict_step(0.1).unwrap();
+            ukf.update_step(&measurement).unwrap();
+            ekf.predict_step(0.1).unwrap();
+            ekf.update_step(&measurement).unwrap();
+        }
+
+        assert_abs_diff_eq!(ukf.x[2], 0.1, epsilon = 0.01);
+        assert_abs_diff_eq!(ukf.x, ekf.x, epsilon = 0.05);
+
+        let distribution = ukf.apogee_samples().unwrap();
+        assert_eq!(distribution.samples.len(), 7);
+        let expected = ukf.model.apogee(&ukf.x);
+        assert_abs_diff_eq!(distribution.mean, expected, epsilon = 1.0);
+        assert!(distribution.std > 0.0);
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        assert!(
+            UnscentedKalmanFilter::new(make_model(), &[1.0; 3], &[0.0; 3], (1.0, 2.0, -3.0))
+                .is_err()
+        );
+        let ukf =
+            UnscentedKalmanFilter::new(make_model(), &[1.0, -1.0, 1.0], &[0.0; 3], (1.0, 2.0, 0.0))
+                .unwrap();
+        assert!(ukf.sigma_points().is_err());
+    }
+}
@@ -0,0 +1,548 @@
+pub(crate) mod mcmc;
+
+use crate::filter_mod::{matrix_to_pyarray, numerical_jacobian};
+use crate::ode::OdeSolver;
+use crate::rocket::{Rocket, RocketProperties};
+use crate::simdata_mod::SimulationData;
+use crate::simulation::{PredictionStatus, Simulation, SimulationExitCondition};
+use crate::state::InitialCondition;
+use crate::targeting_mod::interpolate_row;
+use nalgebra::{DMatrix, DVector};
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::prelude::*;
+
+/// Rocket property which can be fitted to flight data.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FitParameter {
+    /// Drag coefficient `cd`. Not available with an aero table, which replaces it.
+    Cd,
+    /// Axial force coefficient of the aero table at zero angle of attack, with one value per
+    /// Mach breakpoint of the table.
+    CdMachKnots,
+    /// Lift coefficient slope `cl_a`, which only affects the 3DOF model.
+    ClA,
+    /// Static stability margin `stab_margin_dimensional`, which only affects the 3DOF model.
+    StabilityMargin,
+}
+
+/// Result of fitting rocket properties to flight data.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct FitResult {
+    /// The fitted parameter behind each entry of `values`. `CdMachKnots` is repeated once per
+    /// Mach breakpoint.
+    #[pyo3(get)]
+    pub parameters: Vec<FitParameter>,
+    values: Vec<f64>,
+    residuals: Vec<f64>,
+    covariance: DMatrix<f64>,
+    /// Sum of the squared residuals
+    #[pyo3(get)]
+    pub cost: f64,
+    /// Number of Levenberg-Marquardt iterations taken
+    #[pyo3(get)]
+    pub iterations: u32,
+    /// Whether the fit converged before running out of iterations
+    #[pyo3(get)]
+    pub converged: bool,
+    /// Copy of the rocket with the fitted properties
+    #[pyo3(get)]
+    pub rocket: Rocket,
+}
+
+#[pymethods]
+impl FitResult {
+    /// Fitted value of each parameter.
+    #[getter]
+    pub fn values<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.values.to_pyarray(py)
+    }
+
+    /// Residuals of the fit, (model - data) / std, with the altitudes followed by the
+    /// accelerations.
+    #[getter]
+    pub fn residuals<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.residuals.to_pyarray(py)
+    }
+
+    /// Covariance of the fitted values, scaled by the residual variance of the fit.
+    #[getter]
+    pub fn covariance<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.covariance)
+    }
+
+    /// Standard error of each fitted value, from the diagonal of the covariance.
+    #[getter]
+    pub fn standard_errors<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.covariance
+            .diagonal()
+            .map(f64::sqrt)
+            .as_slice()
+            .to_pyarray(py)
+    }
+}
+
+/// Recorded flight to fit to: the altitude and, optionally, the axial acceleration at each time.
+#[derive(Clone, Debug)]
+pub(crate) struct FlightData {
+    pub(crate) time: Vec<f64>,
+    pub(crate) altitude: Vec<f64>,
+    pub(crate) acceleration: Option<Vec<f64>>,
+    /// Noise of the altitudes (m), which weights their residuals
+    pub(crate) altitude_std: f64,
+    /// Noise of the accelerations (m/s^2), which weights their residuals
+    pub(crate) acceleration_std: f64,
+}
+
+impl FlightData {
+    fn validate(&self) -> Result<(), String> {
+        if self.time.len() < 2 || self.altitude.len() != self.time.len() {
+            return Err("time and altitude must have the same length of at least 2".to_string());
+        }
+        if self
+            .acceleration
+            .as_ref()
+            .is_some_and(|acceleration| acceleration.len() != self.time.len())
+        {
+            return Err("acceleration must have the same length as time".to_string());
+        }
+        if self.time.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("time must be strictly increasing".to_string());
+        }
+        if self.altitude_std <= 0.0 || self.acceleration_std <= 0.0 {
+            return Err("altitude_std and acceleration_std must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Number of residuals.
+    fn len(&self) -> usize {
+        self.time.len() * if self.acceleration.is_some() { 2 } else { 1 }
+    }
+}
+
+/// Least squares fit of rocket properties to flight data, with a simulation from the initial
+/// condition at the first data time as the forward model.
+#[derive(Clone, Debug)]
+pub(crate) struct FitProblem {
+    pub(crate) rocket_properties: RocketProperties,
+    pub(crate) initial_condition: InitialCondition,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) parameters: Vec<FitParameter>,
+    pub(crate) data: FlightData,
+    pub(crate) max_iterations: u64,
+}
+
+impl FitProblem {
+    /// Fits the parameters, starting from their current values.
+    pub(crate) fn solve(&self, max_iterations: u32, tolerance: f64) -> Result<FitResult, String> {
+        self.validate()?;
+        let (parameters, x0) = self.initial_values();
+        if self.data.len() < x0.len() {
+            return Err(format!(
+                "The data has {} residuals, fewer than the {} parameters",
+                self.data.len(),
+                x0.len()
+            ));
+        }
+
+        let solution = levenberg_marquardt(|x| self.residuals(x), x0, max_iterations, tolerance)?;
+        Ok(FitResult {
+            parameters,
+            rocket: Rocket {
+                rocket_properties: self.apply(&solution.x),
+            },
+            values: solution.x.as_slice().to_vec(),
+            cost: solution.residuals.norm_squared(),
+            residuals: solution.residuals.as_slice().to_vec(),
+            covariance: solution.covariance,
+            iterations: solution.iterations,
+            converged: solution.converged,
+        })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.data.validate()?;
+        if self.parameters.is_empty() {
+            return Err("At least one parameter must be fitted".to_string());
+        }
+        let has_table = self.rocket_properties.aero_table.is_some();
+        let three_dof = matches!(self.initial_condition, InitialCondition::ThreeDOF(_));
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            if self.parameters[..i].contains(parameter) {
+                return Err(format!("{parameter:?} is fitted more than once"));
+            }
+            match parameter {
+                FitParameter::CdMachKnots if !has_table => {
+                    return Err("CdMachKnots needs the rocket to have an aero table".to_string())
+                }
+                FitParameter::Cd | FitParameter::ClA | FitParameter::StabilityMargin
+                    if has_table =>
+                {
+                    return Err(format!("{parameter:?} is replaced by the aero table"))
+                }
+                FitParameter::ClA | FitParameter::StabilityMargin if !three_dof => {
+                    return Err(format!("{parameter:?} only affects the 3DOF model"))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of values a parameter expands to.
+    fn width(&self, parameter: FitParameter) -> usize {
+        match (parameter, &self.rocket_properties.aero_table) {
+            (FitParameter::CdMachKnots, Some(table)) => table.mach().len(),
+            _ => 1,
+        }
+    }
+
+    /// The parameter behind each value, and the current values of the rocket properties.
+    fn initial_values(&self) -> (Vec<FitParameter>, DVector<f64>) {
+        let (mut parameters, mut values) = (Vec::new(), Vec::new());
+        let properties = &self.rocket_properties;
+        for &parameter in &self.parameters {
+            parameters.extend(std::iter::repeat_n(parameter, self.width(parameter)));
+            match parameter {
+                FitParameter::Cd => values.push(properties.cd),
+                FitParameter::CdMachKnots => {
+                    if let Some(table) = &properties.aero_table {
+                        values.extend(table.axial_force_knots());
+                    }
+                }
+                FitParameter::ClA => values.push(properties.cl_a),
+                FitParameter::StabilityMargin => values.push(properties.stab_margin_dimensional),
+            }
+        }
+        (parameters, DVector::from_vec(values))
+    }
+
+    /// Copies the rocket properties with the parameters set to `values`.
+    fn apply(&self, values: &DVector<f64>) -> RocketProperties {
+        let mut properties = self.rocket_properties.clone();
+        let mut start = 0;
+        for &parameter in &self.parameters {
+            let width = self.width(parameter);
+            let value = &values.as_slice()[start..start + width];
+            match parameter {
+                FitParameter::Cd => properties.cd = value[0],
+                FitParameter::CdMachKnots => {
+                    if let Some(table) = properties.aero_table.as_mut() {
+                        *table = table.with_axial_force_knots(value);
+                    }
+                }
+                FitParameter::ClA => properties.cl_a = value[0],
+                FitParameter::StabilityMargin => properties.stab_margin_dimensional = value[0],
+            }
+            start += width;
+        }
+        properties
+    }
+
+    /// Residuals of the simulated flight with the parameters set to `values`, which are NaN if
+    /// the simulation diverges. Data past the simulated apogee is compared to the apogee.
+    fn residuals(&self, values: &DVector<f64>) -> Result<DVector<f64>, String> {
+        let properties = self.apply(values);
+        let mut simulation = Simulation::new(
+            self.initial_condition.to_state(properties.clone()),
+            self.ode_solver.clone(),
+            SimulationExitCondition::ApogeeReached,
+            self.max_iterations,
+        );
+        let mut log = SimulationData::new();
+        simulation.run(&mut log, false, true);
+        if simulation.status() == PredictionStatus::Diverged || log.time_log.is_empty() {
+            return Ok(DVector::from_element(self.data.len(), f64::NAN));
+        }
+
+        let altitude_index = match self.initial_condition {
+            InitialCondition::OneDOF(_) => 0,
+            InitialCondition::ThreeDOF(_) => 1,
+        };
+        let start = self.data.time[0];
+        let rows: Vec<Vec<f64>> = self
+            .data
+            .time
+            .iter()
+            .map(|t| interpolate_row(&log, t - start))
+            .collect();
+
+        let mut residuals: Vec<f64> = rows
+            .iter()
+            .zip(&self.data.altitude)
+            .map(|(row, altitude)| (row[altitude_index] - altitude) / self.data.altitude_std)
+            .collect();
+        if let Some(acceleration) = &self.data.acceleration {
+            residuals.extend(rows.iter().zip(acceleration).map(|(row, acceleration)| {
+                let state = self
+                    .initial_condition
+                    .with_row(row)
+                    .to_state(properties.clone());
+                (state.get_axial_acceleration() - acceleration) / self.data.acceleration_std
+            }));
+        }
+        Ok(DVector::from_vec(residuals))
+    }
+}
+
+/// Solution of a nonlinear least squares problem.
+#[derive(Clone, Debug)]
+pub(crate) struct LeastSquaresSolution {
+    pub(crate) x: DVector<f64>,
+    pub(crate) residuals: DVector<f64>,
+    /// Covariance of `x`, (J^T J)^-1 scaled by the residual variance. NaN if `x` isn't
+    /// identifiable from the residuals.
+    pub(crate) covariance: DMatrix<f64>,
+    pub(crate) iterations: u32,
+    pub(crate) converged: bool,
+}
+
+/// Minimises the sum of squares of `f` from `x0` with the Levenberg-Marquardt method, using
+/// central difference Jacobians. Steps which make the residuals non-finite are rejected like
+/// steps which increase the cost. Converges when the step or the relative decrease of the cost
+/// falls below `tolerance`, or when no step decreases the cost any more.
+pub(crate) fn levenberg_marquardt(
+    f: impl Fn(&DVector<f64>) -> Result<DVector<f64>, String>,
+    x0: DVector<f64>,
+    max_iterations: u32,
+    tolerance: f64,
+) -> Result<LeastSquaresSolution, String> {
+    let mut x = x0;
+    let mut residuals = f(&x)?;
+    let mut cost = residuals.norm_squared();
+    if !cost.is_finite() {
+        return Err("The residuals are not finite at the initial parameters".to_string());
+    }
+    let mut jacobian = numerical_jacobian(&f, &x)?;
+    let mut lambda = 1e-3;
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while !converged && iterations < max_iterations {
+        iterations += 1;
+        let jtj = jacobian.transpose() * &jacobian;
+        let gradient = jacobian.transpose() * &residuals;
+        // Marquardt's scaling makes the damping independent of the parameters' units
+        let damping = DMatrix::from_diagonal(&jtj.diagonal().map(|d| d.max(f64::EPSILON)));
+        let step = (&jtj + damping * lambda)
+            .lu()
+            .solve(&-gradient)
+            .unwrap_or_else(|| DVector::from_element(x.len(), f64::NAN));
+
+        let x_new = &x + &step;
+        let residuals_new = f(&x_new)?;
+        let cost_new = residuals_new.norm_squared();
+        if cost_new <= cost {
+            converged = step.norm() <= tolerance * (x.norm() + tolerance)
+                || cost - cost_new <= tolerance * cost;
+            x = x_new;
+            residuals = residuals_new;
+            cost = cost_new;
+            jacobian = numerical_jacobian(&f, &x)?;
+            lambda = (lambda / 10.0).max(1e-12);
+        } else {
+            lambda *= 10.0;
+            // The step is a vanishing gradient step, so x is a minimum to numerical precision
+            converged = lambda > 1e12;
+        }
+    }
+
+    let (m, n) = jacobian.shape();
+    let variance = if m > n {
+        cost / (m - n) as f64
+    } else {
+        f64::NAN
+    };
+    let covariance = (jacobian.transpose() * &jacobian)
+        .try_inverse()
+        .map_or_else(|| DMatrix::from_element(n, n, f64::NAN), |c| c * variance);
+    Ok(LeastSquaresSolution {
+        x,
+        residuals,
+        covariance,
+        iterations,
+        converged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aero_mod::AeroTable;
+    use crate::ode::FixedTimeStep;
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+
+    pub(super) fn make_rocket_properties() -> RocketProperties {
+        RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2)
+    }
+
+    pub(super) fn make_problem(
+        rocket_properties: RocketProperties,
+        initial_condition: InitialCondition,
+        parameters: Vec<FitParameter>,
+    ) -> FitProblem {
+        FitProblem {
+            rocket_properties,
+            initial_condition,
+            ode_solver: OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            parameters,
+            data: FlightData {
+                time: vec![0.0, 1.0],
+                altitude: vec![0.0, 0.0],
+                acceleration: None,
+                altitude_std: 1.0,
+                acceleration_std: 1.0,
+            },
+            max_iterations: 100_000,
+        }
+    }
+
+    /// Replaces the data with the simulated flight of the given rocket, sampled at 5 Hz.
+    pub(super) fn with_truth(mut problem: FitProblem, truth: RocketProperties) -> FitProblem {
+        let altitude_index = match problem.initial_condition {
+            InitialCondition::OneDOF(_) => 0,
+            InitialCondition::ThreeDOF(_) => 1,
+        };
+        let mut log = SimulationData::new();
+        Simulation::new(
+            problem.initial_condition.to_state(truth.clone()),
+            problem.ode_solver.clone(),
+            SimulationExitCondition::ApogeeReached,
+            100_000,
+        )
+        .run(&mut log, false, true);
+
+        let time: Vec<f64> = log.time_log.iter().step_by(20).copied().collect();
+        let rows: Vec<Vec<f64>> = time.iter().map(|t| interpolate_row(&log, *t)).collect();
+        problem.data.altitude = rows.iter().map(|row| row[altitude_index]).collect();
+        problem.data.acceleration = Some(
+            rows.iter()
+                .map(|row| {
+                    let initial_condition = problem.initial_condition.with_row(row);
+                    initial_condition
+                        .to_state(truth.clone())
+                        .get_axial_acceleration()
+                })
+                .collect(),
+        );
+        problem.data.time = time;
+        problem
+    }
+
+    #[test]
+    fn levenberg_marquardt_fits_exponential() {
+        let t: Vec<f64> = (0..20).map(|i| i as f64 * 0.25).collect();
+        let f = |x: &DVector<f64>| {
+            Ok(DVector::from_iterator(
+                t.len(),
+                t.iter()
+                    .map(|t| x[0] * (-x[1] * t).exp() - 3.0 * (-0.7 * t).exp()),
+            ))
+        };
+        let solution =
+            levenberg_marquardt(f, DVector::from_column_slice(&[1.0, 2.0]), 100, 1e-12).unwrap();
+
+        assert!(solution.converged);
+        assert_abs_diff_eq!(solution.x[0], 3.0, epsilon = 1e-6);
+        assert_abs_diff_eq!(solution.x[1], 0.7, epsilon = 1e-6);
+        assert!(solution.residuals.norm() < 1e-6);
+    }
+
+    #[test]
+    fn fits_cd_to_1dof_altitude() {
+        let initial_condition = InitialCondition::OneDOF(InitialState1DOF::new(0.0, 150.0));
+        let mut truth = make_rocket_properties();
+        truth.cd = 0.6;
+        let mut problem = with_truth(
+            make_problem(
+                make_rocket_properties(),
+                initial_condition,
+                vec![FitParameter::Cd],
+            ),
+            truth,
+        );
+        problem.data.acceleration = None;
+        let result = problem.solve(50, 1e-10).unwrap();
+
+        assert!(result.converged);
+        assert_abs_diff_eq!(result.values[0], 0.6, epsilon = 1e-6);
+        assert_abs_diff_eq!(result.rocket.rocket_properties.cd, 0.6, epsilon = 1e-6);
+        assert!(result.cost < 1e-6);
+        assert_eq!(result.residuals.len(), problem.data.time.len());
+    }
+
+    #[test]
+    fn fits_drag_curve_knots() {
+        let make_table = |knots: [f64; 3]| {
+            AeroTable::from_vecs(
+                vec![0.0, 0.25, 0.5],
+                vec![0.0, 0.1],
+                vec![knots[0], 0.6, knots[1], 0.7, knots[2], 0.8],
+                vec![0.0, 0.3, 0.0, 0.3, 0.0, 0.3],
+                vec![1.0; 6],
+            )
+            .unwrap()
+        };
+        let mut truth = make_rocket_properties();
+        truth.aero_table = Some(make_table([0.45, 0.5, 0.65]));
+        let mut guess = make_rocket_properties();
+        guess.aero_table = Some(make_table([0.5, 0.5, 0.5]));
+
+        let initial_condition = InitialCondition::OneDOF(InitialState1DOF::new(0.0, 170.0));
+        let problem = with_truth(
+            make_problem(guess, initial_condition, vec![FitParameter::CdMachKnots]),
+            truth,
+        );
+        let result = problem.solve(50, 1e-10).unwrap();
+
+        assert_eq!(result.parameters, vec![FitParameter::CdMachKnots; 3]);
+        for (value, expected) in result.values.iter().zip([0.45, 0.5, 0.65]) {
+            assert_abs_diff_eq!(*value, expected, epsilon = 1e-4);
+        }
+        // The rest of each Mach row keeps its increase with angle of attack
+        let table = result.rocket.rocket_properties.aero_table.unwrap();
+        assert_abs_diff_eq!(table.lookup(0.5, 0.1).0, 0.8 + 0.15, epsilon = 1e-4);
+        assert_eq!(result.covariance.shape(), (3, 3));
+    }
+
+    #[test]
+    fn fits_lift_and_drag_to_3dof_flight() {
+        let initial_condition =
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.1, 0.0, 150.0, 0.0));
+        let mut truth = make_rocket_properties();
+        truth.cd = 0.55;
+        truth.cl_a = 0.3;
+        let problem = with_truth(
+            make_problem(
+                make_rocket_properties(),
+                initial_condition,
+                vec![FitParameter::Cd, FitParameter::ClA],
+            ),
+            truth,
+        );
+        let result = problem.solve(50, 1e-10).unwrap();
+
+        assert_abs_diff_eq!(result.values[0], 0.55, epsilon = 1e-4);
+        assert_abs_diff_eq!(result.values[1], 0.3, epsilon = 1e-3);
+        assert_eq!(result.residuals.len(), 2 * problem.data.time.len());
+    }
+
+    #[test]
+    fn invalid_problems_are_rejected() {
+        let one_dof = InitialCondition::OneDOF(InitialState1DOF::new(0.0, 150.0));
+        let solve = |parameters| {
+            make_problem(make_rocket_properties(), one_dof, parameters).solve(10, 1e-8)
+        };
+        assert!(solve(vec![]).is_err());
+        assert!(solve(vec![FitParameter::Cd, FitParameter::Cd]).is_err());
+        assert!(solve(vec![FitParameter::ClA]).is_err());
+        assert!(solve(vec![FitParameter::CdMachKnots]).is_err());
+
+        let mut problem = make_problem(make_rocket_properties(), one_dof, vec![FitParameter::Cd]);
+        problem.data.time = vec![1.0, 0.0];
+        assert!(problem.solve(10, 1e-8).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use super::{FitParameter, FitProblem};
+use crate::constants::solver_constants::{DEFAULT_FIT_ITERATIONS, DEFAULT_FIT_TOLERANCE};
+use crate::filter_mod::{matrix_to_pyarray, numerical_jacobian, ApogeeDistribution};
+use crate::random_mod::{Distribution, Rng};
+use crate::simulation;
+use nalgebra::{DMatrix, DVector};
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::prelude::*;
+
+/// Number of burn-in steps between adaptations of the proposal
+const ADAPTATION_INTERVAL: usize = 100;
+
+/// Samples from the posterior distribution of fitted rocket properties.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct PosteriorSamples {
+    /// The parameter behind each column of `samples`. `CdMachKnots` is repeated once per Mach
+    /// breakpoint.
+    #[pyo3(get)]
+    pub parameters: Vec<FitParameter>,
+    /// Row per sample
+    samples: DMatrix<f64>,
+    log_posterior: Vec<f64>,
+    /// Fraction of the proposals after burn-in which were accepted
+    #[pyo3(get)]
+    pub acceptance_rate: f64,
+    /// Posterior predictive distribution of the apogee of the recorded flight
+    #[pyo3(get)]
+    pub apogee: ApogeeDistribution,
+}
+
+#[pymethods]
+impl PosteriorSamples {
+    /// Samples of the parameters, with a row per sample.
+    #[getter]
+    pub fn samples<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.samples)
+    }
+
+    /// Log posterior density of each sample, up to a constant.
+    #[getter]
+    pub fn log_posterior<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.log_posterior.to_pyarray(py)
+    }
+
+    /// Posterior mean of each parameter.
+    #[getter]
+    pub fn mean<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.samples.row_mean().as_slice().to_pyarray(py)
+    }
+
+    /// Posterior standard deviation of each parameter.
+    #[getter]
+    pub fn std<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.samples
+            .row_variance()
+            .map(f64::sqrt)
+            .as_slice()
+            .to_pyarray(py)
+    }
+}
+
+/// Settings of the posterior sampler.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SamplerOptions {
+    pub(crate) num_samples: usize,
+    pub(crate) burn_in: usize,
+    pub(crate) seed: u64,
+    /// Number of posterior samples the apogee is predicted for
+    pub(crate) predictive_samples: usize,
+}
+
+impl FitProblem {
+    /// Samples the posterior of the parameters with an adaptive Metropolis sampler, given a
+    /// prior for each parameter and a Gaussian likelihood of the residuals. The chain starts
+    /// from the least squares fit, and the proposal is adapted to the chain's covariance during
+    /// burn-in only, so the kept samples come from a fixed Markov chain.
+    pub(crate) fn sample_posterior(
+        &self,
+        priors: &[Distribution],
+        options: SamplerOptions,
+    ) -> Result<PosteriorSamples, String> {
+        self.validate()?;
+        if priors.len() != self.parameters.len() {
+            return Err("There must be one prior per parameter".to_string());
+        }
+        if options.num_samples == 0 || options.predictive_samples == 0 {
+            return Err("num_samples and predictive_samples must be positive".to_string());
+        }
+        // Each prior applies to every value its parameter expands to
+        let priors: Vec<Distribution> = self
+            .parameters
+            .iter()
+            .zip(priors)
+            .flat_map(|(parameter, prior)| std::iter::repeat_n(*prior, self.width(*parameter)))
+            .collect();
+        let log_posterior = |x: &DVector<f64>| {
+            let log_prior: f64 = priors
+                .iter()
+                .zip(x.iter())
+                .map(|(prior, x)| prior.log_density(*x))
+                .sum();
+            if log_prior == f64::NEG_INFINITY {
+                return log_prior;
+            }
+            match self.residuals(x) {
+                Ok(residuals) if residuals.iter().all(|r| r.is_finite()) => {
+                    log_prior - 0.5 * residuals.norm_squared()
+                }
+                _ => f64::NEG_INFINITY,
+            }
+        };
+
+        let (parameters, initial_values) = self.initial_values();
+        let n = initial_values.len();
+        let fitted = self
+            .solve(DEFAULT_FIT_ITERATIONS, DEFAULT_FIT_TOLERANCE)
+            .map(|fit| DVector::from_vec(fit.values))
+            .ok()
+            .filter(|x| log_posterior(x).is_finite());
+        let mut x = fitted.clone().unwrap_or(initial_values);
+        let mut log_p = log_posterior(&x);
+        if !log_p.is_finite() {
+            return Err("The posterior density is zero at the initial parameters".to_string());
+        }
+
+        // The Laplace approximation of the posterior about the fit is the initial proposal, or
+        // a proposal scaled to the priors if there is no usable fit
+        let prior_precision =
+            DVector::from_iterator(n, priors.iter().map(|prior| prior.std().powi(-2)));
+        let laplace = fitted.and_then(|x| {
+            let jacobian = numerical_jacobian(|x| self.residuals(x), &x).ok()?;
+            let hessian =
+                jacobian.transpose() * jacobian + DMatrix::from_diagonal(&prior_precision);
+            (hessian.try_inverse()? * scale(n)).cholesky()
+        });
+        let mut cholesky = match laplace {
+            Some(cholesky) => cholesky.unpack(),
+            None => DMatrix::from_diagonal(&prior_precision.map(|p| 0.01 / p.sqrt())),
+        };
+
+        let mut rng = Rng::new(options.seed);
+        let total = options.burn_in + options.num_samples;
+        let mut chain = Vec::with_capacity(total);
+        let mut log_posteriors = Vec::with_capacity(options.num_samples);
+        let mut accepted = 0;
+        for i in 0..total {
+            let candidate = &x + &cholesky * rng.normal_vector(n);
+            let log_p_candidate = log_posterior(&candidate);
+            let is_accepted = log_p_candidate - log_p > rng.uniform().ln();
+            if is_accepted {
+                x = candidate;
+                log_p = log_p_candidate;
+            }
+            chain.push(x.clone());
+
+            if i >= options.burn_in {
+                accepted += usize::from(is_accepted);
+                log_posteriors.push(log_p);
+            } else if (i + 1) % ADAPTATION_INTERVAL == 0 && i + 1 > 2 * n {
+                // The jitter keeps the proposal positive definite while the chain is stuck
+                let jitter = DMatrix::from_diagonal(&x.map(|x| (1e-10 * x.abs()).powi(2) + 1e-20));
+                if let Some(c) = ((covariance(&chain) + jitter) * scale(n)).cholesky() {
+                    cholesky = c.unpack();
+                }
+            }
+        }
+
+        let samples = &chain[options.burn_in..];
+        let stride = (samples.len() as f64 / options.predictive_samples as f64).max(1.0);
+        let apogees: Vec<f64> = (0..options.predictive_samples.min(samples.len()))
+            .map(|i| {
+                let properties = self.apply(&samples[(i as f64 * stride) as usize]);
+                simulation::apogee(
+                    self.initial_condition.to_state(properties),
+                    self.ode_solver.clone(),
+                    self.max_iterations,
+                )
+            })
+            .collect();
+        let weights = vec![1.0 / apogees.len() as f64; apogees.len()];
+
+        Ok(PosteriorSamples {
+            parameters,
+            samples: DMatrix::from_fn(samples.len(), n, |i, j| samples[i][j]),
+            log_posterior: log_posteriors,
+            acceptance_rate: accepted as f64 / options.num_samples as f64,
+            apogee: ApogeeDistribution::from_samples(apogees, weights.clone(), &weights),
+        })
+    }
+}
+
+/// Optimal scaling of a Gaussian random walk proposal in `n` dimensions.
+fn scale(n: usize) -> f64 {
+    2.38_f64.powi(2) / n as f64
+}
+
+/// Sample covariance of the points.
+fn covariance(points: &[DVector<f64>]) -> DMatrix<f64> {
+    let count = points.len() as f64;
+    let mean = points
+        .iter()
+        .fold(DVector::zeros(points[0].len()), |sum, x| sum + x)
+        / count;
+    points
+        .iter()
+        .fold(DMatrix::zeros(mean.len(), mean.len()), |sum, x| {
+            sum + (x - &mean) * (x - &mean).transpose()
+        })
+        / (count - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fitting_mod::tests::{make_problem, make_rocket_properties, with_truth};
+    use crate::fitting_mod::FlightData;
+    use crate::ode::{FixedTimeStep, OdeSolver};
+    use crate::state::{InitialCondition, InitialState1DOF};
+    use approx::assert_abs_diff_eq;
+
+    fn options(num_samples: usize) -> SamplerOptions {
+        SamplerOptions {
+            num_samples,
+            burn_in: 300,
+            seed: 1,
+            predictive_samples: 20,
+        }
+    }
+
+    fn make_cd_problem() -> FitProblem {
+        let initial_condition = InitialCondition::OneDOF(InitialState1DOF::new(0.0, 150.0));
+        let mut truth = make_rocket_properties();
+        truth.cd = 0.6;
+        let mut problem = make_problem(
+            make_rocket_properties(),
+            initial_condition,
+            vec![FitParameter::Cd],
+        );
+        // A coarse timestep keeps the thousands of simulations quick
+        problem.ode_solver = OdeSolver::RK3(FixedTimeStep::new(0.05));
+        let mut problem = with_truth(problem, truth);
+        problem.data = FlightData {
+            acceleration: None,
+            altitude_std: 2.0,
+            ..problem.data
+        };
+        problem
+    }
+
+    #[test]
+    fn posterior_is_centred_on_the_truth() {
+        let problem = make_cd_problem();
+        let prior = Distribution::new_uniform(0.0, 2.0).unwrap();
+        let posterior = problem.sample_posterior(&[prior], options(1000)).unwrap();
+
+        assert_eq!(posterior.samples.shape(), (1000, 1));
+        assert_abs_diff_eq!(posterior.samples.mean(), 0.6, epsilon = 0.005);
+        assert!(posterior.samples.variance() > 0.0);
+        assert!((0.2..0.8).contains(&posterior.acceptance_rate));
+
+        // The apogee is predicted for the flight the data was recorded from
+        let mut truth = make_rocket_properties();
+        truth.cd = 0.6;
+        let apogee = simulation::apogee(
+            problem.initial_condition.to_state(truth),
+            problem.ode_solver.clone(),
+            100_000,
+        );
+        assert_abs_diff_eq!(posterior.apogee.mean, apogee, epsilon = 1.0);
+        assert_eq!(posterior.apogee.samples.len(), 20);
+        assert!(posterior.apogee.std > 0.0);
+    }
+
+    #[test]
+    fn prior_dominates_without_informative_data() {
+        let mut problem = make_cd_problem();
+        // Two altitude samples a moment apart barely constrain the drag
+        problem.data.time.truncate(2);
+        problem.data.altitude.truncate(2);
+        problem.data.altitude_std = 1000.0;
+        let prior = Distribution::new_normal(0.5, 0.05).unwrap();
+        let posterior = problem.sample_posterior(&[prior], options(3000)).unwrap();
+
+        assert_abs_diff_eq!(posterior.samples.mean(), 0.5, epsilon = 0.01);
+        assert_abs_diff_eq!(posterior.samples.variance().sqrt(), 0.05, epsilon = 0.01);
+    }
+
+    #[test]
+    fn invalid_priors_are_rejected() {
+        let problem = make_cd_problem();
+        assert!(problem.sample_posterior(&[], options(10)).is_err());
+        // The data lies outside the prior's support
+        let prior = Distribution::new_uniform(1.0, 2.0).unwrap();
+        assert!(problem.sample_posterior(&[prior], options(10)).is_err());
+    }
+}
@@ -0,0 +1,760 @@
+use crate::aero_mod::SkinFriction;
+use crate::constants::aero_constants::{
+    DEFAULT_ESTIMATE_VELOCITY_M_S, DEFAULT_SURFACE_ROUGHNESS_M, NOSE_PROFILE_SEGMENTS,
+};
+use crate::physics_mod;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::f64::consts::PI;
+
+/// Profile shapes supported for the nose cone.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NoseShape {
+    /// Straight cone.
+    Conical,
+    /// Tangent ogive.
+    Ogive,
+    /// Paraboloid, i.e. the 1/2 power series.
+    Parabolic,
+    /// Half ellipse.
+    Elliptical,
+    /// LD-Haack (Von Karman) series.
+    VonKarman,
+}
+
+impl NoseShape {
+    /// Radius of the nose profile at the fractional station `xi` (0 at the tip, 1 at the base),
+    /// for a nose with base radius `radius` and length `length`.
+    fn radius_at(&self, xi: f64, radius: f64, length: f64) -> f64 {
+        match self {
+            NoseShape::Conical => radius * xi,
+            NoseShape::Ogive => {
+                let rho = (radius.powi(2) + length.powi(2)) / (2.0 * radius);
+                let x = xi * length;
+                (rho.powi(2) - (length - x).powi(2)).max(0.0).sqrt() + radius - rho
+            }
+            NoseShape::Parabolic => radius * xi.sqrt(),
+            NoseShape::Elliptical => radius * (1.0 - (1.0 - xi).powi(2)).max(0.0).sqrt(),
+            NoseShape::VonKarman => {
+                let theta = (1.0 - 2.0 * xi).clamp(-1.0, 1.0).acos();
+                radius / PI.sqrt() * (theta - (2.0 * theta).sin() / 2.0).max(0.0).sqrt()
+            }
+        }
+    }
+}
+
+/// Nose cone of the rocket. The nose base diameter is used as the reference diameter.
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Copy, Debug)]
+pub struct NoseCone {
+    pub shape: NoseShape,
+    /// Length of the nose cone (m)
+    pub length: f64,
+    /// Base diameter of the nose cone (m)
+    pub diameter: f64,
+}
+
+#[pymethods]
+impl NoseCone {
+    #[new]
+    pub fn new(shape: NoseShape, length: f64, diameter: f64) -> Self {
+        Self {
+            shape,
+            length,
+            diameter,
+        }
+    }
+}
+
+impl NoseCone {
+    /// Integrates the nose profile as a stack of conical frustums, returning the
+    /// (volume, wetted area) of the nose.
+    fn volume_and_wetted_area(&self) -> (f64, f64) {
+        let radius = self.diameter / 2.0;
+        let dx = self.length / NOSE_PROFILE_SEGMENTS as f64;
+
+        let mut volume = 0.0;
+        let mut wetted_area = 0.0;
+        let mut r0 = 0.0;
+        for i in 1..=NOSE_PROFILE_SEGMENTS {
+            let xi = i as f64 / NOSE_PROFILE_SEGMENTS as f64;
+            let r1 = self.shape.radius_at(xi, radius, self.length);
+            volume += PI * dx * (r0 * r0 + r0 * r1 + r1 * r1) / 3.0;
+            wetted_area += PI * (r0 + r1) * (dx * dx + (r1 - r0).powi(2)).sqrt();
+            r0 = r1;
+        }
+        (volume, wetted_area)
+    }
+
+    /// Distance from the nose tip to the center of pressure of the nose. For a slender body this
+    /// is X = L - V / A_base, which gives the familiar 2/3 L for a cone and 0.466 L for an ogive.
+    fn cp_location(&self) -> f64 {
+        let (volume, _) = self.volume_and_wetted_area();
+        self.length - volume / circle_area(self.diameter)
+    }
+}
+
+/// Cylindrical section of the rocket body.
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Copy, Debug)]
+pub struct BodyTube {
+    /// Length of the tube (m)
+    pub length: f64,
+    /// Outer diameter of the tube (m)
+    pub diameter: f64,
+}
+
+#[pymethods]
+impl BodyTube {
+    #[new]
+    pub fn new(length: f64, diameter: f64) -> Self {
+        Self { length, diameter }
+    }
+}
+
+/// Conical change in body diameter, either a shoulder (growing) or a boattail (shrinking).
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Copy, Debug)]
+pub struct Transition {
+    /// Length of the transition (m)
+    pub length: f64,
+    /// Diameter at the front of the transition (m)
+    pub fore_diameter: f64,
+    /// Diameter at the back of the transition (m)
+    pub aft_diameter: f64,
+}
+
+#[pymethods]
+impl Transition {
+    #[new]
+    pub fn new(length: f64, fore_diameter: f64, aft_diameter: f64) -> Self {
+        Self {
+            length,
+            fore_diameter,
+            aft_diameter,
+        }
+    }
+}
+
+/// A section of the body behind the nose cone. The sections are stacked in the order given.
+#[derive(FromPyObject, IntoPyObject, Clone, Copy, Debug)]
+pub enum BodyComponent {
+    Tube(BodyTube),
+    Transition(Transition),
+}
+
+impl BodyComponent {
+    fn length(&self) -> f64 {
+        match self {
+            BodyComponent::Tube(tube) => tube.length,
+            BodyComponent::Transition(transition) => transition.length,
+        }
+    }
+
+    fn aft_diameter(&self) -> f64 {
+        match self {
+            BodyComponent::Tube(tube) => tube.diameter,
+            BodyComponent::Transition(transition) => transition.aft_diameter,
+        }
+    }
+
+    fn wetted_area(&self) -> f64 {
+        match self {
+            BodyComponent::Tube(tube) => PI * tube.diameter * tube.length,
+            BodyComponent::Transition(t) => {
+                let (r1, r2) = (t.fore_diameter / 2.0, t.aft_diameter / 2.0);
+                PI * (r1 + r2) * (t.length.powi(2) + (r2 - r1).powi(2)).sqrt()
+            }
+        }
+    }
+}
+
+/// A set of identical trapezoidal fins, evenly spaced around the body.
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Copy, Debug)]
+pub struct FinSet {
+    /// Number of fins
+    pub count: u32,
+    /// Root chord length (m)
+    pub root_chord: f64,
+    /// Tip chord length (m)
+    pub tip_chord: f64,
+    /// Semi-span, measured from the body surface to the fin tip (m)
+    pub span: f64,
+    /// Axial distance from the root leading edge to the tip leading edge (m)
+    pub sweep_length: f64,
+    /// Fin thickness (m)
+    pub thickness: f64,
+    /// Distance from the nose tip to the root leading edge (m)
+    pub position: f64,
+}
+
+#[pymethods]
+impl FinSet {
+    #[new]
+    pub fn new(
+        count: u32,
+        root_chord: f64,
+        tip_chord: f64,
+        span: f64,
+        sweep_length: f64,
+        thickness: f64,
+        position: f64,
+    ) -> Self {
+        Self {
+            count,
+            root_chord,
+            tip_chord,
+            span,
+            sweep_length,
+            thickness,
+            position,
+        }
+    }
+}
+
+impl FinSet {
+    /// Planform area of a single fin (m^2)
+    fn planform_area(&self) -> f64 {
+        0.5 * (self.root_chord + self.tip_chord) * self.span
+    }
+
+    /// Mean aerodynamic chord of a single fin (m)
+    fn mean_aerodynamic_chord(&self) -> f64 {
+        let (cr, ct) = (self.root_chord, self.tip_chord);
+        2.0 / 3.0 * (cr + ct - cr * ct / (cr + ct))
+    }
+}
+
+/// A launch lug or rail button modelled as a short tube in the free stream. Only adds drag.
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Copy, Debug)]
+pub struct LaunchLug {
+    /// Length of the lug (m)
+    pub length: f64,
+    /// Outer diameter of the lug (m)
+    pub outer_diameter: f64,
+    /// Inner diameter of the lug (m)
+    pub inner_diameter: f64,
+}
+
+#[pymethods]
+impl LaunchLug {
+    #[new]
+    pub fn new(length: f64, outer_diameter: f64, inner_diameter: f64) -> Self {
+        Self {
+            length,
+            outer_diameter,
+            inner_diameter,
+        }
+    }
+}
+
+/// The aerodynamic coefficients estimated from a `RocketGeometry`. All coefficients are referenced
+/// to `reference_area` and `reference_length` (the nose base area and diameter).
+#[pyclass(get_all)]
+#[derive(Clone, Copy, Debug)]
+pub struct AerodynamicEstimate {
+    /// Normal force coefficient slope (per radian)
+    pub cn_alpha: f64,
+    /// Distance from the nose tip to the center of pressure (m)
+    pub cp_location: f64,
+    /// Distance from the nose tip to the center of gravity used for the estimate (m)
+    pub cg_location: f64,
+    /// Static stability margin, CP - CG (m)
+    pub stability_margin: f64,
+    /// Static stability margin in body calibers
+    pub stability_calibers: f64,
+    /// Pitch damping moment coefficient derivative (per radian)
+    pub cmq: f64,
+    /// Total zero angle of attack drag coefficient
+    pub cd: f64,
+    /// Skin friction part of the drag coefficient
+    pub cd_friction: f64,
+    /// Forebody, fin and launch lug pressure part of the drag coefficient
+    pub cd_pressure: f64,
+    /// Base drag part of the drag coefficient
+    pub cd_base: f64,
+    /// Fin-body interference part of the drag coefficient
+    pub cd_interference: f64,
+    /// Reference area (m^2)
+    pub reference_area: f64,
+    /// Reference length (m)
+    pub reference_length: f64,
+}
+
+/// Geometric description of a rocket, used to estimate its aerodynamic coefficients with the
+/// Barrowman method and a component buildup of the drag.
+#[pyclass(get_all, set_all)]
+#[derive(Clone, Debug)]
+pub struct RocketGeometry {
+    pub nose_cone: NoseCone,
+    /// Body tubes and transitions behind the nose cone, from front to back
+    pub body: Vec<BodyComponent>,
+    pub fins: Option<FinSet>,
+    pub launch_lugs: Vec<LaunchLug>,
+    /// Surface roughness height (m)
+    pub surface_roughness: f64,
+}
+
+#[pymethods]
+impl RocketGeometry {
+    #[new]
+    #[pyo3(signature = (nose_cone, body, fins=None, launch_lugs=Vec::new(), surface_roughness=DEFAULT_SURFACE_ROUGHNESS_M))]
+    pub fn new(
+        nose_cone: NoseCone,
+        body: Vec<BodyComponent>,
+        fins: Option<FinSet>,
+        launch_lugs: Vec<LaunchLug>,
+        surface_roughness: f64,
+    ) -> Self {
+        Self {
+            nose_cone,
+            body,
+            fins,
+            launch_lugs,
+            surface_roughness,
+        }
+    }
+
+    /// Total length of the rocket from the nose tip to the aft end of the body (m).
+    pub fn length(&self) -> f64 {
+        self.nose_cone.length + self.body.iter().map(|c| c.length()).sum::<f64>()
+    }
+
+    /// Reference diameter used for all coefficients (m).
+    pub fn reference_diameter(&self) -> f64 {
+        self.nose_cone.diameter
+    }
+
+    /// Reference area used for all coefficients (m^2).
+    pub fn reference_area(&self) -> f64 {
+        circle_area(self.reference_diameter())
+    }
+
+    /// Reynolds number dependent skin friction model for this geometry, matching the friction
+    /// and interference parts of `estimate`.
+    pub fn skin_friction(&self) -> SkinFriction {
+        let (friction_area, interference_area) = self.friction_areas();
+        SkinFriction::new(
+            friction_area + interference_area,
+            self.length(),
+            self.surface_roughness,
+            0.0,
+        )
+    }
+
+    /// Estimates the aerodynamic coefficients of the rocket for the given center of gravity
+    /// location (measured from the nose tip). The drag is evaluated at the given airspeed at sea
+    /// level.
+    #[pyo3(signature = (cg_location, velocity=DEFAULT_ESTIMATE_VELOCITY_M_S))]
+    pub fn estimate(&self, cg_location: f64, velocity: f64) -> PyResult<AerodynamicEstimate> {
+        self.validate().map_err(PyValueError::new_err)?;
+        Ok(self.estimate_unchecked(cg_location, velocity))
+    }
+}
+
+impl RocketGeometry {
+    /// Checks that the geometry is physically meaningful, returning a description of the
+    /// first problem found.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let mut dimensions = vec![self.nose_cone.length, self.nose_cone.diameter];
+        for component in &self.body {
+            dimensions.push(component.length());
+            dimensions.push(component.aft_diameter());
+        }
+        if let Some(fins) = &self.fins {
+            if fins.count == 0 {
+                return Err("Fin set must have at least one fin".to_string());
+            }
+            dimensions.extend([fins.root_chord, fins.span]);
+        }
+        if dimensions.iter().any(|d| !d.is_finite() || *d <= 0.0) {
+            return Err("Rocket geometry lengths and diameters must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Radius of the body at the given distance from the nose tip (m).
+    fn body_radius_at(&self, x: f64) -> f64 {
+        let mut x_front = self.nose_cone.length;
+        let mut radius = self.nose_cone.diameter / 2.0;
+        for component in &self.body {
+            let x_back = x_front + component.length();
+            if x <= x_back {
+                return match component {
+                    BodyComponent::Tube(tube) => tube.diameter / 2.0,
+                    BodyComponent::Transition(t) => {
+                        let frac = (x - x_front) / t.length;
+                        0.5 * (t.fore_diameter + frac * (t.aft_diameter - t.fore_diameter))
+                    }
+                };
+            }
+            radius = component.aft_diameter() / 2.0;
+            x_front = x_back;
+        }
+        radius
+    }
+
+    /// Barrowman normal force slopes and center of pressure locations of every lifting component,
+    /// as (CN_alpha, X_cp) pairs.
+    fn barrowman_components(&self) -> Vec<(f64, f64)> {
+        let d_ref = self.reference_diameter();
+        let mut components = Vec::with_capacity(self.body.len() + 2);
+
+        // Nose cone
+        components.push((2.0, self.nose_cone.cp_location()));
+
+        // Transitions
+        let mut x_front = self.nose_cone.length;
+        for component in &self.body {
+            if let BodyComponent::Transition(t) = component {
+                let (d_f, d_a) = (t.fore_diameter, t.aft_diameter);
+                let cn_alpha = 2.0 * ((d_a / d_ref).powi(2) - (d_f / d_ref).powi(2));
+                let ratio = d_f / d_a;
+                let x_cp = if (1.0 - ratio).abs() < 1e-9 {
+                    x_front + t.length / 2.0
+                } else {
+                    x_front + t.length / 3.0 * (1.0 + (1.0 - ratio) / (1.0 - ratio.powi(2)))
+                };
+                components.push((cn_alpha, x_cp));
+            }
+            x_front += component.length();
+        }
+
+        // Fins, including the body interference factor
+        if let Some(fins) = &self.fins {
+            let (cr, ct, s, sweep) = (
+                fins.root_chord,
+                fins.tip_chord,
+                fins.span,
+                fins.sweep_length,
+            );
+            let radius = self.body_radius_at(fins.position + cr / 2.0);
+            let mid_chord_length = (s.powi(2) + (sweep + ct / 2.0 - cr / 2.0).powi(2)).sqrt();
+            let interference = 1.0 + radius / (s + radius);
+            let cn_alpha = interference * 4.0 * fins.count as f64 * (s / d_ref).powi(2)
+                / (1.0 + (1.0 + (2.0 * mid_chord_length / (cr + ct)).powi(2)).sqrt());
+            let x_cp = fins.position
+                + sweep / 3.0 * (cr + 2.0 * ct) / (cr + ct)
+                + ((cr + ct) - cr * ct / (cr + ct)) / 6.0;
+            components.push((cn_alpha, x_cp));
+        }
+
+        components
+    }
+
+    /// Wetted areas of the rocket, weighted by their form factors, as (friction area,
+    /// fin-body interference area). Multiplying by the skin friction coefficient and dividing by
+    /// the reference area gives the friction and interference drag coefficients.
+    fn friction_areas(&self) -> (f64, f64) {
+        // Body, with a form factor for the body fineness ratio
+        let (_, nose_wetted_area) = self.nose_cone.volume_and_wetted_area();
+        let body_wetted_area =
+            nose_wetted_area + self.body.iter().map(|c| c.wetted_area()).sum::<f64>();
+        let fineness = self.length() / self.reference_diameter();
+        let mut friction_area = (1.0 + 1.0 / (2.0 * fineness)) * body_wetted_area;
+
+        // Fins, with a form factor for their thickness. Barrowman's interference drag comes from
+        // the part of the fin planform that would extend inside the body.
+        let mut interference_area = 0.0;
+        if let Some(fins) = &self.fins {
+            let n = fins.count as f64;
+            let thickness_factor = 1.0 + 2.0 * fins.thickness / fins.mean_aerodynamic_chord();
+            friction_area += thickness_factor * 2.0 * n * fins.planform_area();
+
+            let body_diameter = 2.0 * self.body_radius_at(fins.position + fins.root_chord / 2.0);
+            interference_area = thickness_factor * n * body_diameter * fins.root_chord;
+        }
+
+        // Launch lugs
+        for lug in &self.launch_lugs {
+            friction_area += PI * lug.outer_diameter * lug.length;
+        }
+
+        (friction_area, interference_area)
+    }
+
+    /// Estimates the coefficients without validating the geometry first.
+    pub(crate) fn estimate_unchecked(
+        &self,
+        cg_location: f64,
+        velocity: f64,
+    ) -> AerodynamicEstimate {
+        let d_ref = self.reference_diameter();
+        let a_ref = self.reference_area();
+
+        // ========== Normal force and center of pressure (Barrowman)
+        let components = self.barrowman_components();
+        let cn_alpha: f64 = components.iter().map(|(cn, _)| cn).sum();
+        let cp_location = components.iter().map(|(cn, x)| cn * x).sum::<f64>() / cn_alpha;
+        let stability_margin = cp_location - cg_location;
+
+        // Damping: every component's normal force acts on its own arm about the CG. Matching
+        // 1/2 rho V A sum(CN_i (X_i - X_cg)^2) q to the Cmq definition gives the sum below.
+        let cmq = -2.0
+            * components
+                .iter()
+                .map(|(cn, x)| cn * (x - cg_location).powi(2))
+                .sum::<f64>()
+            / d_ref.powi(2);
+
+        // ========== Drag component buildup
+        let mach = velocity.abs() / physics_mod::speed_of_sound();
+        let length = self.length();
+        let reynolds = physics_mod::calc_reynolds_number(velocity, length, 0.0);
+        // Fully turbulent boundary layer, with a subsonic compressibility correction
+        let cf =
+            physics_mod::skin_friction_coefficient(reynolds, self.surface_roughness / length, 0.0)
+                * (1.0 - 0.1 * mach.powi(2));
+        let base_pressure = 0.12 + 0.13 * mach.powi(2);
+        // Stagnation pressure drag of a blunt face, per unit frontal area
+        let stagnation_pressure = 0.85;
+
+        // Skin friction over the form factor weighted wetted areas
+        let (friction_area, interference_area) = self.friction_areas();
+        let cd_friction = cf * friction_area / a_ref;
+        let cd_interference = cf * interference_area / a_ref;
+
+        // Nose cone pressure drag, only significant for a blunt cone at subsonic speeds
+        let mut cd_pressure = match self.nose_cone.shape {
+            NoseShape::Conical => {
+                let half_angle = (self.nose_cone.diameter / 2.0 / self.nose_cone.length).atan();
+                0.8 * half_angle.sin().powi(2) * circle_area(self.nose_cone.diameter) / a_ref
+            }
+            _ => 0.0,
+        };
+
+        // Transition pressure drag. Shoulders are treated like a conical nose of the same half
+        // angle, while boattails recover part of the base drag depending on their steepness.
+        for component in &self.body {
+            if let BodyComponent::Transition(t) = component {
+                let area_change = circle_area(t.aft_diameter) - circle_area(t.fore_diameter);
+                let half_angle = ((t.aft_diameter - t.fore_diameter).abs() / 2.0 / t.length).atan();
+                if area_change > 0.0 {
+                    cd_pressure += 0.8 * half_angle.sin().powi(2) * area_change / a_ref;
+                } else if area_change < 0.0 {
+                    let gamma = t.length / (t.fore_diameter - t.aft_diameter);
+                    let factor = if gamma < 1.0 {
+                        1.0
+                    } else if gamma < 3.0 {
+                        (3.0 - gamma) / 2.0
+                    } else {
+                        0.0
+                    };
+                    cd_pressure += base_pressure * factor * -area_change / a_ref;
+                }
+            }
+        }
+
+        // Base drag of the aft end of the body
+        let aft_diameter = self
+            .body
+            .last()
+            .map(|c| c.aft_diameter())
+            .unwrap_or(self.nose_cone.diameter);
+        let mut cd_base = base_pressure * circle_area(aft_diameter) / a_ref;
+
+        // Fins, assumed to have square leading and trailing edges
+        if let Some(fins) = &self.fins {
+            let frontal_area = fins.count as f64 * fins.thickness * fins.span;
+            cd_pressure += stagnation_pressure * frontal_area / a_ref;
+            cd_base += base_pressure * frontal_area / a_ref;
+        }
+
+        // Launch lugs
+        for lug in &self.launch_lugs {
+            let frontal_area = circle_area(lug.outer_diameter) - circle_area(lug.inner_diameter);
+            cd_pressure += (stagnation_pressure + base_pressure) * frontal_area / a_ref;
+        }
+
+        AerodynamicEstimate {
+            cn_alpha,
+            cp_location,
+            cg_location,
+            stability_margin,
+            stability_calibers: stability_margin / d_ref,
+            cmq,
+            cd: cd_friction + cd_pressure + cd_base + cd_interference,
+            cd_friction,
+            cd_pressure,
+            cd_base,
+            cd_interference,
+            reference_area: a_ref,
+            reference_length: d_ref,
+        }
+    }
+}
+
+fn circle_area(diameter: f64) -> f64 {
+    0.25 * PI * diameter.powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::{assert_abs_diff_eq, assert_relative_eq};
+
+    /// Roughly a 6 inch, 2.5 m long high power rocket with a small boattail.
+    fn make_geometry() -> RocketGeometry {
+        RocketGeometry::new(
+            NoseCone::new(NoseShape::Ogive, 0.75, 0.155),
+            vec![
+                BodyComponent::Tube(BodyTube::new(1.6, 0.155)),
+                BodyComponent::Transition(Transition::new(0.1, 0.155, 0.13)),
+            ],
+            Some(FinSet::new(4, 0.3, 0.1, 0.15, 0.2, 0.005, 2.05)),
+            vec![LaunchLug::new(0.05, 0.02, 0.015)],
+            DEFAULT_SURFACE_ROUGHNESS_M,
+        )
+    }
+
+    #[test]
+    fn nose_cone_cp_matches_classic_barrowman_values() {
+        let length = 0.6;
+        let cases = [
+            (NoseShape::Conical, 2.0 / 3.0),
+            (NoseShape::Ogive, 0.466),
+            (NoseShape::Parabolic, 0.5),
+            (NoseShape::Elliptical, 1.0 / 3.0),
+        ];
+        for (shape, fraction) in cases {
+            let nose = NoseCone::new(shape, length, 0.1);
+            assert_abs_diff_eq!(nose.cp_location(), fraction * length, epsilon = 0.005);
+        }
+    }
+
+    #[test]
+    fn nose_cone_wetted_area_of_a_cone_matches_closed_form() {
+        let nose = NoseCone::new(NoseShape::Conical, 0.5, 0.2);
+        let (volume, wetted_area) = nose.volume_and_wetted_area();
+        assert_relative_eq!(volume, PI * 0.1_f64.powi(2) * 0.5 / 3.0, epsilon = 1e-9);
+        assert_relative_eq!(
+            wetted_area,
+            PI * 0.1 * (0.1_f64.powi(2) + 0.5_f64.powi(2)).sqrt(),
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn nose_only_rocket_has_cn_alpha_of_two() {
+        let geometry = RocketGeometry::new(
+            NoseCone::new(NoseShape::Conical, 0.3, 0.1),
+            vec![BodyComponent::Tube(BodyTube::new(1.0, 0.1))],
+            None,
+            Vec::new(),
+            0.0,
+        );
+        let estimate = geometry.estimate_unchecked(0.5, 50.0);
+        assert_relative_eq!(estimate.cn_alpha, 2.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(estimate.cp_location, 0.2, epsilon = 1e-3);
+        // CP ahead of the CG means the rocket is unstable
+        assert!(estimate.stability_margin < 0.0);
+    }
+
+    #[test]
+    fn fin_normal_force_matches_hand_calculation() {
+        let geometry = make_geometry();
+        let components = geometry.barrowman_components();
+        let (cn_fins, x_fins) = components[2];
+
+        let (cr, ct, s, sweep, d, r): (f64, f64, f64, f64, f64, f64) =
+            (0.3, 0.1, 0.15, 0.2, 0.155, 0.0775);
+        let lf = (s * s + (sweep + ct / 2.0 - cr / 2.0) * (sweep + ct / 2.0 - cr / 2.0)).sqrt();
+        let expected_cn = (1.0 + r / (s + r)) * 4.0 * 4.0 * (s / d) * (s / d)
+            / (1.0 + (1.0 + (2.0 * lf / (cr + ct)) * (2.0 * lf / (cr + ct))).sqrt());
+        let expected_x = 2.05
+            + sweep / 3.0 * (cr + 2.0 * ct) / (cr + ct)
+            + ((cr + ct) - cr * ct / (cr + ct)) / 6.0;
+
+        assert_relative_eq!(cn_fins, expected_cn, epsilon = 1e-12);
+        assert_relative_eq!(x_fins, expected_x, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn boattail_has_negative_normal_force() {
+        let geometry = make_geometry();
+        let components = geometry.barrowman_components();
+        let (cn_boattail, x_boattail) = components[1];
+        assert!(cn_boattail < 0.0);
+        // CP lies within the transition
+        assert!(x_boattail > 2.35 && x_boattail < 2.45);
+    }
+
+    #[test]
+    fn estimate_is_stable_and_drag_is_plausible() {
+        let geometry = make_geometry();
+        let estimate = geometry.estimate_unchecked(1.5, 100.0);
+
+        assert!(estimate.cp_location > 1.5);
+        assert!(estimate.stability_calibers > 1.0 && estimate.stability_calibers < 6.0);
+        assert!(estimate.cmq < 0.0);
+
+        // Typical subsonic high power rockets sit between 0.3 and 0.7
+        assert!(
+            estimate.cd > 0.3 && estimate.cd < 0.7,
+            "cd = {}",
+            estimate.cd
+        );
+        assert_relative_eq!(
+            estimate.cd,
+            estimate.cd_friction
+                + estimate.cd_pressure
+                + estimate.cd_base
+                + estimate.cd_interference,
+            epsilon = 1e-12
+        );
+        for part in [
+            estimate.cd_friction,
+            estimate.cd_pressure,
+            estimate.cd_base,
+            estimate.cd_interference,
+        ] {
+            assert!(part > 0.0);
+        }
+    }
+
+    #[test]
+    fn moving_cg_forward_increases_margin_and_damping() {
+        let geometry = make_geometry();
+        let aft = geometry.estimate_unchecked(1.6, 100.0);
+        let forward = geometry.estimate_unchecked(1.3, 100.0);
+
+        assert_relative_eq!(aft.cp_location, forward.cp_location, epsilon = 1e-12);
+        assert_relative_eq!(
+            forward.stability_margin - aft.stability_margin,
+            0.3,
+            epsilon = 1e-12
+        );
+        assert!(forward.cmq < aft.cmq);
+    }
+
+    #[test]
+    fn estimate_rejects_invalid_geometry() {
+        let mut geometry = make_geometry();
+        geometry.nose_cone.length = 0.0;
+        assert!(geometry.validate().is_err());
+
+        let mut geometry = make_geometry();
+        geometry.fins = Some(FinSet::new(0, 0.3, 0.1, 0.15, 0.2, 0.005, 2.05));
+        assert!(geometry.validate().is_err());
+    }
+
+    #[test]
+    fn skin_friction_model_reproduces_estimated_friction_drag() {
+        let geometry = make_geometry();
+        let velocity = 60.0;
+        let estimate = geometry.estimate_unchecked(1.5, velocity);
+        let skin_friction = geometry.skin_friction();
+
+        // The estimate also applies a small compressibility correction
+        let mach = velocity / physics_mod::speed_of_sound();
+        assert_relative_eq!(
+            skin_friction.drag_coefficient(velocity, estimate.reference_area, 0.0)
+                * (1.0 - 0.1 * mach * mach),
+            estimate.cd_friction + estimate.cd_interference,
+            epsilon = 1e-12
+        );
+    }
+}
@@ -1,10 +1,28 @@
+mod actuator_mod;
+mod aero_mod;
+mod airbrake_mod;
+mod batch_mod;
 mod constants;
+mod control_mod;
+mod derived_mod;
+mod dispersion_mod;
+mod dual_mod;
+mod filter_mod;
+mod fitting_mod;
+mod geometry_mod;
 mod ode;
 mod physics_mod;
+mod predictor_mod;
+mod random_mod;
+mod result_mod;
 mod rocket;
+mod sensitivity_mod;
 mod simdata_mod;
 mod simulation;
+mod sobol_mod;
 mod state;
+mod summary_mod;
+mod targeting_mod;
 
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
@@ -15,16 +33,83 @@ use crate::simulation::Simulation;
 use crate::state::{InitialState1DOF, InitialState3DOF};
 use crate::state::{model_1dof::OneDOFModel, model_3dof::ThreeDOFModel, State};
 
+pub use crate::actuator_mod::Actuator;
+pub use crate::aero_mod::{AeroTable, SkinFriction};
+pub use crate::airbrake_mod::{Airbrake, Curve};
+pub use crate::control_mod::Controller;
+pub use crate::derived_mod::DerivedChannel;
+pub use crate::dispersion_mod::{
+    DispersedParameter, DispersionOutput, DispersionStatistics, MonteCarloResult,
+};
+pub use crate::filter_mod::particle::ParticleFilter;
+pub use crate::filter_mod::unscented::UnscentedKalmanFilter;
+pub use crate::filter_mod::{ApogeeDistribution, ExtendedKalmanFilter, Measurement};
+pub use crate::fitting_mod::mcmc::PosteriorSamples;
+pub use crate::fitting_mod::{FitParameter, FitResult};
+pub use crate::geometry_mod::{
+    AerodynamicEstimate, BodyTube, FinSet, LaunchLug, NoseCone, NoseShape, RocketGeometry,
+    Transition,
+};
 pub use crate::ode::{AdaptiveTimeStep, FixedTimeStep, OdeMethod, TimeStepOptions};
+pub use crate::predictor_mod::ApogeePredictor;
+pub use crate::random_mod::Distribution;
+pub use crate::result_mod::{EventKind, FlightEvent, SimulationResult};
 pub use crate::rocket::{Rocket, RocketProperties};
+pub use crate::sensitivity_mod::{Sensitivity, SensitivityMethod};
+pub use crate::simulation::{ApogeePrediction, PredictionStatus};
+pub use crate::sobol_mod::SobolIndices;
+pub use crate::summary_mod::FlightSummary;
+pub use crate::targeting_mod::{LaunchObjective, LaunchOptimum, SolveReport, SolveStatus};
 
 #[pymodule(gil_used = false)]
 fn hprm(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<OdeMethod>()?;
     m.add_class::<Rocket>()?;
+    m.add_class::<ApogeePredictor>()?;
+    m.add_class::<PredictionStatus>()?;
+    m.add_class::<ApogeePrediction>()?;
+    m.add_class::<SimulationResult>()?;
+    m.add_class::<EventKind>()?;
+    m.add_class::<FlightEvent>()?;
+    m.add_class::<FlightSummary>()?;
+    m.add_class::<DerivedChannel>()?;
     m.add_class::<FixedTimeStep>()?;
     m.add_class::<AdaptiveTimeStep>()?;
     m.add_class::<InitialState1DOF>()?;
     m.add_class::<InitialState3DOF>()?;
+    m.add_class::<SkinFriction>()?;
+    m.add_class::<AeroTable>()?;
+    m.add_class::<Curve>()?;
+    m.add_class::<Airbrake>()?;
+    m.add_class::<Actuator>()?;
+    m.add_class::<Controller>()?;
+    m.add_class::<Measurement>()?;
+    m.add_class::<ExtendedKalmanFilter>()?;
+    m.add_class::<UnscentedKalmanFilter>()?;
+    m.add_class::<ParticleFilter>()?;
+    m.add_class::<ApogeeDistribution>()?;
+    m.add_class::<FitParameter>()?;
+    m.add_class::<FitResult>()?;
+    m.add_class::<Distribution>()?;
+    m.add_class::<PosteriorSamples>()?;
+    m.add_class::<DispersedParameter>()?;
+    m.add_class::<DispersionOutput>()?;
+    m.add_class::<DispersionStatistics>()?;
+    m.add_class::<MonteCarloResult>()?;
+    m.add_class::<SensitivityMethod>()?;
+    m.add_class::<Sensitivity>()?;
+    m.add_class::<SobolIndices>()?;
+    m.add_class::<SolveStatus>()?;
+    m.add_class::<SolveReport>()?;
+    m.add_class::<LaunchObjective>()?;
+    m.add_class::<LaunchOptimum>()?;
+    m.add_class::<NoseShape>()?;
+    m.add_class::<NoseCone>()?;
+    m.add_class::<BodyTube>()?;
+    m.add_class::<Transition>()?;
+    m.add_class::<FinSet>()?;
+    m.add_class::<LaunchLug>()?;
+    m.add_class::<RocketGeometry>()?;
+    m.add_class::<AerodynamicEstimate>()?;
     Ok(())
 }
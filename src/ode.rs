@@ -5,8 +5,44 @@ use crate::constants::ode_constants::{
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 
+use crate::dual_mod::{Dual, Real};
+use crate::rocket::ModelParameters;
 use crate::state::State;
 
+/// Stage coefficients of the SSP RK3 method, as used by `runge_kutta_3`.
+const RK3_STAGES: [&[f64]; 3] = [&[], &[1.0], &[0.25, 0.25]];
+/// Weights of the SSP RK3 stages in the step.
+const RK3_WEIGHTS: [f64; 3] = [1.0 / 6.0, 1.0 / 6.0, 2.0 / 3.0];
+/// Stage coefficients of the Dormand-Prince stages `runge_kutta_45` advances the state with.
+const RK45_STAGES: [&[f64]; 6] = [
+    &[],
+    &[0.2],
+    &[0.075, 0.225],
+    &[44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0],
+    &[
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+    ],
+    &[
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+    ],
+];
+/// Weights of the 5th-order solution of `runge_kutta_45`.
+const RK45_WEIGHTS: [f64; 6] = [
+    35.0 / 384.0,
+    0.0,
+    500.0 / 1113.0,
+    125.0 / 192.0,
+    -2187.0 / 6784.0,
+    11.0 / 84.0,
+];
+
 #[pyclass(eq, eq_int)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 /// Numerical integration methods for the ODE solver.
@@ -141,6 +177,15 @@ impl OdeSolver {
         }
     }
 
+    /// The integration method of the solver.
+    pub(crate) fn method(&self) -> OdeMethod {
+        match self {
+            OdeSolver::Euler(_) => OdeMethod::Euler,
+            OdeSolver::RK3(_) => OdeMethod::RK3,
+            OdeSolver::RK45(_) => OdeMethod::RK45,
+        }
+    }
+
     /// Once we have taken a timestep and find that we have passed apogee, we can use this method to backtrack to the
     /// actual apogee by using the previous state and the current state to estimate the time fraction at which we
     /// reached apogee, then we can adjust the timestep accordingly and rerun the timestep to get a more accurate
@@ -155,19 +200,151 @@ impl OdeSolver {
             / (previous_vertical_rate_of_distance_change_with_time_in_meters_per_second
                 - vertical_rate_of_distance_change_with_time_in_meters_per_second);
 
-        // Update the tinestep to be the desired size
+        self.rerun_fraction(state, prev_state, tau);
+    }
+
+    /// Backtracks to the ground after a timestep ended below it, like `backtrack_apogee`, with
+    /// the time fraction at which the altitude crossed zero.
+    pub(crate) fn backtrack_ground(&mut self, state: &mut State, prev_state: &State) {
+        let altitude = state.get_altitude();
+        let prev_altitude = prev_state.get_altitude();
+        // Time fraction which is approx ground impact assuming const velocity
+        let tau = prev_altitude / (prev_altitude - altitude);
+        self.rerun_fraction(state, prev_state, tau);
+    }
+
+    /// Reruns the last timestep from `prev_state` with the timestep scaled by `tau`.
+    fn rerun_fraction(&mut self, state: &mut State, prev_state: &State, tau: f64) {
+        // Update the tinestep to be the desired size. The step which was taken can be shorter
+        // than the solver's step size (e.g. when it ended at a controller update), and an
+        // adaptive solver has already adapted its step size for the next step.
+        let dt = tau * (state.get_time() - prev_state.get_time());
         match self {
-            OdeSolver::Euler(fixed) => fixed.dt *= tau,
-            OdeSolver::RK3(fixed) => fixed.dt *= tau,
-            OdeSolver::RK45(ats) => ats.dt *= tau,
+            OdeSolver::Euler(fixed) => fixed.dt = dt,
+            OdeSolver::RK3(fixed) => fixed.dt = dt,
+            OdeSolver::RK45(ats) => ats.dt = dt,
         };
 
         //Rerun the timestep
-        let mut tmp_state = *prev_state;
+        let mut tmp_state = prev_state.clone();
         self.timestep(&mut tmp_state);
         *state = tmp_state;
     }
 
+    /// The size of the next timestep.
+    pub(crate) fn step_size(&self) -> f64 {
+        match self {
+            OdeSolver::Euler(fixed) | OdeSolver::RK3(fixed) => fixed.dt,
+            OdeSolver::RK45(ats) => ats.dt,
+        }
+    }
+
+    /// Starts an adaptive solver from the given step size, e.g. one adapted during a previous run.
+    /// Fixed timestep solvers are left unchanged.
+    pub(crate) fn warm_start(&mut self, dt: f64) {
+        if let OdeSolver::RK45(ats) = self {
+            ats.dt = dt.clamp(ats.dt_min, ats.dt_max);
+        }
+    }
+
+    /// Takes a timestep of the given size with the same method, without adapting the solver's
+    /// step size, e.g. to repeat a step of a nominal trajectory from a nearby state.
+    pub(crate) fn step_with(&self, state: &mut State, dt: f64) {
+        match self {
+            OdeSolver::Euler(_) => Self::explicit_euler(state, dt),
+            OdeSolver::RK3(_) => Self::runge_kutta_3(state, dt),
+            OdeSolver::RK45(ats) => {
+                Self::runge_kutta_45(state, &mut AdaptiveTimeStep { dt, ..*ats })
+            }
+        }
+    }
+
+    /// Takes a step of size `dt` with the same method as `step_with`, for the state vector `x` of
+    /// the system `dxdt = f(x)` with any scalar type.
+    pub(crate) fn step_generic<T: Real>(
+        &self,
+        f: impl Fn(&[T]) -> Vec<T>,
+        x: &[T],
+        dt: f64,
+    ) -> Vec<T> {
+        match self {
+            OdeSolver::Euler(_) => Self::explicit_runge_kutta(f, x, dt, &[&[]], &[1.0]),
+            OdeSolver::RK3(_) => Self::explicit_runge_kutta(f, x, dt, &RK3_STAGES, &RK3_WEIGHTS),
+            OdeSolver::RK45(_) => Self::explicit_runge_kutta(f, x, dt, &RK45_STAGES, &RK45_WEIGHTS),
+        }
+    }
+
+    /// Carries the derivative `tangent` of `state` (in the layout of `get_state_vec`) across a
+    /// step of size `dt` from it, by repeating the step with dual numbers. `parameters` are the
+    /// scalar rocket properties with their derivatives in the same direction. The result is the
+    /// exact derivative of the step the solver takes, not an approximation of it.
+    pub(crate) fn tangent_step(
+        &self,
+        state: &State,
+        tangent: &[f64],
+        parameters: &ModelParameters<Dual<1>>,
+        dt: f64,
+    ) -> Vec<f64> {
+        let x: Vec<Dual<1>> = state
+            .get_state_vec()
+            .as_array()
+            .iter()
+            .zip(tangent)
+            .map(|(&value, &derivative)| Dual {
+                value,
+                derivatives: [derivative],
+            })
+            .collect();
+        self.step_generic(|x| state.derivatives_with(x, parameters), &x, dt)
+            .iter()
+            .map(|x| x.derivatives[0])
+            .collect()
+    }
+
+    /// Explicit Runge-Kutta step with the stage coefficients `stages` (one row per stage, with a
+    /// coefficient for each of the previous stages) and the stage weights `weights`.
+    fn explicit_runge_kutta<T: Real>(
+        f: impl Fn(&[T]) -> Vec<T>,
+        x: &[T],
+        dt: f64,
+        stages: &[&[f64]],
+        weights: &[f64],
+    ) -> Vec<T> {
+        // Adds the weighted derivatives of the stages to the state
+        let advance = |k: &[Vec<T>], weights: &[f64]| -> Vec<T> {
+            x.iter()
+                .enumerate()
+                .map(|(i, &x)| {
+                    k.iter()
+                        .zip(weights)
+                        .fold(x, |sum, (k, weight)| sum + k[i] * (weight * dt))
+                })
+                .collect()
+        };
+        let mut k: Vec<Vec<T>> = Vec::with_capacity(stages.len());
+        for coefficients in stages {
+            let stage = advance(&k, coefficients);
+            k.push(f(&stage));
+        }
+        advance(&k, weights)
+    }
+
+    /// Takes a timestep which ends no more than `max_dt` after the current time, e.g. at the next
+    /// controller update. Fixed timestep solvers keep their step size for the following steps,
+    /// while an adaptive solver adapts from the shortened step.
+    pub(crate) fn timestep_within(&mut self, state: &mut State, max_dt: f64) {
+        match self {
+            OdeSolver::Euler(fixed) | OdeSolver::RK3(fixed) if fixed.dt > max_dt => {
+                self.step_with(state, max_dt)
+            }
+            OdeSolver::RK45(ats) => {
+                ats.dt = ats.dt.min(max_dt);
+                self.timestep(state)
+            }
+            _ => self.timestep(state),
+        }
+    }
+
     pub(crate) fn timestep(&mut self, state: &mut State) {
         match self {
             OdeSolver::Euler(fixed) => Self::explicit_euler(state, fixed.dt),
@@ -193,7 +370,7 @@ impl OdeSolver {
         // This method is a 3-stage method based off Strong Stability Preserving (SSP) aka.
         // Total variation Diminishing (TVD) form of RK3. (commonly used in PDE applications)
 
-        let mut state_rk: State = *state;
+        let mut state_rk: State = state.clone();
 
         //Stage 1       dt = 1 * DT
         let dudt = state_rk.get_derivatives();
@@ -205,7 +382,7 @@ impl OdeSolver {
         let coeff: f64 = 0.25 * dt;
         du = dudt.clone().scale(coeff) + dudt2.clone().scale(coeff);
 
-        state_rk = *state;
+        state_rk = state.clone();
         state_rk.update(du, 0.0);
 
         // Stage 3
@@ -235,14 +412,14 @@ impl OdeSolver {
         let k1 = dudt1.clone().scale(dt);
 
         // ========== Stage 2 ==========
-        let mut stage = *state;
+        let mut stage = state.clone();
         // ut = u + 0.2 * k1
         stage.update(k1.clone().scale(0.2), 0.0);
         let dudt2 = stage.get_derivatives();
         let k2 = dudt2.clone().scale(dt);
 
         // ========== Stage 3 ==========
-        let mut stage = *state;
+        let mut stage = state.clone();
         // ut = u + 0.075*k1 + 0.225*k2
         stage.update(k1.clone().scale(0.075), 0.0);
         stage.update(k2.clone().scale(0.225), 0.0);
@@ -250,7 +427,7 @@ impl OdeSolver {
         let k3 = dudt3.clone().scale(dt);
 
         // ========== Stage 4 ==========
-        let mut stage = *state;
+        let mut stage = state.clone();
         // ut = u + (44/45)*k1 - (56/15)*k2 + (32/9)*k3
         stage.update(k1.clone().scale(44.0 / 45.0), 0.0);
         stage.update(k2.clone().scale(-56.0 / 15.0), 0.0);
@@ -259,7 +436,7 @@ impl OdeSolver {
         let k4 = dudt4.clone().scale(dt);
 
         // ========== Stage 5 ==========
-        let mut stage = *state;
+        let mut stage = state.clone();
         // ut = u + (19372/6561)*k1 - (25360/2187)*k2
         //          + (64448/6561)*k3 - (212/729)*k4
         stage.update(k1.clone().scale(19372.0 / 6561.0), 0.0);
@@ -270,7 +447,7 @@ impl OdeSolver {
         let k5 = dudt5.clone().scale(dt);
 
         // ========== Stage 6 ==========
-        let mut stage = *state;
+        let mut stage = state.clone();
         // ut = u + (9017/3168)*k1 - (355/33)*k2
         //          + (46732/5247)*k3 + (49/176)*k4
         //          - (5103/18656)*k5
@@ -283,7 +460,7 @@ impl OdeSolver {
         let k6 = dudt6.clone().scale(dt);
 
         // ========== Stage 7 (5th-order combination) ==========
-        let mut stage = *state;
+        let mut stage = state.clone();
         // ut = u + (35/384)*k1 + (500/1113)*k3
         //          + (125/192)*k4 - (2187/6784)*k5
         //          + (11/84)*k6
@@ -330,7 +507,10 @@ impl OdeSolver {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::actuator_mod::Actuator;
+    use crate::airbrake_mod::{Airbrake, Curve};
     use crate::rocket::RocketProperties;
+    use crate::state::state_vector::StateVector;
     use crate::state::{InitialState1DOF, InitialState3DOF, State};
     use approx::{assert_abs_diff_eq, assert_relative_eq};
 
@@ -549,4 +729,84 @@ mod tests {
         let next_clamped = config_max_clamp.next_dt(1e-35);
         assert_abs_diff_eq!(next_clamped, 0.5, epsilon = 1e-12);
     }
+
+    #[test]
+    fn actuator_states_advance_with_the_flight_dynamics() {
+        // Airbrakes commanded fully open behind a first-order lag
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.airbrake = Some(Airbrake::new(
+            Curve::polynomial(vec![0.0, 0.8]),
+            None,
+            None,
+            1.0,
+            Some(Actuator::first_order(0.2, 0.0)),
+        ));
+
+        for mut solver in [
+            OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            OdeSolver::RK45(AdaptiveTimeStep::default()),
+        ] {
+            let mut state = State::new_1dof(
+                rocket_properties.clone(),
+                InitialState1DOF::new(100.0, 150.0),
+            );
+            while state.get_time() < 0.2 {
+                solver.timestep(&mut state);
+            }
+
+            let StateVector::OneDOFAux(u) = state.get_state_vec() else {
+                panic!("Expected the auxiliary states to be part of the state vector");
+            };
+            let expected = 1.0 - (-state.get_time() / 0.2).exp();
+            assert_relative_eq!(u[2], expected, epsilon = 1e-4);
+        }
+
+        // The partially deployed airbrakes slow the rocket down less than instant ones
+        let mut instant_properties = rocket_properties.clone();
+        instant_properties.airbrake.as_mut().unwrap().actuator = None;
+        let mut lagged = State::new_1dof(rocket_properties, InitialState1DOF::new(100.0, 150.0));
+        let mut instant = State::new_1dof(instant_properties, InitialState1DOF::new(100.0, 150.0));
+        let mut solver = OdeSolver::RK3(FixedTimeStep::new(0.01));
+        for _ in 0..50 {
+            solver.timestep(&mut lagged);
+            solver.timestep(&mut instant);
+        }
+        assert!(lagged.get_vertical_velocity() > instant.get_vertical_velocity());
+    }
+
+    #[test]
+    fn generic_steps_match_the_solvers() {
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.airbrake = Some(Airbrake::new(
+            Curve::polynomial(vec![0.0, 0.8]),
+            None,
+            None,
+            1.0,
+            Some(Actuator::first_order(0.2, 0.0)),
+        ));
+        let start = State::new_3dof(
+            rocket_properties.clone(),
+            InitialState3DOF::new(0.0, 100.0, 0.1, -5.0, 150.0, 0.2),
+        );
+        let parameters = rocket_properties.parameters::<f64>();
+
+        for solver in [
+            OdeSolver::Euler(FixedTimeStep::new(0.01)),
+            OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            OdeSolver::RK45(AdaptiveTimeStep::default()),
+        ] {
+            let mut state = start.clone();
+            solver.step_with(&mut state, 0.05);
+            let generic = solver.step_generic(
+                |x| start.derivatives_with(x, &parameters),
+                start.get_state_vec().as_array(),
+                0.05,
+            );
+
+            assert_eq!(generic.len(), 9);
+            for (generic, x) in generic.iter().zip(state.get_state_vec().as_array()) {
+                assert_relative_eq!(generic, x, epsilon = 1e-10, max_relative = 1e-12);
+            }
+        }
+    }
 }
@@ -1,28 +1,190 @@
-use crate::constants::physics_constants::{GRAVITY_M_S_2, SEA_LEVEL_AIR_DENSITY_KG_M_3};
+use crate::aero_mod::SkinFriction;
+use crate::constants::physics_constants::{
+    AIR_GAS_CONSTANT_J_KG_K, GRAVITY_M_S_2, SEA_LEVEL_AIR_DENSITY_KG_M_3,
+    SEA_LEVEL_DYNAMIC_VISCOSITY_PA_S, SEA_LEVEL_SPEED_OF_SOUND_M_S, SEA_LEVEL_TEMPERATURE_K,
+    SUTHERLAND_TEMPERATURE_K, TROPOPAUSE_ALTITUDE_M, TROPOSPHERE_LAPSE_RATE_K_M,
+};
+use crate::dual_mod::Real;
 
+/// Air density (kg/m^3) for the aerodynamic forces, which hold the atmosphere at sea level
+/// conditions at all altitudes.
 pub(crate) fn density() -> f64 {
     SEA_LEVEL_AIR_DENSITY_KG_M_3
 }
 
+/// Air temperature (K) of the International Standard Atmosphere at the altitude (m), falling at
+/// the lapse rate through the troposphere and constant above the tropopause.
+fn temperature<T: Real>(altitude: T) -> T {
+    let tropopause_temperature =
+        SEA_LEVEL_TEMPERATURE_K - TROPOSPHERE_LAPSE_RATE_K_M * TROPOPAUSE_ALTITUDE_M;
+    if altitude.value() < TROPOPAUSE_ALTITUDE_M {
+        -(altitude * TROPOSPHERE_LAPSE_RATE_K_M) + SEA_LEVEL_TEMPERATURE_K
+    } else {
+        T::constant(tropopause_temperature)
+    }
+}
+
+/// Air density (kg/m^3) of the International Standard Atmosphere at the altitude (m). It follows
+/// the temperature through the troposphere and falls off exponentially in the isothermal layer
+/// above it.
+pub(crate) fn density_at<T: Real>(altitude: T) -> T {
+    let exponent = GRAVITY_M_S_2 / (TROPOSPHERE_LAPSE_RATE_K_M * AIR_GAS_CONSTANT_J_KG_K) - 1.0;
+    let temperature = temperature(altitude);
+    // Above the tropopause, this is the density at the tropopause
+    let troposphere_density =
+        (temperature / SEA_LEVEL_TEMPERATURE_K).powf(exponent) * SEA_LEVEL_AIR_DENSITY_KG_M_3;
+    if altitude.value() < TROPOPAUSE_ALTITUDE_M {
+        troposphere_density
+    } else {
+        let scale_height = AIR_GAS_CONSTANT_J_KG_K * temperature.value() / GRAVITY_M_S_2;
+        (-(altitude - TROPOPAUSE_ALTITUDE_M) / scale_height).exp() * troposphere_density
+    }
+}
+
+/// Dynamic viscosity of air (Pa*s) at the altitude (m), from Sutherland's law with the standard
+/// atmosphere temperature.
+pub(crate) fn dynamic_viscosity<T: Real>(altitude: T) -> T {
+    let temperature = temperature(altitude);
+    (temperature / SEA_LEVEL_TEMPERATURE_K).powf(1.5)
+        * (SEA_LEVEL_DYNAMIC_VISCOSITY_PA_S * (SEA_LEVEL_TEMPERATURE_K + SUTHERLAND_TEMPERATURE_K))
+        / (temperature + SUTHERLAND_TEMPERATURE_K)
+}
+
+pub(crate) fn speed_of_sound() -> f64 {
+    SEA_LEVEL_SPEED_OF_SOUND_M_S
+}
+
+/// Reynolds number over the length (m), with the standard atmosphere density and viscosity at the
+/// altitude (m), which fall from the pad to apogee.
+pub(crate) fn calc_reynolds_number<T: Real>(velocity: T, length: f64, altitude: T) -> T {
+    velocity.abs() * density_at(altitude) * length / dynamic_viscosity(altitude)
+}
+
+/// Turbulent flat plate skin friction fit from OpenRocket. Very low Reynolds numbers are clamped
+/// to keep the fit from blowing up.
+fn turbulent_skin_friction<T: Real>(reynolds: T) -> T {
+    if reynolds.value() < 1.0e4 {
+        T::constant(1.48e-2)
+    } else {
+        T::constant(1.0) / (reynolds.ln() * 1.50 - 5.6).powi(2)
+    }
+}
+
+/// Laminar (Blasius) flat plate skin friction, clamped at low Reynolds numbers like the turbulent
+/// fit.
+fn laminar_skin_friction<T: Real>(reynolds: T) -> T {
+    T::constant(1.328) / reynolds.max(T::constant(1.0e4)).sqrt()
+}
+
+/// Flat plate skin friction coefficient, covering three regimes:
+/// - Laminar (Blasius) below `transition_reynolds`
+/// - Turbulent above it, minus a Prandtl-Schlichting style correction for the laminar run at the
+///   front of the plate which keeps the curve continuous at the transition
+/// - Roughness-limited, which puts a floor on the turbulent value for the given roughness height /
+///   length ratio
+///
+/// A `transition_reynolds` of 0 gives fully turbulent flow.
+pub(crate) fn skin_friction_coefficient<T: Real>(
+    reynolds: T,
+    roughness_ratio: f64,
+    transition_reynolds: f64,
+) -> T {
+    if reynolds.value() < transition_reynolds {
+        return laminar_skin_friction(reynolds);
+    }
+
+    let laminar_correction = if transition_reynolds > 0.0 {
+        transition_reynolds
+            * (turbulent_skin_friction(transition_reynolds)
+                - laminar_skin_friction(transition_reynolds))
+    } else {
+        0.0
+    };
+    let cf_turbulent =
+        turbulent_skin_friction(reynolds) - T::constant(laminar_correction) / reynolds;
+
+    let cf_roughness = if roughness_ratio > 0.0 {
+        0.032 * roughness_ratio.powf(0.2)
+    } else {
+        0.0
+    };
+    cf_turbulent.max(T::constant(cf_roughness))
+}
+
 pub(crate) fn gravity() -> f64 {
     -GRAVITY_M_S_2
 }
 
-pub(crate) fn calc_drag_force(velocity: f64, cd: f64, area: f64) -> f64 {
+/// Drag force along the direction of travel. If a skin friction model is given, `cd` only
+/// represents the pressure and base drag, and the Reynolds number dependent friction drag at the
+/// altitude (m) is added to it.
+pub(crate) fn calc_drag_force<T: Real>(
+    velocity: T,
+    cd: T,
+    area: T,
+    skin_friction: Option<&SkinFriction>,
+    altitude: T,
+) -> T {
     let rho = density();
-    -0.5 * rho * velocity.powi(2) * cd * area
+    let cd_friction = skin_friction.map_or(T::constant(0.0), |sf| {
+        sf.friction_drag_coefficient(velocity, area, altitude)
+    });
+    velocity.powi(2) * (-0.5 * rho) * (cd + cd_friction) * area
 }
 
-pub(crate) fn calc_lift_force(velocity: f64, cl_alpha: f64, alpha: f64, area: f64) -> f64 {
+pub(crate) fn calc_lift_force<T: Real>(velocity: T, cl_alpha: T, alpha: T, area: T) -> T {
     let rho = density();
-    0.5 * rho * velocity.powi(2) * cl_alpha * alpha * area
+    velocity.powi(2) * (0.5 * rho) * cl_alpha * alpha * area
+}
+
+/// Normal force perpendicular to the rocket's axis, from a normal force coefficient which already
+/// includes the angle of attack dependence.
+pub(crate) fn calc_normal_force<T: Real>(velocity: T, cn: T, area: T) -> T {
+    let rho = density();
+    velocity.powi(2) * (0.5 * rho) * cn * area
+}
+
+/// Aerodynamic pitch damping moment from the damping derivative Cmq. The non-dimensional pitch
+/// rate is q*L/(2V), so the moment simplifies to 1/4 * rho * V * A * L^2 * Cmq * q.
+pub(crate) fn calc_pitch_damping_moment<T: Real>(
+    velocity: T,
+    cmq: T,
+    area: T,
+    length: T,
+    angular_rate: T,
+) -> T {
+    let rho = density();
+    velocity.abs() * (0.25 * rho) * area * length.powi(2) * cmq * angular_rate
+}
+
+/// Thrust-jet damping moment caused by the exhaust gases being accelerated sideways as the
+/// rocket rotates.
+pub(crate) fn calc_jet_damping_moment<T: Real>(
+    mass_flow_rate: T,
+    nozzle_distance: T,
+    angular_rate: T,
+) -> T {
+    -(angular_rate * mass_flow_rate * nozzle_distance.powi(2))
+}
+
+/// Height a rocket coasts upwards from the given vertical velocity, from the closed-form solution
+/// for quadratic drag with a constant drag coefficient and air density.
+pub(crate) fn calc_coast_height<T: Real>(velocity: T, mass: f64, cd: f64, area: f64) -> T {
+    if velocity.value() <= 0.0 {
+        return T::constant(0.0);
+    }
+    let g = -gravity();
+    let k = 0.5 * density() * cd * area;
+    if k <= 0.0 {
+        return velocity.powi(2) / (2.0 * g);
+    }
+    (velocity.powi(2) * (k / (mass * g))).ln_1p() * (mass / (2.0 * k))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
-    use nalgebra::ComplexField;
 
     #[test]
     fn test_calc_drag_force() {
@@ -31,13 +193,25 @@ mod tests {
         let area = 0.02;
 
         let expected = -0.5 * density() * v.powi(2) * cd * area;
-        assert_relative_eq!(calc_drag_force(v, cd, area), expected, epsilon = 1e-12);
+        assert_relative_eq!(
+            calc_drag_force(v, cd, area, None, 0.0),
+            expected,
+            epsilon = 1e-12
+        );
 
         // Drag should be the same for +v and -v since it uses v^2 (always negative)
-        assert_relative_eq!(calc_drag_force(-v, cd, area), expected, epsilon = 1e-12);
+        assert_relative_eq!(
+            calc_drag_force(-v, cd, area, None, 0.0),
+            expected,
+            epsilon = 1e-12
+        );
 
         // At zero velocity drag should be zero
-        assert_relative_eq!(calc_drag_force(0.0, cd, area), 0.0, epsilon = 0.0);
+        assert_relative_eq!(
+            calc_drag_force(0.0, cd, area, None, 0.0),
+            0.0,
+            epsilon = 0.0
+        );
     }
 
     #[test]
@@ -66,4 +240,226 @@ mod tests {
         // Lift should be zero at alpha = 0
         assert_relative_eq!(calc_lift_force(v, cl_alpha, 0.0, area), 0.0, epsilon = 0.0);
     }
+
+    #[test]
+    fn test_calc_normal_force() {
+        let v = 60.0;
+        let cn = 0.4;
+        let area = 0.0182;
+
+        let expected = 0.5 * density() * v.powi(2) * cn * area;
+        assert_relative_eq!(calc_normal_force(v, cn, area), expected, epsilon = 1e-12);
+        assert_relative_eq!(calc_normal_force(-v, cn, area), expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_skin_friction_coefficient() {
+        // Low Reynolds numbers are clamped
+        assert_relative_eq!(
+            skin_friction_coefficient(5.0e3, 0.0, 0.0),
+            1.48e-2,
+            epsilon = 1e-12
+        );
+
+        // Turbulent fit
+        let re: f64 = 1.0e7;
+        let expected = 1.0 / (1.50 * re.ln() - 5.6).powi(2);
+        assert_relative_eq!(
+            skin_friction_coefficient(re, 0.0, 0.0),
+            expected,
+            epsilon = 1e-12
+        );
+
+        // Friction decreases with Reynolds number for a smooth surface...
+        assert!(
+            skin_friction_coefficient(1.0e8, 0.0, 0.0) < skin_friction_coefficient(1.0e6, 0.0, 0.0)
+        );
+
+        // ...but a rough surface bottoms out at the roughness-limited value
+        let rough = 0.032 * 1.0e-4_f64.powf(0.2);
+        assert_relative_eq!(
+            skin_friction_coefficient(1.0e9, 1.0e-4, 0.0),
+            rough,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_skin_friction_coefficient_regimes() {
+        let transition = 5.0e5;
+
+        // Laminar (Blasius) below the transition Reynolds number
+        let re: f64 = 2.0e5;
+        assert_relative_eq!(
+            skin_friction_coefficient(re, 0.0, transition),
+            1.328 / re.sqrt(),
+            epsilon = 1e-12
+        );
+
+        // Continuous across the transition
+        let below = skin_friction_coefficient(transition * (1.0 - 1e-9), 0.0, transition);
+        let above = skin_friction_coefficient(transition, 0.0, transition);
+        assert_relative_eq!(below, above, epsilon = 1e-9);
+
+        // The laminar run only lowers the friction, and its effect fades at high Reynolds numbers
+        let re: f64 = 1.0e6;
+        assert!(
+            skin_friction_coefficient(re, 0.0, transition)
+                < skin_friction_coefficient(re, 0.0, 0.0)
+        );
+        let re: f64 = 1.0e9;
+        assert_relative_eq!(
+            skin_friction_coefficient(re, 0.0, transition),
+            skin_friction_coefficient(re, 0.0, 0.0),
+            epsilon = 1e-5
+        );
+
+        // Roughness only limits the turbulent regime
+        assert_relative_eq!(
+            skin_friction_coefficient(2.0e5, 1.0e-3, transition),
+            1.328 / 2.0e5_f64.sqrt(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_calc_drag_force_with_skin_friction() {
+        let v = 80.0;
+        let cd = 0.3;
+        let area = 0.0182;
+        let skin_friction = SkinFriction::new(1.5, 2.5, 20e-6, 5.0e5);
+
+        let cd_friction = skin_friction.drag_coefficient(v, area, 1500.0);
+        assert!(cd_friction > 0.0);
+
+        let expected = -0.5 * density() * v.powi(2) * (cd + cd_friction) * area;
+        assert_relative_eq!(
+            calc_drag_force(v, cd, area, Some(&skin_friction), 1500.0),
+            expected,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_calc_reynolds_number() {
+        let expected = density() * 100.0 * 2.0 / SEA_LEVEL_DYNAMIC_VISCOSITY_PA_S;
+        assert_relative_eq!(
+            calc_reynolds_number(100.0, 2.0, 0.0),
+            expected,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            calc_reynolds_number(-100.0, 2.0, 0.0),
+            expected,
+            epsilon = 1e-6
+        );
+
+        // The thinner air aloft lowers the Reynolds number at the same airspeed
+        let aloft = calc_reynolds_number(100.0, 2.0, 3000.0);
+        assert_relative_eq!(
+            aloft,
+            density_at(3000.0) * 100.0 * 2.0 / dynamic_viscosity(3000.0),
+            epsilon = 1e-6
+        );
+        assert!(aloft < 0.8 * expected);
+    }
+
+    #[test]
+    fn test_standard_atmosphere() {
+        // Sea level and tropopause values of the International Standard Atmosphere
+        assert_relative_eq!(
+            density_at(0.0),
+            SEA_LEVEL_AIR_DENSITY_KG_M_3,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            dynamic_viscosity(0.0),
+            SEA_LEVEL_DYNAMIC_VISCOSITY_PA_S,
+            epsilon = 1e-15
+        );
+        assert_relative_eq!(density_at(11_000.0), 0.3639, epsilon = 1e-4);
+        assert_relative_eq!(dynamic_viscosity(11_000.0), 1.4216e-5, max_relative = 1e-3);
+        assert_relative_eq!(density_at(20_000.0), 0.08803, max_relative = 1e-3);
+
+        // Continuous across the tropopause, with the viscosity constant above it
+        let below = 11_000.0 - 1e-6;
+        assert_relative_eq!(density_at(below), density_at(11_000.0), epsilon = 1e-9);
+        assert_relative_eq!(
+            dynamic_viscosity(below),
+            dynamic_viscosity(11_000.0),
+            epsilon = 1e-15
+        );
+        assert_eq!(dynamic_viscosity(15_000.0), dynamic_viscosity(11_000.0));
+
+        assert!(density_at(3000.0) < density_at(1000.0));
+        assert!(dynamic_viscosity(3000.0) < dynamic_viscosity(1000.0));
+    }
+
+    #[test]
+    fn test_calc_pitch_damping_moment() {
+        let v = 50.0;
+        let cmq = -20.0;
+        let area = 0.0182;
+        let length = 0.152;
+        let q = 0.5;
+
+        let expected = 0.25 * density() * v * area * length.powi(2) * cmq * q;
+        assert_relative_eq!(
+            calc_pitch_damping_moment(v, cmq, area, length, q),
+            expected,
+            epsilon = 1e-12
+        );
+
+        // A negative Cmq always opposes the pitch rate, regardless of the flight direction
+        assert!(calc_pitch_damping_moment(v, cmq, area, length, q) < 0.0);
+        assert!(calc_pitch_damping_moment(-v, cmq, area, length, q) < 0.0);
+        assert!(calc_pitch_damping_moment(v, cmq, area, length, -q) > 0.0);
+
+        // No damping without a pitch rate
+        assert_relative_eq!(
+            calc_pitch_damping_moment(v, cmq, area, length, 0.0),
+            0.0,
+            epsilon = 0.0
+        );
+    }
+
+    #[test]
+    fn test_calc_jet_damping_moment() {
+        let mass_flow_rate = 1.2;
+        let nozzle_distance = 1.1;
+        let q = 0.3;
+
+        let expected = -mass_flow_rate * nozzle_distance.powi(2) * q;
+        assert_relative_eq!(
+            calc_jet_damping_moment(mass_flow_rate, nozzle_distance, q),
+            expected,
+            epsilon = 1e-12
+        );
+
+        // Zero when the motor is not burning
+        assert_relative_eq!(
+            calc_jet_damping_moment(0.0, nozzle_distance, q),
+            0.0,
+            epsilon = 0.0
+        );
+    }
+
+    #[test]
+    fn test_calc_coast_height() {
+        // Without drag it's the ballistic height
+        let v: f64 = 50.0;
+        assert_relative_eq!(
+            calc_coast_height(v, 10.0, 0.0, 0.01),
+            v.powi(2) / (2.0 * GRAVITY_M_S_2)
+        );
+
+        // Drag lowers it, and vanishing drag approaches the ballistic height
+        assert!(calc_coast_height(v, 10.0, 0.5, 0.01) < v.powi(2) / (2.0 * GRAVITY_M_S_2));
+        assert_relative_eq!(
+            calc_coast_height(v, 10.0, 1e-9, 0.01),
+            v.powi(2) / (2.0 * GRAVITY_M_S_2),
+            max_relative = 1e-6
+        );
+        assert_eq!(calc_coast_height(-5.0, 10.0, 0.5, 0.01), 0.0);
+    }
 }
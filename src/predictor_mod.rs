@@ -0,0 +1,289 @@
+use crate::constants::simulation_constants::{APOGEE_VELOCITY_THRESHOLD_M_S, MAX_ITERATIONS};
+use crate::ode::{OdeMethod, OdeSolver, TimeStepOptions};
+use crate::rocket::{emit_warnings, Rocket, RocketProperties};
+use crate::simdata_mod::SimulationData;
+use crate::simulation::{PredictionStatus, Simulation, SimulationExitCondition};
+use crate::state::{InitialCondition, InitialState1DOF, InitialState3DOF};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::f64::consts::FRAC_PI_2;
+use std::time::{Duration, Instant};
+
+/// Long-lived apogee predictor for flight computers, which are called many times a second with
+/// the latest estimate of the rocket's state.
+///
+/// The rocket and solver configuration are set up once, the simulation log is reused between
+/// calls, and an adaptive solver starts from the step size it adapted to in the previous call,
+/// so `update` doesn't allocate. `examples/predictor_latency.py` measures the latency of updates
+/// against fresh predictions on your hardware.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct ApogeePredictor {
+    rocket_properties: RocketProperties,
+    ode_solver: OdeSolver,
+    three_dof: bool,
+    max_iterations: u64,
+    log: SimulationData,
+    /// Step size to start the next prediction from
+    #[pyo3(get)]
+    step_size: f64,
+    /// Time of the last update (s), or NaN before the first update
+    #[pyo3(get)]
+    last_time: f64,
+    /// Apogee predicted by the last update (m), or NaN before the first update. If the update
+    /// didn't converge, this is the best estimate it found.
+    #[pyo3(get)]
+    apogee: f64,
+    /// How the last update ended
+    #[pyo3(get)]
+    status: PredictionStatus,
+    /// Wall-clock time each update may take (s), or None for no limit
+    #[pyo3(get, set)]
+    deadline: Option<f64>,
+}
+
+#[pymethods]
+impl ApogeePredictor {
+    /// Predictor which uses the 1DOF model.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, deadline=None))]
+    pub fn new_1dof(
+        py: Python<'_>,
+        rocket: &Rocket,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        max_iterations: u64,
+        deadline: Option<f64>,
+    ) -> PyResult<Self> {
+        emit_warnings(py, rocket.rocket_properties.validate_1dof())?;
+        let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
+        Ok(Self::new(
+            rocket.rocket_properties.clone(),
+            ode_solver,
+            false,
+            max_iterations,
+        )
+        .with_deadline(deadline))
+    }
+
+    /// Predictor which uses the 3DOF model.
+    #[staticmethod]
+    #[pyo3(signature = (rocket, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, deadline=None))]
+    pub fn new_3dof(
+        py: Python<'_>,
+        rocket: &Rocket,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        max_iterations: u64,
+        deadline: Option<f64>,
+    ) -> PyResult<Self> {
+        emit_warnings(py, rocket.rocket_properties.validate_3dof())?;
+        let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
+        Ok(Self::new(
+            rocket.rocket_properties.clone(),
+            ode_solver,
+            true,
+            max_iterations,
+        )
+        .with_deadline(deadline))
+    }
+
+    /// Predicts the apogee from the given state, measured at `time` (s). The 1DOF model only uses
+    /// the altitude and vertical velocity. If `airbrake_deployment` is given, the airbrakes are
+    /// held at it for this and all later predictions. If `time` is earlier than the last update
+    /// (e.g. a new flight), the warm start is reset.
+    #[pyo3(signature = (time, altitude, vertical_velocity, horizontal_velocity=0.0, angle=FRAC_PI_2, angular_rate=0.0, airbrake_deployment=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        &mut self,
+        time: f64,
+        altitude: f64,
+        vertical_velocity: f64,
+        horizontal_velocity: f64,
+        angle: f64,
+        angular_rate: f64,
+        airbrake_deployment: Option<f64>,
+    ) -> PyResult<f64> {
+        if let Some(deployment) = airbrake_deployment {
+            self.set_airbrake_deployment(deployment)
+                .map_err(PyValueError::new_err)?;
+        }
+        let initial_condition = if self.three_dof {
+            InitialCondition::ThreeDOF(InitialState3DOF::new(
+                0.0,
+                altitude,
+                angle,
+                horizontal_velocity,
+                vertical_velocity,
+                angular_rate,
+            ))
+        } else {
+            InitialCondition::OneDOF(InitialState1DOF::new(altitude, vertical_velocity))
+        };
+        Ok(self.predict(time, initial_condition))
+    }
+
+    /// Resets the warm start and the last prediction.
+    pub fn reset(&mut self) {
+        self.step_size = self.ode_solver.step_size();
+        self.last_time = f64::NAN;
+        self.apogee = f64::NAN;
+        self.status = PredictionStatus::Converged;
+    }
+}
+
+impl ApogeePredictor {
+    pub(crate) fn new(
+        rocket_properties: RocketProperties,
+        ode_solver: OdeSolver,
+        three_dof: bool,
+        max_iterations: u64,
+    ) -> Self {
+        Self {
+            rocket_properties,
+            step_size: ode_solver.step_size(),
+            ode_solver,
+            three_dof,
+            max_iterations,
+            log: SimulationData::new(),
+            last_time: f64::NAN,
+            apogee: f64::NAN,
+            status: PredictionStatus::Converged,
+            deadline: None,
+        }
+    }
+
+    fn with_deadline(mut self, deadline: Option<f64>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Predicts the apogee, starting the solver from the step size of the last prediction.
+    pub(crate) fn predict(&mut self, time: f64, initial_condition: InitialCondition) -> f64 {
+        let start = Instant::now();
+        if time < self.last_time {
+            self.reset();
+        }
+        self.last_time = time;
+
+        let state = initial_condition.to_state(self.rocket_properties.clone());
+        if state.get_vertical_velocity() < APOGEE_VELOCITY_THRESHOLD_M_S {
+            self.apogee = state.get_altitude();
+            self.status = PredictionStatus::Converged;
+            return self.apogee;
+        }
+
+        let mut ode_solver = self.ode_solver.clone();
+        ode_solver.warm_start(self.step_size);
+        let mut simulation = Simulation::new(
+            state,
+            ode_solver,
+            SimulationExitCondition::ApogeeReached,
+            self.max_iterations,
+        );
+        if let Some(deadline) = self.deadline {
+            // A negative deadline leaves no time at all
+            let deadline = Duration::try_from_secs_f64(deadline).unwrap_or(Duration::ZERO);
+            simulation = simulation.with_deadline(start + deadline);
+        }
+        self.log.clear();
+        simulation.run(&mut self.log, false, false);
+
+        if let Some(step_size) = simulation.first_step_size() {
+            self.step_size = step_size;
+        }
+        self.apogee = simulation.apogee_estimate();
+        self.status = simulation.status();
+        self.apogee
+    }
+
+    /// Holds the airbrakes at the given deployment, at rest if they have an actuator.
+    fn set_airbrake_deployment(&mut self, deployment: f64) -> Result<(), String> {
+        let airbrake = self
+            .rocket_properties
+            .airbrake
+            .as_mut()
+            .ok_or("airbrake_deployment was given, but the rocket has no airbrake")?;
+        airbrake.deployment = deployment;
+        if let Some(actuator) = airbrake.actuator.as_mut() {
+            actuator.initial_position = deployment;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::{AdaptiveTimeStep, FixedTimeStep};
+    use crate::simulation;
+    use approx::assert_abs_diff_eq;
+
+    fn make_rocket_properties() -> RocketProperties {
+        RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2)
+    }
+
+    fn one_dof(altitude: f64, velocity: f64) -> InitialCondition {
+        InitialCondition::OneDOF(InitialState1DOF::new(altitude, velocity))
+    }
+
+    #[test]
+    fn matches_a_fresh_prediction() {
+        let solver = OdeSolver::RK3(FixedTimeStep::new(0.01));
+        let mut predictor =
+            ApogeePredictor::new(make_rocket_properties(), solver.clone(), false, 100_000);
+
+        for (time, altitude, velocity) in [(0.0, 0.0, 150.0), (0.5, 70.0, 130.0)] {
+            let expected = simulation::apogee(
+                one_dof(altitude, velocity).to_state(make_rocket_properties()),
+                solver.clone(),
+                100_000,
+            );
+            assert_eq!(
+                predictor.predict(time, one_dof(altitude, velocity)),
+                expected
+            );
+        }
+        assert_eq!(predictor.last_time, 0.5);
+    }
+
+    #[test]
+    fn warm_start_reuses_the_adapted_step_size() {
+        let solver = OdeSolver::RK45(AdaptiveTimeStep::default());
+        let mut predictor =
+            ApogeePredictor::new(make_rocket_properties(), solver.clone(), false, 100_000);
+        let cold = predictor.predict(0.0, one_dof(0.0, 150.0));
+        assert_ne!(predictor.step_size, solver.step_size());
+
+        // The warm started prediction agrees with a cold one to within the solver's tolerance
+        let warm = predictor.predict(0.01, one_dof(0.0, 150.0));
+        assert_abs_diff_eq!(warm, cold, epsilon = 0.1);
+
+        // Going back in time starts a new flight from a cold start
+        assert_eq!(predictor.predict(0.0, one_dof(0.0, 150.0)), cold);
+    }
+
+    #[test]
+    fn airbrake_deployment_requires_an_airbrake() {
+        let solver = OdeSolver::RK45(AdaptiveTimeStep::default());
+        let mut predictor = ApogeePredictor::new(make_rocket_properties(), solver, true, 100_000);
+        assert!(predictor.set_airbrake_deployment(0.5).is_err());
+
+        // Past apogee, the current altitude is the apogee
+        let state =
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 500.0, 1.5, 1.0, -2.0, 0.0));
+        assert_eq!(predictor.predict(10.0, state), 500.0);
+    }
+
+    #[test]
+    fn expired_deadline_returns_an_estimate() {
+        let solver = OdeSolver::RK45(AdaptiveTimeStep::default());
+        let mut predictor = ApogeePredictor::new(make_rocket_properties(), solver, false, 100_000)
+            .with_deadline(Some(0.0));
+        let estimate = predictor.predict(0.0, one_dof(100.0, 150.0));
+
+        assert_eq!(predictor.status, PredictionStatus::BudgetExhausted);
+        assert!(estimate > 100.0);
+        assert!(estimate < 100.0 + 150.0_f64.powi(2) / (2.0 * 9.80665));
+    }
+}
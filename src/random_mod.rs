@@ -0,0 +1,205 @@
+use nalgebra::DVector;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::f64::consts::TAU;
+
+/// Probability distribution of an uncertain parameter, e.g. the prior of a fitted parameter.
+#[pyclass]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Distribution {
+    kind: DistributionKind,
+    /// Lower bound or mean
+    a: f64,
+    /// Upper bound or standard deviation
+    b: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DistributionKind {
+    Uniform,
+    Normal,
+}
+
+#[pymethods]
+impl Distribution {
+    /// Uniform distribution between `low` and `high`.
+    #[staticmethod]
+    pub fn uniform(low: f64, high: f64) -> PyResult<Self> {
+        Self::new_uniform(low, high).map_err(PyValueError::new_err)
+    }
+
+    /// Normal distribution with the given mean and standard deviation.
+    #[staticmethod]
+    pub fn normal(mean: f64, std: f64) -> PyResult<Self> {
+        Self::new_normal(mean, std).map_err(PyValueError::new_err)
+    }
+
+    /// Mean of the distribution.
+    #[getter]
+    pub fn mean(&self) -> f64 {
+        match self.kind {
+            DistributionKind::Uniform => (self.a + self.b) / 2.0,
+            DistributionKind::Normal => self.a,
+        }
+    }
+
+    /// Standard deviation of the distribution.
+    #[getter]
+    pub fn std(&self) -> f64 {
+        match self.kind {
+            DistributionKind::Uniform => (self.b - self.a) / 12.0_f64.sqrt(),
+            DistributionKind::Normal => self.b,
+        }
+    }
+}
+
+impl Distribution {
+    pub(crate) fn new_uniform(low: f64, high: f64) -> Result<Self, String> {
+        if low >= high || low.is_nan() || high.is_nan() {
+            return Err("The lower bound must be below the upper bound".to_string());
+        }
+        Ok(Self {
+            kind: DistributionKind::Uniform,
+            a: low,
+            b: high,
+        })
+    }
+
+    pub(crate) fn new_normal(mean: f64, std: f64) -> Result<Self, String> {
+        if std <= 0.0 || std.is_nan() {
+            return Err("The standard deviation must be positive".to_string());
+        }
+        Ok(Self {
+            kind: DistributionKind::Normal,
+            a: mean,
+            b: std,
+        })
+    }
+
+    /// Draws a sample from the distribution.
+    pub(crate) fn sample(&self, rng: &mut Rng) -> f64 {
+        match self.kind {
+            DistributionKind::Uniform => self.a + (self.b - self.a) * rng.uniform(),
+            DistributionKind::Normal => self.a + self.b * rng.normal(),
+        }
+    }
+
+    /// Log of the probability density, up to a constant. Negative infinity outside the support.
+    pub(crate) fn log_density(&self, x: f64) -> f64 {
+        match self.kind {
+            DistributionKind::Uniform if (self.a..=self.b).contains(&x) => 0.0,
+            DistributionKind::Uniform => f64::NEG_INFINITY,
+            DistributionKind::Normal => -0.5 * ((x - self.a) / self.b).powi(2),
+        }
+    }
+}
+
+/// Small, fast pseudo-random number generator (xoshiro256**), seeded with SplitMix64 so that
+/// stochastic methods give the same results for the same seed.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    s: [u64; 4],
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        let mut state = seed;
+        let mut split_mix = || {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        Self {
+            s: [split_mix(), split_mix(), split_mix(), split_mix()],
+        }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        result
+    }
+
+    /// Uniformly distributed number in [0, 1).
+    pub(crate) fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Uniformly distributed index in [0, n).
+    pub(crate) fn index(&mut self, n: usize) -> usize {
+        ((self.uniform() * n as f64) as usize).min(n - 1)
+    }
+
+    /// Standard normally distributed number, from the Box-Muller transform.
+    pub(crate) fn normal(&mut self) -> f64 {
+        // 1 - uniform is in (0, 1], which keeps the log finite
+        let radius = (-2.0 * (1.0 - self.uniform()).ln()).sqrt();
+        radius * (TAU * self.uniform()).cos()
+    }
+
+    /// Vector of independent standard normally distributed numbers.
+    pub(crate) fn normal_vector(&mut self, n: usize) -> DVector<f64> {
+        DVector::from_fn(n, |_, _| self.normal())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let (mut a, mut b, mut c) = (Rng::new(1), Rng::new(1), Rng::new(2));
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(first, (0..5).map(|_| c.next_u64()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn distributions_have_expected_moments() {
+        let mut rng = Rng::new(42);
+        let n = 100_000;
+
+        let uniform: Vec<f64> = (0..n).map(|_| rng.uniform()).collect();
+        assert!(uniform.iter().all(|u| (0.0..1.0).contains(u)));
+        assert_abs_diff_eq!(uniform.iter().sum::<f64>() / n as f64, 0.5, epsilon = 0.01);
+
+        let normal: Vec<f64> = (0..n).map(|_| rng.normal()).collect();
+        let mean = normal.iter().sum::<f64>() / n as f64;
+        let variance = normal.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n as f64;
+        assert_abs_diff_eq!(mean, 0.0, epsilon = 0.01);
+        assert_abs_diff_eq!(variance, 1.0, epsilon = 0.02);
+    }
+
+    #[test]
+    fn distribution_densities_and_moments() {
+        let uniform = Distribution::new_uniform(1.0, 3.0).unwrap();
+        assert_eq!(uniform.log_density(2.0), 0.0);
+        assert_eq!(uniform.log_density(3.5), f64::NEG_INFINITY);
+        assert_abs_diff_eq!(uniform.std(), 2.0 / 12.0_f64.sqrt());
+        let normal = Distribution::new_normal(-2.0, 0.5).unwrap();
+        assert_eq!(normal.mean(), -2.0);
+        assert_abs_diff_eq!(normal.log_density(-1.0), -2.0);
+        assert!(Distribution::new_uniform(1.0, 1.0).is_err());
+        assert!(Distribution::new_normal(0.0, 0.0).is_err());
+
+        let mut rng = Rng::new(7);
+        for distribution in [uniform, normal] {
+            let samples: Vec<f64> = (0..50_000).map(|_| distribution.sample(&mut rng)).collect();
+            let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+            let variance =
+                samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+            assert_abs_diff_eq!(mean, distribution.mean(), epsilon = 0.01);
+            assert_abs_diff_eq!(variance.sqrt(), distribution.std(), epsilon = 0.01);
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use crate::derived_mod::DerivedChannel;
+use crate::filter_mod::matrix_to_pyarray;
+use crate::ode::{OdeMethod, OdeSolver};
+use crate::rocket::Rocket;
+use crate::simdata_mod::SimulationData;
+use crate::simulation::{PredictionStatus, Simulation};
+use crate::state::State;
+use crate::summary_mod::FlightSummary;
+use nalgebra::DMatrix;
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Name and unit of each logged column of the 1DOF model
+const COLUMNS_1DOF: [(&str, &str); 3] = [
+    ("altitude", "m"),
+    ("vertical_velocity", "m/s"),
+    ("vertical_acceleration", "m/s^2"),
+];
+/// Name and unit of each logged column of the 3DOF model
+const COLUMNS_3DOF: [(&str, &str); 9] = [
+    ("downrange", "m"),
+    ("altitude", "m"),
+    ("angle", "rad"),
+    ("horizontal_velocity", "m/s"),
+    ("vertical_velocity", "m/s"),
+    ("angular_rate", "rad/s"),
+    ("horizontal_acceleration", "m/s^2"),
+    ("vertical_acceleration", "m/s^2"),
+    ("angular_acceleration", "rad/s^2"),
+];
+/// Name and unit of each column of the control log
+const CONTROL_COLUMNS: [(&str, &str); 4] = [
+    ("time", "s"),
+    ("predicted_apogee", "m"),
+    ("command", "-"),
+    ("deployment", "-"),
+];
+
+/// Kind of event in a flight.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventKind {
+    /// The vertical velocity reached zero.
+    Apogee,
+    /// The motor burned out, at the rocket's `burn_time`. Flights which start at burnout have a
+    /// `burn_time` of 0, so this is their initial state.
+    Burnout,
+    /// The speed relative to the ground peaked.
+    MaxSpeed,
+    /// The Mach number peaked.
+    MaxMach,
+    /// The magnitude of the acceleration peaked.
+    MaxAcceleration,
+    /// The dynamic pressure peaked (max-Q).
+    MaxDynamicPressure,
+    /// The airbrakes were first commanded to deploy.
+    AirbrakeDeployment,
+    /// The rocket hit the ground.
+    Landing,
+}
+
+/// An event in a flight. Events are detected between the timesteps of the simulation, e.g. by
+/// backtracking to the apogee, rather than taken from the nearest logged row.
+#[pyclass(get_all)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlightEvent {
+    pub kind: EventKind,
+    /// Time of the event (s)
+    pub time: f64,
+    /// Altitude at the event (m)
+    pub altitude: f64,
+    /// Horizontal position at the event (m), always zero for the 1DOF model
+    pub downrange: f64,
+    /// Value of the quantity the event is about, e.g. the speed for `MaxSpeed` or the altitude
+    /// for `Apogee`
+    pub value: f64,
+}
+
+/// Logged flight of a simulation, with a column per logged quantity of the model and the
+/// settings the flight was simulated with.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct SimulationResult {
+    time: Vec<f64>,
+    /// Logged states, with a row per time and a column per entry of `columns`
+    states: DMatrix<f64>,
+    columns: &'static [(&'static str, &'static str)],
+    /// Logged derived channels, with a row per time and a column per entry of `derived_channels`
+    derived: DMatrix<f64>,
+    derived_channels: Vec<DerivedChannel>,
+    /// Control log of a controlled flight, with a column per entry of `CONTROL_COLUMNS`
+    control: Option<DMatrix<f64>>,
+    ode_solver: OdeSolver,
+    /// The rocket the flight was simulated with
+    #[pyo3(get)]
+    pub rocket: Rocket,
+    /// How the simulation ended: `Converged` if it reached its exit condition
+    #[pyo3(get)]
+    pub status: PredictionStatus,
+    /// Summary of the flight, including the descent from apogee to the ground which isn't logged
+    #[pyo3(get)]
+    pub summary: FlightSummary,
+}
+
+impl SimulationResult {
+    /// Collects the log of a finished simulation, which was run with `ode_solver` as it was
+    /// before the run, with the summary of the flight.
+    pub(crate) fn new(
+        rocket: Rocket,
+        ode_solver: OdeSolver,
+        simulation: &Simulation,
+        log: &SimulationData,
+        summary: FlightSummary,
+    ) -> Self {
+        let state = simulation.state();
+        let columns: &'static [(&str, &str)] = match state {
+            State::OneDOF(_) => &COLUMNS_1DOF,
+            State::ThreeDOF(_) => &COLUMNS_3DOF,
+        };
+        let states = DMatrix::from_fn(log.time_log.len(), columns.len(), |i, j| {
+            log.state_log[i][j]
+        });
+        let derived = DMatrix::from_fn(log.time_log.len(), log.derived_channels.len(), |i, j| {
+            log.derived_log[i][j]
+        });
+
+        Self {
+            time: log.time_log.clone(),
+            states,
+            columns,
+            derived,
+            derived_channels: log.derived_channels.clone(),
+            control: None,
+            ode_solver,
+            rocket,
+            status: simulation.status(),
+            summary,
+        }
+    }
+
+    /// Adds the control log of a controlled flight.
+    pub(crate) fn with_control(mut self, log: &SimulationData) -> Self {
+        self.control = Some(DMatrix::from_fn(
+            log.control_log.len(),
+            CONTROL_COLUMNS.len(),
+            |i, j| log.control_log[i][j],
+        ));
+        self
+    }
+
+    /// Values of the named state column or derived channel at each logged time.
+    pub(crate) fn column_values(&self, name: &str) -> Result<Vec<f64>, String> {
+        if let Some(j) = self.columns.iter().position(|(column, _)| *column == name) {
+            return Ok(self.states.column(j).iter().copied().collect());
+        }
+        if let Some(j) = self
+            .derived_channels
+            .iter()
+            .position(|channel| channel.name() == name)
+        {
+            return Ok(self.derived.column(j).iter().copied().collect());
+        }
+        let names: Vec<&str> = self
+            .columns
+            .iter()
+            .map(|(column, _)| *column)
+            .chain(self.derived_channels.iter().map(|channel| channel.name()))
+            .collect();
+        Err(format!(
+            "Unknown column {name:?}, expected one of {names:?}"
+        ))
+    }
+
+    /// Speed relative to the ground at each logged time.
+    pub(crate) fn speed_values(&self) -> Vec<f64> {
+        let vertical = self.column_values("vertical_velocity").unwrap();
+        match self.column_values("horizontal_velocity") {
+            Ok(horizontal) => horizontal
+                .iter()
+                .zip(&vertical)
+                .map(|(vx, vy)| vx.hypot(*vy))
+                .collect(),
+            Err(_) => vertical.iter().map(|v| v.abs()).collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl SimulationResult {
+    /// Logged times (s).
+    #[getter]
+    pub fn time<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.time.to_pyarray(py)
+    }
+
+    /// Logged states, with a row per time and a column per entry of `columns`.
+    #[getter]
+    pub fn states<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.states)
+    }
+
+    /// Name of each column of `states`.
+    #[getter]
+    pub fn columns(&self) -> Vec<&'static str> {
+        self.columns.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Unit of each column of `states`.
+    #[getter]
+    pub fn units(&self) -> Vec<&'static str> {
+        self.columns.iter().map(|(_, unit)| *unit).collect()
+    }
+
+    /// Logged derived channels, with a row per time and a column per entry of
+    /// `derived_channels`. Has no columns if no channels were requested.
+    #[getter]
+    pub fn derived<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.derived)
+    }
+
+    /// The derived channels which were logged, in the column order of `derived`.
+    #[getter]
+    pub fn derived_channels(&self) -> Vec<DerivedChannel> {
+        self.derived_channels.clone()
+    }
+
+    /// Name of each column of `derived`.
+    #[getter]
+    pub fn derived_columns(&self) -> Vec<&'static str> {
+        self.derived_channels
+            .iter()
+            .map(|channel| channel.name())
+            .collect()
+    }
+
+    /// Unit of each column of `derived`.
+    #[getter]
+    pub fn derived_units(&self) -> Vec<&'static str> {
+        self.derived_channels
+            .iter()
+            .map(|channel| channel.unit())
+            .collect()
+    }
+
+    /// Values of the named column of `states` or `derived` at each logged time.
+    pub fn column<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let values = self.column_values(name).map_err(PyValueError::new_err)?;
+        Ok(values.to_pyarray(py))
+    }
+
+    /// Altitude (m) at each logged time.
+    #[getter]
+    pub fn altitude<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.column_values("altitude").unwrap().to_pyarray(py)
+    }
+
+    /// Vertical velocity (m/s) at each logged time.
+    #[getter]
+    pub fn vertical_velocity<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.column_values("vertical_velocity")
+            .unwrap()
+            .to_pyarray(py)
+    }
+
+    /// Vertical acceleration (m/s^2) at each logged time.
+    #[getter]
+    pub fn vertical_acceleration<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.column_values("vertical_acceleration")
+            .unwrap()
+            .to_pyarray(py)
+    }
+
+    /// Horizontal position (m) at each logged time, which is always zero for the 1DOF model.
+    #[getter]
+    pub fn downrange<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.column_values("downrange")
+            .unwrap_or_else(|_| vec![0.0; self.time.len()])
+            .to_pyarray(py)
+    }
+
+    /// Speed (m/s) at each logged time.
+    #[getter]
+    pub fn speed<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.speed_values().to_pyarray(py)
+    }
+
+    /// Control log of a controlled flight, with a row per controller update and a column per
+    /// entry of `control_columns`, or None for an uncontrolled flight.
+    #[getter]
+    pub fn control<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyArray2<f64>>> {
+        self.control
+            .as_ref()
+            .map(|control| matrix_to_pyarray(py, control))
+    }
+
+    /// Name of each column of `control`.
+    #[getter]
+    pub fn control_columns(&self) -> Vec<&'static str> {
+        CONTROL_COLUMNS.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// The integration method the flight was simulated with.
+    #[getter]
+    pub fn integration_method(&self) -> OdeMethod {
+        self.ode_solver.method()
+    }
+
+    /// The time step configuration the flight was simulated with.
+    #[getter]
+    pub fn timestep_config<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        match &self.ode_solver {
+            OdeSolver::Euler(fixed) | OdeSolver::RK3(fixed) => {
+                Ok(Bound::new(py, *fixed)?.into_any())
+            }
+            OdeSolver::RK45(adaptive) => Ok(Bound::new(py, *adaptive)?.into_any()),
+        }
+    }
+
+    /// Events of the flight in time order, from the summary.
+    #[getter]
+    pub fn events(&self) -> Vec<FlightEvent> {
+        self.summary.events()
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.time.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::FixedTimeStep;
+    use crate::rocket::RocketProperties;
+    use crate::simulation::SimulationExitCondition;
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+
+    fn simulate(state: State, derived_channels: Vec<DerivedChannel>) -> SimulationResult {
+        let rocket = Rocket {
+            rocket_properties: state_properties(),
+        };
+        let ode_solver = OdeSolver::RK3(FixedTimeStep::new(0.01));
+        let mut simulation = Simulation::new(
+            state,
+            ode_solver.clone(),
+            SimulationExitCondition::ApogeeReached,
+            100_000,
+        )
+        .with_event_detection();
+        let mut log = SimulationData::with_channels(derived_channels);
+        simulation.run(&mut log, false, true);
+        let summary = simulation.flight_summary(ode_solver.clone());
+        SimulationResult::new(rocket, ode_solver, &simulation, &log, summary)
+    }
+
+    fn state_properties() -> RocketProperties {
+        RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2)
+    }
+
+    #[test]
+    fn one_dof_result_has_named_columns_without_padding() {
+        let result = simulate(
+            State::new_1dof(state_properties(), InitialState1DOF::new(0.0, 150.0)),
+            Vec::new(),
+        );
+        assert_eq!(result.states.ncols(), 3);
+        assert_eq!(result.states.nrows(), result.time.len());
+        assert_eq!(
+            result.columns(),
+            ["altitude", "vertical_velocity", "vertical_acceleration"]
+        );
+        assert_eq!(result.units(), ["m", "m/s", "m/s^2"]);
+        assert_eq!(result.integration_method(), OdeMethod::RK3);
+        assert!(result.control.is_none());
+        assert_eq!(result.derived.shape(), (result.time.len(), 0));
+
+        let altitude = result.column_values("altitude").unwrap();
+        assert_eq!(altitude[0], 0.0);
+        assert!(result.column_values("downrange").is_err());
+        assert_abs_diff_eq!(result.speed_values()[0], 150.0);
+
+        // The apogee event is at the backtracked state, past the last logged row
+        assert_eq!(result.status, PredictionStatus::Converged);
+        let apogee = result.summary.apogee.unwrap();
+        assert_eq!(apogee.kind, EventKind::Apogee);
+        assert_eq!(apogee.value, apogee.altitude);
+        assert!(apogee.time >= *result.time.last().unwrap());
+        assert!(apogee.altitude >= *altitude.last().unwrap());
+    }
+
+    #[test]
+    fn three_dof_result_has_all_logged_columns() {
+        let result = simulate(
+            State::new_3dof(
+                state_properties(),
+                InitialState3DOF::new(0.0, 0.0, 0.0, 3.0, 150.0, 0.0),
+            ),
+            Vec::new(),
+        );
+        assert_eq!(result.states.ncols(), 9);
+        assert_eq!(result.columns()[1], "altitude");
+        assert_eq!(result.column_values("horizontal_velocity").unwrap()[0], 3.0);
+        assert_abs_diff_eq!(result.speed_values()[0], 3.0_f64.hypot(150.0));
+
+        // The events run from burnout to the landing after the logged ascent
+        let events = result.events();
+        assert_eq!(events.len(), 7);
+        assert_eq!(events[0].kind, EventKind::Burnout);
+        assert_eq!(events[6].kind, EventKind::Landing);
+        assert!(events.windows(2).all(|pair| pair[0].time <= pair[1].time));
+
+        // Running out of iterations leaves no apogee event
+        let mut simulation = Simulation::new(
+            State::new_3dof(
+                state_properties(),
+                InitialState3DOF::new(0.0, 0.0, 0.0, 3.0, 150.0, 0.0),
+            ),
+            OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            SimulationExitCondition::ApogeeReached,
+            10,
+        );
+        let mut log = SimulationData::new();
+        simulation.run(&mut log, false, true);
+        let rocket = Rocket {
+            rocket_properties: state_properties(),
+        };
+        let result = SimulationResult::new(
+            rocket,
+            OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            &simulation,
+            &log,
+            simulation.flight_summary(OdeSolver::RK3(FixedTimeStep::new(0.01))),
+        );
+        assert_eq!(result.status, PredictionStatus::BudgetExhausted);
+        assert!(result.events().is_empty());
+        assert_eq!(result.__len__(), 10);
+    }
+
+    #[test]
+    fn derived_channels_are_returned_by_name() {
+        let result = simulate(
+            State::new_1dof(state_properties(), InitialState1DOF::new(0.0, 150.0)),
+            vec![DerivedChannel::DragForce, DerivedChannel::Mach],
+        );
+        assert_eq!(result.derived.ncols(), 2);
+        assert_eq!(result.derived.nrows(), result.time.len());
+        assert_eq!(result.derived_columns(), ["drag_force", "mach"]);
+        assert_eq!(result.derived_units(), ["N", "-"]);
+
+        // The drag is logged at the state of each row, which also makes the acceleration current
+        let drag = result.column_values("drag_force").unwrap();
+        assert_abs_diff_eq!(drag[0], 0.5 * 1.225 * 150.0_f64.powi(2) * 0.5 * 0.0182);
+        let acceleration = result.column_values("vertical_acceleration").unwrap();
+        assert_abs_diff_eq!(acceleration[0], -drag[0] / 15.0 - 9.80665, epsilon = 1e-12);
+
+        // Mach and drag fall off towards apogee
+        let mach = result.column_values("mach").unwrap();
+        assert_abs_diff_eq!(mach[0], 150.0 / 340.294);
+        assert!(mach.last().unwrap() < &0.01);
+        assert!(result.column_values("lift_force").is_err());
+    }
+}
@@ -1,16 +1,51 @@
-use crate::constants::simulation_constants::{DATA_LENGTH, MAX_ITERATIONS};
+use crate::actuator_mod::Actuator;
+use crate::aero_mod::{AeroTable, SkinFriction};
+use crate::airbrake_mod::Airbrake;
+use crate::batch_mod::ApogeeBatch;
+use crate::constants::aero_constants::DEFAULT_ESTIMATE_VELOCITY_M_S;
+use crate::constants::simulation_constants::{CONTROL_DATA_LENGTH, DATA_LENGTH, MAX_ITERATIONS};
+use crate::constants::solver_constants::{
+    DEFAULT_ANGLE_TOLERANCE_RAD, DEFAULT_APOGEE_TOLERANCE_M, DEFAULT_FIT_ITERATIONS,
+    DEFAULT_FIT_TOLERANCE, DEFAULT_MAX_EVALUATIONS, DEFAULT_MAX_RAIL_ANGLE_RAD,
+    DEFAULT_SENSITIVITY_STEP,
+};
+use crate::control_mod::{ControlLoop, Controller};
+use crate::derived_mod::DerivedChannel;
+use crate::dispersion_mod::{
+    DispersedParameter, DispersionOutput, DispersionProblem, MonteCarloResult,
+    DEFAULT_MONTE_CARLO_RUNS,
+};
+use crate::dual_mod::Real;
+use crate::filter_mod::matrix_to_pyarray;
+use crate::fitting_mod::mcmc::{PosteriorSamples, SamplerOptions};
+use crate::fitting_mod::{FitParameter, FitProblem, FitResult, FlightData};
+use crate::geometry_mod::RocketGeometry;
 use crate::ode::{OdeMethod, OdeSolver, TimeStepOptions};
+use crate::random_mod::Distribution;
+use crate::result_mod::SimulationResult;
 use crate::rocket;
+use crate::sensitivity_mod::{Sensitivity, SensitivityMethod, SensitivityProblem};
 use crate::simdata_mod::SimulationData;
-use crate::simulation::{Simulation, SimulationExitCondition};
-use crate::state::{InitialState1DOF, InitialState3DOF, State};
-use numpy::{ndarray::Array2, PyArray1, PyArray2, ToPyArray};
+use crate::simulation::{ApogeePrediction, Simulation, SimulationExitCondition};
+use crate::sobol_mod::{
+    SobolIndices, SobolOptions, SobolProblem, DEFAULT_BOOTSTRAP_RESAMPLES,
+    DEFAULT_CONFIDENCE_LEVEL, DEFAULT_SOBOL_SAMPLES,
+};
+use crate::state::state_vector::AuxVector;
+use crate::state::{InitialCondition, InitialState1DOF, InitialState3DOF, State};
+use crate::targeting_mod::{
+    ApogeeSearch, DesignSearch, LaunchObjective, LaunchOptimum, LaunchSearch, SolveReport,
+};
+use numpy::{ndarray::Array2, PyArray1, PyArray2, PyReadonlyArray2, ToPyArray};
+use pyo3::exceptions::{PyRuntimeError, PyUserWarning, PyValueError};
 use pyo3::prelude::*;
 use pyo3::Bound;
+use std::ffi::CString;
+use std::time::{Duration, Instant};
 
 /// Represents the physical properties of the rocket used in the simulation.
 #[pyclass(get_all, set_all)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct RocketProperties {
     /// Mass of the rocket (kg)
     pub mass: f64,
@@ -18,7 +53,7 @@ pub struct RocketProperties {
     pub cd: f64,
     /// Reference area for drag (m^2)
     pub area_drag: f64,
-    /// Reference area for lift (m^2)
+    /// Reference area for lift, normal force and the pitch damping moment (m^2)
     pub area_lift: f64,
     /// Moment of inertia about the z-axis (kg*m^2)
     pub moment_of_inertia: f64,
@@ -26,6 +61,50 @@ pub struct RocketProperties {
     pub stab_margin_dimensional: f64,
     /// Lift coefficient slope (per radian)
     pub cl_a: f64,
+    /// Pitch damping moment coefficient derivative Cmq (per radian), negative when damping
+    pub cmq: f64,
+    /// Reference length for non-dimensional moment coefficients such as `cmq`, typically the body
+    /// diameter (m)
+    pub reference_length: f64,
+    /// Propellant mass flow rate used for thrust-jet damping while the motor burns (kg/s)
+    pub mass_flow_rate: f64,
+    /// Time from the start of the simulation to motor burnout (s). Thrust-jet damping only acts
+    /// before it, so it stays 0 for simulations which start at burnout.
+    pub burn_time: f64,
+    /// Distance from the center of gravity to the nozzle exit (m)
+    pub nozzle_distance: f64,
+    /// Optional Reynolds number dependent skin friction model. When set, `cd` only represents
+    /// the pressure and base drag.
+    pub skin_friction: Option<SkinFriction>,
+    /// Optional Mach and angle of attack dependent aerodynamic tables. When set, they replace
+    /// `cd`, `cl_a` and `stab_margin_dimensional`.
+    pub aero_table: Option<AeroTable>,
+    /// Distance from the nose tip to the center of gravity (m), used with the CP locations of
+    /// `aero_table`
+    pub cg_location: f64,
+    /// Optional airbrake add-on which increases the drag as it deploys
+    pub airbrake: Option<Airbrake>,
+    /// Horizontal wind speed (m/s), blowing towards +x. Only the 3DOF model sees the wind.
+    pub wind_speed: f64,
+}
+
+/// The scalar rocket properties the models depend on, as the scalar type the models are evaluated
+/// with. With dual numbers, the models are differentiated with respect to them.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ModelParameters<T> {
+    pub(crate) mass: T,
+    pub(crate) cd: T,
+    pub(crate) area_drag: T,
+    pub(crate) area_lift: T,
+    pub(crate) moment_of_inertia: T,
+    pub(crate) stab_margin_dimensional: T,
+    pub(crate) cl_a: T,
+    pub(crate) cmq: T,
+    pub(crate) reference_length: T,
+    pub(crate) mass_flow_rate: T,
+    pub(crate) nozzle_distance: T,
+    pub(crate) cg_location: T,
+    pub(crate) wind_speed: T,
 }
 
 impl RocketProperties {
@@ -47,12 +126,305 @@ impl RocketProperties {
             moment_of_inertia,
             stab_margin_dimensional,
             cl_a,
+            cmq: 0.0,
+            reference_length: 0.0,
+            mass_flow_rate: 0.0,
+            burn_time: 0.0,
+            nozzle_distance: 0.0,
+            skin_friction: None,
+            aero_table: None,
+            cg_location: 0.0,
+            airbrake: None,
+            wind_speed: 0.0,
+        }
+    }
+
+    /// Drag coefficient and drag reference area including the airbrake increments, if the rocket
+    /// has airbrakes. `aux` holds the auxiliary states of the airbrake actuator.
+    pub(crate) fn drag_with_airbrake<T: Real>(
+        &self,
+        cd: T,
+        area_drag: T,
+        mach: T,
+        aux: &AuxVector,
+    ) -> (T, T) {
+        match &self.airbrake {
+            Some(airbrake) => airbrake.apply(cd, area_drag, mach, aux),
+            None => (cd, area_drag),
+        }
+    }
+
+    /// The scalar properties the models depend on, as constants of the scalar type.
+    pub(crate) fn parameters<T: Real>(&self) -> ModelParameters<T> {
+        ModelParameters {
+            mass: T::constant(self.mass),
+            cd: T::constant(self.cd),
+            area_drag: T::constant(self.area_drag),
+            area_lift: T::constant(self.area_lift),
+            moment_of_inertia: T::constant(self.moment_of_inertia),
+            stab_margin_dimensional: T::constant(self.stab_margin_dimensional),
+            cl_a: T::constant(self.cl_a),
+            cmq: T::constant(self.cmq),
+            reference_length: T::constant(self.reference_length),
+            mass_flow_rate: T::constant(self.mass_flow_rate),
+            nozzle_distance: T::constant(self.nozzle_distance),
+            cg_location: T::constant(self.cg_location),
+            wind_speed: T::constant(self.wind_speed),
+        }
+    }
+
+    /// Actuator whose states are integrated along with the flight dynamics, if there is one.
+    pub(crate) fn actuator(&self) -> Option<&Actuator> {
+        self.airbrake.as_ref().and_then(|a| a.actuator.as_ref())
+    }
+
+    /// Auxiliary states at the start of a simulation.
+    pub(crate) fn initial_aux_state(&self) -> AuxVector {
+        self.actuator()
+            .map_or(AuxVector::zeros(), |actuator| actuator.initial_state())
+    }
+
+    /// Time derivatives of the auxiliary states.
+    pub(crate) fn aux_derivatives(&self, aux: &AuxVector) -> AuxVector {
+        match (&self.airbrake, self.actuator()) {
+            (Some(airbrake), Some(actuator)) => actuator.derivatives(aux, airbrake.deployment),
+            _ => AuxVector::zeros(),
+        }
+    }
+
+    /// Checks for fields which affect the 1DOF model but were left at zero, returning a warning
+    /// message for each of them.
+    pub(crate) fn validate_1dof(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut check = |value: f64, name: &str, effect: &str| {
+            if value == 0.0 {
+                warnings.push(format!("{name} is zero, {effect}"));
+            }
+        };
+
+        check(self.mass, "mass", "so the acceleration is undefined");
+        check(self.area_drag, "area_drag", "so there is no drag");
+        if self.aero_table.is_none() {
+            check(self.cd, "cd", "so there is no drag");
+        }
+        warnings
+    }
+
+    /// Checks for fields which affect the 3DOF model but were left at zero, returning a warning
+    /// message for each of them.
+    pub(crate) fn validate_3dof(&self) -> Vec<String> {
+        let mut warnings = self.validate_1dof();
+        let mut check = |value: f64, name: &str, effect: &str| {
+            if value == 0.0 {
+                warnings.push(format!("{name} is zero, {effect}"));
+            }
+        };
+
+        check(
+            self.moment_of_inertia,
+            "moment_of_inertia",
+            "so the angular acceleration is undefined",
+        );
+        check(self.area_lift, "area_lift", "so there is no lift");
+        if self.aero_table.is_none() {
+            check(self.cl_a, "cl_a", "so there is no lift");
+            check(
+                self.stab_margin_dimensional,
+                "stab_margin_dimensional",
+                "so there is no restoring moment",
+            );
+        } else {
+            check(
+                self.cg_location,
+                "cg_location",
+                "so the center of gravity is at the nose tip",
+            );
+        }
+        if self.cmq != 0.0 {
+            check(
+                self.reference_length,
+                "reference_length",
+                "so cmq has no effect",
+            );
+        }
+        if self.mass_flow_rate != 0.0 {
+            check(
+                self.nozzle_distance,
+                "nozzle_distance",
+                "so mass_flow_rate has no effect",
+            );
+            check(
+                self.burn_time,
+                "burn_time",
+                "so mass_flow_rate has no effect",
+            );
+        }
+        warnings
+    }
+}
+
+impl Rocket {
+    /// Copies the rocket properties, overriding the airbrake deployment if one is given.
+    pub(crate) fn properties_with_deployment(
+        &self,
+        airbrake_deployment: Option<f64>,
+    ) -> Result<RocketProperties, String> {
+        let mut rocket_properties = self.rocket_properties.clone();
+        if let Some(deployment) = airbrake_deployment {
+            let airbrake = rocket_properties
+                .airbrake
+                .as_mut()
+                .ok_or("airbrake_deployment was given, but the rocket has no airbrake")?;
+            airbrake.deployment = deployment;
+        }
+        Ok(rocket_properties)
+    }
+}
+
+impl Rocket {
+    /// Predicts the apogee for each row of `initial_states`, whose rows are read like the first
+    /// columns of a state log of the model of `template`, and/or each row of parameter `values`.
+    #[allow(clippy::too_many_arguments)]
+    fn predict_apogee_batch<'py>(
+        &self,
+        py: Python<'py>,
+        template: InitialCondition,
+        initial_states: PyReadonlyArray2<f64>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        parameters: Option<Vec<DispersedParameter>>,
+        values: Option<PyReadonlyArray2<f64>>,
+        num_threads: Option<usize>,
+        max_iterations: u64,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let (width, warnings) = match template {
+            InitialCondition::OneDOF(_) => (2, self.rocket_properties.validate_1dof()),
+            InitialCondition::ThreeDOF(_) => (6, self.rocket_properties.validate_3dof()),
+        };
+        emit_warnings(py, warnings)?;
+        let initial_states = initial_states.as_array();
+        if initial_states.ncols() != width {
+            return Err(PyValueError::new_err(format!(
+                "initial_states must have {width} columns"
+            )));
         }
+        let rows = |array: numpy::ndarray::ArrayView2<f64>| -> Vec<Vec<f64>> {
+            array.rows().into_iter().map(|row| row.to_vec()).collect()
+        };
+        let batch = ApogeeBatch {
+            rocket_properties: self.rocket_properties.clone(),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            max_iterations,
+            initial_conditions: rows(initial_states)
+                .iter()
+                .map(|row| template.with_row(row))
+                .collect(),
+            parameters: parameters.unwrap_or_default(),
+            values: values.map_or_else(Vec::new, |values| rows(values.as_array())),
+        };
+
+        let apogees = py
+            .detach(|| batch.run(num_threads))
+            .map_err(PyValueError::new_err)?;
+        Ok(apogees.to_pyarray(py))
+    }
+
+    /// Runs an apogee prediction for either model, stopping at the deadline (s from now) if one
+    /// is given.
+    #[allow(clippy::too_many_arguments)]
+    fn predict_apogee(
+        &self,
+        py: Python<'_>,
+        initial_condition: InitialCondition,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        max_iterations: u64,
+        print_output: bool,
+        airbrake_deployment: Option<f64>,
+        deadline: Option<f64>,
+    ) -> PyResult<ApogeePrediction> {
+        let start = Instant::now();
+        let rocket_properties = self
+            .properties_with_deployment(airbrake_deployment)
+            .map_err(PyValueError::new_err)?;
+        let warnings = match initial_condition {
+            InitialCondition::OneDOF(_) => rocket_properties.validate_1dof(),
+            InitialCondition::ThreeDOF(_) => rocket_properties.validate_3dof(),
+        };
+        emit_warnings(py, warnings)?;
+        let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
+        let deadline = deadline
+            .map(|seconds| Duration::try_from_secs_f64(seconds).map(|duration| start + duration))
+            .transpose()
+            .map_err(|_| {
+                PyValueError::new_err("deadline must be a non-negative number of seconds")
+            })?;
+
+        let state = initial_condition.to_state(rocket_properties);
+        let mut simulation = Simulation::new(
+            state,
+            ode_solver,
+            SimulationExitCondition::ApogeeReached,
+            max_iterations,
+        );
+        if let Some(deadline) = deadline {
+            simulation = simulation.with_deadline(deadline);
+        }
+
+        let mut log = SimulationData::new();
+        simulation.run(&mut log, print_output, false);
+
+        Ok(ApogeePrediction {
+            apogee: simulation.apogee_estimate(),
+            status: simulation.status(),
+            iterations: simulation.steps_taken,
+            elapsed: start.elapsed().as_secs_f64(),
+        })
     }
 }
 
+/// Runs a simulation to apogee with a closed-loop controller, returning the finished simulation
+/// with its logged data (including the given derived channels), or the error message of a failed
+/// controller.
+pub(crate) fn run_controlled_simulation(
+    state: State,
+    ode_solver: OdeSolver,
+    controller: Controller,
+    max_iterations: u64,
+    print_output: bool,
+    derived_channels: Vec<DerivedChannel>,
+) -> Result<(Simulation, SimulationData), String> {
+    let control_loop = ControlLoop::new(controller, &state)?;
+    let mut simulation = Simulation::new(
+        state,
+        ode_solver,
+        SimulationExitCondition::ApogeeReached,
+        max_iterations,
+    )
+    .with_controller(control_loop)
+    .with_event_detection();
+
+    let mut log = SimulationData::with_channels(derived_channels);
+    simulation.run(&mut log, print_output, true);
+
+    match simulation.controller_error() {
+        Some(error) => Err(format!("Controller failed: {error}")),
+        None => Ok((simulation, log)),
+    }
+}
+
+/// Emits a Python `UserWarning` for each validation message.
+pub(crate) fn emit_warnings(py: Python<'_>, warnings: Vec<String>) -> PyResult<()> {
+    let category = py.get_type::<PyUserWarning>();
+    for warning in warnings {
+        let message = CString::new(warning)?;
+        PyErr::warn(py, &category, &message, 1)?;
+    }
+    Ok(())
+}
+
 #[pyclass(get_all, set_all)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Rocket {
     pub rocket_properties: RocketProperties,
 }
@@ -60,6 +432,8 @@ pub struct Rocket {
 #[pymethods]
 impl Rocket {
     #[new]
+    #[pyo3(signature = (mass, cd, area_drag, area_lift, moment_of_inertia, stab_margin_dimensional, cl_a, cmq=0.0, reference_length=0.0, mass_flow_rate=0.0, nozzle_distance=0.0, skin_friction=None, aero_table=None, cg_location=0.0, airbrake=None, wind_speed=0.0, burn_time=0.0))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         mass: f64,
         cd: f64,
@@ -68,153 +442,1127 @@ impl Rocket {
         moment_of_inertia: f64,
         stab_margin_dimensional: f64,
         cl_a: f64,
+        cmq: f64,
+        reference_length: f64,
+        mass_flow_rate: f64,
+        nozzle_distance: f64,
+        skin_friction: Option<SkinFriction>,
+        aero_table: Option<AeroTable>,
+        cg_location: f64,
+        airbrake: Option<Airbrake>,
+        wind_speed: f64,
+        burn_time: f64,
     ) -> Self {
-        Self {
-            rocket_properties: RocketProperties::new(
-                mass,
-                cd,
-                area_drag,
-                area_lift,
-                moment_of_inertia,
-                stab_margin_dimensional,
-                cl_a,
-            ),
+        let mut rocket_properties = RocketProperties::new(
+            mass,
+            cd,
+            area_drag,
+            area_lift,
+            moment_of_inertia,
+            stab_margin_dimensional,
+            cl_a,
+        );
+        rocket_properties.cmq = cmq;
+        rocket_properties.reference_length = reference_length;
+        rocket_properties.mass_flow_rate = mass_flow_rate;
+        rocket_properties.nozzle_distance = nozzle_distance;
+        rocket_properties.skin_friction = skin_friction;
+        rocket_properties.aero_table = aero_table;
+        rocket_properties.cg_location = cg_location;
+        rocket_properties.airbrake = airbrake;
+        rocket_properties.wind_speed = wind_speed;
+        rocket_properties.burn_time = burn_time;
+
+        Self { rocket_properties }
+    }
+
+    /// Creates a rocket whose aerodynamic properties are estimated from its geometry with the
+    /// Barrowman method. The center of gravity is measured from the nose tip. With
+    /// `reynolds_dependent_friction`, the friction drag is split off of `cd` into a skin friction
+    /// model instead of being frozen at `velocity`.
+    #[staticmethod]
+    #[pyo3(signature = (geometry, mass, moment_of_inertia, cg_location, velocity=DEFAULT_ESTIMATE_VELOCITY_M_S, reynolds_dependent_friction=false))]
+    pub fn from_geometry(
+        geometry: &RocketGeometry,
+        mass: f64,
+        moment_of_inertia: f64,
+        cg_location: f64,
+        velocity: f64,
+        reynolds_dependent_friction: bool,
+    ) -> PyResult<Self> {
+        let estimate = geometry.estimate(cg_location, velocity)?;
+
+        let cd = if reynolds_dependent_friction {
+            estimate.cd - estimate.cd_friction - estimate.cd_interference
+        } else {
+            estimate.cd
+        };
+
+        let mut rocket_properties = RocketProperties::new(
+            mass,
+            cd,
+            estimate.reference_area,
+            estimate.reference_area,
+            moment_of_inertia,
+            estimate.stability_margin,
+            estimate.cn_alpha,
+        );
+        rocket_properties.cmq = estimate.cmq;
+        rocket_properties.reference_length = estimate.reference_length;
+        rocket_properties.cg_location = cg_location;
+        if reynolds_dependent_friction {
+            rocket_properties.skin_friction = Some(geometry.skin_friction());
         }
+
+        Ok(Self { rocket_properties })
     }
 
-    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false))]
+    /// Simulates a 1DOF flight from the initial state to apogee, returning the logged flight
+    /// with a summary of its events up to the landing. The `derived_channels` are logged
+    /// alongside the states.
+    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, derived_channels=None))]
     #[allow(clippy::too_many_arguments)]
-    #[allow(clippy::type_complexity)]
-    pub fn simulate_flight_1dof<'py>(
+    pub fn simulate_flight_1dof(
         &self,
-        py: Python<'py>,
+        py: Python<'_>,
         initial_state: InitialState1DOF,
         integration_method: OdeMethod,
         timestep_config: Option<TimeStepOptions>,
         max_iterations: u64,
         print_output: bool,
-    ) -> PyResult<(Bound<'py, PyArray1<f64>>, Bound<'py, PyArray2<f64>>)> {
+        derived_channels: Option<Vec<DerivedChannel>>,
+    ) -> PyResult<SimulationResult> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
         // Create the ODE solver based on the specified integration method and time step configuration
         let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
-        // Initialize the state of the rocket for a 1DOF simulation
-        let state = State::new_1dof(self.rocket_properties, initial_state);
+        let state = State::new_1dof(self.rocket_properties.clone(), initial_state);
 
         // Create a new simulation instance with the initialized state, ODE solver, and exit condition
         let mut simulation = Simulation::new(
             state,
-            ode_solver,
+            ode_solver.clone(),
             SimulationExitCondition::ApogeeReached,
             max_iterations,
-        );
+        )
+        .with_event_detection();
 
         // Run the simulation and log the results into our custom SimulationData struct
-        let mut log = SimulationData::new();
+        let mut log = SimulationData::with_channels(derived_channels.unwrap_or_default());
         simulation.run(&mut log, print_output, true);
+        let summary = simulation.flight_summary(ode_solver.clone());
 
-        // Then converts the logged time and state data into NumPy arrays to return to Python
-        let time_array = log.time_log.to_pyarray(py);
-        let rows = log.time_log.len();
-        // First flattens the 2D state log into a 1D vector, then reshapes it back into a 2D array
-        let flat_data: Vec<f64> = log.state_log.iter().flatten().copied().collect();
-        let matrix = Array2::from_shape_vec((rows, DATA_LENGTH), flat_data).unwrap();
-        let state_matrix = matrix.to_pyarray(py);
-
-        Ok((time_array, state_matrix))
+        Ok(SimulationResult::new(
+            self.clone(),
+            ode_solver,
+            &simulation,
+            &log,
+            summary,
+        ))
     }
 
-    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false))]
+    /// Simulates a 3DOF flight from the initial state to apogee, returning the logged flight
+    /// with a summary of its events up to the landing. The `derived_channels` are logged
+    /// alongside the states.
+    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, derived_channels=None))]
     #[allow(clippy::too_many_arguments)]
-    #[allow(clippy::type_complexity)]
-    pub fn simulate_flight_3dof<'py>(
+    pub fn simulate_flight_3dof(
         &self,
-        py: Python<'py>,
+        py: Python<'_>,
         initial_state: InitialState3DOF,
         integration_method: OdeMethod,
         timestep_config: Option<TimeStepOptions>,
         max_iterations: u64,
         print_output: bool,
-    ) -> PyResult<(Bound<'py, PyArray1<f64>>, Bound<'py, PyArray2<f64>>)> {
+        derived_channels: Option<Vec<DerivedChannel>>,
+    ) -> PyResult<SimulationResult> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        // Create the ODE solver based on the specified integration method and time step configuration
         let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
+        let state = State::new_3dof(self.rocket_properties.clone(), initial_state);
 
-        let state = State::new_3dof(self.rocket_properties, initial_state);
-
+        // Create a new simulation instance with the initialized state, ODE solver, and exit condition
         let mut simulation = Simulation::new(
             state,
-            ode_solver,
+            ode_solver.clone(),
             SimulationExitCondition::ApogeeReached,
             max_iterations,
-        );
-        let mut log = SimulationData::new();
-        simulation.run(&mut log, print_output, true);
+        )
+        .with_event_detection();
 
-        let time_array = log.time_log.to_pyarray(py);
-        let rows = log.time_log.len();
-        let flat_data: Vec<f64> = log.state_log.iter().flatten().copied().collect();
-        let matrix = Array2::from_shape_vec((rows, DATA_LENGTH), flat_data).unwrap();
-        let state_matrix = matrix.to_pyarray(py);
+        // Run the simulation and log the results into our custom SimulationData struct
+        let mut log = SimulationData::with_channels(derived_channels.unwrap_or_default());
+        simulation.run(&mut log, print_output, true);
+        let summary = simulation.flight_summary(ode_solver.clone());
 
-        Ok((time_array, state_matrix))
+        Ok(SimulationResult::new(
+            self.clone(),
+            ode_solver,
+            &simulation,
+            &log,
+            summary,
+        ))
     }
 
-    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false))]
+    /// Simulates a 1DOF flight with a closed-loop airbrake controller, returning the logged
+    /// flight like `simulate_flight_1dof` with a control log of one (time, predicted apogee,
+    /// command, actual deployment) row per controller update.
+    #[pyo3(signature = (initial_state, integration_method, controller, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, derived_channels=None))]
     #[allow(clippy::too_many_arguments)]
-    pub fn predict_apogee_1dof(
+    pub fn simulate_controlled_flight_1dof(
         &self,
+        py: Python<'_>,
         initial_state: InitialState1DOF,
         integration_method: OdeMethod,
+        controller: Controller,
         timestep_config: Option<TimeStepOptions>,
         max_iterations: u64,
         print_output: bool,
-    ) -> PyResult<f64> {
+        derived_channels: Option<Vec<DerivedChannel>>,
+    ) -> PyResult<SimulationResult> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
         let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
+        let state = State::new_1dof(self.rocket_properties.clone(), initial_state);
 
-        let state = State::new_1dof(self.rocket_properties, initial_state);
-
-        let mut simulation = Simulation::new(
+        let (simulation, log) = run_controlled_simulation(
             state,
-            ode_solver,
-            SimulationExitCondition::ApogeeReached,
+            ode_solver.clone(),
+            controller,
             max_iterations,
-        );
+            print_output,
+            derived_channels.unwrap_or_default(),
+        )
+        .map_err(PyRuntimeError::new_err)?;
+        let summary = simulation.flight_summary(ode_solver.clone());
 
-        let mut log = SimulationData::new();
-        simulation.run(&mut log, print_output, false);
+        Ok(
+            SimulationResult::new(self.clone(), ode_solver, &simulation, &log, summary)
+                .with_control(&log),
+        )
+    }
+
+    /// Simulates a 3DOF flight with a closed-loop airbrake controller, returning the logged
+    /// flight like `simulate_flight_3dof` with a control log of one (time, predicted apogee,
+    /// command, actual deployment) row per controller update.
+    #[pyo3(signature = (initial_state, integration_method, controller, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, derived_channels=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn simulate_controlled_flight_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        integration_method: OdeMethod,
+        controller: Controller,
+        timestep_config: Option<TimeStepOptions>,
+        max_iterations: u64,
+        print_output: bool,
+        derived_channels: Option<Vec<DerivedChannel>>,
+    ) -> PyResult<SimulationResult> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
+        let state = State::new_3dof(self.rocket_properties.clone(), initial_state);
+
+        let (simulation, log) = run_controlled_simulation(
+            state,
+            ode_solver.clone(),
+            controller,
+            max_iterations,
+            print_output,
+            derived_channels.unwrap_or_default(),
+        )
+        .map_err(PyRuntimeError::new_err)?;
+        let summary = simulation.flight_summary(ode_solver.clone());
 
-        const HEIGHT_COL: usize = 1;
+        Ok(
+            SimulationResult::new(self.clone(), ode_solver, &simulation, &log, summary)
+                .with_control(&log),
+        )
+    }
 
-        let max_height = log.get_val((log.time_log.len()) - 1, HEIGHT_COL);
+    /// Predicts the apogee with the 1DOF model. If `airbrake_deployment` is given, it
+    /// overrides the deployment of the rocket's airbrakes for this prediction. If `deadline` (s)
+    /// passes or `max_iterations` runs out before apogee, the best estimate so far is returned.
+    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, airbrake_deployment=None, deadline=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn predict_apogee_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        max_iterations: u64,
+        print_output: bool,
+        airbrake_deployment: Option<f64>,
+        deadline: Option<f64>,
+    ) -> PyResult<f64> {
+        let prediction = self.predict_apogee(
+            py,
+            InitialCondition::OneDOF(initial_state),
+            integration_method,
+            timestep_config,
+            max_iterations,
+            print_output,
+            airbrake_deployment,
+            deadline,
+        )?;
+        Ok(prediction.apogee)
+    }
 
-        Ok(max_height)
+    /// Same as `predict_apogee_1dof`, but also reports whether the prediction reached apogee,
+    /// ran out of its budget or diverged.
+    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, airbrake_deployment=None, deadline=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn predict_apogee_1dof_with_status(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        max_iterations: u64,
+        print_output: bool,
+        airbrake_deployment: Option<f64>,
+        deadline: Option<f64>,
+    ) -> PyResult<ApogeePrediction> {
+        self.predict_apogee(
+            py,
+            InitialCondition::OneDOF(initial_state),
+            integration_method,
+            timestep_config,
+            max_iterations,
+            print_output,
+            airbrake_deployment,
+            deadline,
+        )
     }
 
-    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false))]
+    /// Predicts the apogee with the 3DOF model. If `airbrake_deployment` is given, it
+    /// overrides the deployment of the rocket's airbrakes for this prediction. If `deadline` (s)
+    /// passes or `max_iterations` runs out before apogee, the best estimate so far is returned.
+    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, airbrake_deployment=None, deadline=None))]
     #[allow(clippy::too_many_arguments)]
     pub fn predict_apogee_3dof(
         &self,
+        py: Python<'_>,
         initial_state: InitialState3DOF,
         integration_method: OdeMethod,
         timestep_config: Option<TimeStepOptions>,
         max_iterations: u64,
         print_output: bool,
+        airbrake_deployment: Option<f64>,
+        deadline: Option<f64>,
     ) -> PyResult<f64> {
-        let ode_solver = OdeSolver::from_method(integration_method, timestep_config)?;
+        let prediction = self.predict_apogee(
+            py,
+            InitialCondition::ThreeDOF(initial_state),
+            integration_method,
+            timestep_config,
+            max_iterations,
+            print_output,
+            airbrake_deployment,
+            deadline,
+        )?;
+        Ok(prediction.apogee)
+    }
 
-        let state = State::new_3dof(self.rocket_properties, initial_state);
+    /// Same as `predict_apogee_3dof`, but also reports whether the prediction reached apogee,
+    /// ran out of its budget or diverged.
+    #[pyo3(signature = (initial_state, integration_method, timestep_config=None, max_iterations=MAX_ITERATIONS, print_output=false, airbrake_deployment=None, deadline=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn predict_apogee_3dof_with_status(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        max_iterations: u64,
+        print_output: bool,
+        airbrake_deployment: Option<f64>,
+        deadline: Option<f64>,
+    ) -> PyResult<ApogeePrediction> {
+        self.predict_apogee(
+            py,
+            InitialCondition::ThreeDOF(initial_state),
+            integration_method,
+            timestep_config,
+            max_iterations,
+            print_output,
+            airbrake_deployment,
+            deadline,
+        )
+    }
 
-        let mut simulation = Simulation::new(
-            state,
-            ode_solver,
-            SimulationExitCondition::ApogeeReached,
+    /// Predicts the apogee with the 1DOF model for a batch of flights on a pool of threads, with
+    /// the GIL released. Each row of `initial_states` is an initial state (altitude, vertical velocity), and each row
+    /// of `values` holds the values of `parameters` for a flight. Either may have a single row,
+    /// which is shared by the whole batch.
+    #[pyo3(signature = (initial_states, integration_method, timestep_config=None, parameters=None, values=None, num_threads=None, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn predict_apogee_1dof_batch<'py>(
+        &self,
+        py: Python<'py>,
+        initial_states: PyReadonlyArray2<f64>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        parameters: Option<Vec<DispersedParameter>>,
+        values: Option<PyReadonlyArray2<f64>>,
+        num_threads: Option<usize>,
+        max_iterations: u64,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        self.predict_apogee_batch(
+            py,
+            InitialCondition::OneDOF(InitialState1DOF::new(0.0, 0.0)),
+            initial_states,
+            integration_method,
+            timestep_config,
+            parameters,
+            values,
+            num_threads,
+            max_iterations,
+        )
+    }
+
+    /// Predicts the apogee with the 3DOF model for a batch of flights on a pool of threads, with
+    /// the GIL released. Each row of `initial_states` is an initial state (x, y, angle, vx, vy, angular rate), and each row
+    /// of `values` holds the values of `parameters` for a flight. Either may have a single row,
+    /// which is shared by the whole batch.
+    #[pyo3(signature = (initial_states, integration_method, timestep_config=None, parameters=None, values=None, num_threads=None, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn predict_apogee_3dof_batch<'py>(
+        &self,
+        py: Python<'py>,
+        initial_states: PyReadonlyArray2<f64>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        parameters: Option<Vec<DispersedParameter>>,
+        values: Option<PyReadonlyArray2<f64>>,
+        num_threads: Option<usize>,
+        max_iterations: u64,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        self.predict_apogee_batch(
+            py,
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+            initial_states,
+            integration_method,
+            timestep_config,
+            parameters,
+            values,
+            num_threads,
+            max_iterations,
+        )
+    }
+
+    /// Finds the constant airbrake deployment (0 to 1) which hits `target_apogee` with the 1DOF
+    /// model, by root-finding over at most `max_evaluations` apogee predictions.
+    #[pyo3(signature = (initial_state, target_apogee, integration_method, timestep_config=None, tolerance=DEFAULT_APOGEE_TOLERANCE_M, max_evaluations=DEFAULT_MAX_EVALUATIONS, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn deployment_for_apogee_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        target_apogee: f64,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        tolerance: f64,
+        max_evaluations: u32,
+        max_iterations: u64,
+    ) -> PyResult<SolveReport> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let search = ApogeeSearch {
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            target_apogee,
+            tolerance,
+            max_evaluations,
+            max_iterations,
+        };
+        search
+            .deployment(&self.rocket_properties)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Finds the time (s, from the initial state) at which deploying the airbrakes to
+    /// `deployment` hits `target_apogee` with the 1DOF model, by root-finding over at most
+    /// `max_evaluations` apogee predictions. The airbrakes are retracted before then.
+    #[pyo3(signature = (initial_state, target_apogee, integration_method, deployment=1.0, timestep_config=None, tolerance=DEFAULT_APOGEE_TOLERANCE_M, max_evaluations=DEFAULT_MAX_EVALUATIONS, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn deployment_time_for_apogee_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        target_apogee: f64,
+        integration_method: OdeMethod,
+        deployment: f64,
+        timestep_config: Option<TimeStepOptions>,
+        tolerance: f64,
+        max_evaluations: u32,
+        max_iterations: u64,
+    ) -> PyResult<SolveReport> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let search = ApogeeSearch {
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            target_apogee,
+            tolerance,
+            max_evaluations,
+            max_iterations,
+        };
+        search
+            .deployment_time(&self.rocket_properties, deployment)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Finds the constant airbrake deployment (0 to 1) which hits `target_apogee` with the 3DOF
+    /// model, by root-finding over at most `max_evaluations` apogee predictions.
+    #[pyo3(signature = (initial_state, target_apogee, integration_method, timestep_config=None, tolerance=DEFAULT_APOGEE_TOLERANCE_M, max_evaluations=DEFAULT_MAX_EVALUATIONS, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn deployment_for_apogee_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        target_apogee: f64,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        tolerance: f64,
+        max_evaluations: u32,
+        max_iterations: u64,
+    ) -> PyResult<SolveReport> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let search = ApogeeSearch {
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            target_apogee,
+            tolerance,
+            max_evaluations,
+            max_iterations,
+        };
+        search
+            .deployment(&self.rocket_properties)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Finds the time (s, from the initial state) at which deploying the airbrakes to
+    /// `deployment` hits `target_apogee` with the 3DOF model, by root-finding over at most
+    /// `max_evaluations` apogee predictions. The airbrakes are retracted before then.
+    #[pyo3(signature = (initial_state, target_apogee, integration_method, deployment=1.0, timestep_config=None, tolerance=DEFAULT_APOGEE_TOLERANCE_M, max_evaluations=DEFAULT_MAX_EVALUATIONS, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn deployment_time_for_apogee_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        target_apogee: f64,
+        integration_method: OdeMethod,
+        deployment: f64,
+        timestep_config: Option<TimeStepOptions>,
+        tolerance: f64,
+        max_evaluations: u32,
+        max_iterations: u64,
+    ) -> PyResult<SolveReport> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let search = ApogeeSearch {
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            target_apogee,
+            tolerance,
+            max_evaluations,
+            max_iterations,
+        };
+        search
+            .deployment_time(&self.rocket_properties, deployment)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Inverse design with the 1DOF model: finds the value of `parameter` between `bounds`
+    /// at which `output` hits `target`, e.g. the ballast mass giving an apogee, by root-finding
+    /// over at most `max_evaluations` simulations. `tolerance` is in the units of the output.
+    #[pyo3(signature = (initial_state, parameter, bounds, target, integration_method, timestep_config=None, output=DispersionOutput::Apogee, tolerance=DEFAULT_APOGEE_TOLERANCE_M, max_evaluations=DEFAULT_MAX_EVALUATIONS, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn parameter_for_target_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        parameter: DispersedParameter,
+        bounds: (f64, f64),
+        target: f64,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        output: DispersionOutput,
+        tolerance: f64,
+        max_evaluations: u32,
+        max_iterations: u64,
+    ) -> PyResult<SolveReport> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let search = DesignSearch {
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameter,
+            bounds,
+            output,
+            target,
+            tolerance,
+            max_evaluations,
+            max_iterations,
+        };
+        search
+            .solve(&self.rocket_properties)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Inverse design with the 3DOF model: finds the value of `parameter` between `bounds`
+    /// at which `output` hits `target`, e.g. the ballast mass giving an apogee, by root-finding
+    /// over at most `max_evaluations` simulations. `tolerance` is in the units of the output.
+    #[pyo3(signature = (initial_state, parameter, bounds, target, integration_method, timestep_config=None, output=DispersionOutput::Apogee, tolerance=DEFAULT_APOGEE_TOLERANCE_M, max_evaluations=DEFAULT_MAX_EVALUATIONS, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn parameter_for_target_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        parameter: DispersedParameter,
+        bounds: (f64, f64),
+        target: f64,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        output: DispersionOutput,
+        tolerance: f64,
+        max_evaluations: u32,
+        max_iterations: u64,
+    ) -> PyResult<SolveReport> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let search = DesignSearch {
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameter,
+            bounds,
+            output,
+            target,
+            tolerance,
+            max_evaluations,
+            max_iterations,
+        };
+        search
+            .solve(&self.rocket_properties)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Finds the launch angle of a 3DOF flight which optimizes `objective`, within
+    /// `max_rail_angle` of vertical, by golden section search over at most `max_evaluations`
+    /// simulations to the ground. The rocket leaves the rail at the speed of `initial_state`,
+    /// with its velocity along its axis.
+    #[pyo3(signature = (initial_state, objective, integration_method, timestep_config=None, target_downrange=0.0, max_rail_angle=DEFAULT_MAX_RAIL_ANGLE_RAD, tolerance=DEFAULT_ANGLE_TOLERANCE_RAD, max_evaluations=DEFAULT_MAX_EVALUATIONS, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn optimize_launch_angle_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        objective: LaunchObjective,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        target_downrange: f64,
+        max_rail_angle: f64,
+        tolerance: f64,
+        max_evaluations: u32,
+        max_iterations: u64,
+    ) -> PyResult<LaunchOptimum> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let search = LaunchSearch {
+            initial_state,
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            objective,
+            target_downrange,
+            max_rail_angle,
+            tolerance,
+            max_evaluations,
+            max_iterations,
+        };
+        py.detach(|| search.optimize(&self.rocket_properties))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Fits rocket properties to a recorded flight with the 1DOF model, by Levenberg-Marquardt
+    /// least squares with the simulation as the forward model. The simulation starts from
+    /// `initial_state` at `time[0]` and runs to apogee, so the data should cover the coast
+    /// after burnout. The altitudes (and the axial accelerations, if given) are weighted by
+    /// their noise. The Jacobians of the fit are central differences of whole simulations.
+    #[pyo3(signature = (initial_state, time, altitude, parameters, integration_method, timestep_config=None, acceleration=None, altitude_std=1.0, acceleration_std=1.0, max_fit_iterations=DEFAULT_FIT_ITERATIONS, tolerance=DEFAULT_FIT_TOLERANCE, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        time: Vec<f64>,
+        altitude: Vec<f64>,
+        parameters: Vec<FitParameter>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        acceleration: Option<Vec<f64>>,
+        altitude_std: f64,
+        acceleration_std: f64,
+        max_fit_iterations: u32,
+        tolerance: f64,
+        max_iterations: u64,
+    ) -> PyResult<FitResult> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let problem = FitProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            data: FlightData {
+                time,
+                altitude,
+                acceleration,
+                altitude_std,
+                acceleration_std,
+            },
+            max_iterations,
+        };
+        problem
+            .solve(max_fit_iterations, tolerance)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Fits rocket properties to a recorded flight with the 3DOF model, by Levenberg-Marquardt
+    /// least squares with the simulation as the forward model. The simulation starts from
+    /// `initial_state` at `time[0]` and runs to apogee, so the data should cover the coast
+    /// after burnout. The altitudes (and the axial accelerations, if given) are weighted by
+    /// their noise. The Jacobians of the fit are central differences of whole simulations.
+    #[pyo3(signature = (initial_state, time, altitude, parameters, integration_method, timestep_config=None, acceleration=None, altitude_std=1.0, acceleration_std=1.0, max_fit_iterations=DEFAULT_FIT_ITERATIONS, tolerance=DEFAULT_FIT_TOLERANCE, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        time: Vec<f64>,
+        altitude: Vec<f64>,
+        parameters: Vec<FitParameter>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        acceleration: Option<Vec<f64>>,
+        altitude_std: f64,
+        acceleration_std: f64,
+        max_fit_iterations: u32,
+        tolerance: f64,
+        max_iterations: u64,
+    ) -> PyResult<FitResult> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let problem = FitProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            data: FlightData {
+                time,
+                altitude,
+                acceleration,
+                altitude_std,
+                acceleration_std,
+            },
+            max_iterations,
+        };
+        problem
+            .solve(max_fit_iterations, tolerance)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Samples the posterior distribution of rocket properties given a recorded flight with the
+    /// 1DOF model, with an adaptive Metropolis sampler. Each parameter has a prior, and the
+    /// likelihood is Gaussian in the altitudes (and the axial accelerations, if given) with the
+    /// given noise. The apogee of the recorded flight is predicted for `predictive_samples`
+    /// evenly spaced posterior samples.
+    #[pyo3(signature = (initial_state, time, altitude, parameters, priors, integration_method, timestep_config=None, acceleration=None, altitude_std=1.0, acceleration_std=1.0, num_samples=5000, burn_in=1000, seed=0, predictive_samples=200, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sample_posterior_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        time: Vec<f64>,
+        altitude: Vec<f64>,
+        parameters: Vec<FitParameter>,
+        priors: Vec<Distribution>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        acceleration: Option<Vec<f64>>,
+        altitude_std: f64,
+        acceleration_std: f64,
+        num_samples: usize,
+        burn_in: usize,
+        seed: u64,
+        predictive_samples: usize,
+        max_iterations: u64,
+    ) -> PyResult<PosteriorSamples> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let problem = FitProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            data: FlightData {
+                time,
+                altitude,
+                acceleration,
+                altitude_std,
+                acceleration_std,
+            },
+            max_iterations,
+        };
+        let options = SamplerOptions {
+            num_samples,
+            burn_in,
+            seed,
+            predictive_samples,
+        };
+        problem
+            .sample_posterior(&priors, options)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Samples the posterior distribution of rocket properties given a recorded flight with the
+    /// 3DOF model, with an adaptive Metropolis sampler. Each parameter has a prior, and the
+    /// likelihood is Gaussian in the altitudes (and the axial accelerations, if given) with the
+    /// given noise. The apogee of the recorded flight is predicted for `predictive_samples`
+    /// evenly spaced posterior samples.
+    #[pyo3(signature = (initial_state, time, altitude, parameters, priors, integration_method, timestep_config=None, acceleration=None, altitude_std=1.0, acceleration_std=1.0, num_samples=5000, burn_in=1000, seed=0, predictive_samples=200, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sample_posterior_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        time: Vec<f64>,
+        altitude: Vec<f64>,
+        parameters: Vec<FitParameter>,
+        priors: Vec<Distribution>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        acceleration: Option<Vec<f64>>,
+        altitude_std: f64,
+        acceleration_std: f64,
+        num_samples: usize,
+        burn_in: usize,
+        seed: u64,
+        predictive_samples: usize,
+        max_iterations: u64,
+    ) -> PyResult<PosteriorSamples> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let problem = FitProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            data: FlightData {
+                time,
+                altitude,
+                acceleration,
+                altitude_std,
+                acceleration_std,
+            },
+            max_iterations,
+        };
+        let options = SamplerOptions {
+            num_samples,
+            burn_in,
+            seed,
+            predictive_samples,
+        };
+        problem
+            .sample_posterior(&priors, options)
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Monte Carlo analysis with the 1DOF model: runs `num_runs` simulations with each parameter
+    /// drawn from its distribution, from the initial state to apogee and on to the ground. The
+    /// runs are spread over `num_threads` threads (one per core by default), and give the same
+    /// results for the same seed regardless of the number of threads.
+    #[pyo3(signature = (initial_state, parameters, distributions, integration_method, timestep_config=None, num_runs=DEFAULT_MONTE_CARLO_RUNS, seed=0, num_threads=None, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn monte_carlo_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        parameters: Vec<DispersedParameter>,
+        distributions: Vec<Distribution>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        num_runs: usize,
+        seed: u64,
+        num_threads: Option<usize>,
+        max_iterations: u64,
+    ) -> PyResult<MonteCarloResult> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let problem = DispersionProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            distributions,
+            max_iterations,
+        };
+        py.detach(|| problem.run(num_runs, seed, num_threads))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Monte Carlo analysis with the 3DOF model: runs `num_runs` simulations with each parameter
+    /// drawn from its distribution, from the initial state to apogee and on to the ground. The
+    /// runs are spread over `num_threads` threads (one per core by default), and give the same
+    /// results for the same seed regardless of the number of threads.
+    #[pyo3(signature = (initial_state, parameters, distributions, integration_method, timestep_config=None, num_runs=DEFAULT_MONTE_CARLO_RUNS, seed=0, num_threads=None, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn monte_carlo_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        parameters: Vec<DispersedParameter>,
+        distributions: Vec<Distribution>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        num_runs: usize,
+        seed: u64,
+        num_threads: Option<usize>,
+        max_iterations: u64,
+    ) -> PyResult<MonteCarloResult> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let problem = DispersionProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            distributions,
+            max_iterations,
+        };
+        py.detach(|| problem.run(num_runs, seed, num_threads))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Local sensitivity of the apogee with the 1DOF model to each parameter, about the rocket's
+    /// properties and the initial state. `Forward` repeats each solver step with dual numbers;
+    /// `FiniteDifference` differences whole simulations, displaced by `relative_step`. With
+    /// `trajectory`, the sensitivities of the states along the flight are returned too.
+    #[pyo3(signature = (initial_state, parameters, integration_method, timestep_config=None, method=SensitivityMethod::Forward, trajectory=false, relative_step=DEFAULT_SENSITIVITY_STEP, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn apogee_sensitivity_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        parameters: Vec<DispersedParameter>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        method: SensitivityMethod,
+        trajectory: bool,
+        relative_step: f64,
+        max_iterations: u64,
+    ) -> PyResult<Sensitivity> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let problem = SensitivityProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            relative_step,
+            max_iterations,
+        };
+        py.detach(|| problem.solve(method, trajectory))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Local sensitivity of the apogee with the 3DOF model to each parameter, about the rocket's
+    /// properties and the initial state. `Forward` repeats each solver step with dual numbers;
+    /// `FiniteDifference` differences whole simulations, displaced by `relative_step`. With
+    /// `trajectory`, the sensitivities of the states along the flight are returned too.
+    #[pyo3(signature = (initial_state, parameters, integration_method, timestep_config=None, method=SensitivityMethod::Forward, trajectory=false, relative_step=DEFAULT_SENSITIVITY_STEP, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn apogee_sensitivity_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        parameters: Vec<DispersedParameter>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        method: SensitivityMethod,
+        trajectory: bool,
+        relative_step: f64,
+        max_iterations: u64,
+    ) -> PyResult<Sensitivity> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let problem = SensitivityProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            relative_step,
+            max_iterations,
+        };
+        py.detach(|| problem.solve(method, trajectory))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Global sensitivity of an output of the flight with the 1DOF model: first and total order
+    /// Sobol indices of each parameter, uniformly distributed over its `(lower, upper)` range,
+    /// with bootstrap confidence intervals. Saltelli's scheme runs `num_samples` *
+    /// (parameters + 2) simulations, spread over `num_threads` threads (one per core by default).
+    #[pyo3(signature = (initial_state, parameters, bounds, integration_method, timestep_config=None, output=DispersionOutput::Apogee, num_samples=DEFAULT_SOBOL_SAMPLES, num_resamples=DEFAULT_BOOTSTRAP_RESAMPLES, confidence_level=DEFAULT_CONFIDENCE_LEVEL, seed=0, num_threads=None, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sobol_indices_1dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState1DOF,
+        parameters: Vec<DispersedParameter>,
+        bounds: Vec<(f64, f64)>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        output: DispersionOutput,
+        num_samples: usize,
+        num_resamples: usize,
+        confidence_level: f64,
+        seed: u64,
+        num_threads: Option<usize>,
+        max_iterations: u64,
+    ) -> PyResult<SobolIndices> {
+        emit_warnings(py, self.rocket_properties.validate_1dof())?;
+        let problem = SobolProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::OneDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            bounds,
+            output,
             max_iterations,
+        };
+        let options = SobolOptions {
+            num_samples,
+            num_resamples,
+            confidence_level,
+            seed,
+        };
+        py.detach(|| problem.run(options, num_threads))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Global sensitivity of an output of the flight with the 3DOF model: first and total order
+    /// Sobol indices of each parameter, uniformly distributed over its `(lower, upper)` range,
+    /// with bootstrap confidence intervals. Saltelli's scheme runs `num_samples` *
+    /// (parameters + 2) simulations, spread over `num_threads` threads (one per core by default).
+    #[pyo3(signature = (initial_state, parameters, bounds, integration_method, timestep_config=None, output=DispersionOutput::Apogee, num_samples=DEFAULT_SOBOL_SAMPLES, num_resamples=DEFAULT_BOOTSTRAP_RESAMPLES, confidence_level=DEFAULT_CONFIDENCE_LEVEL, seed=0, num_threads=None, max_iterations=MAX_ITERATIONS))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn sobol_indices_3dof(
+        &self,
+        py: Python<'_>,
+        initial_state: InitialState3DOF,
+        parameters: Vec<DispersedParameter>,
+        bounds: Vec<(f64, f64)>,
+        integration_method: OdeMethod,
+        timestep_config: Option<TimeStepOptions>,
+        output: DispersionOutput,
+        num_samples: usize,
+        num_resamples: usize,
+        confidence_level: f64,
+        seed: u64,
+        num_threads: Option<usize>,
+        max_iterations: u64,
+    ) -> PyResult<SobolIndices> {
+        emit_warnings(py, self.rocket_properties.validate_3dof())?;
+        let problem = SobolProblem {
+            rocket_properties: self.rocket_properties.clone(),
+            initial_condition: InitialCondition::ThreeDOF(initial_state),
+            ode_solver: OdeSolver::from_method(integration_method, timestep_config)?,
+            parameters,
+            bounds,
+            output,
+            max_iterations,
+        };
+        let options = SobolOptions {
+            num_samples,
+            num_resamples,
+            confidence_level,
+            seed,
+        };
+        py.detach(|| problem.run(options, num_threads))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Jacobian of the time derivative of the 1DOF state with respect to the state, computed
+    /// exactly with forward-mode dual numbers rather than finite differences. Airbrakes are at
+    /// their initial deployment.
+    pub fn state_jacobian_1dof<'py>(
+        &self,
+        py: Python<'py>,
+        initial_state: InitialState1DOF,
+    ) -> Bound<'py, PyArray2<f64>> {
+        let state =
+            InitialCondition::OneDOF(initial_state).to_state(self.rocket_properties.clone());
+        matrix_to_pyarray(py, &state.get_jacobian())
+    }
+
+    /// Jacobian of the time derivative of the 3DOF state with respect to the state, computed
+    /// exactly with forward-mode dual numbers rather than finite differences. Airbrakes are at
+    /// their initial deployment.
+    pub fn state_jacobian_3dof<'py>(
+        &self,
+        py: Python<'py>,
+        initial_state: InitialState3DOF,
+    ) -> Bound<'py, PyArray2<f64>> {
+        let state =
+            InitialCondition::ThreeDOF(initial_state).to_state(self.rocket_properties.clone());
+        matrix_to_pyarray(py, &state.get_jacobian())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::airbrake_mod::Curve;
+
+    #[test]
+    fn validate_warns_about_zero_fields_used_by_the_model() {
+        let rocket_properties = RocketProperties::new(15.0, 0.5, 0.0182, 0.0, 5.0, 0.5, 0.2);
+
+        // area_lift is only used by the 3DOF model
+        assert!(rocket_properties.validate_1dof().is_empty());
+        let warnings = rocket_properties.validate_3dof();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("area_lift"));
+    }
+
+    #[test]
+    fn validate_checks_dependent_fields() {
+        let mut rocket_properties = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        assert!(rocket_properties.validate_3dof().is_empty());
+
+        rocket_properties.cmq = -20.0;
+        rocket_properties.mass_flow_rate = 1.0;
+        let warnings = rocket_properties.validate_3dof();
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].starts_with("reference_length"));
+        assert!(warnings[1].starts_with("nozzle_distance"));
+        assert!(warnings[2].starts_with("burn_time"));
+    }
+
+    #[test]
+    fn validate_skips_fields_replaced_by_aero_table() {
+        let mut rocket_properties = RocketProperties::new(15.0, 0.0, 0.0182, 0.05, 5.0, 0.0, 0.0);
+        assert_eq!(rocket_properties.validate_3dof().len(), 3);
+
+        rocket_properties.aero_table = Some(
+            AeroTable::from_vecs(vec![0.0], vec![0.0], vec![0.5], vec![0.0], vec![1.0]).unwrap(),
         );
+        let warnings = rocket_properties.validate_3dof();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("cg_location"));
 
-        let mut log = SimulationData::new();
-        simulation.run(&mut log, print_output, false);
+        rocket_properties.cg_location = 0.8;
+        assert!(rocket_properties.validate_3dof().is_empty());
+    }
 
-        const HEIGHT_COL: usize = 2;
+    #[test]
+    fn properties_with_deployment_overrides_the_airbrake() {
+        let mut rocket = Rocket::new(
+            15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2, 0.0, 0.0, 0.0, 0.0, None, None, 0.0, None, 0.0,
+            0.0,
+        );
+        assert!(rocket.properties_with_deployment(None).is_ok());
+        assert!(rocket.properties_with_deployment(Some(0.5)).is_err());
 
-        let max_height = log.get_val((log.time_log.len()) - 1, HEIGHT_COL);
+        rocket.rocket_properties.airbrake =
+            Some(Airbrake::new(Curve::constant(0.5), None, None, 0.0, None));
+        let rocket_properties = rocket.properties_with_deployment(Some(0.7)).unwrap();
+        assert_eq!(rocket_properties.airbrake.unwrap().deployment, 0.7);
 
-        Ok(max_height)
+        // The rocket itself is left untouched
+        assert_eq!(rocket.rocket_properties.airbrake.unwrap().deployment, 0.0);
     }
 }
@@ -0,0 +1,506 @@
+use crate::constants::simulation_constants::APOGEE_VELOCITY_THRESHOLD_M_S;
+use crate::dispersion_mod::{apply_parameters, validate_parameters, DispersedParameter};
+use crate::dual_mod::Dual;
+use crate::ode::OdeSolver;
+use crate::rocket::{ModelParameters, RocketProperties};
+use crate::state::{InitialCondition, State};
+use nalgebra::DMatrix;
+use numpy::{ndarray::Array3, PyArray1, PyArray3, ToPyArray};
+use pyo3::prelude::*;
+
+/// Method of a local sensitivity analysis.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SensitivityMethod {
+    /// Central finite differences of whole simulations. Adaptive timesteps and the apogee
+    /// detection make the differences noisy for small steps.
+    FiniteDifference,
+    /// Forward-mode sensitivities: every step of the ODE solver is repeated with dual numbers,
+    /// which carries the derivatives of the state through it exactly, with the same step sizes.
+    Forward,
+}
+
+/// Derivatives of the apogee, and optionally of the trajectory, with respect to parameters.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Sensitivity {
+    /// The parameter behind each entry of `gradient`
+    #[pyo3(get)]
+    pub parameters: Vec<DispersedParameter>,
+    #[pyo3(get)]
+    pub method: SensitivityMethod,
+    /// Apogee of the nominal flight (m)
+    #[pyo3(get)]
+    pub apogee: f64,
+    gradient: Vec<f64>,
+    /// Empty unless the trajectory sensitivities were requested
+    time: Vec<f64>,
+    /// Model state x parameter sensitivities at each time
+    trajectory: Vec<DMatrix<f64>>,
+}
+
+#[pymethods]
+impl Sensitivity {
+    /// Derivative of the apogee with respect to each parameter.
+    #[getter]
+    pub fn gradient<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.gradient.to_pyarray(py)
+    }
+
+    /// Times of the nominal trajectory, if its sensitivities were requested.
+    #[getter]
+    pub fn time<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyArray1<f64>>> {
+        (!self.trajectory.is_empty()).then(|| self.time.to_pyarray(py))
+    }
+
+    /// Derivatives of the model states with respect to each parameter at each time, with the
+    /// shape (time, state, parameter), if they were requested.
+    #[getter]
+    pub fn trajectory<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyArray3<f64>>> {
+        let first = self.trajectory.first()?;
+        let shape = (self.trajectory.len(), first.nrows(), first.ncols());
+        Some(Array3::from_shape_fn(shape, |(i, j, k)| self.trajectory[i][(j, k)]).to_pyarray(py))
+    }
+}
+
+/// Derivative of the state (including any auxiliary states) with respect to a parameter, which
+/// is propagated alongside the nominal trajectory.
+struct Tangent {
+    values: Vec<f64>,
+    /// The scalar rocket properties, with a unit derivative for the parameter if it is one of them
+    parameters: ModelParameters<Dual<1>>,
+}
+
+impl Tangent {
+    /// Propagates the derivative across a step of size `dt` from the nominal state `start`.
+    fn step(&mut self, ode: &OdeSolver, start: &State, dt: f64) {
+        self.values = ode.tangent_step(start, &self.values, &self.parameters, dt);
+    }
+}
+
+/// Trajectory to apogee, with the derivatives of the state if tangents were propagated.
+#[derive(Default)]
+struct Trajectory {
+    time: Vec<f64>,
+    /// Model states at each time
+    states: Vec<Vec<f64>>,
+    sensitivities: Vec<DMatrix<f64>>,
+    apogee: f64,
+    apogee_gradient: Vec<f64>,
+}
+
+impl Trajectory {
+    /// Model state linearly interpolated at the given time, clamped to the trajectory.
+    fn state_at(&self, time: f64) -> Vec<f64> {
+        if self.time.len() < 2 {
+            return self.states[0].clone();
+        }
+        let i = self
+            .time
+            .partition_point(|&t| t <= time)
+            .clamp(1, self.time.len() - 1);
+        let fraction =
+            ((time - self.time[i - 1]) / (self.time[i] - self.time[i - 1])).clamp(0.0, 1.0);
+        self.states[i - 1]
+            .iter()
+            .zip(&self.states[i])
+            .map(|(start, end)| start + fraction * (end - start))
+            .collect()
+    }
+}
+
+/// Local sensitivity analysis of the apogee of a flight.
+#[derive(Clone, Debug)]
+pub(crate) struct SensitivityProblem {
+    pub(crate) rocket_properties: RocketProperties,
+    pub(crate) initial_condition: InitialCondition,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) parameters: Vec<DispersedParameter>,
+    /// Step of each parameter for finite differences, relative to its magnitude, or to 1 for
+    /// smaller magnitudes
+    pub(crate) relative_step: f64,
+    pub(crate) max_iterations: u64,
+}
+
+impl SensitivityProblem {
+    pub(crate) fn solve(
+        &self,
+        method: SensitivityMethod,
+        trajectory: bool,
+    ) -> Result<Sensitivity, String> {
+        validate_parameters(&self.parameters, &self.initial_condition)?;
+        if self.relative_step <= 0.0 {
+            return Err("relative_step must be positive".to_string());
+        }
+        let (apogee, gradient, time, sensitivities) = match method {
+            SensitivityMethod::FiniteDifference => self.finite_difference(trajectory)?,
+            SensitivityMethod::Forward => self.forward(trajectory)?,
+        };
+        Ok(Sensitivity {
+            parameters: self.parameters.clone(),
+            method,
+            apogee,
+            gradient,
+            time,
+            trajectory: sensitivities,
+        })
+    }
+
+    /// Step and displaced (rocket properties, initial conditions) of a parameter.
+    fn displaced(
+        &self,
+        parameter: DispersedParameter,
+    ) -> (f64, [(RocketProperties, InitialCondition); 2]) {
+        let value = parameter.value(&self.rocket_properties, &self.initial_condition);
+        let step = self.relative_step * value.abs().max(1.0);
+        let displace = |sign: f64| {
+            apply_parameters(
+                &[parameter],
+                &[value + sign * step],
+                &self.rocket_properties,
+                self.initial_condition,
+            )
+        };
+        (step, [displace(1.0), displace(-1.0)])
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn finite_difference(
+        &self,
+        record: bool,
+    ) -> Result<(f64, Vec<f64>, Vec<f64>, Vec<DMatrix<f64>>), String> {
+        let nominal = self.integrate(
+            self.rocket_properties.clone(),
+            self.initial_condition,
+            Vec::new(),
+            record,
+        )?;
+        let mut gradient = Vec::with_capacity(self.parameters.len());
+        let mut sensitivities =
+            vec![DMatrix::zeros(self.dim(), self.parameters.len()); nominal.time.len()];
+        for (j, parameter) in self.parameters.iter().enumerate() {
+            let (step, [(plus_properties, plus), (minus_properties, minus)]) =
+                self.displaced(*parameter);
+            let plus = self.integrate(plus_properties, plus, Vec::new(), record)?;
+            let minus = self.integrate(minus_properties, minus, Vec::new(), record)?;
+            gradient.push((plus.apogee - minus.apogee) / (2.0 * step));
+
+            // The displaced trajectories are compared at the nominal times
+            for (time, sensitivity) in nominal.time.iter().zip(sensitivities.iter_mut()) {
+                let (plus, minus) = (plus.state_at(*time), minus.state_at(*time));
+                for i in 0..self.dim() {
+                    sensitivity[(i, j)] = (plus[i] - minus[i]) / (2.0 * step);
+                }
+            }
+        }
+        if !record {
+            sensitivities.clear();
+        }
+        Ok((nominal.apogee, gradient, nominal.time, sensitivities))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn forward(
+        &self,
+        record: bool,
+    ) -> Result<(f64, Vec<f64>, Vec<f64>, Vec<DMatrix<f64>>), String> {
+        let tangents = self
+            .parameters
+            .iter()
+            .map(|&parameter| {
+                let mut parameters = self.rocket_properties.parameters::<Dual<1>>();
+                if let Some(value) = parameter.model_parameter(&mut parameters) {
+                    value.derivatives = [1.0];
+                }
+                Tangent {
+                    values: self.initial_tangent(parameter),
+                    parameters,
+                }
+            })
+            .collect();
+        let trajectory = self.integrate(
+            self.rocket_properties.clone(),
+            self.initial_condition,
+            tangents,
+            record,
+        )?;
+        Ok((
+            trajectory.apogee,
+            trajectory.apogee_gradient,
+            trajectory.time,
+            trajectory.sensitivities,
+        ))
+    }
+
+    /// Derivative of the initial state (including any auxiliary states) with respect to a
+    /// parameter. The rocket properties don't change the initial state, and an impulse deviation
+    /// changes the speed along the rocket's axis by the deviation over the mass.
+    fn initial_tangent(&self, parameter: DispersedParameter) -> Vec<f64> {
+        let state = self
+            .initial_condition
+            .to_state(self.rocket_properties.clone());
+        let mut tangent = vec![0.0; state.get_state_vec().as_array().len()];
+        let mass = self.rocket_properties.mass;
+        let component = match (parameter, self.initial_condition) {
+            (DispersedParameter::ImpulseDeviation, InitialCondition::OneDOF(_)) => {
+                tangent[1] = 1.0 / mass;
+                None
+            }
+            (DispersedParameter::ImpulseDeviation, InitialCondition::ThreeDOF(initial)) => {
+                // Same orientation convention as the 3DOF model
+                tangent[3] = -initial.angle.sin() / mass;
+                tangent[4] = initial.angle.cos() / mass;
+                None
+            }
+            (DispersedParameter::InitialAltitude, InitialCondition::OneDOF(_)) => Some(0),
+            (DispersedParameter::InitialVerticalVelocity, InitialCondition::OneDOF(_)) => Some(1),
+            (DispersedParameter::InitialDownrange, _) => Some(0),
+            (DispersedParameter::InitialAltitude, _) => Some(1),
+            (DispersedParameter::InitialAngle, _) => Some(2),
+            (DispersedParameter::InitialHorizontalVelocity, _) => Some(3),
+            (DispersedParameter::InitialVerticalVelocity, _) => Some(4),
+            (DispersedParameter::InitialAngularRate, _) => Some(5),
+            _ => None,
+        };
+        if let Some(i) = component {
+            tangent[i] = 1.0;
+        }
+        tangent
+    }
+
+    /// Number of model states, without any auxiliary states.
+    fn dim(&self) -> usize {
+        match self.initial_condition {
+            InitialCondition::OneDOF(_) => 2,
+            InitialCondition::ThreeDOF(_) => 6,
+        }
+    }
+
+    /// Simulates the flight to apogee like `Simulation`, propagating the tangents alongside it.
+    fn integrate(
+        &self,
+        rocket_properties: RocketProperties,
+        initial_condition: InitialCondition,
+        mut tangents: Vec<Tangent>,
+        record: bool,
+    ) -> Result<Trajectory, String> {
+        let (altitude, velocity) = match initial_condition {
+            InitialCondition::OneDOF(_) => (0, 1),
+            InitialCondition::ThreeDOF(_) => (1, 4),
+        };
+        let dim = self.dim();
+        let mut trajectory = Trajectory::default();
+        let push = |trajectory: &mut Trajectory, state: &State, tangents: &[Tangent]| {
+            trajectory.time.push(state.get_time());
+            trajectory
+                .states
+                .push(state.get_state_vec().as_array()[..dim].to_vec());
+            trajectory
+                .sensitivities
+                .push(DMatrix::from_fn(dim, tangents.len(), |i, j| {
+                    tangents[j].values[i]
+                }));
+        };
+
+        let mut ode = self.ode_solver.clone();
+        let mut state = initial_condition.to_state(rocket_properties);
+        if record {
+            push(&mut trajectory, &state, &tangents);
+        }
+        for _ in 0..self.max_iterations {
+            let previous = state.clone();
+            ode.timestep(&mut state);
+            if !state.is_finite() {
+                return Err("The simulation diverged".to_string());
+            }
+            let done = state.get_vertical_velocity() < APOGEE_VELOCITY_THRESHOLD_M_S;
+            if done {
+                ode.backtrack_apogee(&mut state, &previous);
+            }
+
+            let dt = state.get_time() - previous.get_time();
+            for tangent in tangents.iter_mut() {
+                tangent.step(&ode, &previous, dt);
+            }
+            if record {
+                push(&mut trajectory, &state, &tangents);
+            }
+
+            if done {
+                // The apogee also moves with the time the vertical velocity crosses zero
+                let vy = state.get_vertical_velocity();
+                let ay = state.get_derivatives().as_array()[velocity];
+                trajectory.apogee = state.get_altitude();
+                trajectory.apogee_gradient = tangents
+                    .iter()
+                    .map(|tangent| tangent.values[altitude] - vy * tangent.values[velocity] / ay)
+                    .collect();
+                return Ok(trajectory);
+            }
+        }
+        Err("The simulation didn't reach apogee within max_iterations".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::{AdaptiveTimeStep, FixedTimeStep};
+    use crate::physics_mod;
+    use crate::simulation;
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::{assert_abs_diff_eq, assert_relative_eq};
+
+    fn make_problem(
+        initial_condition: InitialCondition,
+        parameters: Vec<DispersedParameter>,
+    ) -> SensitivityProblem {
+        let mut rocket_properties = RocketProperties::new(10.0, 0.5, 0.0182, 0.0182, 5.0, 0.3, 2.0);
+        rocket_properties.cmq = -20.0;
+        rocket_properties.reference_length = 0.15;
+        SensitivityProblem {
+            rocket_properties,
+            initial_condition,
+            ode_solver: OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            parameters,
+            relative_step: 1e-5,
+            max_iterations: 100_000,
+        }
+    }
+
+    fn one_dof() -> InitialCondition {
+        InitialCondition::OneDOF(InitialState1DOF::new(10.0, 150.0))
+    }
+
+    #[test]
+    fn forward_sensitivities_match_the_vacuum_solution() {
+        // Without drag the apogee is h + v^2 / 2g
+        let mut problem = make_problem(
+            one_dof(),
+            vec![
+                DispersedParameter::InitialAltitude,
+                DispersedParameter::InitialVerticalVelocity,
+                DispersedParameter::Cd,
+                DispersedParameter::ImpulseDeviation,
+            ],
+        );
+        problem.rocket_properties.area_drag = 0.0;
+        let sensitivity = problem.solve(SensitivityMethod::Forward, false).unwrap();
+        let g = -physics_mod::gravity();
+
+        assert_relative_eq!(
+            sensitivity.apogee,
+            10.0 + 150.0_f64.powi(2) / (2.0 * g),
+            epsilon = 1e-3
+        );
+        assert_relative_eq!(sensitivity.gradient[0], 1.0, epsilon = 1e-6);
+        assert_relative_eq!(sensitivity.gradient[1], 150.0 / g, epsilon = 1e-4);
+        assert_abs_diff_eq!(sensitivity.gradient[2], 0.0, epsilon = 1e-9);
+        // An impulse changes the velocity by 1 / mass
+        assert_relative_eq!(sensitivity.gradient[3], 150.0 / g / 10.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn forward_sensitivities_are_the_derivatives_of_the_steps() {
+        let mut problem = make_problem(
+            one_dof(),
+            vec![DispersedParameter::Cd, DispersedParameter::Mass],
+        );
+        problem.ode_solver = OdeSolver::Euler(FixedTimeStep::new(0.01));
+        let sensitivity = problem.solve(SensitivityMethod::Forward, true).unwrap();
+
+        // An Euler step changes the velocity by dt * (drag / mass + g), which is linear in cd
+        let drag = physics_mod::calc_drag_force(150.0, 0.5, 0.0182, None, 0.0);
+        let first_step = &sensitivity.trajectory[1];
+        assert_relative_eq!(
+            first_step[(1, 0)],
+            0.01 * drag / 0.5 / 10.0,
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            first_step[(1, 1)],
+            -0.01 * drag / 10.0_f64.powi(2),
+            max_relative = 1e-12
+        );
+        assert_eq!(first_step[(0, 0)], 0.0);
+    }
+
+    #[test]
+    fn forward_and_finite_differences_agree() {
+        let parameters = vec![
+            DispersedParameter::Cd,
+            DispersedParameter::Mass,
+            DispersedParameter::InitialVerticalVelocity,
+        ];
+        let mut problem = make_problem(one_dof(), parameters);
+        let forward = problem.solve(SensitivityMethod::Forward, true).unwrap();
+        // Whole simulations need a larger step to stay clear of the apogee detection's noise
+        problem.relative_step = 1e-3;
+        let finite = problem
+            .solve(SensitivityMethod::FiniteDifference, true)
+            .unwrap();
+
+        assert_eq!(forward.apogee, finite.apogee);
+        for (forward, finite) in forward.gradient.iter().zip(&finite.gradient) {
+            assert_relative_eq!(forward, finite, max_relative = 1e-3);
+        }
+        // More drag and less mass lower the apogee
+        assert!(forward.gradient[0] < 0.0 && forward.gradient[1] > 0.0);
+
+        assert_eq!(forward.time, finite.time);
+        assert_eq!(forward.trajectory.len(), forward.time.len());
+        assert_eq!(forward.trajectory[0].shape(), (2, 3));
+        let middle = forward.time.len() / 2;
+        for (forward, finite) in forward.trajectory[middle]
+            .iter()
+            .zip(finite.trajectory[middle].iter())
+        {
+            assert_abs_diff_eq!(forward, finite, epsilon = 1e-3 * forward.abs().max(1.0));
+        }
+    }
+
+    #[test]
+    fn forward_sensitivities_follow_adaptive_steps_in_3dof() {
+        let initial_condition =
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.1, -15.0, 150.0, 0.0));
+        let mut problem = make_problem(
+            initial_condition,
+            vec![DispersedParameter::WindSpeed, DispersedParameter::ClA],
+        );
+        problem.ode_solver = OdeSolver::RK45(AdaptiveTimeStep::default());
+        let sensitivity = problem.solve(SensitivityMethod::Forward, false).unwrap();
+        assert!(sensitivity.time.is_empty() && sensitivity.trajectory.is_empty());
+
+        // The apogee is the one a simulation finds
+        let nominal = simulation::apogee(
+            initial_condition.to_state(problem.rocket_properties.clone()),
+            problem.ode_solver.clone(),
+            problem.max_iterations,
+        );
+        assert_abs_diff_eq!(sensitivity.apogee, nominal, epsilon = 1e-9);
+
+        problem.relative_step = 1e-3;
+        let finite = problem
+            .solve(SensitivityMethod::FiniteDifference, false)
+            .unwrap();
+        for (forward, finite) in sensitivity.gradient.iter().zip(&finite.gradient) {
+            assert_relative_eq!(forward, finite, max_relative = 1e-3);
+        }
+        // Wind blowing against the horizontal velocity raises this apogee
+        assert!(sensitivity.gradient[0] > 0.0);
+    }
+
+    #[test]
+    fn invalid_problems_are_rejected() {
+        let problem = make_problem(one_dof(), vec![DispersedParameter::InitialAngle]);
+        assert!(problem.solve(SensitivityMethod::Forward, false).is_err());
+        let mut problem = make_problem(one_dof(), vec![DispersedParameter::Cd]);
+        problem.relative_step = 0.0;
+        assert!(problem.solve(SensitivityMethod::Forward, false).is_err());
+        problem.relative_step = 1e-5;
+        problem.max_iterations = 10;
+        assert!(problem
+            .solve(SensitivityMethod::FiniteDifference, false)
+            .is_err());
+    }
+}
@@ -1,10 +1,19 @@
-use crate::constants::simulation_constants::{DATA_LENGTH, INITIAL_DATA_CAPACITY};
+use crate::constants::simulation_constants::{
+    CONTROL_DATA_LENGTH, DATA_LENGTH, DERIVED_DATA_LENGTH, INITIAL_DATA_CAPACITY,
+};
+use crate::derived_mod::{DerivedChannel, FlightQuantities};
 use crate::state::state_vector::StateVector;
 
 #[derive(Clone, Debug)]
 pub(crate) struct SimulationData {
     pub(crate) time_log: Vec<f64>,
     pub(crate) state_log: Vec<[f64; DATA_LENGTH]>,
+    /// Rows of (time, predicted apogee, command, actual deployment), one per controller update
+    pub(crate) control_log: Vec<[f64; CONTROL_DATA_LENGTH]>,
+    /// Derived channels which are recorded, in the column order of `derived_log`
+    pub(crate) derived_channels: Vec<DerivedChannel>,
+    /// Rows of the derived channels, one per state row. Empty if no channels are recorded.
+    pub(crate) derived_log: Vec<[f64; DERIVED_DATA_LENGTH]>,
 }
 
 impl SimulationData {
@@ -12,12 +21,40 @@ impl SimulationData {
         Self {
             time_log: Vec::with_capacity(INITIAL_DATA_CAPACITY),
             state_log: Vec::with_capacity(INITIAL_DATA_CAPACITY),
+            control_log: Vec::new(),
+            derived_channels: Vec::new(),
+            derived_log: Vec::new(),
         }
     }
 
+    /// Makes an empty log which also records the given derived channels with every row.
+    /// Repeated channels are only recorded once.
+    pub(crate) fn with_channels(channels: Vec<DerivedChannel>) -> Self {
+        let mut derived_channels = Vec::with_capacity(channels.len());
+        for channel in channels {
+            if !derived_channels.contains(&channel) {
+                derived_channels.push(channel);
+            }
+        }
+        Self {
+            derived_log: Vec::with_capacity(INITIAL_DATA_CAPACITY),
+            derived_channels,
+            ..Self::new()
+        }
+    }
+
+    /// Removes all logged data, keeping the allocated capacity for reuse.
+    pub(crate) fn clear(&mut self) {
+        self.time_log.clear();
+        self.state_log.clear();
+        self.control_log.clear();
+        self.derived_log.clear();
+    }
+
     /// Retrieves the value at the specified row index and column index from the simulation data.
     /// Column index 0 corresponds to time, while column indices 1 to DATA_LENGTH correspond to the
     /// state vector components.
+    #[allow(dead_code)]
     pub(crate) fn get_val(&self, index: usize, col: usize) -> f64 {
         if index >= self.time_log.len() {
             panic!("Index out of bounds");
@@ -30,8 +67,9 @@ impl SimulationData {
         }
     }
 
-    /// Adds a new row of simulation data consisting of the given state vector and time value.
-    pub(crate) fn add_row(&mut self, row: StateVector, time: f64) {
+    /// Adds a new row of simulation data consisting of the given state vector and time value,
+    /// along with the recorded channels of the derived quantities.
+    pub(crate) fn add_row(&mut self, row: StateVector, quantities: &FlightQuantities, time: f64) {
         self.time_log.push(time);
 
         let rowdata = row.as_array();
@@ -41,6 +79,19 @@ impl SimulationData {
         let copy_len = rowdata.len();
         padded_row[..copy_len].copy_from_slice(&rowdata[..copy_len]);
         self.state_log.push(padded_row);
+
+        if !self.derived_channels.is_empty() {
+            let mut derived_row = [0.0; DERIVED_DATA_LENGTH];
+            for (value, channel) in derived_row.iter_mut().zip(&self.derived_channels) {
+                *value = channel.value(quantities);
+            }
+            self.derived_log.push(derived_row);
+        }
+    }
+
+    /// Adds a row to the control log.
+    pub(crate) fn add_control_row(&mut self, row: [f64; CONTROL_DATA_LENGTH]) {
+        self.control_log.push(row);
     }
 }
 
@@ -68,7 +119,7 @@ mod tests {
         let state = StateVector::OneDOF(Vector2::new(altitude, velocity));
         let time = 12.34;
 
-        data.add_row(state, time);
+        data.add_row(state, &FlightQuantities::default(), time);
 
         // Column 0 must return the recorded timestamp
         assert_abs_diff_eq!(data.get_val(0, 0), time, epsilon = 1e-12);
@@ -84,6 +135,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn test_for_three_dof() {
         let mut data = SimulationData::new();
 
@@ -92,7 +144,7 @@ mod tests {
         let state = StateVector::ThreeDOF(Vector6::from_column_slice(&elements));
         let time = 56.78;
 
-        data.add_row(state, time);
+        data.add_row(state, &FlightQuantities::default(), time);
 
         // Column 0 must return the timestamp
         assert_abs_diff_eq!(data.get_val(0, 0), time, epsilon = 1e-12);
@@ -109,4 +161,32 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_derived_channels_are_recorded_in_order() {
+        let quantities = FlightQuantities {
+            mach: 0.4,
+            dynamic_pressure: 9000.0,
+            ..Default::default()
+        };
+        let state = StateVector::OneDOF(Vector2::new(100.0, 120.0));
+
+        // Without channels only the states are logged
+        let mut data = SimulationData::new();
+        data.add_row(state, &quantities, 1.0);
+        assert!(data.derived_log.is_empty());
+
+        let mut data = SimulationData::with_channels(vec![
+            DerivedChannel::DynamicPressure,
+            DerivedChannel::Mach,
+            DerivedChannel::DynamicPressure,
+        ]);
+        assert_eq!(data.derived_channels.len(), 2);
+        data.add_row(state, &quantities, 1.0);
+        assert_eq!(data.derived_log.len(), 1);
+        assert_eq!(data.derived_log[0][..3], [9000.0, 0.4, 0.0]);
+
+        data.clear();
+        assert!(data.derived_log.is_empty());
+    }
 }
@@ -1,16 +1,48 @@
-use crate::constants::simulation_constants::APOGEE_VELOCITY_THRESHOLD_M_S;
-use crate::ode::OdeSolver;
+use crate::constants::simulation_constants::{APOGEE_VELOCITY_THRESHOLD_M_S, MAX_ITERATIONS};
+use crate::control_mod::ControlLoop;
+use crate::ode::{AdaptiveTimeStep, OdeSolver};
+use crate::physics_mod;
+use crate::rocket::RocketProperties;
 use crate::simdata_mod::SimulationData;
-use crate::state::State;
+use crate::state::{InitialState1DOF, State};
+use crate::summary_mod::{EventDetector, FlightSummary};
+use pyo3::prelude::*;
+use std::time::Instant;
 
-/// Enum defining the various exit conditions for the simulation. Eventually, more exit
-/// conditions, such as ground impact, can be added here.
+/// Enum defining the various exit conditions for the simulation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum SimulationExitCondition {
     ApogeeReached,
+    /// The rocket descended below zero altitude
+    GroundHit,
     // TODO: Add more exit conditions as needed
 }
 
+/// Outcome of a simulation run, e.g. of an apogee prediction.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PredictionStatus {
+    /// The exit condition (e.g. apogee) was reached.
+    Converged,
+    /// The iteration budget or the deadline ran out before the exit condition was reached.
+    BudgetExhausted,
+    /// The state stopped being finite, e.g. because of a bad initial state or timestep.
+    Diverged,
+}
+
+/// Apogee prediction together with how it ended.
+#[pyclass(get_all)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ApogeePrediction {
+    /// Predicted apogee (m). If the prediction didn't converge, this is the best estimate so far.
+    pub apogee: f64,
+    pub status: PredictionStatus,
+    /// Number of timesteps taken
+    pub iterations: u64,
+    /// Wall-clock time the prediction took (s)
+    pub elapsed: f64,
+}
+
 /// Struct used to coordinate the execution of a simulation. It is supplied with a
 /// State space/model, and a timestepping method, and will carry out iterations until a stopping
 /// criterea is reached, or the maximum number of iterations have been carried out.
@@ -20,7 +52,17 @@ pub(crate) struct Simulation {
     ode: OdeSolver,
     exit_condition: SimulationExitCondition,
     pub(crate) current_iteration: u64,
+    /// Number of timesteps taken by the last run
+    pub(crate) steps_taken: u64,
     max_iterations: u64,
+    controller: Option<ControlLoop>,
+    /// Step size the ODE solver adapted to after the first timestep
+    first_step_size: Option<f64>,
+    /// Wall-clock time after which the simulation is stopped
+    deadline: Option<Instant>,
+    status: PredictionStatus,
+    /// Detects the events of the flight after every timestep
+    events: Option<EventDetector>,
 }
 
 impl Simulation {
@@ -35,19 +77,67 @@ impl Simulation {
             ode,
             exit_condition,
             current_iteration: 0,
+            steps_taken: 0,
             max_iterations,
+            controller: None,
+            first_step_size: None,
+            deadline: None,
+            status: PredictionStatus::BudgetExhausted,
+            events: None,
         }
     }
 
+    /// Adds a closed-loop controller which is updated before every timestep.
+    pub(crate) fn with_controller(mut self, controller: ControlLoop) -> Self {
+        self.controller = Some(controller);
+        self
+    }
+
+    /// Detects the events of the flight during runs, for `flight_summary`.
+    pub(crate) fn with_event_detection(mut self) -> Self {
+        self.events = Some(EventDetector::new());
+        self
+    }
+
+    /// Stops the simulation once the wall-clock time passes the deadline.
+    pub(crate) fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Error message of a failed controller, which stops the simulation early.
+    pub(crate) fn controller_error(&self) -> Option<&str> {
+        self.controller.as_ref()?.error.as_deref()
+    }
+
     /// Runs the simulation until the exit condition is met or the maximum number of iterations is reached.
     pub(crate) fn run(&mut self, log: &mut SimulationData, print_output: bool, log_output: bool) {
+        self.status = PredictionStatus::BudgetExhausted;
+        self.steps_taken = 0;
+
         // Executes the simulation
         for i in 0..self.max_iterations {
-            let old_state = self.state;
+            if self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                break;
+            }
+
+            // Update the control command before the step, so that it is held during the step
+            if let Some(controller) = self.controller.as_mut() {
+                controller.update(&mut self.state, log);
+                if controller.error.is_some() {
+                    break;
+                }
+            }
+
+            let old_state = self.state.clone();
 
             self.current_iteration = i;
             if log_output {
-                log.add_row(self.state.get_row_log(), self.state.get_time())
+                let quantities = self.state.get_flight_quantities();
+                log.add_row(self.state.get_row_log(), &quantities, self.state.get_time())
             };
 
             // Output simulation info to terminal
@@ -55,8 +145,25 @@ impl Simulation {
                 self.state.print_state(i);
             }
 
-            // Does the next iteration of the simulation
-            self.ode.timestep(&mut self.state);
+            // Does the next iteration of the simulation, ending it at the next controller update
+            match self.controller.as_ref() {
+                Some(controller) => {
+                    let max_dt = controller.time_to_update(self.state.get_time());
+                    self.ode.timestep_within(&mut self.state, max_dt)
+                }
+                None => self.ode.timestep(&mut self.state),
+            }
+            self.steps_taken += 1;
+            if i == 0 {
+                self.first_step_size = Some(self.ode.step_size());
+            }
+
+            // Keep the last finite state, so there is still something to estimate from
+            if !self.state.is_finite() {
+                self.state = old_state;
+                self.status = PredictionStatus::Diverged;
+                break;
+            }
 
             // Check Exit Condition
             if self.is_done() {
@@ -65,10 +172,19 @@ impl Simulation {
                     SimulationExitCondition::ApogeeReached => {
                         self.ode.backtrack_apogee(&mut self.state, &old_state)
                     }
+                    SimulationExitCondition::GroundHit => {
+                        self.ode.backtrack_ground(&mut self.state, &old_state)
+                    }
+                }
+
+                self.status = PredictionStatus::Converged;
+                if let Some(events) = self.events.as_mut() {
+                    events.step(&old_state, &mut self.state, &self.ode);
                 }
 
                 if !log_output {
-                    log.add_row(self.state.get_row_log(), self.state.get_time())
+                    let quantities = self.state.get_flight_quantities();
+                    log.add_row(self.state.get_row_log(), &quantities, self.state.get_time())
                 };
 
                 if print_output {
@@ -79,9 +195,74 @@ impl Simulation {
 
                 break;
             }
+
+            if let Some(events) = self.events.as_mut() {
+                events.step(&old_state, &mut self.state, &self.ode);
+            }
         }
     }
 
+    /// Summary of the flight from the events detected during the last run. A flight which
+    /// reached apogee is continued on to the ground with `ode_solver`, without logging, to
+    /// complete the summary with the descent and landing.
+    pub(crate) fn flight_summary(&self, ode_solver: OdeSolver) -> FlightSummary {
+        let mut events = self.events.clone().unwrap_or_default();
+        if self.status != PredictionStatus::Converged
+            || self.exit_condition != SimulationExitCondition::ApogeeReached
+        {
+            return events.summary();
+        }
+        events.apogee(&self.state);
+
+        let mut descent = Simulation::new(
+            self.state.clone(),
+            ode_solver,
+            SimulationExitCondition::GroundHit,
+            self.max_iterations,
+        );
+        descent.events = Some(events);
+        descent.run(&mut SimulationData::new(), false, false);
+        let mut events = descent.events.take().unwrap_or_default();
+        if descent.status == PredictionStatus::Converged {
+            events.landing(&descent.state);
+        }
+        events.summary()
+    }
+
+    /// Step size the ODE solver adapted to after the first timestep of the run, which is a good
+    /// starting step size for a later run from a nearby state.
+    pub(crate) fn first_step_size(&self) -> Option<f64> {
+        self.first_step_size
+    }
+
+    /// Outcome of the last run. `BudgetExhausted` before the first run.
+    pub(crate) fn status(&self) -> PredictionStatus {
+        self.status
+    }
+
+    /// State the last run ended in.
+    pub(crate) fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Best estimate of the apogee after a run. If the apogee wasn't reached, the height the
+    /// rocket would still coast with constant drag (using `cd` and `area_drag`) is added to the
+    /// current altitude.
+    pub(crate) fn apogee_estimate(&self) -> f64 {
+        let altitude = self.state.get_altitude();
+        if self.status == PredictionStatus::Converged {
+            return altitude;
+        }
+        let rocket_properties = self.state.get_rocket_properties();
+        altitude
+            + physics_mod::calc_coast_height(
+                self.state.get_vertical_velocity(),
+                rocket_properties.mass,
+                rocket_properties.cd,
+                rocket_properties.area_drag,
+            )
+    }
+
     #[allow(dead_code)]
     pub(crate) fn apogee(&mut self) -> f64 {
         // Getter to obtain the apogee of aa flight after the simulation is complete
@@ -96,15 +277,45 @@ impl Simulation {
     fn is_done(&self) -> bool {
         match self.exit_condition {
             SimulationExitCondition::ApogeeReached => self.condition_apogee(),
+            SimulationExitCondition::GroundHit => self.condition_ground(),
         }
     }
 
+    fn condition_ground(&self) -> bool {
+        // Only a descending rocket can hit the ground, so a launch from zero altitude isn't one
+        self.state.get_altitude() < 0.0 && self.state.get_vertical_velocity() < 0.0
+    }
+
     fn condition_apogee(&self) -> bool {
         // Stop calculation when apogee is reached
         self.state.get_vertical_velocity() < APOGEE_VELOCITY_THRESHOLD_M_S
     }
 }
 
+/// Runs a simulation of the given state to apogee and returns the best estimate of the apogee.
+pub(crate) fn apogee(state: State, ode: OdeSolver, max_iterations: u64) -> f64 {
+    let mut simulation = Simulation::new(
+        state,
+        ode,
+        SimulationExitCondition::ApogeeReached,
+        max_iterations,
+    );
+    simulation.run(&mut SimulationData::new(), false, false);
+    simulation.apogee_estimate()
+}
+
+/// Predicts the apogee from the given state, with its model and the default adaptive RK45 solver.
+pub(crate) fn predict_apogee(state: State) -> f64 {
+    if state.get_vertical_velocity() < APOGEE_VELOCITY_THRESHOLD_M_S {
+        return state.get_altitude();
+    }
+    apogee(
+        state,
+        OdeSolver::RK45(AdaptiveTimeStep::default()),
+        MAX_ITERATIONS,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -126,6 +337,7 @@ mod tests {
             moment_of_inertia: 0.0,
             stab_margin_dimensional: 0.0,
             cl_a: 0.0,
+            ..Default::default()
         };
 
         let state = State::OneDOF(OneDOFModel::new(
@@ -178,6 +390,41 @@ mod tests {
         assert!(!simulation.is_done());
         // We have to do - 1 here because current_iteration is zero-indexed
         assert_eq!(simulation.current_iteration, max_iterations - 1);
+        assert_eq!(simulation.steps_taken, max_iterations);
+
+        // The estimate extrapolates the rest of the climb
+        assert_eq!(simulation.status(), PredictionStatus::BudgetExhausted);
+        assert!(simulation.apogee_estimate() > simulation.state.get_altitude());
+        assert!((simulation.apogee_estimate() - target).abs() < 20.0);
+    }
+
+    #[test]
+    fn test_run_stops_at_deadline_and_divergence() {
+        let mut simulation = make_simulation().with_deadline(Instant::now());
+        simulation.run(&mut SimulationData::new(), false, false);
+        assert_eq!(simulation.status(), PredictionStatus::BudgetExhausted);
+        assert_eq!(simulation.steps_taken, 0);
+
+        // A massless rocket has infinite acceleration
+        let rocket_properties = RocketProperties {
+            mass: 0.0,
+            cd: 0.75,
+            area_drag: 0.03,
+            ..Default::default()
+        };
+        let state = State::OneDOF(OneDOFModel::new(
+            Vector2::new(10.0, 100.0),
+            rocket_properties,
+        ));
+        let mut simulation = Simulation::new(
+            state,
+            OdeSolver::Euler(FixedTimeStep { dt: 0.1 }),
+            SimulationExitCondition::ApogeeReached,
+            100,
+        );
+        simulation.run(&mut SimulationData::new(), false, false);
+        assert_eq!(simulation.status(), PredictionStatus::Diverged);
+        assert_eq!(simulation.state.get_altitude(), 10.0);
     }
 
     #[test]
@@ -191,6 +438,7 @@ mod tests {
             moment_of_inertia: 0.0,
             stab_margin_dimensional: 0.0,
             cl_a: 0.0,
+            ..Default::default()
         };
 
         let state_positive_vel = State::OneDOF(OneDOFModel::new(
@@ -214,6 +462,7 @@ mod tests {
             moment_of_inertia: 0.0,
             stab_margin_dimensional: 0.0,
             cl_a: 0.0,
+            ..Default::default()
         };
         let state_negative_vel = State::OneDOF(OneDOFModel::new(
             Vector2::new(0.00, -0.01),
@@ -228,6 +477,23 @@ mod tests {
         assert!(sim_negative_vel.is_done());
     }
 
+    #[test]
+    fn test_exit_condition_ground_hit() {
+        let mut simulation = make_simulation();
+        // Launching from the ground is not a ground hit
+        simulation.exit_condition = SimulationExitCondition::GroundHit;
+        simulation.max_iterations = 10_000;
+        assert!(!simulation.is_done());
+
+        simulation.run(&mut SimulationData::new(), false, false);
+        assert_eq!(simulation.status(), PredictionStatus::Converged);
+        // Backtracking lands the rocket close to the ground, while falling slower than in vacuum
+        assert_abs_diff_eq!(simulation.state().get_altitude(), 0.0, epsilon = 0.5);
+        let velocity = simulation.state().get_vertical_velocity();
+        assert!(velocity < 0.0);
+        assert!(velocity.abs() < (2.0 * 9.80665 * 453.87_f64).sqrt());
+    }
+
     #[test]
     fn test_apogee() {
         // Before running, the sim should not be done, so apogee() returns NaN
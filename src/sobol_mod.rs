@@ -0,0 +1,429 @@
+use crate::batch_mod::parallel_map;
+use crate::dispersion_mod::{
+    percentile, simulate_run, validate_parameters, DispersedParameter, DispersionOutput,
+};
+use crate::filter_mod::matrix_to_pyarray;
+use crate::ode::OdeSolver;
+use crate::random_mod::Rng;
+use crate::rocket::RocketProperties;
+use crate::state::InitialCondition;
+use nalgebra::DMatrix;
+use numpy::{PyArray1, PyArray2, ToPyArray};
+use pyo3::prelude::*;
+
+/// Default number of base samples of a Sobol analysis, which runs (parameters + 2) simulations
+/// per base sample
+pub(crate) const DEFAULT_SOBOL_SAMPLES: usize = 512;
+/// Default number of bootstrap resamples behind the confidence intervals of Sobol indices
+pub(crate) const DEFAULT_BOOTSTRAP_RESAMPLES: usize = 200;
+/// Default confidence level of the intervals of Sobol indices
+pub(crate) const DEFAULT_CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// Variance-based (Sobol) sensitivity indices of an output, with each parameter uniformly
+/// distributed over its range. The first order index of a parameter is the fraction of the
+/// output's variance it causes on its own, and the total order index adds its interactions with
+/// the other parameters.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct SobolIndices {
+    /// The parameter behind each index
+    #[pyo3(get)]
+    pub parameters: Vec<DispersedParameter>,
+    #[pyo3(get)]
+    pub output: DispersionOutput,
+    /// Variance of the output over the parameter ranges
+    #[pyo3(get)]
+    pub variance: f64,
+    #[pyo3(get)]
+    pub confidence_level: f64,
+    /// Number of base samples whose runs all completed, which the indices are estimated from
+    #[pyo3(get)]
+    pub num_samples: usize,
+    /// Number of base samples left out because one of their runs didn't complete the output
+    #[pyo3(get)]
+    pub failed_samples: usize,
+    first_order: Vec<f64>,
+    total_order: Vec<f64>,
+    /// (lower, upper) row per parameter
+    first_order_interval: DMatrix<f64>,
+    total_order_interval: DMatrix<f64>,
+}
+
+#[pymethods]
+impl SobolIndices {
+    /// First order index of each parameter.
+    #[getter]
+    pub fn first_order<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.first_order.to_pyarray(py)
+    }
+
+    /// Total order index of each parameter.
+    #[getter]
+    pub fn total_order<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        self.total_order.to_pyarray(py)
+    }
+
+    /// Bootstrap confidence interval of each first order index, with a (lower, upper) row per
+    /// parameter.
+    #[getter]
+    pub fn first_order_interval<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.first_order_interval)
+    }
+
+    /// Bootstrap confidence interval of each total order index, with a (lower, upper) row per
+    /// parameter.
+    #[getter]
+    pub fn total_order_interval<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f64>> {
+        matrix_to_pyarray(py, &self.total_order_interval)
+    }
+}
+
+/// The two independent sample matrices of Saltelli's scheme, with a row per base sample.
+struct SaltelliSample {
+    a: Vec<Vec<f64>>,
+    b: Vec<Vec<f64>>,
+}
+
+impl SaltelliSample {
+    /// Draws both matrices uniformly within the bounds of each parameter.
+    fn draw(bounds: &[(f64, f64)], num_samples: usize, rng: &mut Rng) -> Self {
+        let mut draw_matrix = || -> Vec<Vec<f64>> {
+            (0..num_samples)
+                .map(|_| {
+                    bounds
+                        .iter()
+                        .map(|(lower, upper)| lower + (upper - lower) * rng.uniform())
+                        .collect()
+                })
+                .collect()
+        };
+        let a = draw_matrix();
+        let b = draw_matrix();
+        Self { a, b }
+    }
+
+    /// Every row to evaluate: A, B, then for each parameter i, A with its column i taken from B.
+    fn rows(&self) -> Vec<Vec<f64>> {
+        let num_parameters = self.a.first().map_or(0, Vec::len);
+        let mut rows = self.a.clone();
+        rows.extend(self.b.iter().cloned());
+        for i in 0..num_parameters {
+            rows.extend(self.a.iter().zip(&self.b).map(|(a, b)| {
+                let mut row = a.clone();
+                row[i] = b[i];
+                row
+            }));
+        }
+        rows
+    }
+}
+
+/// Output variance and the first and total order indices, estimated from the outputs of the rows
+/// of a Saltelli sample of `num_samples` base samples, over the base samples in `samples` (which
+/// may repeat). The first order estimator is from Saltelli et al. (2010), applied to the centred
+/// outputs since it is noisy for outputs with a large mean like the apogee, and the total order
+/// estimator is from Jansen (1999).
+fn estimate(
+    outputs: &[f64],
+    num_samples: usize,
+    num_parameters: usize,
+    samples: &[usize],
+) -> (f64, Vec<f64>, Vec<f64>) {
+    let count = samples.len() as f64;
+    let f_a = |j: usize| outputs[j];
+    let f_b = |j: usize| outputs[num_samples + j];
+    let f_ab = |i: usize, j: usize| outputs[(2 + i) * num_samples + j];
+
+    let mean = samples.iter().map(|&j| f_a(j) + f_b(j)).sum::<f64>() / (2.0 * count);
+    let variance = samples
+        .iter()
+        .map(|&j| (f_a(j) - mean).powi(2) + (f_b(j) - mean).powi(2))
+        .sum::<f64>()
+        / (2.0 * count);
+
+    let first_order = (0..num_parameters)
+        .map(|i| {
+            samples
+                .iter()
+                .map(|&j| (f_b(j) - mean) * (f_ab(i, j) - f_a(j)))
+                .sum::<f64>()
+                / count
+                / variance
+        })
+        .collect();
+    let total_order = (0..num_parameters)
+        .map(|i| {
+            samples
+                .iter()
+                .map(|&j| (f_a(j) - f_ab(i, j)).powi(2))
+                .sum::<f64>()
+                / (2.0 * count)
+                / variance
+        })
+        .collect();
+    (variance, first_order, total_order)
+}
+
+/// Bootstrap percentile interval of the given confidence level, with a (lower, upper) row per
+/// index.
+fn intervals(resampled: Vec<Vec<f64>>, confidence_level: f64) -> DMatrix<f64> {
+    let mut matrix = DMatrix::zeros(resampled.len(), 2);
+    for (i, mut values) in resampled.into_iter().enumerate() {
+        values.retain(|x| x.is_finite());
+        values.sort_by(f64::total_cmp);
+        matrix[(i, 0)] = percentile(&values, 50.0 * (1.0 - confidence_level));
+        matrix[(i, 1)] = percentile(&values, 50.0 * (1.0 + confidence_level));
+    }
+    matrix
+}
+
+/// Sobol analysis of an output of a flight, with the given parameters uniformly distributed
+/// within their bounds.
+#[derive(Clone, Debug)]
+pub(crate) struct SobolProblem {
+    pub(crate) rocket_properties: RocketProperties,
+    pub(crate) initial_condition: InitialCondition,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) parameters: Vec<DispersedParameter>,
+    /// (lower, upper) bounds of each parameter
+    pub(crate) bounds: Vec<(f64, f64)>,
+    pub(crate) output: DispersionOutput,
+    pub(crate) max_iterations: u64,
+}
+
+/// Sample sizes and confidence level of a Sobol analysis.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SobolOptions {
+    pub(crate) num_samples: usize,
+    pub(crate) num_resamples: usize,
+    pub(crate) confidence_level: f64,
+    pub(crate) seed: u64,
+}
+
+impl SobolProblem {
+    fn validate(&self, options: &SobolOptions) -> Result<(), String> {
+        if self.parameters.len() != self.bounds.len() {
+            return Err("There must be one range per parameter".to_string());
+        }
+        if let Some((parameter, _)) = self
+            .parameters
+            .iter()
+            .zip(&self.bounds)
+            .find(|(_, (lower, upper))| !(lower < upper && lower.is_finite() && upper.is_finite()))
+        {
+            return Err(format!(
+                "The range of {parameter:?} must have a finite lower bound below its upper bound"
+            ));
+        }
+        if options.num_samples < 2 {
+            return Err("num_samples must be at least 2".to_string());
+        }
+        if options.num_resamples == 0 {
+            return Err("num_resamples must be positive".to_string());
+        }
+        if !(options.confidence_level > 0.0 && options.confidence_level < 1.0) {
+            return Err("confidence_level must be between 0 and 1".to_string());
+        }
+        validate_parameters(&self.parameters, &self.initial_condition)
+    }
+
+    /// Runs the (parameters + 2) * `num_samples` simulations of Saltelli's scheme on
+    /// `num_threads` threads, or one per available core, and estimates the indices. The samples and
+    /// the bootstrap resamples come from a single generator, so the results only depend on the
+    /// seed and not on the number of threads.
+    pub(crate) fn run(
+        &self,
+        options: SobolOptions,
+        num_threads: Option<usize>,
+    ) -> Result<SobolIndices, String> {
+        self.validate(&options)?;
+        let (num_samples, num_parameters) = (options.num_samples, self.parameters.len());
+
+        let mut rng = Rng::new(options.seed);
+        let rows = SaltelliSample::draw(&self.bounds, num_samples, &mut rng).rows();
+        let outputs = parallel_map(&rows, num_threads, |row| {
+            simulate_run(
+                &self.rocket_properties,
+                self.initial_condition,
+                &self.ode_solver,
+                self.max_iterations,
+                &self.parameters,
+                row,
+            )
+            .get(self.output)
+        })?;
+
+        // A base sample is only usable if all of its runs completed
+        let completed: Vec<usize> = (0..num_samples)
+            .filter(|&j| (0..num_parameters + 2).all(|r| outputs[r * num_samples + j].is_finite()))
+            .collect();
+        if completed.len() < 2 {
+            return Err("Fewer than 2 base samples completed all of their runs".to_string());
+        }
+        let (variance, first_order, total_order) =
+            estimate(&outputs, num_samples, num_parameters, &completed);
+        if variance == 0.0 {
+            return Err(format!(
+                "{:?} doesn't vary over the parameter ranges",
+                self.output
+            ));
+        }
+
+        let mut first_resampled = vec![Vec::with_capacity(options.num_resamples); num_parameters];
+        let mut total_resampled = first_resampled.clone();
+        for _ in 0..options.num_resamples {
+            let resample: Vec<usize> = (0..completed.len())
+                .map(|_| completed[rng.index(completed.len())])
+                .collect();
+            let (_, first, total) = estimate(&outputs, num_samples, num_parameters, &resample);
+            for i in 0..num_parameters {
+                first_resampled[i].push(first[i]);
+                total_resampled[i].push(total[i]);
+            }
+        }
+
+        Ok(SobolIndices {
+            parameters: self.parameters.clone(),
+            output: self.output,
+            variance,
+            confidence_level: options.confidence_level,
+            num_samples: completed.len(),
+            failed_samples: num_samples - completed.len(),
+            first_order,
+            total_order,
+            first_order_interval: intervals(first_resampled, options.confidence_level),
+            total_order_interval: intervals(total_resampled, options.confidence_level),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::FixedTimeStep;
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+    use std::f64::consts::PI;
+
+    fn make_problem(
+        initial_condition: InitialCondition,
+        parameters: Vec<DispersedParameter>,
+        bounds: Vec<(f64, f64)>,
+    ) -> SobolProblem {
+        SobolProblem {
+            rocket_properties: RocketProperties::new(10.0, 0.5, 0.0182, 0.0182, 5.0, 0.3, 2.0),
+            initial_condition,
+            ode_solver: OdeSolver::RK3(FixedTimeStep::new(0.05)),
+            parameters,
+            bounds,
+            output: DispersionOutput::Apogee,
+            max_iterations: 100_000,
+        }
+    }
+
+    fn options(num_samples: usize) -> SobolOptions {
+        SobolOptions {
+            num_samples,
+            num_resamples: 100,
+            confidence_level: 0.95,
+            seed: 1,
+        }
+    }
+
+    fn one_dof() -> InitialCondition {
+        InitialCondition::OneDOF(InitialState1DOF::new(0.0, 150.0))
+    }
+
+    #[test]
+    fn estimators_recover_the_ishigami_indices() {
+        // Standard test function with known indices for inputs uniform on [-pi, pi]
+        let ishigami =
+            |x: &Vec<f64>| x[0].sin() + 7.0 * x[1].sin().powi(2) + 0.1 * x[2].powi(4) * x[0].sin();
+        let num_samples = 8192;
+        let sample = SaltelliSample::draw(&[(-PI, PI); 3], num_samples, &mut Rng::new(7));
+        let outputs: Vec<f64> = sample.rows().iter().map(ishigami).collect();
+        let samples: Vec<usize> = (0..num_samples).collect();
+        let (variance, first_order, total_order) = estimate(&outputs, num_samples, 3, &samples);
+
+        assert_abs_diff_eq!(variance, 13.845, epsilon = 0.5);
+        for (estimate, expected) in first_order.iter().zip([0.3139, 0.4424, 0.0]) {
+            assert_abs_diff_eq!(*estimate, expected, epsilon = 0.05);
+        }
+        for (estimate, expected) in total_order.iter().zip([0.5576, 0.4424, 0.2437]) {
+            assert_abs_diff_eq!(*estimate, expected, epsilon = 0.05);
+        }
+    }
+
+    #[test]
+    fn drag_dominates_a_small_altitude_uncertainty() {
+        // The initial altitude adds to the apogee, with a variance of 1/12 m^2
+        let problem = make_problem(
+            one_dof(),
+            vec![DispersedParameter::Cd, DispersedParameter::InitialAltitude],
+            vec![(0.4, 0.6), (0.0, 1.0)],
+        );
+        let indices = problem.run(options(256), Some(1)).unwrap();
+        assert_eq!(indices.num_samples, 256);
+        assert_eq!(indices.failed_samples, 0);
+
+        // Drag causes almost all of the variance, up to the sampling error of the estimators
+        assert_abs_diff_eq!(indices.first_order[0], 1.0, epsilon = 0.25);
+        assert_abs_diff_eq!(indices.total_order[0], 1.0, epsilon = 0.25);
+        assert!(indices.first_order[1].abs() < 0.01 && indices.total_order[1] < 0.01);
+        for (i, (first, total)) in indices
+            .first_order
+            .iter()
+            .zip(&indices.total_order)
+            .enumerate()
+        {
+            let (first_interval, total_interval) = (
+                indices.first_order_interval.row(i),
+                indices.total_order_interval.row(i),
+            );
+            assert!(first_interval[0] <= *first && *first <= first_interval[1]);
+            assert!(total_interval[0] <= *total && *total <= total_interval[1]);
+        }
+
+        // The results don't depend on the number of threads
+        let threaded = problem.run(options(256), Some(4)).unwrap();
+        assert_eq!(threaded.first_order, indices.first_order);
+        assert_eq!(threaded.total_order_interval, indices.total_order_interval);
+    }
+
+    #[test]
+    fn invalid_problems_are_rejected() {
+        let mismatched = make_problem(one_dof(), vec![DispersedParameter::Cd], vec![]);
+        assert!(mismatched.run(options(8), None).is_err());
+        let empty_range = make_problem(one_dof(), vec![DispersedParameter::Cd], vec![(0.5, 0.5)]);
+        assert!(empty_range.run(options(8), None).is_err());
+        let wind = make_problem(
+            one_dof(),
+            vec![DispersedParameter::WindSpeed],
+            vec![(0.0, 5.0)],
+        );
+        assert!(wind.run(options(8), None).is_err());
+
+        let problem = make_problem(one_dof(), vec![DispersedParameter::Cd], vec![(0.4, 0.6)]);
+        assert!(problem.run(options(1), None).is_err());
+        let no_confidence = SobolOptions {
+            confidence_level: 1.0,
+            ..options(8)
+        };
+        assert!(problem.run(no_confidence, None).is_err());
+        // The 1DOF model always lands where it launched
+        let landing = SobolProblem {
+            output: DispersionOutput::LandingDownrange,
+            ..problem
+        };
+        assert!(landing.run(options(8), None).is_err());
+
+        // But it does vary for the 3DOF model
+        let three_dof =
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 0.1, -15.0, 150.0, 0.0));
+        let landing = SobolProblem {
+            initial_condition: three_dof,
+            ..landing
+        };
+        assert!(landing.run(options(8), None).is_ok());
+    }
+}
@@ -2,13 +2,16 @@ pub(crate) mod model_1dof;
 pub(crate) mod model_3dof;
 pub(crate) mod state_vector;
 
-use nalgebra::{Vector2, Vector6};
+use nalgebra::{DMatrix, SVector, Vector2, Vector6};
 use pyo3::prelude::*;
 
-use crate::rocket::{Rocket, RocketProperties};
+use crate::derived_mod::FlightQuantities;
+use crate::dual_mod::Real;
+use crate::physics_mod;
+use crate::rocket::{ModelParameters, Rocket, RocketProperties};
 use crate::state::model_1dof::OneDOFModel;
 use crate::state::model_3dof::ThreeDOFModel;
-use crate::state::state_vector::StateVector;
+use crate::state::state_vector::{AuxVector, StateVector};
 
 /// Struct for defining the initial conditions of a 1-DOF simulation.
 #[pyclass(get_all, set_all)]
@@ -56,6 +59,39 @@ impl InitialState3DOF {
     }
 }
 
+/// Initial conditions of either model, used where the same search or analysis is run for both.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum InitialCondition {
+    OneDOF(InitialState1DOF),
+    ThreeDOF(InitialState3DOF),
+}
+
+impl InitialCondition {
+    /// Makes a new state for the model matching the initial conditions.
+    pub(crate) fn to_state(self, rocket_properties: RocketProperties) -> State {
+        match self {
+            InitialCondition::OneDOF(initial_state) => {
+                State::new_1dof(rocket_properties, initial_state)
+            }
+            InitialCondition::ThreeDOF(initial_state) => {
+                State::new_3dof(rocket_properties, initial_state)
+            }
+        }
+    }
+
+    /// Initial conditions of the same model taken from a row of the state log.
+    pub(crate) fn with_row(self, row: &[f64]) -> Self {
+        match self {
+            InitialCondition::OneDOF(_) => {
+                InitialCondition::OneDOF(InitialState1DOF::new(row[0], row[1]))
+            }
+            InitialCondition::ThreeDOF(_) => InitialCondition::ThreeDOF(InitialState3DOF::new(
+                row[0], row[1], row[2], row[3], row[4], row[5],
+            )),
+        }
+    }
+}
+
 /// The internal simulation state, wrapping either a 1-DOF or 3-DOF model. These models are what
 /// contain the actual state information. The State struct provides a common interface for the ODE
 /// solver to interact with, while the underlying models handle the specific details of the state.
@@ -65,7 +101,7 @@ impl InitialState3DOF {
 /// will get the derivatives of the model represented as a `StateVector`. Then with this `StateVector`,
 /// the ODE solver can perform its calculations and then call `update()` on the State struct to update
 /// the state with the new values.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub(crate) enum State {
     OneDOF(OneDOFModel),
     ThreeDOF(ThreeDOFModel),
@@ -99,6 +135,24 @@ impl State {
         State::ThreeDOF(ThreeDOFModel::new(u3, rocket_properties))
     }
 
+    /// Makes a state of the same model with the same states and time, but other rocket
+    /// properties, e.g. to predict the flight with a different airbrake command. Any auxiliary
+    /// states start from the initial ones of the new properties.
+    pub(crate) fn with_rocket_properties(&self, rocket_properties: RocketProperties) -> Self {
+        match self {
+            State::OneDOF(dof1) => {
+                let mut model = OneDOFModel::new(dof1.u, rocket_properties);
+                model.time = dof1.time;
+                State::OneDOF(model)
+            }
+            State::ThreeDOF(dof3) => {
+                let mut model = ThreeDOFModel::new(dof3.u, rocket_properties);
+                model.time = dof3.time;
+                State::ThreeDOF(model)
+            }
+        }
+    }
+
     /// Gets the current state vector with the additional log information (e.g. acceleration)
     /// included, which is used for logging the simulation data.
     pub(crate) fn get_row_log(&self) -> StateVector {
@@ -117,14 +171,71 @@ impl State {
     }
 
     /// Gets the state represented as a `StateVector`.
-    #[allow(dead_code)]
     pub(crate) fn get_state_vec(&self) -> StateVector {
         match self {
+            State::OneDOF(dof1) if dof1.has_aux() => {
+                StateVector::OneDOFAux(Self::augment(&dof1.u, &dof1.aux))
+            }
+            State::ThreeDOF(dof3) if dof3.has_aux() => {
+                StateVector::ThreeDOFAux(Self::augment(&dof3.u, &dof3.aux))
+            }
             State::OneDOF(dof1) => StateVector::OneDOF(dof1.u),
             State::ThreeDOF(dof3) => StateVector::ThreeDOF(dof3.u),
         }
     }
 
+    /// Jacobian of the time derivative of the model states with respect to the model states, with
+    /// any auxiliary states held fixed.
+    pub(crate) fn get_jacobian(&self) -> DMatrix<f64> {
+        match self {
+            State::OneDOF(dof1) => DMatrix::from_column_slice(2, 2, dof1.jacobian().as_slice()),
+            State::ThreeDOF(dof3) => DMatrix::from_column_slice(6, 6, dof3.jacobian().as_slice()),
+        }
+    }
+
+    /// Time derivative of a state vector in the layout of `get_state_vec`, with the model's time
+    /// and rocket but the given scalar rocket properties. Generic over the scalar type so it can
+    /// be evaluated with dual numbers. Any auxiliary states are evaluated with their values only,
+    /// as they depend on neither the model states nor the rocket properties.
+    pub(crate) fn derivatives_with<T: Real>(
+        &self,
+        x: &[T],
+        parameters: &ModelParameters<T>,
+    ) -> Vec<T> {
+        let dim = match self {
+            State::OneDOF(_) => 2,
+            State::ThreeDOF(_) => 6,
+        };
+        let aux = match self {
+            _ if x.len() > dim => AuxVector::from_iterator(x[dim..].iter().map(|x| x.value())),
+            State::OneDOF(dof1) => dof1.aux,
+            State::ThreeDOF(dof3) => dof3.aux,
+        };
+        let mut dxdt: Vec<T> = match self {
+            State::OneDOF(dof1) => dof1
+                .derivatives_with(&Vector2::from_column_slice(&x[..dim]), &aux, parameters)
+                .as_slice()
+                .to_vec(),
+            State::ThreeDOF(dof3) => dof3
+                .derivatives_with(&Vector6::from_column_slice(&x[..dim]), &aux, parameters)
+                .as_slice()
+                .to_vec(),
+        };
+        if x.len() > dim {
+            let daux = self.get_rocket_properties().aux_derivatives(&aux);
+            dxdt.extend(daux.iter().map(|&d| T::constant(d)));
+        }
+        dxdt
+    }
+
+    /// Whether all components of the state are finite.
+    pub(crate) fn is_finite(&self) -> bool {
+        self.get_state_vec()
+            .as_array()
+            .iter()
+            .all(|x| x.is_finite())
+    }
+
     /// Gets the altitude of the rocket from the state.
     #[allow(dead_code)]
     pub(crate) fn get_altitude(&self) -> f64 {
@@ -134,6 +245,23 @@ impl State {
         }
     }
 
+    /// Gets the horizontal distance the rocket travelled from the origin. The 1DOF model flies
+    /// straight up, so this is always zero for it.
+    pub(crate) fn get_downrange(&self) -> f64 {
+        match self {
+            State::OneDOF(_) => 0.0,
+            State::ThreeDOF(dof3) => dof3.u[0],
+        }
+    }
+
+    /// Gets the speed of the rocket relative to the ground.
+    pub(crate) fn get_speed(&self) -> f64 {
+        match self {
+            State::OneDOF(dof1) => dof1.get_velocity().abs(),
+            State::ThreeDOF(dof3) => dof3.u[3].hypot(dof3.u[4]),
+        }
+    }
+
     /// Gets the vertical velocity of the rocket from the state.
     pub(crate) fn get_vertical_velocity(&self) -> f64 {
         match self {
@@ -142,6 +270,31 @@ impl State {
         }
     }
 
+    /// Gets the rocket properties used by the model.
+    pub(crate) fn get_rocket_properties(&self) -> &RocketProperties {
+        match self {
+            State::OneDOF(dof1) => dof1.get_rocket_properties(),
+            State::ThreeDOF(dof3) => dof3.get_rocket_properties(),
+        }
+    }
+
+    /// Sets the commanded airbrake deployment. Does nothing if the rocket has no airbrake.
+    pub(crate) fn set_airbrake_command(&mut self, command: f64) {
+        match self {
+            State::OneDOF(dof1) => dof1.set_airbrake_command(command),
+            State::ThreeDOF(dof3) => dof3.set_airbrake_command(command),
+        }
+    }
+
+    /// Gets the actual airbrake deployment, which lags behind the command if the airbrake has an
+    /// actuator. Returns `None` if the rocket has no airbrake.
+    pub(crate) fn get_airbrake_deployment(&self) -> Option<f64> {
+        match self {
+            State::OneDOF(dof1) => dof1.get_airbrake_deployment(),
+            State::ThreeDOF(dof3) => dof3.get_airbrake_deployment(),
+        }
+    }
+
     /// Gets the time value from the state.
     pub(crate) fn get_time(&self) -> f64 {
         match self {
@@ -154,8 +307,67 @@ impl State {
     /// used by the ODE solver to perform its calculations.
     pub(crate) fn get_derivatives(&mut self) -> StateVector {
         match self {
-            State::OneDOF(dof1) => StateVector::OneDOF(dof1.get_derivatives()),
-            State::ThreeDOF(dof3) => StateVector::ThreeDOF(dof3.get_derivatives()),
+            State::OneDOF(dof1) => {
+                let dudt = dof1.get_derivatives();
+                if dof1.has_aux() {
+                    StateVector::OneDOFAux(Self::augment(&dudt, &dof1.daux))
+                } else {
+                    StateVector::OneDOF(dudt)
+                }
+            }
+            State::ThreeDOF(dof3) => {
+                let dudt = dof3.get_derivatives();
+                if dof3.has_aux() {
+                    StateVector::ThreeDOFAux(Self::augment(&dudt, &dof3.daux))
+                } else {
+                    StateVector::ThreeDOF(dudt)
+                }
+            }
+        }
+    }
+
+    /// Gets the quantities derived from the current state along with its derivatives, such as
+    /// the Mach number and aerodynamic forces.
+    pub(crate) fn get_flight_quantities(&mut self) -> FlightQuantities {
+        match self {
+            State::OneDOF(dof1) => dof1.get_flight_quantities(),
+            State::ThreeDOF(dof3) => dof3.get_flight_quantities(),
+        }
+    }
+
+    /// Gets the magnitude of the rocket's acceleration (m/s^2), including gravity.
+    pub(crate) fn get_acceleration(&mut self) -> f64 {
+        let derivatives = self.get_derivatives();
+        let dudt = derivatives.as_array();
+        match self {
+            State::OneDOF(_) => dudt[1].abs(),
+            State::ThreeDOF(_) => dudt[3].hypot(dudt[4]),
+        }
+    }
+
+    /// Gets the specific force along the rocket's axis (m/s^2), which an axial accelerometer
+    /// reads: zero in free fall and negative while drag decelerates the rocket.
+    pub(crate) fn get_axial_acceleration(&self) -> f64 {
+        let parameters = self.get_rocket_properties().parameters();
+        self.axial_acceleration_with(self.get_state_vec().as_array(), &parameters)
+    }
+
+    /// Specific force along the rocket's axis like `get_axial_acceleration`, for a state vector
+    /// and scalar rocket properties like `derivatives_with`.
+    pub(crate) fn axial_acceleration_with<T: Real>(
+        &self,
+        x: &[T],
+        parameters: &ModelParameters<T>,
+    ) -> T {
+        let dxdt = self.derivatives_with(x, parameters);
+        let g = physics_mod::gravity();
+        match self {
+            State::OneDOF(_) => dxdt[1] - g,
+            State::ThreeDOF(_) => {
+                // Same orientation convention as the 3DOF model
+                let (sin, cos) = (x[2].sin(), x[2].cos());
+                -(sin * dxdt[3]) + cos * (dxdt[4] - g)
+            }
         }
     }
 
@@ -165,10 +377,26 @@ impl State {
         match (self, du_vec) {
             (State::OneDOF(dof1), StateVector::OneDOF(du)) => dof1.update_state(du, dt),
             (State::ThreeDOF(dof3), StateVector::ThreeDOF(du)) => dof3.update_state(du, dt),
+            (State::OneDOF(dof1), StateVector::OneDOFAux(du)) => {
+                dof1.update_aux(du.fixed_rows::<3>(2).into_owned());
+                dof1.update_state(du.fixed_rows::<2>(0).into_owned(), dt)
+            }
+            (State::ThreeDOF(dof3), StateVector::ThreeDOFAux(du)) => {
+                dof3.update_aux(du.fixed_rows::<3>(6).into_owned());
+                dof3.update_state(du.fixed_rows::<6>(0).into_owned(), dt)
+            }
             // This case should *never* happen because increment types match DOF models.
             _ => {
                 unreachable!("Invalid State/update combination");
             }
         }
     }
+
+    /// Appends the auxiliary states to a model's state (or derivative) vector.
+    fn augment<const N: usize, const M: usize>(
+        u: &SVector<f64, N>,
+        aux: &AuxVector,
+    ) -> SVector<f64, M> {
+        SVector::<f64, M>::from_iterator(u.iter().chain(aux.iter()).copied())
+    }
 }
@@ -1,9 +1,12 @@
 //use crate::math::vec_ops::MathVector;
+use crate::derived_mod::FlightQuantities;
+use crate::dual_mod::{Dual, Real};
 use crate::physics_mod;
-use crate::rocket::{Rocket, RocketProperties};
-use nalgebra::{Vector2, Vector3};
+use crate::rocket::{ModelParameters, Rocket, RocketProperties};
+use crate::state::state_vector::AuxVector;
+use nalgebra::{Matrix2, Vector2, Vector3};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub(crate) struct OneDOFModel {
     // This model is a simple 1D, (position,velocity) model
     // The assumtion is that the rocket is flying perfectly vertical and that there are no
@@ -12,6 +15,12 @@ pub(crate) struct OneDOFModel {
     pub(super) u: Vector2<f64>,
     /// (d_height/dt, d_velocity/dt)
     pub(super) dudt: Vector2<f64>,
+    /// Auxiliary (actuator) states, integrated alongside u
+    pub(super) aux: AuxVector,
+    /// Time derivatives of the auxiliary states
+    pub(super) daux: AuxVector,
+    /// Quantities derived along with `dudt`
+    pub(super) quantities: FlightQuantities,
     rocket_properties: RocketProperties,
     is_current: bool,
     pub(super) time: f64,
@@ -22,6 +31,9 @@ impl OneDOFModel {
         Self {
             u,
             dudt: Vector2::new(f64::NAN, f64::NAN),
+            aux: rocket_properties.initial_aux_state(),
+            daux: AuxVector::zeros(),
+            quantities: FlightQuantities::default(),
             rocket_properties,
             is_current: false,
             time: 0.0,
@@ -66,35 +78,142 @@ impl OneDOFModel {
         self.is_current = false;
     }
 
+    /// Whether the model has auxiliary states which need to be integrated.
+    pub(super) fn has_aux(&self) -> bool {
+        self.rocket_properties.actuator().is_some()
+    }
+
+    pub(super) fn update_aux(&mut self, daux: AuxVector) {
+        self.aux += daux;
+        self.is_current = false;
+    }
+
+    pub(super) fn get_rocket_properties(&self) -> &RocketProperties {
+        &self.rocket_properties
+    }
+
+    pub(super) fn set_airbrake_command(&mut self, command: f64) {
+        if let Some(airbrake) = self.rocket_properties.airbrake.as_mut() {
+            airbrake.deployment = command;
+            self.is_current = false;
+        }
+    }
+
+    pub(super) fn get_airbrake_deployment(&self) -> Option<f64> {
+        self.rocket_properties
+            .airbrake
+            .as_ref()
+            .map(|airbrake| airbrake.actual_deployment(&self.aux))
+    }
+
     pub(super) fn update_state_derivatives(&mut self) {
         // If already current, no need to recompute
         if self.is_current {
             return;
         }
+        (self.dudt, self.quantities) = self.derivatives_and_quantities(
+            &self.u,
+            &self.aux,
+            &self.rocket_properties.parameters(),
+        );
+        self.daux = self.rocket_properties.aux_derivatives(&self.aux);
+        self.is_current = true;
+    }
 
+    /// Gets the derived flight quantities at the current state.
+    pub(super) fn get_flight_quantities(&mut self) -> FlightQuantities {
+        self.update_state_derivatives();
+        self.quantities
+    }
+
+    /// Time derivatives of the state `u`, with the model's auxiliary states. Generic over the
+    /// scalar type so it can be evaluated with dual numbers.
+    pub(crate) fn derivatives<T: Real>(&self, u: &Vector2<T>) -> Vector2<T> {
+        self.derivatives_with(u, &self.aux, &self.rocket_properties.parameters())
+    }
+
+    /// Time derivatives of the state `u`, with the given auxiliary states and scalar rocket
+    /// properties in place of the model's.
+    pub(crate) fn derivatives_with<T: Real>(
+        &self,
+        u: &Vector2<T>,
+        aux: &AuxVector,
+        parameters: &ModelParameters<T>,
+    ) -> Vector2<T> {
+        self.derivatives_and_quantities(u, aux, parameters).0
+    }
+
+    /// Time derivatives of the state `u`, together with the flight quantities derived on the way.
+    fn derivatives_and_quantities<T: Real>(
+        &self,
+        u: &Vector2<T>,
+        aux: &AuxVector,
+        parameters: &ModelParameters<T>,
+    ) -> (Vector2<T>, FlightQuantities) {
+        // With aerodynamic tables the drag comes from the axial force coefficient at zero angle
+        // of attack, which varies with Mach number
+        let mach = u[1].abs() / physics_mod::speed_of_sound();
+        let (cd, stab_margin) = match &self.rocket_properties.aero_table {
+            Some(table) => {
+                let coeffs = table.interpolate(mach, T::constant(0.0));
+                (
+                    coeffs.ca,
+                    coeffs.cp.value() - self.rocket_properties.cg_location,
+                )
+            }
+            None => (
+                parameters.cd,
+                self.rocket_properties.stab_margin_dimensional,
+            ),
+        };
+        let (cd, area_drag) =
+            self.rocket_properties
+                .drag_with_airbrake(cd, parameters.area_drag, mach, aux);
+
+        // The drag force is always negative, so it is flipped to oppose a descending rocket
         let force_drag = physics_mod::calc_drag_force(
-            self.u[1],
-            self.rocket_properties.cd,
-            self.rocket_properties.area_drag,
-        );
+            u[1],
+            cd,
+            area_drag,
+            self.rocket_properties.skin_friction.as_ref(),
+            u[0],
+        ) * u[1].signum();
         let g = physics_mod::gravity();
 
         // dhdt = velocity
-        let dhdt = self.u[1];
+        let dhdt = u[1];
 
         //a = F/m + g
-        let dvdt = force_drag / self.rocket_properties.mass + g;
+        let dvdt = force_drag / parameters.mass + g;
+
+        // The rocket flies straight along its axis, so there is no lift or angle of attack
+        let quantities = FlightQuantities::new(
+            &self.rocket_properties,
+            u[1].value().abs(),
+            0.0,
+            force_drag.value().abs(),
+            0.0,
+            stab_margin,
+        );
 
-        self.dudt = Vector2::new(dhdt, dvdt);
-        self.is_current = true;
+        (Vector2::new(dhdt, dvdt), quantities)
+    }
+
+    /// Jacobian of the state derivative with respect to the state, from dual numbers.
+    pub(super) fn jacobian(&self) -> Matrix2<f64> {
+        let u = Vector2::from_fn(|i, _| Dual::<2>::variable(self.u[i], i));
+        let dudt = self.derivatives(&u);
+        Matrix2::from_fn(|i, j| dudt[i].derivatives[j])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aero_mod::{AeroTable, SkinFriction};
+    use crate::airbrake_mod::{Airbrake, Curve};
     use approx::assert_abs_diff_eq;
-    use nalgebra::Vector2;
+    use nalgebra::{Matrix2, Vector2};
 
     /// Makes a rocket_properties with known values for 1DOF tests.
     fn make_rocket_properties(mass: f64, cd: f64, area_drag: f64) -> RocketProperties {
@@ -172,7 +291,7 @@ mod tests {
         assert_abs_diff_eq!(dof.dudt[0], v, epsilon = 1e-12);
 
         // dvdt = drag/m + g
-        let drag = physics_mod::calc_drag_force(v, cd, area);
+        let drag = physics_mod::calc_drag_force(v, cd, area, None, 0.0);
         let g = physics_mod::gravity();
         let expected_dvdt = drag / mass + g;
 
@@ -232,6 +351,18 @@ mod tests {
         assert!(d_after[1] != d_before[1]);
     }
 
+    #[test]
+    fn drag_opposes_a_descending_rocket() {
+        let rocket_properties = make_rocket_properties(5.0, 0.5, 0.02);
+        let mut dof = OneDOFModel::new(Vector2::new(100.0, -30.0), rocket_properties);
+        dof.update_state_derivatives();
+
+        let drag = physics_mod::calc_drag_force(30.0, 0.5, 0.02, None, 0.0);
+        let g = physics_mod::gravity();
+        assert_abs_diff_eq!(dof.dudt[1], g - drag / 5.0, epsilon = 1e-12);
+        assert!(dof.dudt[1] > g);
+    }
+
     #[test]
     fn get_row_log_returns_h_v_a() {
         let h = 42.0;
@@ -247,4 +378,124 @@ mod tests {
         assert_abs_diff_eq!(row[1], v, epsilon = 1e-12);
         assert_abs_diff_eq!(row[2], dof.dudt[1], epsilon = 1e-12);
     }
+
+    #[test]
+    fn skin_friction_adds_reynolds_dependent_drag() {
+        let mut rocket_properties = make_rocket_properties(10.0, 0.3, 0.0182);
+        let mut plain = OneDOFModel::new(Vector2::new(0.0, 100.0), rocket_properties.clone());
+
+        rocket_properties.skin_friction = Some(SkinFriction::new(1.5, 2.5, 20e-6, 5.0e5));
+        let mut with_friction =
+            OneDOFModel::new(Vector2::new(0.0, 100.0), rocket_properties.clone());
+
+        plain.update_state_derivatives();
+        with_friction.update_state_derivatives();
+
+        let drag = physics_mod::calc_drag_force(
+            100.0,
+            0.3,
+            0.0182,
+            rocket_properties.skin_friction.as_ref(),
+            0.0,
+        );
+        assert!(with_friction.dudt[1] < plain.dudt[1]);
+        assert_abs_diff_eq!(
+            with_friction.dudt[1],
+            drag / 10.0 + physics_mod::gravity(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn aero_table_drag_varies_with_mach() {
+        // CA rises linearly from 0.4 at Mach 0 to 0.8 at Mach 1
+        let table = AeroTable::from_vecs(
+            vec![0.0, 1.0],
+            vec![0.0, 0.2],
+            vec![0.4, 0.4, 0.8, 0.8],
+            vec![0.0, 1.0, 0.0, 1.0],
+            vec![1.0; 4],
+        )
+        .unwrap();
+        let mut rocket_properties = make_rocket_properties(10.0, 0.3, 0.0182);
+        rocket_properties.aero_table = Some(table);
+
+        let v = 0.5 * physics_mod::speed_of_sound();
+        let mut dof = OneDOFModel::new(Vector2::new(0.0, v), rocket_properties);
+        dof.update_state_derivatives();
+
+        let drag = physics_mod::calc_drag_force(v, 0.6, 0.0182, None, 0.0);
+        assert_abs_diff_eq!(
+            dof.dudt[1],
+            drag / 10.0 + physics_mod::gravity(),
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn airbrake_adds_deployment_dependent_drag() {
+        let airbrake = Airbrake::new(
+            Curve::polynomial(vec![0.0, 0.6]),
+            Some(Curve::polynomial(vec![0.0, 0.004])),
+            None,
+            0.5,
+            None,
+        );
+        let mut rocket_properties = make_rocket_properties(10.0, 0.4, 0.0182);
+        rocket_properties.airbrake = Some(airbrake);
+
+        let mut dof = OneDOFModel::new(Vector2::new(0.0, 100.0), rocket_properties);
+        dof.update_state_derivatives();
+
+        let drag = physics_mod::calc_drag_force(100.0, 0.4 + 0.3, 0.0182 + 0.002, None, 0.0);
+        assert_abs_diff_eq!(
+            dof.dudt[1],
+            drag / 10.0 + physics_mod::gravity(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn jacobian_matches_the_analytic_derivatives() {
+        let rocket_properties = make_rocket_properties(10.0, 0.5, 0.0182);
+        let dof = OneDOFModel::new(Vector2::new(100.0, 80.0), rocket_properties);
+        let jacobian = dof.jacobian();
+
+        // dh/dt = v, and the drag -1/2 rho v^2 cd A / m has the derivative -rho v cd A / m
+        assert_eq!(jacobian.row(0), Matrix2::new(0.0, 1.0, 0.0, 0.0).row(0));
+        assert_eq!(jacobian[(1, 0)], 0.0);
+        assert_abs_diff_eq!(
+            jacobian[(1, 1)],
+            -physics_mod::density() * 80.0 * 0.5 * 0.0182 / 10.0,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn jacobian_matches_finite_differences() {
+        // Skin friction and a Mach dependent airbrake make the drag depend on the velocity in
+        // several ways
+        let airbrake = Airbrake::new(
+            Curve::constant(0.3),
+            None,
+            Some(Curve::from_table(vec![0.0, 1.0], vec![1.0, 2.0]).unwrap()),
+            1.0,
+            None,
+        );
+        let mut rocket_properties = make_rocket_properties(10.0, 0.4, 0.0182);
+        rocket_properties.skin_friction = Some(SkinFriction::new(1.5, 2.5, 20e-6, 5.0e5));
+        rocket_properties.airbrake = Some(airbrake);
+        let dof = OneDOFModel::new(Vector2::new(100.0, 150.0), rocket_properties);
+
+        let jacobian = dof.jacobian();
+        let h = 1e-4;
+        for j in 0..2 {
+            let step = Vector2::ith(j, h);
+            let column =
+                (dof.derivatives(&(dof.u + step)) - dof.derivatives(&(dof.u - step))) / (2.0 * h);
+            for i in 0..2 {
+                assert_abs_diff_eq!(jacobian[(i, j)], column[i], epsilon = 1e-7);
+            }
+        }
+    }
 }
@@ -1,9 +1,11 @@
+use crate::derived_mod::FlightQuantities;
+use crate::dual_mod::{Dual, Real};
 use crate::physics_mod;
-use crate::rocket::{Rocket, RocketProperties};
-use nalgebra::{Rotation2, SVector, Vector2, Vector3, Vector6};
-use std::f64::consts::PI;
+use crate::rocket::{ModelParameters, Rocket, RocketProperties};
+use crate::state::state_vector::AuxVector;
+use nalgebra::{Matrix6, SVector, Vector3, Vector6};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub(crate) struct ThreeDOFModel {
     // This model is a 3 Degree of Freedom model which has 2 spatial dimensions
     // (x=horizontal, y=vertical) and a 3rd variable for the rotation of the rocket
@@ -12,6 +14,12 @@ pub(crate) struct ThreeDOFModel {
     pub(super) u: Vector6<f64>,
     /// (dxdt,dydt,d_angle_dt,dvxdt,dvydt,d_angular rate_dt)
     pub(super) dudt: Vector6<f64>,
+    /// Auxiliary (actuator) states, integrated alongside u
+    pub(super) aux: AuxVector,
+    /// Time derivatives of the auxiliary states
+    pub(super) daux: AuxVector,
+    /// Quantities derived along with `dudt`
+    pub(super) quantities: FlightQuantities,
     pub(crate) rocket_properties: RocketProperties,
     pub(crate) is_current: bool,
     pub(super) time: f64,
@@ -22,6 +30,9 @@ impl ThreeDOFModel {
         Self {
             u,
             dudt: Vector6::from_element(f64::NAN),
+            aux: rocket_properties.initial_aux_state(),
+            daux: AuxVector::zeros(),
+            quantities: FlightQuantities::default(),
             rocket_properties,
             is_current: false,
             time: 0.0,
@@ -70,77 +81,242 @@ impl ThreeDOFModel {
         self.is_current = false;
     }
 
+    /// Whether the model has auxiliary states which need to be integrated.
+    pub(super) fn has_aux(&self) -> bool {
+        self.rocket_properties.actuator().is_some()
+    }
+
+    pub(super) fn update_aux(&mut self, daux: AuxVector) {
+        self.aux += daux;
+        self.is_current = false;
+    }
+
+    pub(super) fn get_rocket_properties(&self) -> &RocketProperties {
+        &self.rocket_properties
+    }
+
+    pub(super) fn set_airbrake_command(&mut self, command: f64) {
+        if let Some(airbrake) = self.rocket_properties.airbrake.as_mut() {
+            airbrake.deployment = command;
+            self.is_current = false;
+        }
+    }
+
+    pub(super) fn get_airbrake_deployment(&self) -> Option<f64> {
+        self.rocket_properties
+            .airbrake
+            .as_ref()
+            .map(|airbrake| airbrake.actual_deployment(&self.aux))
+    }
+
     pub(super) fn update_state_derivatives(&mut self) {
         if self.is_current {
             return;
         }
+        (self.dudt, self.quantities) = self.derivatives_and_quantities(
+            &self.u,
+            &self.aux,
+            &self.rocket_properties.parameters(),
+        );
+        self.daux = self.rocket_properties.aux_derivatives(&self.aux);
+        self.is_current = true;
+    }
+
+    /// Gets the derived flight quantities at the current state.
+    pub(super) fn get_flight_quantities(&mut self) -> FlightQuantities {
+        self.update_state_derivatives();
+        self.quantities
+    }
+
+    /// Time derivatives of the state `u`, with the model's auxiliary states. Generic over the
+    /// scalar type so it can be evaluated with dual numbers, so the vectors are worked with as
+    /// (x, y) components.
+    pub(crate) fn derivatives<T: Real>(&self, u: &Vector6<T>) -> Vector6<T> {
+        self.derivatives_with(u, &self.aux, &self.rocket_properties.parameters())
+    }
+
+    /// Time derivatives of the state `u`, with the given auxiliary states and scalar rocket
+    /// properties in place of the model's.
+    pub(crate) fn derivatives_with<T: Real>(
+        &self,
+        u: &Vector6<T>,
+        aux: &AuxVector,
+        parameters: &ModelParameters<T>,
+    ) -> Vector6<T> {
+        self.derivatives_and_quantities(u, aux, parameters).0
+    }
+
+    /// Time derivatives of the state `u`, together with the flight quantities derived on the way.
+    fn derivatives_and_quantities<T: Real>(
+        &self,
+        u: &Vector6<T>,
+        aux: &AuxVector,
+        parameters: &ModelParameters<T>,
+    ) -> (Vector6<T>, FlightQuantities) {
+        let rocket_properties = &self.rocket_properties;
         // Find vector representing the rocket's orientation cand velocity
-        let ox = -f64::sin(self.u[2]);
-        let oy = 1.0 * f64::cos(self.u[2]);
-        let orientation = Vector2::new(ox, oy);
-        let velocity = Vector2::new(self.u[3], self.u[4]);
+        let (ox, oy) = (-u[2].sin(), u[2].cos());
+        // The aerodynamics act on the velocity relative to the air
+        let (vx, vy) = (u[3] - parameters.wind_speed, u[4]);
 
         // ========== Find Angle of attack
         //
-        let vmag = velocity.norm();
+        let vmag = (vx * vx + vy * vy).sqrt();
         //
         // used to get the direction of angle of attack (pos = orientation ccw of velocity)
-        let cross_prod = velocity.perp(&orientation);
+        let cross_prod = vx * oy - vy * ox;
         let alpha_dir = cross_prod.signum();
         //
         // find component of velocity in direction of rocket
-        let vel_comp_in_ori = velocity.dot(&orientation);
+        let vel_comp_in_ori = vx * ox + vy * oy;
         //
-        // Use trig to find the angle between the two vectors
-        // Will give radians, with the convention being that the rocket pointing CCW of the velocity
-        // is positive.
-        let alpha = (vel_comp_in_ori / vmag).acos() * alpha_dir;
+        // The angle between the two vectors, in radians, with the convention being that the
+        // rocket pointing CCW of the velocity is positive. atan2 keeps it differentiable at zero
+        // angle of attack, unlike the arccosine of the velocity component.
+        let alpha = cross_prod.atan2(vel_comp_in_ori);
+        //
+        // Rotates a vector a quarter turn towards the side the rocket points to
+        let quarter_turn = |x: T, y: T| (-y * alpha_dir, x * alpha_dir);
 
         // ========== Forces
         //
-        let cd_total = self.rocket_properties.cd + self.rocket_properties.cl_a * alpha.abs(); //crappy estimation for drag increasing with AoA
+        let mach = vmag / physics_mod::speed_of_sound();
+        let (force_x, force_y, stab_margin) = match &rocket_properties.aero_table {
+            Some(table) => {
+                // Tabulated coefficients are in body axes: the axial force acts along the rocket
+                // and the normal force perpendicular to it, towards the side the nose points to.
+                let coeffs = table.interpolate(mach, alpha);
+                let (ca, area_drag) = rocket_properties.drag_with_airbrake(
+                    coeffs.ca,
+                    parameters.area_drag,
+                    mach,
+                    aux,
+                );
+
+                let force_axial = physics_mod::calc_drag_force(
+                    vmag,
+                    ca,
+                    area_drag,
+                    rocket_properties.skin_friction.as_ref(),
+                    u[1],
+                );
+                //
+                let force_normal =
+                    physics_mod::calc_normal_force(vmag, coeffs.cn, parameters.area_lift);
+                let (nx, ny) = quarter_turn(ox, oy);
+
+                (
+                    ox * force_axial + nx * force_normal,
+                    oy * force_axial + ny * force_normal,
+                    coeffs.cp - parameters.cg_location,
+                )
+            }
+            None => {
+                let cd_total = alpha.abs() * parameters.cl_a + parameters.cd; //crappy estimation for drag increasing with AoA
+                let (cd_total, area_drag) =
+                    rocket_properties.drag_with_airbrake(cd_total, parameters.area_drag, mach, aux);
+
+                let force_drag = physics_mod::calc_drag_force(
+                    vmag,
+                    cd_total,
+                    area_drag,
+                    rocket_properties.skin_friction.as_ref(),
+                    u[1],
+                );
+                let drag = force_drag / vmag;
+                //
+                let force_lift = physics_mod::calc_lift_force(
+                    vmag,
+                    parameters.cl_a,
+                    alpha.abs(),
+                    parameters.area_lift,
+                );
+                let lift = force_lift / vmag;
+                let (lx, ly) = quarter_turn(vx, vy);
+
+                (
+                    lx * lift + vx * drag,
+                    ly * lift + vy * drag,
+                    parameters.stab_margin_dimensional,
+                )
+            }
+        };
 
-        let force_drag =
-            physics_mod::calc_drag_force(vmag, cd_total, self.rocket_properties.area_drag);
-        let drag_vec = velocity * (force_drag / vmag);
+        // ========== Moments
+        // assuming that all aerodynamic forces are acting on the center of pressure of the rocket
+        let (arm_x, arm_y) = (ox * stab_margin, oy * stab_margin);
+        let restoring_moment = force_x * arm_y - force_y * arm_x;
         //
-        let force_lift = physics_mod::calc_lift_force(
+        // damping moments always oppose the angular rate of the rocket. Cmq shares the lift
+        // reference area and is non-dimensionalized with the reference length.
+        let aero_damping_moment = physics_mod::calc_pitch_damping_moment(
             vmag,
-            self.rocket_properties.cl_a,
-            alpha.abs(),
-            self.rocket_properties.area_drag,
+            parameters.cmq,
+            parameters.area_lift,
+            parameters.reference_length,
+            u[5],
         );
-        let lift_vec = Rotation2::new(0.5 * PI * alpha_dir) * velocity * (force_lift / vmag);
-        //
-        let sum_force = lift_vec + drag_vec;
-
-        // ========== Moments
-        // assuming that all aerodynamic forces are acting on the center of pressure of the rocket
-        let moment_arm = orientation * (self.rocket_properties.stab_margin_dimensional);
-        let sum_moment = sum_force.perp(&moment_arm);
+        // The exhaust only damps the rotation until burnout
+        let mass_flow_rate = if self.time < rocket_properties.burn_time {
+            parameters.mass_flow_rate
+        } else {
+            T::constant(0.0)
+        };
+        let jet_damping_moment =
+            physics_mod::calc_jet_damping_moment(mass_flow_rate, parameters.nozzle_distance, u[5]);
+        let sum_moment = restoring_moment + aero_damping_moment + jet_damping_moment;
 
         // ========== 2nd Order Derivatives of ODE System
         //Linear Acceleration
-        let accel = sum_force * (1.0 / self.rocket_properties.mass);
-        let dvxdt = accel[0];
-        let dvydt = accel[1] + physics_mod::gravity();
+        let dvxdt = force_x / parameters.mass;
+        let dvydt = force_y / parameters.mass + physics_mod::gravity();
 
         //Angular Acceleration
-        let domegadt = sum_moment / self.rocket_properties.moment_of_inertia;
+        let domegadt = sum_moment / parameters.moment_of_inertia;
 
         // 1st order terms
-        let dxdt = self.u[3];
-        let dydt = self.u[4];
-        let omega = self.u[5];
+        let dxdt = u[3];
+        let dydt = u[4];
+        let omega = u[5];
+
+        // Split the aerodynamic force into drag and lift, along and across the airspeed
+        let (drag, lift) = if vmag.value() > 0.0 {
+            let (lx, ly) = quarter_turn(vx, vy);
+            (
+                -((force_x * vx + force_y * vy) / vmag).value(),
+                ((force_x * lx + force_y * ly) / vmag).value(),
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        let quantities = FlightQuantities::new(
+            rocket_properties,
+            vmag.value(),
+            alpha.value(),
+            drag,
+            lift,
+            stab_margin.value(),
+        );
 
-        self.dudt = Vector6::new(dxdt, dydt, omega, dvxdt, dvydt, domegadt);
-        self.is_current = true;
+        (
+            Vector6::new(dxdt, dydt, omega, dvxdt, dvydt, domegadt),
+            quantities,
+        )
+    }
+
+    /// Jacobian of the state derivative with respect to the state, from dual numbers.
+    pub(super) fn jacobian(&self) -> Matrix6<f64> {
+        let u = Vector6::from_fn(|i, _| Dual::<6>::variable(self.u[i], i));
+        let dudt = self.derivatives(&u);
+        Matrix6::from_fn(|i, j| dudt[i].derivatives[j])
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aero_mod::AeroTable;
+    use crate::airbrake_mod::{Airbrake, Curve};
     use nalgebra::{Rotation2, SVector, Vector2, Vector6};
     use std::f64::consts::PI;
 
@@ -166,7 +342,18 @@ mod tests {
 
     /// Makes a RocketProperties with known values for 3DOF tests.
     fn make_rocket_properties() -> RocketProperties {
-        RocketProperties::new(10.0, 0.6, 0.02, 0.0, 2.5, 0.3, 5.0)
+        RocketProperties::new(10.0, 0.6, 0.02, 0.03, 2.5, 0.3, 5.0)
+    }
+
+    /// Same as `make_rocket_properties`, but with aerodynamic and thrust-jet damping enabled.
+    fn make_damped_rocket_properties() -> RocketProperties {
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.cmq = -25.0;
+        rocket_properties.reference_length = 0.15;
+        rocket_properties.mass_flow_rate = 1.0;
+        rocket_properties.nozzle_distance = 1.2;
+        rocket_properties.burn_time = 10.0;
+        rocket_properties
     }
 
     #[test]
@@ -290,22 +477,39 @@ mod tests {
 
         let cd_total = dof.rocket_properties.cd + dof.rocket_properties.cl_a * alpha.abs();
 
-        let force_drag =
-            physics_mod::calc_drag_force(vmag, cd_total, dof.rocket_properties.area_drag);
+        let force_drag = physics_mod::calc_drag_force(
+            vmag,
+            cd_total,
+            dof.rocket_properties.area_drag,
+            dof.rocket_properties.skin_friction.as_ref(),
+            u0[1],
+        );
         let drag_vec = velocity * (force_drag / vmag);
 
         let force_lift = physics_mod::calc_lift_force(
             vmag,
             dof.rocket_properties.cl_a,
             alpha.abs(),
-            dof.rocket_properties.area_drag,
+            dof.rocket_properties.area_lift,
         );
         let lift_vec = Rotation2::new(0.5 * PI * alpha_dir) * velocity * (force_lift / vmag);
 
         let sum_force = lift_vec + drag_vec;
 
         let moment_arm = orientation * dof.rocket_properties.stab_margin_dimensional;
-        let sum_moment = sum_force.perp(&moment_arm);
+        let sum_moment = sum_force.perp(&moment_arm)
+            + physics_mod::calc_pitch_damping_moment(
+                vmag,
+                dof.rocket_properties.cmq,
+                dof.rocket_properties.area_lift,
+                dof.rocket_properties.reference_length,
+                u0[5],
+            )
+            + physics_mod::calc_jet_damping_moment(
+                dof.rocket_properties.mass_flow_rate,
+                dof.rocket_properties.nozzle_distance,
+                u0[5],
+            );
 
         let accel = sum_force * (1.0 / dof.rocket_properties.mass);
         let dvxdt = accel[0];
@@ -362,4 +566,279 @@ mod tests {
         assert_approx(dof.dudt[1], u0[4], 1e-12);
         assert_approx(dof.dudt[2], u0[5], 1e-12);
     }
+
+    #[test]
+    fn damping_opposes_angular_rate() {
+        // Flying straight along the body axis there is no restoring moment, so the only angular
+        // acceleration comes from the damping terms.
+        let u0 = Vector6::new(0.0, 100.0, 0.0, 0.0, 80.0, 0.4);
+
+        let mut undamped = ThreeDOFModel::new(u0, make_rocket_properties());
+        undamped.update_state_derivatives();
+        assert_approx(undamped.dudt[5], 0.0, 1e-12);
+
+        let mut damped = ThreeDOFModel::new(u0, make_damped_rocket_properties());
+        damped.update_state_derivatives();
+        assert!(damped.dudt[5] < 0.0);
+
+        let mut damped_neg = ThreeDOFModel::new(
+            Vector6::new(0.0, 100.0, 0.0, 0.0, 80.0, -0.4),
+            make_damped_rocket_properties(),
+        );
+        damped_neg.update_state_derivatives();
+        assert_approx(damped_neg.dudt[5], -damped.dudt[5], 1e-12);
+    }
+
+    #[test]
+    fn jet_damping_stops_at_burnout() {
+        let u0 = Vector6::new(0.0, 100.0, 0.0, 0.0, 80.0, 0.4);
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.mass_flow_rate = 1.0;
+        rocket_properties.nozzle_distance = 1.2;
+        rocket_properties.burn_time = 2.0;
+
+        let mut dof = ThreeDOFModel::new(u0, rocket_properties);
+        dof.update_state_derivatives();
+        assert_approx(dof.dudt[5], -1.2_f64.powi(2) * 0.4 / 2.5, 1e-12);
+
+        dof.update_state(Vector6::zeros(), 2.0);
+        dof.update_state_derivatives();
+        assert_approx(dof.dudt[5], 0.0, 1e-12);
+    }
+
+    #[test]
+    fn damping_decays_pitch_oscillation() {
+        // Integrates a pitch oscillation with explicit Euler and checks that the damped rocket
+        // loses amplitude much faster than the undamped one.
+        fn peak_rate_after(rocket_properties: RocketProperties) -> f64 {
+            let mut dof = ThreeDOFModel::new(
+                Vector6::new(0.0, 0.0, 0.0, 0.0, 100.0, 1.0),
+                rocket_properties,
+            );
+            let dt = 1e-3;
+            let mut peak: f64 = 0.0;
+            for i in 0..3000 {
+                let dudt = dof.get_derivatives();
+                // keep the velocity fixed so only the rotational dynamics evolve
+                let du = Vector6::new(0.0, 0.0, dudt[2] * dt, 0.0, 0.0, dudt[5] * dt);
+                dof.update_state(du, dt);
+                if i >= 2000 {
+                    peak = peak.max(dof.u[5].abs());
+                }
+            }
+            peak
+        }
+
+        let undamped_peak = peak_rate_after(make_rocket_properties());
+        let damped_peak = peak_rate_after(make_damped_rocket_properties());
+
+        assert!(undamped_peak > 0.9);
+        assert!(damped_peak < 0.5 * undamped_peak);
+    }
+
+    #[test]
+    fn aero_table_forces_act_in_body_axes() {
+        // Constant CA and CP with a CN which is linear in alpha, so interpolation is exact
+        let table = AeroTable::from_vecs(
+            vec![0.0, 1.0],
+            vec![0.0, 0.2],
+            vec![0.5; 4],
+            vec![0.0, 2.0, 0.0, 2.0],
+            vec![1.0; 4],
+        )
+        .unwrap();
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.aero_table = Some(table);
+        rocket_properties.cg_location = 0.7;
+
+        // Pointing straight up with the wind coming from the right
+        let u0 = Vector6::new(0.0, 100.0, 0.0, 10.0, 100.0, 0.0);
+        let mut dof = ThreeDOFModel::new(u0, rocket_properties.clone());
+        dof.update_state_derivatives();
+
+        let vmag = Vector2::new(10.0_f64, 100.0).norm();
+        let alpha = (10.0_f64 / 100.0).atan();
+        let q = 0.5 * physics_mod::density() * vmag.powi(2);
+        let force_axial = q * rocket_properties.area_drag * 0.5;
+        let force_normal = q * rocket_properties.area_lift * 10.0 * alpha;
+
+        // Axial force along the body axis, normal force pushing the rocket to the left
+        assert_approx(dof.dudt[3], -force_normal / rocket_properties.mass, 1e-9);
+        assert_approx(
+            dof.dudt[4],
+            -force_axial / rocket_properties.mass + physics_mod::gravity(),
+            1e-9,
+        );
+
+        // The normal force acts 0.3 m behind the CG and turns the nose into the wind
+        let expected_moment = -force_normal * 0.3;
+        assert_approx(
+            dof.dudt[5],
+            expected_moment / rocket_properties.moment_of_inertia,
+            1e-9,
+        );
+        assert!(dof.dudt[5] < 0.0);
+    }
+
+    #[test]
+    fn wind_acts_through_the_air_relative_velocity() {
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.wind_speed = 5.0;
+        let mut windy = ThreeDOFModel::new(
+            Vector6::new(0.0, 100.0, 0.05, 10.0, 80.0, 0.1),
+            rocket_properties,
+        );
+        let mut calm = ThreeDOFModel::new(
+            Vector6::new(0.0, 100.0, 0.05, 5.0, 80.0, 0.1),
+            make_rocket_properties(),
+        );
+        windy.update_state_derivatives();
+        calm.update_state_derivatives();
+
+        // The rocket still moves relative to the ground, but sees the same aerodynamics
+        assert_approx(windy.dudt[0], 10.0, 0.0);
+        for i in 1..6 {
+            assert_approx(windy.dudt[i], calm.dudt[i], 1e-12);
+        }
+    }
+
+    #[test]
+    fn lift_uses_lift_reference_area() {
+        // Flying at an angle of attack, only the force perpendicular to the velocity should
+        // depend on area_lift.
+        let u0 = Vector6::new(0.0, 100.0, 0.1, 0.0, 80.0, 0.0);
+        let velocity_dir = Vector2::new(0.0, 1.0);
+
+        let mut no_lift_properties = make_rocket_properties();
+        no_lift_properties.area_lift = 0.0;
+        let mut no_lift = ThreeDOFModel::new(u0, no_lift_properties);
+        no_lift.update_state_derivatives();
+
+        let mut with_lift = ThreeDOFModel::new(u0, make_rocket_properties());
+        with_lift.update_state_derivatives();
+
+        let accel = |dof: &ThreeDOFModel| Vector2::new(dof.dudt[3], dof.dudt[4]);
+        assert_approx(
+            accel(&no_lift).dot(&velocity_dir),
+            accel(&with_lift).dot(&velocity_dir),
+            1e-12,
+        );
+        assert_approx(accel(&no_lift).perp(&velocity_dir), 0.0, 1e-12);
+        assert!(accel(&with_lift).perp(&velocity_dir).abs() > 0.0);
+    }
+
+    #[test]
+    fn airbrake_adds_drag_along_the_velocity() {
+        let u0 = Vector6::new(0.0, 100.0, 0.0, 0.0, 80.0, 0.0);
+
+        let mut retracted = ThreeDOFModel::new(u0, make_rocket_properties());
+        retracted.update_state_derivatives();
+
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.airbrake =
+            Some(Airbrake::new(Curve::constant(0.4), None, None, 1.0, None));
+        let mut deployed = ThreeDOFModel::new(u0, rocket_properties.clone());
+        deployed.update_state_derivatives();
+
+        let extra_drag =
+            physics_mod::calc_drag_force(80.0, 0.4, rocket_properties.area_drag, None, 0.0);
+        assert_approx(
+            deployed.dudt[4] - retracted.dudt[4],
+            extra_drag / rocket_properties.mass,
+            1e-12,
+        );
+        assert_approx(deployed.dudt[3], 0.0, 1e-12);
+        assert_approx(deployed.dudt[5], 0.0, 1e-12);
+    }
+
+    /// Checks the columns `columns` of the model's Jacobian against central differences.
+    fn assert_jacobian_matches_finite_differences(dof: &ThreeDOFModel, columns: &[usize]) {
+        let jacobian = dof.jacobian();
+        let h = 1e-6;
+        for &j in columns {
+            let step = Vector6::ith(j, h);
+            let column =
+                (dof.derivatives(&(dof.u + step)) - dof.derivatives(&(dof.u - step))) / (2.0 * h);
+            for i in 0..6 {
+                assert_approx(jacobian[(i, j)], column[i], 1e-5 * column[i].abs().max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn jacobian_matches_finite_differences() {
+        let mut rocket_properties = make_damped_rocket_properties();
+        rocket_properties.wind_speed = 4.0;
+        let u0 = Vector6::new(5.0, 300.0, 0.1, -12.0, 120.0, 0.3);
+        let dof = ThreeDOFModel::new(u0, rocket_properties.clone());
+        assert_jacobian_matches_finite_differences(&dof, &[0, 1, 2, 3, 4, 5]);
+
+        // Tabulated coefficients are interpolated in both Mach number and angle of attack
+        rocket_properties.aero_table = Some(
+            AeroTable::from_vecs(
+                vec![0.0, 1.0],
+                vec![0.0, 0.2],
+                vec![0.5, 0.6, 0.7, 0.9],
+                vec![0.0, 2.0, 0.0, 2.5],
+                vec![1.0, 1.1, 1.2, 1.3],
+            )
+            .unwrap(),
+        );
+        rocket_properties.cg_location = 0.7;
+        let dof = ThreeDOFModel::new(u0, rocket_properties);
+        assert_jacobian_matches_finite_differences(&dof, &[0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn jacobian_is_finite_at_zero_angle_of_attack() {
+        let u0 = Vector6::new(0.0, 100.0, 0.0, 0.0, 150.0, 0.0);
+        let dof = ThreeDOFModel::new(u0, make_rocket_properties());
+        let jacobian = dof.jacobian();
+        assert!(jacobian.iter().all(|x| x.is_finite()));
+
+        // The drag has a kink in the angle of attack there, but the lift and the restoring moment
+        // don't: tilting the rocket turns it back into the velocity
+        let h = 1e-6;
+        let step = Vector6::ith(2, h);
+        let column =
+            (dof.derivatives(&(dof.u + step)) - dof.derivatives(&(dof.u - step))) / (2.0 * h);
+        assert_approx(jacobian[(3, 2)], column[3], 1e-5 * column[3].abs());
+        assert_approx(jacobian[(5, 2)], column[5], 1e-5 * column[5].abs());
+        assert!(jacobian[(5, 2)] < 0.0);
+    }
+
+    #[test]
+    fn flight_quantities_split_the_aerodynamic_force_into_drag_and_lift() {
+        // Pointing 0.05 rad left of vertical, while the wind turns the airspeed left as well
+        let u0 = Vector6::new(0.0, 100.0, 0.05, 0.0, 150.0, 0.0);
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.wind_speed = 5.0;
+        let mut dof = ThreeDOFModel::new(u0, rocket_properties.clone());
+        let quantities = dof.get_flight_quantities();
+        assert!(dof.is_current);
+
+        let airspeed = 5.0_f64.hypot(150.0);
+        assert_approx(quantities.mach, airspeed / 340.294, 1e-12);
+        assert_approx(
+            quantities.dynamic_pressure,
+            0.5 * 1.225 * airspeed.powi(2),
+            1e-9,
+        );
+        assert_approx(
+            quantities.angle_of_attack,
+            0.05 - (5.0_f64 / 150.0).atan(),
+            1e-12,
+        );
+        assert!(quantities.drag_force > 0.0);
+        assert!(quantities.lift_force > 0.0);
+
+        // Drag and lift add back up to the aerodynamic force
+        let force_x = rocket_properties.mass * dof.dudt[3];
+        let force_y = rocket_properties.mass * (dof.dudt[4] - physics_mod::gravity());
+        assert_approx(
+            quantities.drag_force.hypot(quantities.lift_force),
+            force_x.hypot(force_y),
+            1e-9,
+        );
+    }
 }
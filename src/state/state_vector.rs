@@ -18,8 +18,18 @@ pub(crate) enum StateVector {
     ThreeDOF(Vector6<f64>),
     /// Three DOF log state vector, contains [x, y, angle, vx, vy, angular rate, ax, ay, angular acceleration]
     ThreeDOFLog(SVector<f64, 9>),
+    /// One DOF state vector with auxiliary (actuator) states appended, contains
+    /// [altitude, velocity, aux 0, aux 1, aux 2]
+    OneDOFAux(SVector<f64, 5>),
+    /// Three DOF state vector with auxiliary (actuator) states appended, contains
+    /// [x, y, angle, vx, vy, angular rate, aux 0, aux 1, aux 2]
+    ThreeDOFAux(SVector<f64, 9>),
 }
 
+/// Auxiliary states which are integrated alongside the flight dynamics, e.g. the position, rate
+/// and current of an actuator. Models which don't use all of them leave the rest at zero.
+pub(crate) type AuxVector = Vector3<f64>;
+
 impl Add for StateVector {
     type Output = Self;
 
@@ -31,6 +41,12 @@ impl Add for StateVector {
             (StateVector::ThreeDOF(avec), StateVector::ThreeDOF(bvec)) => {
                 StateVector::ThreeDOF(avec + bvec)
             }
+            (StateVector::OneDOFAux(avec), StateVector::OneDOFAux(bvec)) => {
+                StateVector::OneDOFAux(avec + bvec)
+            }
+            (StateVector::ThreeDOFAux(avec), StateVector::ThreeDOFAux(bvec)) => {
+                StateVector::ThreeDOFAux(avec + bvec)
+            }
             _ => {
                 panic!("Invalid addition, mismatching State Vectors.")
             }
@@ -43,6 +59,8 @@ impl AddAssign for StateVector {
         match (self, rhs) {
             (StateVector::OneDOF(avec), StateVector::OneDOF(bvec)) => *avec += bvec,
             (StateVector::ThreeDOF(avec), StateVector::ThreeDOF(bvec)) => *avec += bvec,
+            (StateVector::OneDOFAux(avec), StateVector::OneDOFAux(bvec)) => *avec += bvec,
+            (StateVector::ThreeDOFAux(avec), StateVector::ThreeDOFAux(bvec)) => *avec += bvec,
             _ => {
                 panic!("Invalid addition, mismatching State Vectors.")
             }
@@ -61,6 +79,12 @@ impl Sub for StateVector {
             (StateVector::ThreeDOF(avec), StateVector::ThreeDOF(bvec)) => {
                 StateVector::ThreeDOF(avec - bvec)
             }
+            (StateVector::OneDOFAux(avec), StateVector::OneDOFAux(bvec)) => {
+                StateVector::OneDOFAux(avec - bvec)
+            }
+            (StateVector::ThreeDOFAux(avec), StateVector::ThreeDOFAux(bvec)) => {
+                StateVector::ThreeDOFAux(avec - bvec)
+            }
             _ => {
                 panic!("Invalid addition, mismatching State Vectors.")
             }
@@ -73,6 +97,8 @@ impl SubAssign for StateVector {
         match (self, rhs) {
             (StateVector::OneDOF(avec), StateVector::OneDOF(bvec)) => *avec -= bvec,
             (StateVector::ThreeDOF(avec), StateVector::ThreeDOF(bvec)) => *avec -= bvec,
+            (StateVector::OneDOFAux(avec), StateVector::OneDOFAux(bvec)) => *avec -= bvec,
+            (StateVector::ThreeDOFAux(avec), StateVector::ThreeDOFAux(bvec)) => *avec -= bvec,
             _ => {
                 panic!("Invalid addition, mismatching State Vectors.")
             }
@@ -91,6 +117,12 @@ impl Mul for StateVector {
             (StateVector::ThreeDOF(avec), StateVector::ThreeDOF(bvec)) => {
                 StateVector::ThreeDOF(avec.component_mul(&bvec))
             }
+            (StateVector::OneDOFAux(avec), StateVector::OneDOFAux(bvec)) => {
+                StateVector::OneDOFAux(avec.component_mul(&bvec))
+            }
+            (StateVector::ThreeDOFAux(avec), StateVector::ThreeDOFAux(bvec)) => {
+                StateVector::ThreeDOFAux(avec.component_mul(&bvec))
+            }
             _ => {
                 panic!("Invalid addition, mismatching State Vectors.")
             }
@@ -107,6 +139,12 @@ impl MulAssign for StateVector {
             (StateVector::ThreeDOF(avec), StateVector::ThreeDOF(bvec)) => {
                 avec.component_mul_assign(&bvec)
             }
+            (StateVector::OneDOFAux(avec), StateVector::OneDOFAux(bvec)) => {
+                avec.component_mul_assign(&bvec)
+            }
+            (StateVector::ThreeDOFAux(avec), StateVector::ThreeDOFAux(bvec)) => {
+                avec.component_mul_assign(&bvec)
+            }
             _ => {
                 panic!("Invalid addition, mismatching State Vectors.")
             }
@@ -119,6 +157,8 @@ impl StateVector {
         match (self, b) {
             (StateVector::OneDOF(avec), StateVector::OneDOF(bvec)) => avec.dot(bvec),
             (StateVector::ThreeDOF(avec), StateVector::ThreeDOF(bvec)) => avec.dot(bvec),
+            (StateVector::OneDOFAux(avec), StateVector::OneDOFAux(bvec)) => avec.dot(bvec),
+            (StateVector::ThreeDOFAux(avec), StateVector::ThreeDOFAux(bvec)) => avec.dot(bvec),
             _ => {
                 panic!("Invalid Dot Product, mismatching State Vectors.")
             }
@@ -129,6 +169,8 @@ impl StateVector {
         match self {
             StateVector::OneDOF(avec) => StateVector::OneDOF(avec * k),
             StateVector::ThreeDOF(avec) => StateVector::ThreeDOF(avec * k),
+            StateVector::OneDOFAux(avec) => StateVector::OneDOFAux(avec * k),
+            StateVector::ThreeDOFAux(avec) => StateVector::ThreeDOFAux(avec * k),
             _ => {
                 panic!("State Vector Scale Impl")
             }
@@ -142,6 +184,8 @@ impl StateVector {
             StateVector::ThreeDOF(_) => panic!("Requires 2d math vector"),
             StateVector::OneDOFLog(_) => panic!("Requires 2d math vector"),
             StateVector::ThreeDOFLog(_) => panic!("Requires 2d math vector"),
+            StateVector::OneDOFAux(_) => panic!("Requires 2d math vector"),
+            StateVector::ThreeDOFAux(_) => panic!("Requires 2d math vector"),
         }
     }
 
@@ -152,6 +196,8 @@ impl StateVector {
             StateVector::ThreeDOF(_) => panic!("Requires 3d math vector"),
             StateVector::OneDOFLog(_) => panic!("Requires 3d math vector"),
             StateVector::ThreeDOFLog(_) => panic!("Requires 3d math vector"),
+            StateVector::OneDOFAux(_) => panic!("Requires 3d math vector"),
+            StateVector::ThreeDOFAux(_) => panic!("Requires 3d math vector"),
         }
     }
 
@@ -171,6 +217,8 @@ impl StateVector {
             StateVector::ThreeDOF(_) => panic!("Requires 2d math vector"),
             StateVector::OneDOFLog(_) => panic!("Requires 2d math vector"),
             StateVector::ThreeDOFLog(_) => panic!("Requires 2d math vector"),
+            StateVector::OneDOFAux(_) => panic!("Requires 2d math vector"),
+            StateVector::ThreeDOFAux(_) => panic!("Requires 2d math vector"),
         }
     }
 }
@@ -182,6 +230,8 @@ impl StateVector {
             StateVector::ThreeDOF(avec) => avec.as_slice(),
             StateVector::OneDOFLog(avec) => avec.as_slice(),
             StateVector::ThreeDOFLog(avec) => avec.as_slice(),
+            StateVector::OneDOFAux(avec) => avec.as_slice(),
+            StateVector::ThreeDOFAux(avec) => avec.as_slice(),
         }
     }
 }
@@ -0,0 +1,357 @@
+use crate::constants::simulation_constants::{EVENT_TIME_TOLERANCE_S, MAX_EVENT_EVALUATIONS};
+use crate::ode::OdeSolver;
+use crate::result_mod::{EventKind, FlightEvent};
+use crate::state::State;
+use crate::targeting_mod::minimize_golden;
+use pyo3::prelude::*;
+
+/// Quantity whose largest value over the flight is reported in the summary.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Peak {
+    Speed,
+    Mach,
+    Acceleration,
+    DynamicPressure,
+}
+
+const PEAKS: [Peak; 4] = [
+    Peak::Speed,
+    Peak::Mach,
+    Peak::Acceleration,
+    Peak::DynamicPressure,
+];
+
+impl Peak {
+    fn kind(self) -> EventKind {
+        match self {
+            Peak::Speed => EventKind::MaxSpeed,
+            Peak::Mach => EventKind::MaxMach,
+            Peak::Acceleration => EventKind::MaxAcceleration,
+            Peak::DynamicPressure => EventKind::MaxDynamicPressure,
+        }
+    }
+
+    fn value(self, state: &mut State) -> f64 {
+        match self {
+            Peak::Speed => state.get_speed(),
+            Peak::Mach => state.get_flight_quantities().mach,
+            Peak::Acceleration => state.get_acceleration(),
+            Peak::DynamicPressure => state.get_flight_quantities().dynamic_pressure,
+        }
+    }
+}
+
+/// Summary of a flight, with the event of each milestone, or None if the flight didn't get
+/// there. The models have no thrust or launch rail, so there is no rail exit, and burnout is at
+/// the rocket's `burn_time`: the initial state for flights which start at burnout.
+#[pyclass(get_all)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FlightSummary {
+    /// Motor burnout at the rocket's `burn_time`, or the initial state if it is 0, with the
+    /// speed as its value
+    pub burnout: Option<FlightEvent>,
+    /// Largest speed relative to the ground (m/s)
+    pub max_speed: Option<FlightEvent>,
+    /// Largest Mach number
+    pub max_mach: Option<FlightEvent>,
+    /// Largest magnitude of the acceleration (m/s^2)
+    pub max_acceleration: Option<FlightEvent>,
+    /// Largest dynamic pressure (Pa)
+    pub max_dynamic_pressure: Option<FlightEvent>,
+    /// First time the airbrakes were commanded to deploy, with the commanded deployment as its
+    /// value
+    pub airbrake_deployment: Option<FlightEvent>,
+    /// The apogee, with the altitude as its value
+    pub apogee: Option<FlightEvent>,
+    /// Impact with the ground after a ballistic descent, with the speed as its value
+    pub landing: Option<FlightEvent>,
+}
+
+impl FlightSummary {
+    /// All events of the summary in time order.
+    pub(crate) fn events(&self) -> Vec<FlightEvent> {
+        let mut events: Vec<FlightEvent> = [
+            self.burnout,
+            self.max_speed,
+            self.max_mach,
+            self.max_acceleration,
+            self.max_dynamic_pressure,
+            self.airbrake_deployment,
+            self.apogee,
+            self.landing,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        events
+    }
+}
+
+/// Detects the events of a flight from the states at the end of each timestep. The peaks are
+/// located between timesteps by repeating the steps around them with a varying step size, so
+/// they don't depend on which states happened to be logged.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EventDetector {
+    summary: FlightSummary,
+    /// The state before the last one, from which steps are repeated to locate a peak
+    before: Option<State>,
+    /// Values of the peak quantities at `before` and at the last state
+    values_before: [f64; PEAKS.len()],
+    values_last: [f64; PEAKS.len()],
+}
+
+impl EventDetector {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the step from `old_state` to `state`, which the `ode` solver just took.
+    pub(crate) fn step(&mut self, old_state: &State, state: &mut State, ode: &OdeSolver) {
+        if self.before.is_none() {
+            let mut start = old_state.clone();
+            for (i, peak) in PEAKS.iter().enumerate() {
+                self.values_last[i] = peak.value(&mut start);
+                self.record(*peak, &start, self.values_last[i]);
+            }
+        }
+
+        // Burnout is located by repeating the step which crossed it up to the burn time
+        if self.summary.burnout.is_none() {
+            let burn_time = old_state.get_rocket_properties().burn_time;
+            let dt = burn_time - old_state.get_time();
+            if burn_time <= 0.0 || (dt >= 0.0 && state.get_time() >= burn_time) {
+                let mut burnout = old_state.clone();
+                if dt > 0.0 {
+                    ode.step_with(&mut burnout, dt);
+                }
+                self.summary.burnout =
+                    Some(event(EventKind::Burnout, &burnout, burnout.get_speed()));
+            }
+        }
+
+        // The command is held during the step, so it is applied from the start of the step
+        if self.summary.airbrake_deployment.is_none() {
+            let command = old_state
+                .get_rocket_properties()
+                .airbrake
+                .as_ref()
+                .map_or(0.0, |airbrake| airbrake.deployment);
+            if command > 0.0 {
+                self.summary.airbrake_deployment =
+                    Some(event(EventKind::AirbrakeDeployment, old_state, command));
+            }
+        }
+
+        for (i, peak) in PEAKS.iter().enumerate() {
+            let value = peak.value(state);
+            let is_peak =
+                self.values_before[i] < self.values_last[i] && value <= self.values_last[i];
+            if let (true, Some(before)) = (is_peak, &self.before) {
+                let span = state.get_time() - before.get_time();
+                let (peak_state, peak_value) = locate_peak(*peak, before, span, ode);
+                self.record(*peak, &peak_state, peak_value);
+            }
+            self.record(*peak, state, value);
+            self.values_before[i] = self.values_last[i];
+            self.values_last[i] = value;
+        }
+        self.before = Some(old_state.clone());
+    }
+
+    /// Records the apogee the flight ended at.
+    pub(crate) fn apogee(&mut self, state: &State) {
+        self.summary.apogee = Some(event(EventKind::Apogee, state, state.get_altitude()));
+    }
+
+    /// Records the landing the flight ended with.
+    pub(crate) fn landing(&mut self, state: &State) {
+        self.summary.landing = Some(event(EventKind::Landing, state, state.get_speed()));
+    }
+
+    pub(crate) fn summary(&self) -> FlightSummary {
+        self.summary
+    }
+
+    /// Keeps the state as the peak if its value is larger than the largest one so far.
+    fn record(&mut self, peak: Peak, state: &State, value: f64) {
+        let best = match peak {
+            Peak::Speed => &mut self.summary.max_speed,
+            Peak::Mach => &mut self.summary.max_mach,
+            Peak::Acceleration => &mut self.summary.max_acceleration,
+            Peak::DynamicPressure => &mut self.summary.max_dynamic_pressure,
+        };
+        if best.is_none_or(|best| value > best.value) {
+            *best = Some(event(peak.kind(), state, value));
+        }
+    }
+}
+
+/// Locates the peak within `span` (s) after `before`, by maximizing the value over the size of a
+/// step from it. The airbrake command of `before` is held over the whole span. Returns the state
+/// at the peak with its value.
+fn locate_peak(peak: Peak, before: &State, span: f64, ode: &OdeSolver) -> (State, f64) {
+    let state_after = |dt: f64| {
+        let mut state = before.clone();
+        if dt > 0.0 {
+            ode.step_with(&mut state, dt);
+        }
+        state
+    };
+    let ((dt, _), _, _) = minimize_golden(
+        |dt| -peak.value(&mut state_after(dt)),
+        (0.0, span),
+        EVENT_TIME_TOLERANCE_S,
+        MAX_EVENT_EVALUATIONS,
+    );
+    let mut state = state_after(dt);
+    let value = peak.value(&mut state);
+    (state, value)
+}
+
+fn event(kind: EventKind, state: &State, value: f64) -> FlightEvent {
+    FlightEvent {
+        kind,
+        time: state.get_time(),
+        altitude: state.get_altitude(),
+        downrange: state.get_downrange(),
+        value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actuator_mod::Actuator;
+    use crate::airbrake_mod::{Airbrake, Curve};
+    use crate::dispersion_mod::{simulate_run, DispersionOutput};
+    use crate::ode::FixedTimeStep;
+    use crate::rocket::RocketProperties;
+    use crate::simdata_mod::SimulationData;
+    use crate::simulation::{Simulation, SimulationExitCondition};
+    use crate::state::{InitialCondition, InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+
+    fn make_rocket_properties() -> RocketProperties {
+        RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2)
+    }
+
+    /// Simulates a logged flight to apogee, returning its summary and log.
+    fn summarize(state: State, dt: f64) -> (FlightSummary, SimulationData) {
+        let ode_solver = OdeSolver::RK3(FixedTimeStep::new(dt));
+        let mut simulation = Simulation::new(
+            state,
+            ode_solver.clone(),
+            SimulationExitCondition::ApogeeReached,
+            1_000_000,
+        )
+        .with_event_detection();
+        let mut log = SimulationData::new();
+        simulation.run(&mut log, false, true);
+        (simulation.flight_summary(ode_solver), log)
+    }
+
+    #[test]
+    fn peaks_are_located_between_timesteps() {
+        // The airbrakes open from burnout while the rocket slows down, so the drag deceleration
+        // peaks a little later
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.airbrake = Some(Airbrake::new(
+            Curve::polynomial(vec![0.0, 1.0]),
+            None,
+            None,
+            1.0,
+            Some(Actuator::first_order(0.5, 0.0)),
+        ));
+        let state = State::new_1dof(rocket_properties, InitialState1DOF::new(0.0, 150.0));
+        let (summary, log) = summarize(state.clone(), 0.05);
+        let (reference, _) = summarize(state, 5e-4);
+
+        let peak = summary.max_acceleration.unwrap();
+        let reference_peak = reference.max_acceleration.unwrap();
+        assert_eq!(peak.kind, EventKind::MaxAcceleration);
+        assert_abs_diff_eq!(peak.time, reference_peak.time, epsilon = 1e-3);
+        assert_abs_diff_eq!(peak.value, reference_peak.value, epsilon = 1e-3);
+
+        // The peak is higher than at any logged row
+        let logged_max = log
+            .state_log
+            .iter()
+            .map(|row| row[2].abs())
+            .fold(0.0, f64::max);
+        assert!(peak.value > logged_max);
+
+        // The airbrakes were commanded open from burnout
+        let deployment = summary.airbrake_deployment.unwrap();
+        assert_eq!(deployment.time, 0.0);
+        assert_eq!(deployment.value, 1.0);
+    }
+
+    #[test]
+    fn burnout_is_at_the_burn_time() {
+        let mut rocket_properties = make_rocket_properties();
+        rocket_properties.burn_time = 0.237;
+        let state = State::new_1dof(rocket_properties, InitialState1DOF::new(0.0, 150.0));
+        let (summary, log) = summarize(state.clone(), 0.01);
+
+        // The burn time falls between the logged rows at 0.23 s and 0.24 s
+        let burnout = summary.burnout.unwrap();
+        assert_eq!(burnout.kind, EventKind::Burnout);
+        assert_abs_diff_eq!(burnout.time, 0.237, epsilon = 1e-12);
+        assert!(burnout.altitude > log.state_log[23][0] && burnout.altitude < log.state_log[24][0]);
+        assert!(burnout.value < log.state_log[23][1] && burnout.value > log.state_log[24][1]);
+
+        // The same steps as the simulation up to 0.23 s, then the rest up to the burn time
+        let ode = OdeSolver::RK3(FixedTimeStep::new(0.01));
+        let mut reference = state;
+        for _ in 0..23 {
+            ode.step_with(&mut reference, 0.01);
+        }
+        let dt = 0.237 - reference.get_time();
+        ode.step_with(&mut reference, dt);
+        assert_abs_diff_eq!(burnout.value, reference.get_speed(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn summary_covers_the_flight_to_landing() {
+        let initial_state = InitialState3DOF::new(0.0, 0.0, 0.0, 3.0, 150.0, 0.0);
+        let rocket_properties = make_rocket_properties();
+        let (summary, _) = summarize(
+            State::new_3dof(rocket_properties.clone(), initial_state),
+            0.01,
+        );
+
+        // The flight decelerates from burnout, so that is where the speed and max-Q peak
+        let burnout = summary.burnout.unwrap();
+        assert_eq!(burnout.time, 0.0);
+        assert_abs_diff_eq!(burnout.value, 3.0_f64.hypot(150.0));
+        assert_eq!(summary.max_speed.unwrap().time, 0.0);
+        assert_abs_diff_eq!(
+            summary.max_dynamic_pressure.unwrap().value,
+            0.5 * 1.225 * burnout.value.powi(2),
+            epsilon = 1e-9
+        );
+        assert!(summary.airbrake_deployment.is_none());
+
+        // The apogee and landing match a run which isn't logged
+        let outputs = simulate_run(
+            &rocket_properties,
+            InitialCondition::ThreeDOF(initial_state),
+            &OdeSolver::RK3(FixedTimeStep::new(0.01)),
+            1_000_000,
+            &[],
+            &[],
+        );
+        let apogee = summary.apogee.unwrap();
+        let landing = summary.landing.unwrap();
+        assert_abs_diff_eq!(apogee.value, outputs.get(DispersionOutput::Apogee));
+        assert_abs_diff_eq!(
+            landing.downrange,
+            outputs.get(DispersionOutput::LandingDownrange)
+        );
+        assert_abs_diff_eq!(landing.altitude, 0.0, epsilon = 0.5);
+        assert!(landing.time > apogee.time);
+        assert!(landing.downrange > apogee.downrange);
+        assert!(landing.value < summary.max_speed.unwrap().value);
+    }
+}
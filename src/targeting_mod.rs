@@ -0,0 +1,676 @@
+use crate::dispersion_mod::{
+    simulate_run, validate_parameters, DispersedParameter, DispersionOutput,
+};
+use crate::ode::OdeSolver;
+use crate::rocket::RocketProperties;
+use crate::simdata_mod::SimulationData;
+use crate::simulation::{self, Simulation, SimulationExitCondition};
+use crate::state::{InitialCondition, InitialState3DOF};
+use pyo3::prelude::*;
+
+/// Outcome of a targeting search.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SolveStatus {
+    /// The output is within the tolerance of the target.
+    Converged,
+    /// The evaluation budget ran out before the tolerance was met. The best value found so far
+    /// is reported.
+    MaxEvaluations,
+    /// The target lies outside the range of outputs at the bounds of the search. The bound
+    /// which comes closest is reported.
+    TargetUnreachable,
+}
+
+/// Result of a targeting search, e.g. the airbrake deployment which hits a target apogee.
+#[pyclass(get_all)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolveReport {
+    /// The value of the searched variable
+    pub value: f64,
+    /// The output (e.g. apogee) achieved with `value`
+    pub achieved: f64,
+    /// The output which was searched for
+    pub target: f64,
+    /// Number of times the output was evaluated (i.e. simulations run)
+    pub evaluations: u32,
+    pub status: SolveStatus,
+}
+
+/// Searches `bounds` for the value at which `f` equals `target`, to within `tolerance`. `f` must
+/// be continuous, and it is evaluated at most `max_evaluations` times (but at least at both
+/// bounds). Uses regula falsi with the Illinois modification, which converges superlinearly
+/// while keeping the root bracketed.
+pub(crate) fn solve_bracketed(
+    mut f: impl FnMut(f64) -> f64,
+    bounds: (f64, f64),
+    target: f64,
+    tolerance: f64,
+    max_evaluations: u32,
+) -> SolveReport {
+    let (mut a, mut b) = bounds;
+    let (mut fa, mut fb) = (f(a) - target, f(b) - target);
+    let mut evaluations = 2;
+
+    // Best point found so far, as (value, error)
+    let mut best = if fa.abs() <= fb.abs() {
+        (a, fa)
+    } else {
+        (b, fb)
+    };
+    let report = |best: (f64, f64), evaluations, status| SolveReport {
+        value: best.0,
+        achieved: best.1 + target,
+        target,
+        evaluations,
+        status,
+    };
+
+    if best.1.abs() <= tolerance {
+        return report(best, evaluations, SolveStatus::Converged);
+    }
+    if fa.signum() == fb.signum() {
+        return report(best, evaluations, SolveStatus::TargetUnreachable);
+    }
+
+    while evaluations < max_evaluations {
+        let x = (a * fb - b * fa) / (fb - fa);
+        let fx = f(x) - target;
+        evaluations += 1;
+
+        if fx.abs() < best.1.abs() {
+            best = (x, fx);
+        }
+        if fx.abs() <= tolerance {
+            return report(best, evaluations, SolveStatus::Converged);
+        }
+
+        if fx.signum() != fb.signum() {
+            (a, fa) = (b, fb);
+        } else {
+            // Halve the retained end's error so it can't stall the bracket
+            fa /= 2.0;
+        }
+        (b, fb) = (x, fx);
+    }
+    report(best, evaluations, SolveStatus::MaxEvaluations)
+}
+
+/// Searches `bounds` for the minimum of `f`, which must be unimodal there, until it is bracketed
+/// to within `tolerance` or `f` has been evaluated `max_evaluations` times (but at least twice).
+/// Uses golden section search, which shrinks the bracket by the golden ratio per evaluation.
+/// Returns the best point found as (x, f(x)), with the number of evaluations and the status.
+pub(crate) fn minimize_golden(
+    mut f: impl FnMut(f64) -> f64,
+    bounds: (f64, f64),
+    tolerance: f64,
+    max_evaluations: u32,
+) -> ((f64, f64), u32, SolveStatus) {
+    let inverse_ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = bounds;
+    let (mut c, mut d) = (b - inverse_ratio * (b - a), a + inverse_ratio * (b - a));
+    let (mut fc, mut fd) = (f(c), f(d));
+    let mut evaluations = 2;
+
+    let status = loop {
+        if b - a <= tolerance {
+            break SolveStatus::Converged;
+        }
+        if evaluations >= max_evaluations {
+            break SolveStatus::MaxEvaluations;
+        }
+        // Keep the side of the bracket holding the lower interior point, whose other interior
+        // point is reused
+        if fc < fd {
+            (b, d, fd) = (d, c, fc);
+            c = b - inverse_ratio * (b - a);
+            fc = f(c);
+        } else {
+            (a, c, fc) = (c, d, fd);
+            d = a + inverse_ratio * (b - a);
+            fd = f(d);
+        }
+        evaluations += 1;
+    };
+    let best = if fc < fd { (c, fc) } else { (d, fd) };
+    (best, evaluations, status)
+}
+
+/// What a launch angle search optimizes.
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LaunchObjective {
+    /// Maximize the apogee.
+    MaxApogee,
+    /// Minimize the horizontal distance between the initial and landing positions.
+    MinDrift,
+    /// Land as close as possible to a target downrange position.
+    LandingTarget,
+}
+
+/// Result of a launch angle search.
+#[pyclass(get_all)]
+#[derive(Clone, Copy, Debug)]
+pub struct LaunchOptimum {
+    /// The optimal launch angle (rad), with the same convention as the 3DOF state's angle
+    pub angle: f64,
+    /// The initial state launched at `angle`
+    pub initial_state: InitialState3DOF,
+    pub apogee: f64,
+    pub landing_downrange: f64,
+    /// Number of flights simulated
+    pub evaluations: u32,
+    /// Converged if the optimal angle was bracketed to within the tolerance
+    pub status: SolveStatus,
+}
+
+/// Settings of a search for the launch angle of a 3DOF flight which optimizes an objective. The
+/// rocket leaves the rail at the speed of the initial state, with its velocity along its axis,
+/// and the angle is constrained to within `max_rail_angle` of vertical.
+#[derive(Clone, Debug)]
+pub(crate) struct LaunchSearch {
+    pub(crate) initial_state: InitialState3DOF,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) objective: LaunchObjective,
+    /// Downrange position (m) to land at, for `LaunchObjective::LandingTarget`
+    pub(crate) target_downrange: f64,
+    pub(crate) max_rail_angle: f64,
+    pub(crate) tolerance: f64,
+    pub(crate) max_evaluations: u32,
+    pub(crate) max_iterations: u64,
+}
+
+impl LaunchSearch {
+    /// The initial state with the rocket and its velocity turned to `angle`.
+    fn launched_at(&self, angle: f64) -> InitialState3DOF {
+        let speed = self.initial_state.vx.hypot(self.initial_state.vy);
+        InitialState3DOF {
+            angle,
+            vx: -speed * angle.sin(),
+            vy: speed * angle.cos(),
+            ..self.initial_state
+        }
+    }
+
+    /// Simulates the flight launched at `angle`, returning its apogee and landing position.
+    fn fly(&self, rocket_properties: &RocketProperties, angle: f64) -> (f64, f64) {
+        let outputs = simulate_run(
+            rocket_properties,
+            InitialCondition::ThreeDOF(self.launched_at(angle)),
+            &self.ode_solver,
+            self.max_iterations,
+            &[],
+            &[],
+        );
+        (
+            outputs.get(DispersionOutput::Apogee),
+            outputs.get(DispersionOutput::LandingDownrange),
+        )
+    }
+
+    /// The objective to minimize for a flight with the given apogee and landing position.
+    fn cost(&self, (apogee, landing_downrange): (f64, f64)) -> f64 {
+        match self.objective {
+            LaunchObjective::MaxApogee => -apogee,
+            LaunchObjective::MinDrift => (landing_downrange - self.initial_state.x).abs(),
+            LaunchObjective::LandingTarget => (landing_downrange - self.target_downrange).abs(),
+        }
+    }
+
+    /// Finds the launch angle which optimizes the objective.
+    pub(crate) fn optimize(
+        &self,
+        rocket_properties: &RocketProperties,
+    ) -> Result<LaunchOptimum, String> {
+        if !(self.max_rail_angle > 0.0 && self.max_rail_angle < std::f64::consts::FRAC_PI_2) {
+            return Err("max_rail_angle must be between 0 and pi/2".to_string());
+        }
+
+        let ((angle, cost), evaluations, status) = minimize_golden(
+            |angle| {
+                let cost = self.cost(self.fly(rocket_properties, angle));
+                // Flights which don't land are never optimal
+                if cost.is_nan() {
+                    f64::INFINITY
+                } else {
+                    cost
+                }
+            },
+            (-self.max_rail_angle, self.max_rail_angle),
+            self.tolerance,
+            self.max_evaluations,
+        );
+        if cost.is_infinite() {
+            return Err("None of the flights landed within the iteration limit".to_string());
+        }
+
+        let (apogee, landing_downrange) = self.fly(rocket_properties, angle);
+        Ok(LaunchOptimum {
+            angle,
+            initial_state: self.launched_at(angle),
+            apogee,
+            landing_downrange,
+            evaluations,
+            status,
+        })
+    }
+}
+
+/// Settings shared by the apogee targeting searches.
+#[derive(Clone, Debug)]
+pub(crate) struct ApogeeSearch {
+    pub(crate) initial_condition: InitialCondition,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) target_apogee: f64,
+    pub(crate) tolerance: f64,
+    pub(crate) max_evaluations: u32,
+    pub(crate) max_iterations: u64,
+}
+
+impl ApogeeSearch {
+    /// Finds the constant airbrake deployment (0 to 1) which hits the target apogee.
+    pub(crate) fn deployment(
+        &self,
+        rocket_properties: &RocketProperties,
+    ) -> Result<SolveReport, String> {
+        let mut deployed = with_deployment(rocket_properties, 0.0)?;
+
+        Ok(solve_bracketed(
+            |deployment| {
+                if let Some(airbrake) = deployed.airbrake.as_mut() {
+                    airbrake.deployment = deployment;
+                }
+                self.apogee(deployed.clone())
+            },
+            (0.0, 1.0),
+            self.target_apogee,
+            self.tolerance,
+            self.max_evaluations,
+        ))
+    }
+
+    /// Finds the time at which the airbrakes must be deployed to `deployment` to hit the target
+    /// apogee, with the airbrakes retracted before then. The time is measured from the initial
+    /// state, and is searched between the initial state and the apogee without airbrakes. The
+    /// coasting flight is simulated once, which isn't counted as an evaluation.
+    pub(crate) fn deployment_time(
+        &self,
+        rocket_properties: &RocketProperties,
+        deployment: f64,
+    ) -> Result<SolveReport, String> {
+        let retracted = with_deployment(rocket_properties, 0.0)?;
+        let deployed = with_deployment(rocket_properties, deployment)?;
+
+        let mut coast = Simulation::new(
+            self.initial_condition.to_state(retracted),
+            self.ode_solver.clone(),
+            SimulationExitCondition::ApogeeReached,
+            self.max_iterations,
+        );
+        let mut log = SimulationData::new();
+        coast.run(&mut log, false, true);
+        let coast_duration = *log
+            .time_log
+            .last()
+            .ok_or("The coasting flight has no steps")?;
+
+        Ok(solve_bracketed(
+            |time| {
+                let row = interpolate_row(&log, time);
+                let initial_condition = self.initial_condition.with_row(&row);
+                simulation::apogee(
+                    initial_condition.to_state(deployed.clone()),
+                    self.ode_solver.clone(),
+                    self.max_iterations,
+                )
+            },
+            (0.0, coast_duration),
+            self.target_apogee,
+            self.tolerance,
+            self.max_evaluations,
+        ))
+    }
+
+    fn apogee(&self, rocket_properties: RocketProperties) -> f64 {
+        simulation::apogee(
+            self.initial_condition.to_state(rocket_properties),
+            self.ode_solver.clone(),
+            self.max_iterations,
+        )
+    }
+}
+
+/// Settings of an inverse design search, which finds the value of a parameter (e.g. the ballast
+/// mass) at which an output of the flight hits a target.
+#[derive(Clone, Debug)]
+pub(crate) struct DesignSearch {
+    pub(crate) initial_condition: InitialCondition,
+    pub(crate) ode_solver: OdeSolver,
+    pub(crate) parameter: DispersedParameter,
+    /// (lower, upper) bounds the parameter is searched between
+    pub(crate) bounds: (f64, f64),
+    pub(crate) output: DispersionOutput,
+    pub(crate) target: f64,
+    pub(crate) tolerance: f64,
+    pub(crate) max_evaluations: u32,
+    pub(crate) max_iterations: u64,
+}
+
+impl DesignSearch {
+    /// Finds the value of the parameter which hits the target, with each evaluation simulating
+    /// the flight from the initial state.
+    pub(crate) fn solve(
+        &self,
+        rocket_properties: &RocketProperties,
+    ) -> Result<SolveReport, String> {
+        validate_parameters(&[self.parameter], &self.initial_condition)?;
+        let (lower, upper) = self.bounds;
+        if !(lower < upper && lower.is_finite() && upper.is_finite()) {
+            return Err(
+                "The bounds must have a finite lower bound below the upper bound".to_string(),
+            );
+        }
+
+        let report = solve_bracketed(
+            |value| {
+                simulate_run(
+                    rocket_properties,
+                    self.initial_condition,
+                    &self.ode_solver,
+                    self.max_iterations,
+                    &[self.parameter],
+                    &[value],
+                )
+                .get(self.output)
+            },
+            self.bounds,
+            self.target,
+            self.tolerance,
+            self.max_evaluations,
+        );
+        if report.achieved.is_nan() {
+            return Err(format!(
+                "The flight didn't reach {:?} within the iteration limit",
+                self.output
+            ));
+        }
+        Ok(report)
+    }
+}
+
+/// Copies the rocket properties with the airbrakes set to a fixed deployment, starting from
+/// rest if they have an actuator.
+fn with_deployment(
+    rocket_properties: &RocketProperties,
+    deployment: f64,
+) -> Result<RocketProperties, String> {
+    let mut rocket_properties = rocket_properties.clone();
+    let airbrake = rocket_properties
+        .airbrake
+        .as_mut()
+        .ok_or("Targeting the apogee requires the rocket to have an airbrake")?;
+    airbrake.deployment = deployment;
+    if let Some(actuator) = airbrake.actuator.as_mut() {
+        actuator.initial_position = 0.0;
+    }
+    Ok(rocket_properties)
+}
+
+/// Linearly interpolates the state log at the given time, clamped to the logged range.
+pub(crate) fn interpolate_row(log: &SimulationData, time: f64) -> Vec<f64> {
+    let times = &log.time_log;
+    if times.len() < 2 {
+        return log.state_log[0].to_vec();
+    }
+    let i = times
+        .partition_point(|&t| t <= time)
+        .clamp(1, times.len() - 1);
+    let fraction = ((time - times[i - 1]) / (times[i] - times[i - 1])).clamp(0.0, 1.0);
+    log.state_log[i - 1]
+        .iter()
+        .zip(log.state_log[i].iter())
+        .map(|(start, end)| start + fraction * (end - start))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::airbrake_mod::{Airbrake, Curve};
+    use crate::ode::{AdaptiveTimeStep, FixedTimeStep};
+    use crate::state::{InitialState1DOF, InitialState3DOF};
+    use approx::assert_abs_diff_eq;
+
+    fn make_rocket_properties() -> RocketProperties {
+        let mut rocket_properties = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        rocket_properties.airbrake = Some(Airbrake::new(
+            Curve::polynomial(vec![0.0, 1.0]),
+            None,
+            None,
+            0.0,
+            None,
+        ));
+        rocket_properties
+    }
+
+    fn make_search(initial_condition: InitialCondition, target_apogee: f64) -> ApogeeSearch {
+        ApogeeSearch {
+            initial_condition,
+            ode_solver: OdeSolver::RK45(AdaptiveTimeStep::default()),
+            target_apogee,
+            tolerance: 0.5,
+            max_evaluations: 20,
+            max_iterations: 100_000,
+        }
+    }
+
+    #[test]
+    fn solve_bracketed_finds_root() {
+        let report = solve_bracketed(|x| x * x, (0.0, 2.0), 2.0, 1e-10, 50);
+        assert_eq!(report.status, SolveStatus::Converged);
+        assert_abs_diff_eq!(report.value, 2.0_f64.sqrt(), epsilon = 1e-9);
+        assert_abs_diff_eq!(report.achieved, 2.0, epsilon = 1e-10);
+        assert!(report.evaluations < 20);
+
+        // Decreasing functions work the same
+        let report = solve_bracketed(|x| -x * x * x, (0.0, 2.0), -1.0, 1e-10, 50);
+        assert_abs_diff_eq!(report.value, 1.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn solve_bracketed_reports_unreachable_targets_and_budget() {
+        let report = solve_bracketed(|x| x, (0.0, 1.0), 3.0, 1e-6, 50);
+        assert_eq!(report.status, SolveStatus::TargetUnreachable);
+        assert_eq!(report.value, 1.0);
+        assert_eq!(report.evaluations, 2);
+
+        let report = solve_bracketed(|x| x.powi(9), (0.0, 2.0), 1.0, 1e-12, 4);
+        assert_eq!(report.status, SolveStatus::MaxEvaluations);
+        assert_eq!(report.evaluations, 4);
+    }
+
+    #[test]
+    fn deployment_hits_target_apogee() {
+        let rocket_properties = make_rocket_properties();
+        let initial_condition = InitialCondition::OneDOF(InitialState1DOF::new(0.0, 150.0));
+        let search = make_search(initial_condition, 780.0);
+        let report = search.deployment(&rocket_properties).unwrap();
+
+        assert_eq!(report.status, SolveStatus::Converged);
+        assert!((0.0..=1.0).contains(&report.value));
+        let apogee = search.apogee(with_deployment(&rocket_properties, report.value).unwrap());
+        assert_abs_diff_eq!(apogee, 780.0, epsilon = 0.5);
+
+        // Above the apogee without airbrakes
+        let report = make_search(initial_condition, 900.0)
+            .deployment(&rocket_properties)
+            .unwrap();
+        assert_eq!(report.status, SolveStatus::TargetUnreachable);
+        assert_eq!(report.value, 0.0);
+
+        // Needs an airbrake
+        let no_airbrake = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        assert!(search.deployment(&no_airbrake).is_err());
+    }
+
+    #[test]
+    fn deployment_time_hits_target_apogee() {
+        let rocket_properties = make_rocket_properties();
+        let initial_condition =
+            InitialCondition::ThreeDOF(InitialState3DOF::new(0.0, 0.0, 1.55, 0.0, 150.0, 0.0));
+        let mut search = make_search(initial_condition, 700.0);
+        search.ode_solver = OdeSolver::RK3(FixedTimeStep::new(0.01));
+        let report = search.deployment_time(&rocket_properties, 1.0).unwrap();
+
+        assert_eq!(report.status, SolveStatus::Converged);
+        assert!(report.value > 0.0);
+        assert_abs_diff_eq!(report.achieved, 700.0, epsilon = 0.5);
+    }
+
+    fn make_design_search(
+        parameter: DispersedParameter,
+        bounds: (f64, f64),
+        target: f64,
+    ) -> DesignSearch {
+        DesignSearch {
+            initial_condition: InitialCondition::OneDOF(InitialState1DOF::new(0.0, 150.0)),
+            ode_solver: OdeSolver::RK45(AdaptiveTimeStep::default()),
+            parameter,
+            bounds,
+            output: DispersionOutput::Apogee,
+            target,
+            tolerance: 0.5,
+            max_evaluations: 20,
+            max_iterations: 100_000,
+        }
+    }
+
+    #[test]
+    fn design_search_finds_mass_and_cd_for_target_apogee() {
+        let rocket_properties = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        let search = make_design_search(DispersedParameter::Mass, (5.0, 50.0), 0.0);
+        let apogee = |rocket_properties: RocketProperties| {
+            simulation::apogee(
+                search.initial_condition.to_state(rocket_properties),
+                search.ode_solver.clone(),
+                search.max_iterations,
+            )
+        };
+        let nominal = apogee(rocket_properties.clone());
+
+        // A heavier rocket coasts higher, more drag lowers the apogee
+        let search = make_design_search(DispersedParameter::Mass, (5.0, 50.0), nominal + 50.0);
+        let report = search.solve(&rocket_properties).unwrap();
+        assert_eq!(report.status, SolveStatus::Converged);
+        assert!(report.value > 15.0);
+        assert_abs_diff_eq!(report.achieved, nominal + 50.0, epsilon = 0.5);
+
+        let search = make_design_search(DispersedParameter::Cd, (0.1, 2.0), nominal - 50.0);
+        let report = search.solve(&rocket_properties).unwrap();
+        assert_eq!(report.status, SolveStatus::Converged);
+        assert!(report.value > 0.5);
+        let mut solved = rocket_properties.clone();
+        solved.cd = report.value;
+        assert_abs_diff_eq!(apogee(solved), nominal - 50.0, epsilon = 0.5);
+
+        // Out of reach within the bounds
+        let search = make_design_search(DispersedParameter::Cd, (0.1, 2.0), nominal + 5000.0);
+        let report = search.solve(&rocket_properties).unwrap();
+        assert_eq!(report.status, SolveStatus::TargetUnreachable);
+        assert_eq!(report.value, 0.1);
+    }
+
+    #[test]
+    fn design_search_rejects_invalid_searches() {
+        let rocket_properties = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        let empty_bounds = make_design_search(DispersedParameter::Mass, (20.0, 10.0), 800.0);
+        assert!(empty_bounds.solve(&rocket_properties).is_err());
+        let wind = make_design_search(DispersedParameter::WindSpeed, (0.0, 10.0), 800.0);
+        assert!(wind.solve(&rocket_properties).is_err());
+    }
+
+    #[test]
+    fn minimize_golden_finds_minimum() {
+        let ((x, fx), evaluations, status) =
+            minimize_golden(|x| (x - 0.3).powi(2) + 1.0, (-1.0, 2.0), 1e-6, 100);
+        assert_eq!(status, SolveStatus::Converged);
+        assert_abs_diff_eq!(x, 0.3, epsilon = 1e-6);
+        assert_abs_diff_eq!(fx, 1.0, epsilon = 1e-12);
+        assert!(evaluations < 40);
+
+        // Minima at a bound are approached from inside
+        let ((x, _), _, _) = minimize_golden(|x| x, (0.0, 1.0), 1e-6, 100);
+        assert_abs_diff_eq!(x, 0.0, epsilon = 1e-6);
+
+        let (_, evaluations, status) = minimize_golden(|x| x * x, (-1.0, 1.0), 1e-12, 10);
+        assert_eq!(status, SolveStatus::MaxEvaluations);
+        assert_eq!(evaluations, 10);
+    }
+
+    fn make_launch_search(objective: LaunchObjective) -> LaunchSearch {
+        LaunchSearch {
+            initial_state: InitialState3DOF::new(0.0, 0.0, 0.0, 0.0, 150.0, 0.0),
+            ode_solver: OdeSolver::RK3(FixedTimeStep::new(0.02)),
+            objective,
+            target_downrange: 0.0,
+            max_rail_angle: 0.349,
+            tolerance: 1e-3,
+            max_evaluations: 30,
+            max_iterations: 100_000,
+        }
+    }
+
+    #[test]
+    fn launch_search_optimizes_each_objective() {
+        let calm = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        let mut windy = calm.clone();
+        windy.wind_speed = 5.0;
+
+        // Without wind, the highest flight is vertical
+        let search = make_launch_search(LaunchObjective::MaxApogee);
+        let optimum = search.optimize(&calm).unwrap();
+        assert_eq!(optimum.status, SolveStatus::Converged);
+        assert_abs_diff_eq!(optimum.angle, 0.0, epsilon = 1e-2);
+        assert_abs_diff_eq!(optimum.initial_state.vy, 150.0, epsilon = 1e-2);
+
+        // With wind, the least drift beats launching vertically and at either limit
+        let search = make_launch_search(LaunchObjective::MinDrift);
+        let optimum = search.optimize(&windy).unwrap();
+        assert_eq!(optimum.status, SolveStatus::Converged);
+        let drift = optimum.landing_downrange.abs();
+        for angle in [-0.349, 0.0, 0.349] {
+            assert!(drift <= search.cost(search.fly(&windy, angle)));
+        }
+
+        // Landing where a tilted launch lands recovers its angle
+        let mut search = make_launch_search(LaunchObjective::LandingTarget);
+        search.target_downrange = search.fly(&windy, -0.1).1;
+        let optimum = search.optimize(&windy).unwrap();
+        assert_abs_diff_eq!(optimum.angle, -0.1, epsilon = 2e-3);
+        assert_abs_diff_eq!(
+            optimum.landing_downrange,
+            search.target_downrange,
+            epsilon = 5.0
+        );
+    }
+
+    #[test]
+    fn launch_search_rejects_invalid_rail_angles() {
+        let rocket_properties = RocketProperties::new(15.0, 0.5, 0.0182, 0.05, 5.0, 0.5, 0.2);
+        let mut search = make_launch_search(LaunchObjective::MaxApogee);
+        search.max_rail_angle = 0.0;
+        assert!(search.optimize(&rocket_properties).is_err());
+        search.max_rail_angle = 2.0;
+        assert!(search.optimize(&rocket_properties).is_err());
+    }
+
+    #[test]
+    fn interpolate_row_is_linear_in_time() {
+        let mut log = SimulationData::new();
+        log.time_log = vec![0.0, 1.0, 3.0];
+        log.state_log = vec![[0.0; 9], [1.0; 9], [5.0; 9]];
+        assert_abs_diff_eq!(interpolate_row(&log, 0.5)[0], 0.5);
+        assert_abs_diff_eq!(interpolate_row(&log, 2.0)[3], 3.0);
+        assert_abs_diff_eq!(interpolate_row(&log, 10.0)[8], 5.0);
+    }
+}
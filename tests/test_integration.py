@@ -1,8 +1,26 @@
 import pytest
 import numpy as np
 from hprm import (
+    Airbrake,
+    ApogeePredictor,
+    Controller,
+    Curve,
+    DerivedChannel,
+    DispersedParameter,
+    DispersionOutput,
+    Distribution,
+    EventKind,
+    ExtendedKalmanFilter,
+    FitParameter,
+    LaunchObjective,
+    Measurement,
     Rocket,
     OdeMethod,
+    ParticleFilter,
+    PredictionStatus,
+    SensitivityMethod,
+    SolveStatus,
+    UnscentedKalmanFilter,
     AdaptiveTimeStep,
     FixedTimeStep,
     InitialState1DOF,
@@ -60,10 +78,10 @@ def test_simulation_integration_1dof(
 @pytest.mark.parametrize(
     "initial_height, initial_velocity, initial_angle, ode_method, expected_apogee",
     [
-        (0.0, 150.0, 5.0, OdeMethod.Euler, 717.4301398012462),
-        (0.0, 150.0, 5.0, OdeMethod.RK45, 754.8978591535089),
-        (100.0, 50.0, 5.0, OdeMethod.Euler, 222.01259116956078),
-        (100.0, 50.0, 5.0, OdeMethod.RK45, 219.86912837488353),
+        (0.0, 150.0, 5.0, OdeMethod.Euler, 724.0768803462562),
+        (0.0, 150.0, 5.0, OdeMethod.RK45, 756.1073554543476),
+        (100.0, 50.0, 5.0, OdeMethod.Euler, 221.91088959243055),
+        (100.0, 50.0, 5.0, OdeMethod.RK45, 219.79663665856674),
     ],
     ids=[
         "ground_start_3dof_euler",
@@ -95,23 +113,591 @@ def test_simulation_integration_3dof(
 
 def test_simulate_flight_1dof_format():
     """
-    Verifies that the NumPy array structures returned by simulate_flight_1dof
-    conform to expected dimensional bounds, shapes, and structural baselines.
+    Verifies that the result of simulate_flight_1dof has the 1-DOF columns without padding,
+    named accessors consistent with them, and the settings of the simulation.
     """
     rocket = make_rocket()
     state = InitialState1DOF(initial_height=10.0, initial_velocity=150.0)
 
-    time_arr, state_mat = rocket.simulate_flight_1dof(
+    flight = rocket.simulate_flight_1dof(
         initial_state=state,
         integration_method=OdeMethod.Euler,
         timestep_config=FixedTimeStep(0.1),
     )
 
-    assert time_arr.ndim == 1
-    assert state_mat.ndim == 2
-    assert time_arr.shape[0] == state_mat.shape[0]
-    assert state_mat.shape[1] >= 2
+    assert flight.time.ndim == 1
+    assert flight.states.shape == (len(flight), 3)
+    assert flight.columns == ["altitude", "vertical_velocity", "vertical_acceleration"]
+    assert flight.units == ["m", "m/s", "m/s^2"]
+    np.testing.assert_array_equal(flight.altitude, flight.states[:, 0])
+    np.testing.assert_array_equal(flight.column("vertical_velocity"), flight.states[:, 1])
+    assert np.all(flight.downrange == 0.0)
 
-    assert time_arr[0] == 0.0
-    assert state_mat[0, 0] == pytest.approx(10.0)
-    assert state_mat[0, 1] == pytest.approx(150.0)
+    assert flight.time[0] == 0.0
+    assert flight.altitude[0] == pytest.approx(10.0)
+    assert flight.vertical_velocity[0] == pytest.approx(150.0)
+
+    assert flight.integration_method == OdeMethod.Euler
+    assert flight.timestep_config.dt == pytest.approx(0.1)
+    assert flight.rocket.rocket_properties.mass == 15.0
+    assert flight.status == PredictionStatus.Converged
+    assert flight.control is None
+    apogee = flight.summary.apogee
+    assert apogee.kind == EventKind.Apogee
+    assert apogee.altitude >= flight.altitude.max()
+    assert [event.kind for event in flight.events][-2:] == [EventKind.Apogee, EventKind.Landing]
+
+    with pytest.raises(ValueError):
+        flight.column("downrange")
+
+
+def test_simulate_flight_3dof_columns():
+    """
+    Verifies that the result of simulate_flight_3dof names its columns, so the altitude is found
+    by name rather than by its position.
+    """
+    flight = make_rocket().simulate_flight_3dof(
+        InitialState3DOF(0.0, 5.0, 0.0, 3.0, 150.0, 0.0), OdeMethod.RK3, FixedTimeStep(0.01)
+    )
+    assert flight.states.shape[1] == 9
+    assert flight.columns[:2] == ["downrange", "altitude"]
+    assert flight.altitude[0] == pytest.approx(5.0)
+    assert flight.downrange[0] == pytest.approx(0.0)
+    assert flight.speed[0] == pytest.approx(np.hypot(3.0, 150.0))
+
+
+def test_flight_summary_events():
+    """
+    Verifies that the summary of a flight reports its milestones from burnout to the landing,
+    which comes after the logged ascent.
+    """
+    flight = make_rocket().simulate_flight_3dof(
+        InitialState3DOF(0.0, 0.0, 0.0, 3.0, 150.0, 0.0), OdeMethod.RK45
+    )
+    summary = flight.summary
+    assert summary.burnout.time == 0.0
+    assert summary.max_speed.value == pytest.approx(np.hypot(3.0, 150.0))
+    assert summary.max_mach.value == pytest.approx(summary.max_speed.value / 340.294)
+    assert summary.max_dynamic_pressure.value == pytest.approx(
+        0.5 * 1.225 * summary.max_speed.value**2
+    )
+    assert summary.max_acceleration.value > 9.80665
+    assert summary.airbrake_deployment is None
+
+    assert summary.apogee.value == pytest.approx(flight.altitude.max(), abs=1.0)
+    assert summary.apogee.downrange > 0.0
+    assert summary.landing.time > flight.time[-1]
+    assert summary.landing.altitude == pytest.approx(0.0, abs=0.5)
+    assert summary.landing.downrange > summary.apogee.downrange
+
+    kinds = [event.kind for event in flight.events]
+    assert kinds[0] == EventKind.Burnout
+    assert kinds[-2:] == [EventKind.Apogee, EventKind.Landing]
+
+
+def test_derived_channels_max_q():
+    """
+    Verifies that requested derived channels are logged alongside the states and found by name,
+    e.g. to find the max-Q of a flight.
+    """
+    channels = [
+        DerivedChannel.Mach,
+        DerivedChannel.DynamicPressure,
+        DerivedChannel.AngleOfAttack,
+        DerivedChannel.StabilityCalibers,
+    ]
+    flight = make_rocket().simulate_flight_3dof(
+        InitialState3DOF(0.0, 0.0, 0.0, 3.0, 150.0, 0.0),
+        OdeMethod.RK3,
+        FixedTimeStep(0.01),
+        derived_channels=channels,
+    )
+    assert flight.derived_channels == channels
+    assert flight.derived.shape == (len(flight), 4)
+    assert flight.derived_columns[1] == "dynamic_pressure"
+
+    # The flight starts at burnout, so max-Q is at the first row
+    q = flight.column("dynamic_pressure")
+    assert np.argmax(q) == 0
+    assert q[0] == pytest.approx(0.5 * 1.225 * flight.speed[0] ** 2)
+    assert flight.column("mach")[0] == pytest.approx(flight.speed[0] / 340.294)
+    assert flight.column("angle_of_attack")[0] > 0.0
+    # The margin is measured in diameters of the drag reference area
+    diameter = np.sqrt(4.0 * 0.0182 / np.pi)
+    assert flight.column("stability_calibers")[0] == pytest.approx(0.5 / diameter)
+
+    with pytest.raises(ValueError):
+        flight.column("lift_force")
+
+    # Without channels, nothing extra is logged
+    flight = make_rocket().simulate_flight_1dof(InitialState1DOF(0.0, 150.0), OdeMethod.RK3)
+    assert flight.derived.shape == (len(flight), 0)
+
+
+def test_airbrake_deployment_lowers_apogee():
+    """
+    Verifies that deploying the airbrakes further always lowers the predicted apogee, and that a
+    deployment override requires the rocket to have airbrakes.
+    """
+    rocket = make_rocket()
+    state = InitialState1DOF(initial_height=0.0, initial_velocity=150.0)
+
+    with pytest.raises(ValueError):
+        rocket.predict_apogee_1dof(state, OdeMethod.RK45, airbrake_deployment=0.5)
+
+    rocket = Rocket(
+        mass=15.0,
+        cd=0.5,
+        area_drag=0.0182,
+        area_lift=0.05,
+        moment_of_inertia=5.0,
+        stab_margin_dimensional=0.5,
+        cl_a=0.2,
+        airbrake=Airbrake(Curve.table([0.0, 1.0], [0.0, 0.8])),
+    )
+    apogees = [
+        rocket.predict_apogee_1dof(state, OdeMethod.RK45, airbrake_deployment=deployment)
+        for deployment in [0.0, 0.5, 1.0]
+    ]
+
+    assert apogees[0] == pytest.approx(829.640509126735)
+    assert apogees[0] > apogees[1] > apogees[2]
+
+
+def test_controlled_flight_reaches_target_apogee():
+    """
+    Verifies that a closed-loop controller steers the apogee towards its target, and that
+    everything the controller does is returned in the control log.
+    """
+    rocket = Rocket(
+        mass=15.0,
+        cd=0.5,
+        area_drag=0.0182,
+        area_lift=0.05,
+        moment_of_inertia=5.0,
+        stab_margin_dimensional=0.5,
+        cl_a=0.2,
+        airbrake=Airbrake(Curve.polynomial([0.0, 1.0])),
+    )
+    state = InitialState1DOF(initial_height=0.0, initial_velocity=150.0)
+
+    flight = rocket.simulate_controlled_flight_1dof(
+        state,
+        OdeMethod.RK3,
+        Controller.pid(780.0, kp=0.05, ki=0.01),
+        timestep_config=FixedTimeStep(0.01),
+    )
+
+    assert flight.altitude[-1] == pytest.approx(780.0, abs=2.0)
+    control_log = flight.control
+    assert control_log.shape[1] == len(flight.control_columns) == 4
+    assert np.all((control_log[:, 2] >= 0.0) & (control_log[:, 2] <= 1.0))
+
+    def failing_controller(time, state, predicted_apogee):
+        raise RuntimeError("sensor fault")
+
+    with pytest.raises(RuntimeError):
+        rocket.simulate_controlled_flight_1dof(
+            state, OdeMethod.RK3, Controller.python(failing_controller), FixedTimeStep(0.01)
+        )
+
+
+def test_deployment_for_apogee_hits_target():
+    """
+    Verifies that the deployment found by the targeting search reproduces the target apogee.
+    """
+    rocket = Rocket(
+        mass=15.0,
+        cd=0.5,
+        area_drag=0.0182,
+        area_lift=0.05,
+        moment_of_inertia=5.0,
+        stab_margin_dimensional=0.5,
+        cl_a=0.2,
+        airbrake=Airbrake(Curve.polynomial([0.0, 1.0])),
+    )
+    state = InitialState1DOF(initial_height=0.0, initial_velocity=150.0)
+
+    report = rocket.deployment_for_apogee_1dof(state, 780.0, OdeMethod.RK45)
+    assert report.status == SolveStatus.Converged
+    assert rocket.predict_apogee_1dof(
+        state, OdeMethod.RK45, airbrake_deployment=report.value
+    ) == pytest.approx(780.0, abs=0.5)
+
+    report = rocket.deployment_time_for_apogee_1dof(state, 780.0, OdeMethod.RK45)
+    assert report.status == SolveStatus.Converged
+    assert report.value > 0.0
+
+
+def test_apogee_predictor_matches_predict_apogee():
+    """
+    Verifies that the persistent predictor gives the same apogee as a one-off prediction.
+    """
+    rocket = make_rocket()
+    predictor = ApogeePredictor.new_1dof(rocket, OdeMethod.RK3, FixedTimeStep(0.1))
+
+    for time, height, velocity in [(0.0, 0.0, 150.0), (1.0, 140.0, 130.0)]:
+        expected = rocket.predict_apogee_1dof(
+            InitialState1DOF(height, velocity), OdeMethod.RK3, FixedTimeStep(0.1)
+        )
+        assert predictor.update(time, height, velocity) == pytest.approx(expected)
+
+    assert predictor.last_time == 1.0
+
+
+def test_prediction_budget_returns_estimate():
+    """
+    Verifies that a prediction which runs out of its budget returns an estimate and says so,
+    instead of the last simulated altitude.
+    """
+    rocket = make_rocket()
+    state = InitialState1DOF(initial_height=0.0, initial_velocity=150.0)
+
+    prediction = rocket.predict_apogee_1dof_with_status(state, OdeMethod.RK45)
+    assert prediction.status == PredictionStatus.Converged
+    assert prediction.apogee == pytest.approx(829.640509126735)
+
+    prediction = rocket.predict_apogee_1dof_with_status(
+        state, OdeMethod.RK3, FixedTimeStep(0.01), max_iterations=100
+    )
+    assert prediction.status == PredictionStatus.BudgetExhausted
+    assert prediction.iterations == 100
+    assert prediction.apogee == pytest.approx(829.640509126735, rel=0.05)
+
+    prediction = rocket.predict_apogee_1dof_with_status(state, OdeMethod.RK45, deadline=0.0)
+    assert prediction.status == PredictionStatus.BudgetExhausted
+
+
+def test_ekf_tracks_simulated_altitude():
+    """
+    Verifies that the EKF follows a simulated flight from barometer readings alone.
+    """
+    rocket = make_rocket()
+    flight = rocket.simulate_flight_1dof(
+        InitialState1DOF(0.0, 150.0), OdeMethod.RK3, FixedTimeStep(0.1)
+    )
+
+    ekf = ExtendedKalmanFilter.new_1dof(
+        rocket, InitialState1DOF(0.0, 140.0), [1.0, 100.0], [1e-2, 1e-2]
+    )
+    for altitude in flight.altitude[1:]:
+        ekf.predict(0.1)
+        ekf.update(Measurement.barometric_altitude(altitude, 0.5))
+
+    assert ekf.state[1] == pytest.approx(flight.vertical_velocity[-1], abs=1.0)
+    assert ekf.covariance.shape == (2, 2)
+
+
+@pytest.mark.parametrize("make_filter", [UnscentedKalmanFilter.new_1dof, ParticleFilter.new_1dof])
+def test_sampling_filters_predict_apogee_distribution(make_filter):
+    """
+    Verifies that the unscented and particle filters follow a simulated flight and predict an
+    apogee distribution around the true apogee.
+    """
+    rocket = make_rocket()
+    flight = rocket.simulate_flight_1dof(
+        InitialState1DOF(0.0, 150.0), OdeMethod.RK3, FixedTimeStep(0.1)
+    )
+
+    estimator = make_filter(rocket, InitialState1DOF(0.0, 145.0), [1.0, 25.0], [1e-2, 1e-2])
+    for altitude in flight.altitude[1:30]:
+        estimator.predict(0.1)
+        estimator.update(Measurement.barometric_altitude(altitude, 0.5))
+
+    assert estimator.state[1] == pytest.approx(flight.vertical_velocity[29], abs=2.0)
+    distribution = estimator.apogee_distribution()
+    assert distribution.mean == pytest.approx(flight.altitude.max(), abs=5.0)
+    assert distribution.std > 0.0
+    assert len(distribution.samples) == len(distribution.weights)
+
+
+def test_fit_recovers_cd_from_altitude():
+    """
+    Verifies that fitting cd to a simulated flight recovers the cd it was simulated with.
+    """
+    truth = make_rocket()
+    properties = truth.rocket_properties
+    properties.cd = 0.6
+    truth.rocket_properties = properties
+    flight = truth.simulate_flight_1dof(
+        InitialState1DOF(0.0, 150.0), OdeMethod.RK3, FixedTimeStep(0.01)
+    )
+
+    result = make_rocket().fit_1dof(
+        InitialState1DOF(0.0, 150.0),
+        flight.time[::20],
+        flight.altitude[::20],
+        [FitParameter.Cd],
+        OdeMethod.RK3,
+        FixedTimeStep(0.01),
+    )
+
+    assert result.converged
+    assert result.values[0] == pytest.approx(0.6, abs=1e-4)
+    assert result.rocket.rocket_properties.cd == pytest.approx(0.6, abs=1e-4)
+    assert result.covariance.shape == (1, 1)
+
+    with pytest.raises(ValueError):
+        make_rocket().fit_1dof(
+            InitialState1DOF(0.0, 150.0),
+            flight.time,
+            flight.altitude,
+            [FitParameter.ClA],
+            OdeMethod.RK3,
+        )
+
+
+def test_posterior_samples_cover_true_cd():
+    """
+    Verifies that the posterior of cd given a simulated flight is centred on the true cd, and
+    that the predicted apogee is close to the simulated one.
+    """
+    truth = make_rocket()
+    properties = truth.rocket_properties
+    properties.cd = 0.6
+    truth.rocket_properties = properties
+    flight = truth.simulate_flight_1dof(
+        InitialState1DOF(0.0, 150.0), OdeMethod.RK3, FixedTimeStep(0.05)
+    )
+
+    posterior = make_rocket().sample_posterior_1dof(
+        InitialState1DOF(0.0, 150.0),
+        flight.time[::20],
+        flight.altitude[::20],
+        [FitParameter.Cd],
+        [Distribution.uniform(0.0, 2.0)],
+        OdeMethod.RK3,
+        FixedTimeStep(0.05),
+        altitude_std=2.0,
+        num_samples=1000,
+        burn_in=300,
+        predictive_samples=20,
+    )
+
+    assert posterior.samples.shape == (1000, 1)
+    assert posterior.mean[0] == pytest.approx(0.6, abs=0.005)
+    assert posterior.apogee.mean == pytest.approx(flight.altitude.max(), abs=1.0)
+    assert len(posterior.apogee.samples) == 20
+
+
+def test_monte_carlo_is_reproducible_across_threads():
+    """
+    Verifies that a Monte Carlo analysis gives the same runs for the same seed regardless of the
+    number of threads, and that its statistics are consistent with the runs.
+    """
+    rocket = make_rocket()
+    args = (
+        InitialState3DOF(0.0, 0.0, 0.05, 0.0, 150.0, 0.0),
+        [DispersedParameter.Cd, DispersedParameter.WindSpeed],
+        [Distribution.normal(0.5, 0.05), Distribution.uniform(0.0, 8.0)],
+        OdeMethod.RK3,
+        FixedTimeStep(0.05),
+    )
+    single = rocket.monte_carlo_3dof(*args, num_runs=50, seed=1, num_threads=1)
+    multi = rocket.monte_carlo_3dof(*args, num_runs=50, seed=1, num_threads=4)
+
+    assert single.samples.shape == (50, 2)
+    assert single.failed_runs == 0
+    np.testing.assert_array_equal(single.apogee, multi.apogee)
+    np.testing.assert_array_equal(single.landing_downrange, multi.landing_downrange)
+    assert np.all(single.time_to_apogee > 0.0)
+
+    statistics = single.statistics(DispersionOutput.Apogee)
+    assert statistics.mean == pytest.approx(single.apogee.mean())
+    assert statistics.min <= statistics.p50 <= statistics.max
+    assert single.percentile(DispersionOutput.Apogee, 50.0) == pytest.approx(
+        np.median(single.apogee)
+    )
+
+    # The 1-DOF model has no wind
+    with pytest.raises(ValueError):
+        rocket.monte_carlo_1dof(
+            InitialState1DOF(0.0, 150.0),
+            [DispersedParameter.WindSpeed],
+            [Distribution.normal(0.0, 1.0)],
+            OdeMethod.RK3,
+        )
+
+
+def test_batch_apogee_matches_single_predictions():
+    """
+    Verifies that a batch of apogee predictions over initial states and drag coefficients
+    matches one prediction per flight.
+    """
+    rocket = make_rocket()
+    velocities = [100.0, 150.0, 200.0]
+    initial_states = np.array([[0.0, v] for v in velocities])
+
+    apogees = rocket.predict_apogee_1dof_batch(initial_states, OdeMethod.RK3, num_threads=2)
+
+    assert apogees.shape == (3,)
+    for v, apogee in zip(velocities, apogees):
+        expected = rocket.predict_apogee_1dof(InitialState1DOF(0.0, v), OdeMethod.RK3)
+        assert apogee == pytest.approx(expected)
+
+    # A single initial state is shared by every row of values
+    cd_apogees = rocket.predict_apogee_1dof_batch(
+        initial_states[1:2],
+        OdeMethod.RK3,
+        parameters=[DispersedParameter.Cd],
+        values=np.array([[0.3], [0.5], [0.7]]),
+    )
+    assert cd_apogees[1] == pytest.approx(apogees[1])
+    assert cd_apogees[0] > cd_apogees[1] > cd_apogees[2]
+
+    with pytest.raises(ValueError):
+        rocket.predict_apogee_3dof_batch(initial_states, OdeMethod.RK3)
+
+
+def test_apogee_sensitivity_methods_agree():
+    """
+    Verifies that forward and finite-difference apogee sensitivities agree, and that the
+    trajectory sensitivities have one matrix per time.
+    """
+    rocket = make_rocket()
+    initial_state = InitialState1DOF(0.0, 150.0)
+    parameters = [DispersedParameter.Cd, DispersedParameter.InitialVerticalVelocity]
+
+    forward = rocket.apogee_sensitivity_1dof(
+        initial_state, parameters, OdeMethod.RK3, trajectory=True
+    )
+    finite = rocket.apogee_sensitivity_1dof(
+        initial_state,
+        parameters,
+        OdeMethod.RK3,
+        method=SensitivityMethod.FiniteDifference,
+        relative_step=1e-3,
+    )
+
+    assert forward.apogee == pytest.approx(rocket.predict_apogee_1dof(initial_state, OdeMethod.RK3))
+    assert forward.gradient == pytest.approx(finite.gradient, rel=1e-3)
+    assert forward.gradient[0] < 0.0 < forward.gradient[1]
+    assert forward.trajectory.shape == (len(forward.time), 2, 2)
+    assert finite.trajectory is None
+
+    with pytest.raises(ValueError):
+        rocket.apogee_sensitivity_1dof(initial_state, [DispersedParameter.WindSpeed], OdeMethod.RK3)
+
+
+def test_state_jacobians():
+    """
+    Verifies the shapes and the kinematic rows of the state derivative Jacobians.
+    """
+    rocket = make_rocket()
+
+    jacobian = rocket.state_jacobian_1dof(InitialState1DOF(100.0, 80.0))
+    assert jacobian.shape == (2, 2)
+    assert jacobian[0] == pytest.approx([0.0, 1.0])
+    # More speed means more drag
+    assert jacobian[1, 1] < 0.0
+
+    jacobian = rocket.state_jacobian_3dof(InitialState3DOF(0.0, 100.0, 0.05, -5.0, 80.0, 0.1))
+    assert jacobian.shape == (6, 6)
+    assert np.all(np.isfinite(jacobian))
+    assert jacobian[:3] == pytest.approx(np.hstack([np.zeros((3, 3)), np.eye(3)]))
+
+
+def test_sobol_indices_attribute_variance_to_drag():
+    """
+    Verifies that Sobol indices attribute the apogee variance to a wide drag coefficient range
+    rather than a small initial altitude range, reproducibly across thread counts.
+    """
+    rocket = make_rocket()
+    args = (
+        InitialState1DOF(0.0, 150.0),
+        [DispersedParameter.Cd, DispersedParameter.InitialAltitude],
+        [(0.4, 0.6), (0.0, 1.0)],
+        OdeMethod.RK3,
+        FixedTimeStep(0.05),
+    )
+    kwargs = dict(num_samples=64, num_resamples=50, confidence_level=0.9, seed=3)
+    single = rocket.sobol_indices_1dof(*args, num_threads=1, **kwargs)
+    multi = rocket.sobol_indices_1dof(*args, num_threads=4, **kwargs)
+
+    np.testing.assert_array_equal(single.first_order, multi.first_order)
+    assert single.failed_samples == 0
+    assert single.first_order_interval.shape == (2, 2)
+    assert single.first_order[0] == pytest.approx(1.0, abs=0.3)
+    assert single.total_order[0] == pytest.approx(1.0, abs=0.3)
+    assert single.total_order[1] < 0.01
+    lower, upper = single.total_order_interval.T
+    assert np.all(lower <= single.total_order) and np.all(single.total_order <= upper)
+
+    with pytest.raises(ValueError):
+        rocket.sobol_indices_1dof(*args[:2], [(0.4, 0.6)], *args[3:])
+
+
+def test_parameter_for_target_apogee():
+    """
+    Verifies that inverse design finds the mass and drag coefficient which hit a target apogee,
+    and reports targets out of reach of the bounds.
+    """
+    rocket = make_rocket()
+    initial_state = InitialState1DOF(0.0, 150.0)
+    nominal = rocket.predict_apogee_1dof(initial_state, OdeMethod.RK45)
+
+    report = rocket.parameter_for_target_1dof(
+        initial_state, DispersedParameter.Mass, (5.0, 50.0), nominal + 50.0, OdeMethod.RK45
+    )
+    assert report.status == SolveStatus.Converged
+    assert report.value > 15.0
+    assert report.achieved == pytest.approx(nominal + 50.0, abs=0.5)
+
+    report = rocket.parameter_for_target_1dof(
+        initial_state, DispersedParameter.Cd, (0.1, 2.0), nominal - 50.0, OdeMethod.RK45
+    )
+    assert report.status == SolveStatus.Converged
+    assert report.value > 0.5
+
+    report = rocket.parameter_for_target_1dof(
+        initial_state, DispersedParameter.Cd, (0.1, 2.0), nominal + 5000.0, OdeMethod.RK45
+    )
+    assert report.status == SolveStatus.TargetUnreachable
+
+    with pytest.raises(ValueError):
+        rocket.parameter_for_target_1dof(
+            initial_state, DispersedParameter.Mass, (20.0, 10.0), nominal, OdeMethod.RK45
+        )
+
+
+def test_launch_angle_optimization():
+    """
+    Verifies that the launch angle search flies vertically for the highest apogee without wind,
+    and tilts the launch to reduce drift with wind.
+    """
+    initial_state = InitialState3DOF(0.0, 0.0, 0.0, 0.0, 150.0, 0.0)
+    rocket = make_rocket()
+    optimum = rocket.optimize_launch_angle_3dof(
+        initial_state, LaunchObjective.MaxApogee, OdeMethod.RK3, FixedTimeStep(0.02)
+    )
+    assert optimum.status == SolveStatus.Converged
+    assert optimum.angle == pytest.approx(0.0, abs=1e-2)
+    assert abs(optimum.angle) <= 0.349
+
+    windy = Rocket(
+        mass=15.0,
+        cd=0.5,
+        area_drag=0.0182,
+        area_lift=0.05,
+        moment_of_inertia=5.0,
+        stab_margin_dimensional=0.5,
+        cl_a=0.2,
+        wind_speed=5.0,
+    )
+    vertical = windy.optimize_launch_angle_3dof(
+        initial_state,
+        LaunchObjective.MinDrift,
+        OdeMethod.RK3,
+        FixedTimeStep(0.02),
+        max_rail_angle=1e-6,
+    )
+    optimum = windy.optimize_launch_angle_3dof(
+        initial_state, LaunchObjective.MinDrift, OdeMethod.RK3, FixedTimeStep(0.02)
+    )
+    assert abs(optimum.landing_downrange) < abs(vertical.landing_downrange)
+    assert optimum.initial_state.angle == optimum.angle
+
+    with pytest.raises(ValueError):
+        rocket.optimize_launch_angle_3dof(
+            initial_state, LaunchObjective.MaxApogee, OdeMethod.RK3, max_rail_angle=0.0
+        )